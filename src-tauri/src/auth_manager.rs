@@ -15,6 +15,26 @@ pub fn get_auth_dir() -> PathBuf {
     dir
 }
 
+/// Calls `on_change` (debounced) whenever an account file is added, changed or removed. The
+/// watch lasts as long as the returned debouncer is kept alive.
+pub fn watch_auth_dir<F>(
+    mut on_change: F,
+) -> Result<notify_debouncer_mini::Debouncer<notify::RecommendedWatcher>, String>
+where
+    F: FnMut() + Send + 'static,
+{
+    let mut debouncer =
+        notify_debouncer_mini::new_debouncer(std::time::Duration::from_millis(500), move |_res| {
+            on_change()
+        })
+        .map_err(|e| format!("Failed to create auth directory watcher: {}", e))?;
+    debouncer
+        .watcher()
+        .watch(&get_auth_dir(), notify::RecursiveMode::NonRecursive)
+        .map_err(|e| format!("Failed to watch auth directory: {}", e))?;
+    Ok(debouncer)
+}
+
 pub fn scan_auth_directory() -> HashMap<ServiceType, ServiceAccounts> {
     let mut result: HashMap<ServiceType, ServiceAccounts> = HashMap::new();
 
@@ -165,6 +165,11 @@ pub fn is_binary_available_for_app(app_handle: &tauri::AppHandle) -> bool {
 }
 
 pub fn ensure_binary_installed(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
+    ensure_binary_installed_from(get_bundled_binary_path(app_handle))
+}
+
+/// Resolves the runtime binary, copying `bundled_path` into the local data dir when needed.
+pub fn ensure_binary_installed_from(bundled_path: Option<PathBuf>) -> Result<PathBuf, String> {
     let local_path = get_binary_path();
     if local_path.exists() {
         #[cfg(unix)]
@@ -174,7 +179,7 @@ pub fn ensure_binary_installed(app_handle: &tauri::AppHandle) -> Result<PathBuf,
         return Ok(local_path);
     }
 
-    let bundled_path = get_bundled_binary_path(app_handle)
+    let bundled_path = bundled_path
         .ok_or_else(|| "Binary not available. Please download it first.".to_string())?;
 
     let parent = local_path
@@ -0,0 +1,444 @@
+use crate::types::{
+    BudgetAlert, BudgetAlertLevel, BudgetMetric, BudgetPeriod, BudgetScope, UsageBudget,
+};
+use crate::usage_tracker::{UsageEvent, UsageTracker};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Instant;
+
+pub type BudgetAlertListener = Arc<dyn Fn(&BudgetAlert) + Send + Sync>;
+
+/// How long cached period usage is trusted before it is read from rollups again. Events
+/// written by this process are added as they land; the refresh picks up anything else.
+const USAGE_REFRESH: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// First UTC day counted towards the current period.
+pub fn period_start(period: BudgetPeriod, now: DateTime<Utc>) -> NaiveDate {
+    let today = now.date_naive();
+    match period {
+        BudgetPeriod::Daily => today,
+        BudgetPeriod::Monthly => today.with_day(1).unwrap_or(today),
+    }
+}
+
+/// When the current period ends and usage starts again from zero.
+pub fn period_reset(period: BudgetPeriod, now: DateTime<Utc>) -> DateTime<Utc> {
+    let start = period_start(period, now);
+    let next = match period {
+        BudgetPeriod::Daily => start + Duration::days(1),
+        BudgetPeriod::Monthly => {
+            let (year, month) = if start.month() == 12 {
+                (start.year() + 1, 1)
+            } else {
+                (start.year(), start.month() + 1)
+            };
+            NaiveDate::from_ymd_opt(year, month, 1).unwrap_or(start + Duration::days(31))
+        }
+    };
+    Utc.from_utc_datetime(&next.and_hms_opt(0, 0, 0).unwrap_or_default())
+}
+
+pub fn validate_budget(budget: &UsageBudget) -> Result<(), String> {
+    if budget.target.trim().is_empty() {
+        return Err("Budget target cannot be empty".to_string());
+    }
+    if budget.soft_limit.is_none() && budget.hard_limit.is_none() {
+        return Err("Set a soft limit, a hard limit, or both".to_string());
+    }
+    if [budget.soft_limit, budget.hard_limit]
+        .iter()
+        .flatten()
+        .any(|limit| *limit <= 0)
+    {
+        return Err("Budget limits must be greater than zero".to_string());
+    }
+    if let (Some(soft), Some(hard)) = (budget.soft_limit, budget.hard_limit) {
+        if soft > hard {
+            return Err("Soft limit cannot exceed the hard limit".to_string());
+        }
+    }
+    Ok(())
+}
+
+pub fn describe(budget: &UsageBudget) -> String {
+    let scope = match budget.scope {
+        BudgetScope::Provider => "provider",
+        BudgetScope::Model => "model",
+        BudgetScope::Account => "account",
+    };
+    let period = match budget.period {
+        BudgetPeriod::Daily => "daily",
+        BudgetPeriod::Monthly => "monthly",
+    };
+    let metric = match budget.metric {
+        BudgetMetric::Tokens => "token",
+        BudgetMetric::Requests => "request",
+    };
+    format!(
+        "{} {} budget for {} {}",
+        period, metric, scope, budget.target
+    )
+}
+
+fn matches(budget: &UsageBudget, provider: &str, model: &str, account_key: &str) -> bool {
+    let value = match budget.scope {
+        BudgetScope::Provider => provider,
+        BudgetScope::Model => model,
+        BudgetScope::Account => account_key,
+    };
+    value.eq_ignore_ascii_case(budget.target.trim())
+}
+
+/// A request refused because a hard limit has been reached.
+#[derive(Debug, Clone)]
+pub struct BudgetRejection {
+    pub budget: UsageBudget,
+    pub used: i64,
+    pub limit: i64,
+    pub resets_at: DateTime<Utc>,
+}
+
+impl BudgetRejection {
+    pub fn message(&self) -> String {
+        format!(
+            "CodeForwarder {} reached ({} of {}). Resets at {}.",
+            describe(&self.budget),
+            self.used,
+            self.limit,
+            self.resets_at.to_rfc3339()
+        )
+    }
+}
+
+/// Live budget state used by ThinkingProxy for every inference request.
+#[derive(Default)]
+pub struct BudgetGuard {
+    budgets: RwLock<Vec<UsageBudget>>,
+    /// `(budget id, level, period start)` already alerted, so each alert fires once per period.
+    alerted: Mutex<HashSet<(String, BudgetAlertLevel, NaiveDate)>>,
+    /// `(budget id, period start)` -> usage so far and when it was read from rollups.
+    usage: Mutex<HashMap<(String, NaiveDate), (i64, Instant)>>,
+    listener: RwLock<Option<BudgetAlertListener>>,
+}
+
+impl BudgetGuard {
+    pub fn new(budgets: Vec<UsageBudget>) -> Self {
+        Self {
+            budgets: RwLock::new(budgets),
+            ..Self::default()
+        }
+    }
+
+    pub fn set_budgets(&self, budgets: Vec<UsageBudget>) {
+        if let Ok(mut current) = self.budgets.write() {
+            *current = budgets;
+        }
+        // An edited budget may keep its id but count something else.
+        if let Ok(mut usage) = self.usage.lock() {
+            usage.clear();
+        }
+    }
+
+    pub fn set_alert_listener(&self, listener: Option<BudgetAlertListener>) {
+        if let Ok(mut current) = self.listener.write() {
+            *current = listener;
+        }
+    }
+
+    /// Adds a just-written usage event to the cached usage of the budgets it counts towards.
+    pub fn record_event(&self, event: &UsageEvent) {
+        let Some(at) = Utc.timestamp_opt(event.timestamp_utc, 0).single() else {
+            return;
+        };
+        let Ok(budgets) = self.budgets.read() else {
+            return;
+        };
+        let Ok(mut usage) = self.usage.lock() else {
+            return;
+        };
+        for budget in budgets
+            .iter()
+            .filter(|b| matches(b, &event.provider, &event.model, &event.account_key))
+        {
+            let key = (budget.id.clone(), period_start(budget.period, at));
+            if let Some((used, _)) = usage.get_mut(&key) {
+                *used += match budget.metric {
+                    BudgetMetric::Tokens => event.total_tokens.unwrap_or(0),
+                    BudgetMetric::Requests => 1,
+                };
+            }
+        }
+    }
+
+    /// Checks the budgets matching this request against current-period usage, read from
+    /// rollups only when the cached figure is missing or stale.
+    /// Fails open: if usage can't be read, the request is allowed.
+    pub async fn check(
+        &self,
+        usage_tracker: &UsageTracker,
+        provider: &str,
+        model: &str,
+        account_key: &str,
+    ) -> Option<BudgetRejection> {
+        let matching: Vec<UsageBudget> = self
+            .budgets
+            .read()
+            .ok()?
+            .iter()
+            .filter(|b| matches(b, provider, model, account_key))
+            .cloned()
+            .collect();
+        if matching.is_empty() {
+            return None;
+        }
+
+        let now = Utc::now();
+        let key = |budget: &UsageBudget| (budget.id.clone(), period_start(budget.period, now));
+        let cached: Vec<Option<i64>> = {
+            let usage = self.usage.lock().ok()?;
+            matching
+                .iter()
+                .map(|budget| {
+                    usage
+                        .get(&key(budget))
+                        .filter(|(_, read_at)| read_at.elapsed() < USAGE_REFRESH)
+                        .map(|(used, _)| *used)
+                })
+                .collect()
+        };
+        let stale: Vec<UsageBudget> = matching
+            .iter()
+            .zip(&cached)
+            .filter(|(_, used)| used.is_none())
+            .map(|(budget, _)| budget.clone())
+            .collect();
+        let mut fetched = Vec::new();
+        if !stale.is_empty() {
+            fetched = match usage_tracker.budget_usage(stale.clone(), now).await {
+                Ok(usage) => usage,
+                Err(e) => {
+                    log::warn!("[Budgets] Failed to read budget usage: {}", e);
+                    return None;
+                }
+            };
+            if let Ok(mut usage) = self.usage.lock() {
+                let read_at = Instant::now();
+                usage.retain(|_, (_, at)| at.elapsed() < USAGE_REFRESH);
+                for (budget, used) in stale.iter().zip(&fetched) {
+                    usage.insert(key(budget), (*used, read_at));
+                }
+            }
+        }
+        let mut fetched = fetched.into_iter();
+        let usage: Vec<i64> = cached
+            .into_iter()
+            .map(|used| used.or_else(|| fetched.next()).unwrap_or(0))
+            .collect();
+
+        let mut rejection = None;
+        for (budget, used) in matching.into_iter().zip(usage) {
+            if let Some(limit) = budget.soft_limit.filter(|limit| used >= *limit) {
+                self.alert(&budget, BudgetAlertLevel::Soft, used, limit, now);
+            }
+            if let Some(limit) = budget.hard_limit.filter(|limit| used >= *limit) {
+                self.alert(&budget, BudgetAlertLevel::Hard, used, limit, now);
+                if rejection.is_none() {
+                    rejection = Some(BudgetRejection {
+                        resets_at: period_reset(budget.period, now),
+                        budget,
+                        used,
+                        limit,
+                    });
+                }
+            }
+        }
+        rejection
+    }
+
+    fn alert(
+        &self,
+        budget: &UsageBudget,
+        level: BudgetAlertLevel,
+        used: i64,
+        limit: i64,
+        now: DateTime<Utc>,
+    ) {
+        let key = (budget.id.clone(), level, period_start(budget.period, now));
+        let first = self
+            .alerted
+            .lock()
+            .map(|mut alerted| alerted.insert(key))
+            .unwrap_or(false);
+        if !first {
+            return;
+        }
+
+        log::warn!(
+            "[Budgets] {:?} limit reached: {} ({} of {})",
+            level,
+            describe(budget),
+            used,
+            limit
+        );
+        let listener = self.listener.read().ok().and_then(|l| l.clone());
+        if let Some(listener) = listener {
+            listener(&BudgetAlert {
+                budget: budget.clone(),
+                level,
+                used,
+                limit,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::usage_tracker::test_event;
+    use std::path::PathBuf;
+
+    fn temp_tracker() -> (Arc<UsageTracker>, PathBuf) {
+        let dir = std::env::temp_dir().join(format!(
+            "codeforwarder-budgets-test-{}",
+            uuid::Uuid::new_v4()
+        ));
+        std::fs::create_dir_all(&dir).expect("Failed to create temp dir");
+        let tracker = Arc::new(UsageTracker::open(dir.join("usage.db")).unwrap());
+        (tracker, dir)
+    }
+
+    fn opus_event(request_id: &str, total_tokens: i64) -> UsageEvent {
+        UsageEvent {
+            model: "claude-opus-4-5".to_string(),
+            ..test_event(request_id, Utc::now().timestamp(), total_tokens)
+        }
+    }
+
+    fn budget() -> UsageBudget {
+        UsageBudget {
+            id: "b1".to_string(),
+            scope: BudgetScope::Model,
+            target: "claude-opus-4-5".to_string(),
+            period: BudgetPeriod::Daily,
+            metric: BudgetMetric::Tokens,
+            soft_limit: Some(1_000),
+            hard_limit: Some(2_000),
+        }
+    }
+
+    #[test]
+    fn periods_follow_utc_days_and_months() {
+        let now = Utc.with_ymd_and_hms(2025, 12, 17, 15, 30, 0).unwrap();
+        assert_eq!(
+            period_start(BudgetPeriod::Daily, now),
+            NaiveDate::from_ymd_opt(2025, 12, 17).unwrap()
+        );
+        assert_eq!(
+            period_start(BudgetPeriod::Monthly, now),
+            NaiveDate::from_ymd_opt(2025, 12, 1).unwrap()
+        );
+        assert_eq!(
+            period_reset(BudgetPeriod::Daily, now),
+            Utc.with_ymd_and_hms(2025, 12, 18, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            period_reset(BudgetPeriod::Monthly, now),
+            Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn validate_and_match_budgets() {
+        assert!(validate_budget(&budget()).is_ok());
+        assert!(validate_budget(&UsageBudget {
+            soft_limit: Some(3_000),
+            ..budget()
+        })
+        .is_err());
+        assert!(validate_budget(&UsageBudget {
+            soft_limit: None,
+            hard_limit: None,
+            ..budget()
+        })
+        .is_err());
+
+        assert!(matches(&budget(), "claude", "Claude-Opus-4-5", "unknown"));
+        assert!(!matches(
+            &budget(),
+            "claude",
+            "claude-sonnet-4-5",
+            "unknown"
+        ));
+    }
+
+    #[tokio::test]
+    async fn check_alerts_once_and_rejects_from_cached_usage() {
+        let (tracker, dir) = temp_tracker();
+        let guard = Arc::new(BudgetGuard::new(vec![budget()]));
+        let alerts = Arc::new(Mutex::new(Vec::new()));
+        let seen = alerts.clone();
+        guard.set_alert_listener(Some(Arc::new(move |alert| {
+            seen.lock().unwrap().push(alert.level);
+        })));
+        let listener_guard = guard.clone();
+        tracker.set_event_listener(Some(Arc::new(move |event| {
+            listener_guard.record_event(event)
+        })));
+        let check = || guard.check(&tracker, "claude", "claude-opus-4-5", "a@example.com");
+
+        assert!(check().await.is_none());
+        tracker.record_event(opus_event("r1", 1_200)).await.unwrap();
+        // Another model's usage doesn't count.
+        tracker
+            .record_event(test_event("r2", Utc::now().timestamp(), 5_000))
+            .await
+            .unwrap();
+        assert!(check().await.is_none());
+        assert!(check().await.is_none());
+        assert_eq!(*alerts.lock().unwrap(), [BudgetAlertLevel::Soft]);
+
+        // With the database gone, the rejection can only come from the cached usage that
+        // the write listener kept current.
+        tracker.record_event(opus_event("r3", 900)).await.unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+        let rejection = check().await.expect("hard limit should reject");
+        assert_eq!(rejection.used, 2_100);
+        assert_eq!(rejection.limit, 2_000);
+        assert_eq!(
+            rejection.resets_at,
+            period_reset(BudgetPeriod::Daily, Utc::now())
+        );
+        assert!(check().await.is_some());
+        assert_eq!(
+            *alerts.lock().unwrap(),
+            [BudgetAlertLevel::Soft, BudgetAlertLevel::Hard]
+        );
+    }
+
+    #[tokio::test]
+    async fn check_fails_open_when_usage_cannot_be_read() {
+        let (tracker, dir) = temp_tracker();
+        let guard = BudgetGuard::new(vec![UsageBudget {
+            hard_limit: Some(1),
+            ..budget()
+        }]);
+        tracker.record_event(opus_event("r1", 10)).await.unwrap();
+        assert!(guard
+            .check(&tracker, "claude", "claude-opus-4-5", "a@example.com")
+            .await
+            .is_some());
+
+        // Editing budgets drops cached usage, so the next check has to read the database.
+        guard.set_budgets(vec![UsageBudget {
+            hard_limit: Some(1),
+            ..budget()
+        }]);
+        std::fs::remove_dir_all(&dir).ok();
+        assert!(guard
+            .check(&tracker, "claude", "claude-opus-4-5", "a@example.com")
+            .await
+            .is_none());
+    }
+}
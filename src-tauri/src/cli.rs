@@ -0,0 +1,660 @@
+use crate::auth_manager;
+use crate::factory_settings;
+use crate::headless::{self, HeadlessConfig};
+use crate::pricing::PricingTable;
+use crate::server_manager::ServerManager;
+use crate::types::{
+    AppSettings, AuthCommand, FactoryCustomModelInput, ServiceType, UsageDashboardQuery,
+    UsageExportFormat, UsageExportKind, UsageExportRequest,
+};
+use crate::usage_tracker::{UsageQuery, UsageTracker};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::Read;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+const START_TIMEOUT_SECS: u64 = 20;
+const STOP_TIMEOUT_SECS: u64 = 15;
+const HEADLESS_LOG_FILE: &str = "codeforwarder-headless.log";
+
+const USAGE: &str = "\
+Usage: codeforwarder-cli [--config <path>] [--pretty] <command>
+
+Commands:
+  status                          Show whether the proxy and backend are listening
+  start                           Start a headless instance in the background
+  serve                           Run a headless instance in the foreground
+  stop                            Stop the headless instance started with `start`
+  accounts list                   List auth accounts by provider
+  accounts delete <file>          Delete an auth account file
+  auth <provider> [--email <e>]   Run the OAuth login flow for a provider
+  usage [--range 24h|7d|30d|all] [--from <date>] [--to <date>]
+        [--bucket minute|hour|day|week|month] [--utc-offset <minutes>]
+        [--time-zone <iana>] [--provider <p>] [--model <m>] [--account <key>]
+        [--client <id>] [--status success|error|4xx|5xx] [--page <n>] [--page-size <n>]
+                                  Print the usage dashboard
+  usage export [--format csv|jsonl] [--kind events|rollups] [--out <path>]
+               [--from <date>] [--to <date>] [--provider <p>] [--model <m>]
+               [--account <key>] [--status success|error|<code>]
+                                  Write usage to a file (default: ~/.cli-proxy-api/exports)
+  usage stats                     Show usage database size and row counts
+  usage maintain                  Apply the configured retention and compact the database
+  models install <agent> [--file <path>|-]
+                                  Install Factory custom models from a JSON array
+  providers set <provider> <on|off>
+                                  Enable or disable a provider
+
+Settings are read from the headless config file
+(default: ~/.cli-proxy-api/codeforwarder-headless.json).
+All commands print JSON on stdout; failures print {\"error\": ...} on stderr.";
+
+/// Options that consume the following argument as their value.
+const VALUE_OPTIONS: &[&str] = &[
+    "--config",
+    "--range",
+    "--email",
+    "--file",
+    "--format",
+    "--kind",
+    "--out",
+    "--from",
+    "--to",
+    "--provider",
+    "--model",
+    "--account",
+    "--status",
+    "--bucket",
+    "--utc-offset",
+    "--time-zone",
+    "--client",
+    "--page",
+    "--page-size",
+];
+
+#[derive(Debug, Default, PartialEq)]
+struct CliArgs {
+    positionals: Vec<String>,
+    options: HashMap<String, String>,
+    pretty: bool,
+}
+
+impl CliArgs {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut parsed = CliArgs::default();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if arg == "--pretty" {
+                parsed.pretty = true;
+                continue;
+            }
+            if let Some((name, value)) = arg.split_once('=') {
+                if VALUE_OPTIONS.contains(&name) {
+                    parsed.options.insert(name.to_string(), value.to_string());
+                    continue;
+                }
+            }
+            if VALUE_OPTIONS.contains(&arg.as_str()) {
+                let value = iter
+                    .next()
+                    .ok_or_else(|| format!("{} requires a value", arg))?;
+                parsed.options.insert(arg.clone(), value.clone());
+                continue;
+            }
+            if arg.starts_with("--") {
+                return Err(format!("Unknown option: {}", arg));
+            }
+            parsed.positionals.push(arg.clone());
+        }
+        Ok(parsed)
+    }
+
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(String::as_str)
+    }
+
+    fn parsed_option<T: std::str::FromStr>(&self, name: &str) -> Result<Option<T>, String> {
+        self.option(name)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|_| format!("Invalid {}: {}", name, value))
+            })
+            .transpose()
+    }
+
+    /// Parses a lowercase enum value the same way the Tauri commands deserialize it.
+    fn enum_option<T: serde::de::DeserializeOwned>(&self, name: &str) -> Result<Option<T>, String> {
+        self.option(name)
+            .map(|value| {
+                serde_json::from_value(Value::String(value.to_string()))
+                    .map_err(|_| format!("Invalid {}: {}", name, value))
+            })
+            .transpose()
+    }
+
+    fn config_path(&self) -> PathBuf {
+        self.option("--config")
+            .map(PathBuf::from)
+            .unwrap_or_else(headless::default_config_path)
+    }
+}
+
+/// Entry point for the `codeforwarder-cli` binary. Returns the process exit code.
+pub fn run(args: &[String]) -> i32 {
+    let parsed = match CliArgs::parse(args) {
+        Ok(parsed) => parsed,
+        Err(e) => return fail(&e, false),
+    };
+
+    let positionals: Vec<&str> = parsed.positionals.iter().map(String::as_str).collect();
+    let result = match positionals.as_slice() {
+        [] | ["help"] => {
+            println!("{}", USAGE);
+            return 0;
+        }
+        ["serve"] => {
+            let mut serve_args = Vec::new();
+            if let Some(config) = parsed.option("--config") {
+                serve_args.push(format!("--config={}", config));
+            }
+            return headless::run(&serve_args);
+        }
+        ["status"] => status(&parsed),
+        ["start"] => start(&parsed),
+        ["stop"] => stop(),
+        ["accounts", "list"] => list_accounts(),
+        ["accounts", "delete", file_path] => delete_account(file_path),
+        ["auth", provider] => run_auth(&parsed, provider),
+        ["usage"] => usage(&parsed),
+        ["usage", "export"] => export_usage(&parsed),
+        ["usage", "stats"] => usage_stats(),
+        ["usage", "maintain"] => maintain_usage(&parsed),
+        ["models", "install", agent_key] => install_models(&parsed, agent_key),
+        ["providers", "set", provider, state] => set_provider_enabled(&parsed, provider, state),
+        _ => Err(format!(
+            "Unknown command: {}. Run `codeforwarder-cli help`.",
+            parsed.positionals.join(" ")
+        )),
+    };
+
+    match result {
+        Ok(value) => {
+            print_json(&value, parsed.pretty);
+            0
+        }
+        Err(e) => fail(&e, parsed.pretty),
+    }
+}
+
+fn print_json(value: &impl Serialize, pretty: bool) {
+    let rendered = if pretty {
+        serde_json::to_string_pretty(value)
+    } else {
+        serde_json::to_string(value)
+    };
+    match rendered {
+        Ok(text) => println!("{}", text),
+        Err(e) => eprintln!("{{\"error\":\"Failed to serialize output: {}\"}}", e),
+    }
+}
+
+fn fail(message: &str, pretty: bool) -> i32 {
+    let value = json!({ "error": message });
+    let rendered = if pretty {
+        serde_json::to_string_pretty(&value)
+    } else {
+        serde_json::to_string(&value)
+    };
+    eprintln!("{}", rendered.unwrap_or_default());
+    1
+}
+
+fn block_on<F: std::future::Future>(future: F) -> Result<F::Output, String> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| format!("Failed to build tokio runtime: {}", e))?;
+    Ok(runtime.block_on(future))
+}
+
+fn to_value(value: impl Serialize) -> Result<Value, String> {
+    serde_json::to_value(value).map_err(|e| format!("Failed to serialize output: {}", e))
+}
+
+// ---------------------------------------------------------------------------
+// Lifecycle
+// ---------------------------------------------------------------------------
+
+/// Address to probe for a listener bound to `bind_address:port` from this machine.
+fn local_probe_addr(bind_address: &str, port: u16) -> SocketAddr {
+    let ip = match bind_address.trim().parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) if ip.is_unspecified() => IpAddr::V4(Ipv4Addr::LOCALHOST),
+        Ok(IpAddr::V6(ip)) if ip.is_unspecified() => IpAddr::V6(Ipv6Addr::LOCALHOST),
+        Ok(ip) => ip,
+        Err(_) => IpAddr::V4(Ipv4Addr::LOCALHOST),
+    };
+    SocketAddr::new(ip, port)
+}
+
+fn is_listening(addr: SocketAddr) -> bool {
+    TcpStream::connect_timeout(&addr, Duration::from_millis(300)).is_ok()
+}
+
+fn listener_status(settings: &AppSettings) -> (bool, bool) {
+    let proxy = is_listening(local_probe_addr(
+        &settings.bind_address,
+        settings.proxy_port,
+    ));
+    let backend = is_listening(local_probe_addr("127.0.0.1", settings.backend_port));
+    (proxy, backend)
+}
+
+fn status(args: &CliArgs) -> Result<Value, String> {
+    let config = headless::load_config(&args.config_path())?;
+    let settings = &config.settings;
+    let (proxy_listening, backend_listening) = listener_status(settings);
+    Ok(json!({
+        "is_running": proxy_listening && backend_listening,
+        "proxy_listening": proxy_listening,
+        "backend_listening": backend_listening,
+        "proxy_port": settings.proxy_port,
+        "backend_port": settings.backend_port,
+        "proxy_url": settings.proxy_url(),
+        "headless_pid": headless::load_pid(),
+    }))
+}
+
+fn start(args: &CliArgs) -> Result<Value, String> {
+    let config_path = args.config_path();
+    let config = headless::load_config(&config_path)?;
+    let settings = &config.settings;
+    settings.validate_network()?;
+
+    let (proxy_listening, backend_listening) = listener_status(settings);
+    if proxy_listening || backend_listening {
+        return Err(format!(
+            "Ports {}/{} are already in use. Is CodeForwarder already running?",
+            settings.proxy_port, settings.backend_port
+        ));
+    }
+
+    let log_path = auth_manager::get_auth_dir().join(HEADLESS_LOG_FILE);
+    let log_file = std::fs::File::create(&log_path)
+        .map_err(|e| format!("Failed to create log file: {}", e))?;
+    let log_file_err = log_file
+        .try_clone()
+        .map_err(|e| format!("Failed to create log file: {}", e))?;
+
+    let exe = std::env::current_exe()
+        .map_err(|e| format!("Failed to resolve current executable: {}", e))?;
+    let mut cmd = std::process::Command::new(exe);
+    cmd.arg(format!("--config={}", config_path.to_string_lossy()))
+        .arg("serve")
+        .stdin(std::process::Stdio::null())
+        .stdout(log_file)
+        .stderr(log_file_err);
+    detach(&mut cmd);
+
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to spawn headless instance: {}", e))?;
+
+    let deadline = Instant::now() + Duration::from_secs(START_TIMEOUT_SECS);
+    let proxy_addr = local_probe_addr(&settings.bind_address, settings.proxy_port);
+    let backend_addr = local_probe_addr("127.0.0.1", settings.backend_port);
+    loop {
+        if let Ok(Some(exit)) = child.try_wait() {
+            return Err(format!(
+                "Headless instance exited during startup ({}). See {}",
+                exit,
+                log_path.to_string_lossy()
+            ));
+        }
+        if is_listening(proxy_addr) && is_listening(backend_addr) {
+            break;
+        }
+        if Instant::now() >= deadline {
+            return Err(format!(
+                "Timed out waiting for the server to start. See {}",
+                log_path.to_string_lossy()
+            ));
+        }
+        std::thread::sleep(Duration::from_millis(250));
+    }
+
+    Ok(json!({
+        "pid": child.id(),
+        "proxy_url": settings.proxy_url(),
+        "proxy_port": settings.proxy_port,
+        "backend_port": settings.backend_port,
+        "log_path": log_path.to_string_lossy(),
+    }))
+}
+
+#[cfg(unix)]
+fn detach(cmd: &mut std::process::Command) {
+    use std::os::unix::process::CommandExt;
+    // New process group so the daemon survives the invoking shell's Ctrl-C.
+    cmd.process_group(0);
+}
+
+#[cfg(windows)]
+fn detach(cmd: &mut std::process::Command) {
+    use std::os::windows::process::CommandExt;
+    const DETACHED_PROCESS: u32 = 0x00000008;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+    cmd.creation_flags(DETACHED_PROCESS | CREATE_NO_WINDOW);
+}
+
+fn stop() -> Result<Value, String> {
+    let Some(pid) = headless::load_pid() else {
+        return Ok(json!({ "stopped": false, "reason": "No headless instance is running" }));
+    };
+
+    if !is_process_alive(pid) {
+        headless::clear_pid();
+        return Ok(json!({ "stopped": false, "pid": pid, "reason": "Stale PID file removed" }));
+    }
+
+    signal_terminate(pid)?;
+
+    let deadline = Instant::now() + Duration::from_secs(STOP_TIMEOUT_SECS);
+    while is_process_alive(pid) {
+        if Instant::now() >= deadline {
+            return Err(format!(
+                "Headless instance (PID {}) did not exit within {}s",
+                pid, STOP_TIMEOUT_SECS
+            ));
+        }
+        std::thread::sleep(Duration::from_millis(200));
+    }
+    headless::clear_pid();
+
+    Ok(json!({ "stopped": true, "pid": pid }))
+}
+
+#[cfg(unix)]
+fn is_process_alive(pid: u32) -> bool {
+    std::process::Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+#[cfg(unix)]
+fn signal_terminate(pid: u32) -> Result<(), String> {
+    let output = std::process::Command::new("kill")
+        .args(["-TERM", &pid.to_string()])
+        .output()
+        .map_err(|e| format!("Failed to run kill: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to signal PID {}: {}",
+            pid,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn is_process_alive(pid: u32) -> bool {
+    std::process::Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/NH", "/FO", "CSV"])
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).contains(&format!("\"{}\"", pid)))
+        .unwrap_or(false)
+}
+
+#[cfg(windows)]
+fn signal_terminate(pid: u32) -> Result<(), String> {
+    // Console-less processes cannot receive Ctrl-C, so the tree is killed. The next start sweeps
+    // any backend that outlives it via `kill_orphaned_processes`.
+    let output = std::process::Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/T", "/F"])
+        .output()
+        .map_err(|e| format!("Failed to run taskkill: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to stop PID {}: {}",
+            pid,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Accounts
+// ---------------------------------------------------------------------------
+
+fn list_accounts() -> Result<Value, String> {
+    let accounts: HashMap<String, _> = auth_manager::scan_auth_directory()
+        .into_iter()
+        .map(|(st, sa)| (st.provider_key().to_string(), sa))
+        .collect();
+    to_value(accounts)
+}
+
+fn delete_account(file_path: &str) -> Result<Value, String> {
+    auth_manager::delete_account(file_path)?;
+    Ok(json!({ "deleted": true, "file_path": file_path }))
+}
+
+fn parse_auth_command(provider: &str, email: Option<&str>) -> Result<AuthCommand, String> {
+    let service = ServiceType::from_str_loose(provider)
+        .ok_or_else(|| format!("Unknown provider: {}", provider))?;
+    match service {
+        ServiceType::Claude => Ok(AuthCommand::ClaudeLogin),
+        ServiceType::Codex => Ok(AuthCommand::CodexLogin),
+        ServiceType::Copilot => Ok(AuthCommand::CopilotLogin),
+        ServiceType::Gemini => Ok(AuthCommand::GeminiLogin),
+        ServiceType::Antigravity => Ok(AuthCommand::AntigravityLogin),
+        ServiceType::Qwen => {
+            let email = email
+                .map(str::trim)
+                .filter(|e| !e.is_empty())
+                .ok_or_else(|| "Qwen login requires --email".to_string())?;
+            Ok(AuthCommand::QwenLogin {
+                email: email.to_string(),
+            })
+        }
+        ServiceType::Zai => Err("Z.AI uses an API key, not an OAuth login".to_string()),
+    }
+}
+
+fn run_auth(args: &CliArgs, provider: &str) -> Result<Value, String> {
+    let command = parse_auth_command(provider, args.option("--email"))?;
+    let config = headless::load_config(&args.config_path())?;
+    let (config_path, binary_path) = headless::prepare_runtime(&config)?;
+
+    let (success, message) = block_on(ServerManager::run_auth_command(
+        &binary_path.to_string_lossy(),
+        &config_path.to_string_lossy(),
+        &command,
+    ))??;
+    Ok(json!({ "success": success, "message": message }))
+}
+
+// ---------------------------------------------------------------------------
+// Usage / models / providers
+// ---------------------------------------------------------------------------
+
+fn usage(args: &CliArgs) -> Result<Value, String> {
+    let owned = |name: &str| args.option(name).map(str::to_string);
+    let request = UsageDashboardQuery {
+        range: owned("--range"),
+        from: owned("--from"),
+        to: owned("--to"),
+        bucket: args.enum_option("--bucket")?,
+        utc_offset_minutes: args.parsed_option("--utc-offset")?.unwrap_or(0),
+        time_zone: owned("--time-zone"),
+        provider: owned("--provider"),
+        model: owned("--model"),
+        account_key: owned("--account"),
+        client_id: owned("--client"),
+        status: args.enum_option("--status")?,
+        page: args.parsed_option("--page")?.unwrap_or(0),
+        page_size: args.parsed_option("--page-size")?.unwrap_or(0),
+    };
+    let query = UsageQuery::resolve(&request, chrono::Utc::now())?;
+    let config = headless::load_config(&args.config_path())?;
+    let pricing = PricingTable::new(&config.settings.pricing_overrides);
+    let tracker = UsageTracker::new()?;
+    let dashboard = block_on(tracker.get_usage_dashboard(query, pricing))??;
+    to_value(dashboard)
+}
+
+fn export_usage(args: &CliArgs) -> Result<Value, String> {
+    let format = match args.option("--format").unwrap_or("csv") {
+        "csv" => UsageExportFormat::Csv,
+        "jsonl" => UsageExportFormat::Jsonl,
+        other => return Err(format!("Unknown export format: {}", other)),
+    };
+    let kind = match args.option("--kind").unwrap_or("events") {
+        "events" => UsageExportKind::Events,
+        "rollups" => UsageExportKind::Rollups,
+        other => return Err(format!("Unknown export kind: {}", other)),
+    };
+    let owned = |name: &str| args.option(name).map(str::to_string);
+    let request = UsageExportRequest {
+        path: owned("--out"),
+        format,
+        kind,
+        from: owned("--from"),
+        to: owned("--to"),
+        provider: owned("--provider"),
+        model: owned("--model"),
+        account_key: owned("--account"),
+        status: owned("--status"),
+    };
+    let tracker = UsageTracker::new()?;
+    let result = block_on(tracker.export_usage(request))??;
+    to_value(result)
+}
+
+fn usage_stats() -> Result<Value, String> {
+    let tracker = UsageTracker::new()?;
+    let stats = block_on(tracker.get_storage_stats())??;
+    to_value(stats)
+}
+
+fn maintain_usage(args: &CliArgs) -> Result<Value, String> {
+    let config = headless::load_config(&args.config_path())?;
+    let tracker = UsageTracker::new()?;
+    let report = block_on(tracker.run_maintenance(config.settings.usage_retention))??;
+    to_value(report)
+}
+
+fn read_models_input(file: Option<&str>) -> Result<Vec<FactoryCustomModelInput>, String> {
+    let contents = match file {
+        None | Some("-") => {
+            let mut buf = String::new();
+            std::io::stdin()
+                .read_to_string(&mut buf)
+                .map_err(|e| format!("Failed to read models from stdin: {}", e))?;
+            buf
+        }
+        Some(path) => std::fs::read_to_string(Path::new(path))
+            .map_err(|e| format!("Failed to read models file: {}", e))?,
+    };
+    serde_json::from_str(&contents).map_err(|e| format!("Failed to parse models JSON: {}", e))
+}
+
+fn install_models(args: &CliArgs, agent_key: &str) -> Result<Value, String> {
+    let models = read_models_input(args.option("--file"))?;
+    let config = headless::load_config(&args.config_path())?;
+    let result =
+        factory_settings::install_agent_models(agent_key, models, &[config.settings.proxy_port])?;
+    to_value(result)
+}
+
+fn parse_toggle(raw: &str) -> Result<bool, String> {
+    match raw.to_ascii_lowercase().as_str() {
+        "on" | "true" | "enable" | "enabled" | "1" => Ok(true),
+        "off" | "false" | "disable" | "disabled" | "0" => Ok(false),
+        _ => Err(format!("Expected on/off, got: {}", raw)),
+    }
+}
+
+fn set_provider_enabled(args: &CliArgs, provider: &str, state: &str) -> Result<Value, String> {
+    let enabled = parse_toggle(state)?;
+    let provider_key = ServiceType::from_str_loose(provider)
+        .map(|st| st.provider_key().to_string())
+        .ok_or_else(|| format!("Unknown provider: {}", provider))?;
+
+    let config_path = args.config_path();
+    let mut config: HeadlessConfig = headless::load_config(&config_path)?;
+    config
+        .settings
+        .enabled_providers
+        .insert(provider_key.clone(), enabled);
+    headless::save_config(&config_path, &config)?;
+
+    // Regenerate the merged config so a running backend hot-reloads it.
+    headless::write_merged_config(&config)?;
+
+    Ok(json!({ "provider": provider_key, "enabled": enabled }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(raw: &[&str]) -> Vec<String> {
+        raw.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_collects_positionals_and_options() {
+        let parsed = CliArgs::parse(&args(&[
+            "--pretty",
+            "usage",
+            "--range",
+            "30d",
+            "--config=/tmp/cfg.json",
+        ]))
+        .unwrap();
+        assert!(parsed.pretty);
+        assert_eq!(parsed.positionals, vec!["usage".to_string()]);
+        assert_eq!(parsed.option("--range"), Some("30d"));
+        assert_eq!(parsed.config_path(), PathBuf::from("/tmp/cfg.json"));
+    }
+
+    #[test]
+    fn parse_rejects_unknown_and_incomplete_options() {
+        assert!(CliArgs::parse(&args(&["status", "--verbose"])).is_err());
+        assert!(CliArgs::parse(&args(&["usage", "--range"])).is_err());
+    }
+
+    #[test]
+    fn parse_auth_command_maps_providers() {
+        assert!(matches!(
+            parse_auth_command("copilot", None),
+            Ok(AuthCommand::CopilotLogin)
+        ));
+        assert!(parse_auth_command("qwen", None).is_err());
+        assert!(matches!(
+            parse_auth_command("qwen", Some("a@b.c")),
+            Ok(AuthCommand::QwenLogin { .. })
+        ));
+        assert!(parse_auth_command("zai", None).is_err());
+        assert!(parse_auth_command("nope", None).is_err());
+    }
+
+    #[test]
+    fn local_probe_addr_maps_unspecified_to_loopback() {
+        assert_eq!(
+            local_probe_addr("0.0.0.0", 8317),
+            "127.0.0.1:8317".parse::<SocketAddr>().unwrap()
+        );
+        assert_eq!(
+            local_probe_addr("192.168.1.5", 8317),
+            "192.168.1.5:8317".parse::<SocketAddr>().unwrap()
+        );
+    }
+}
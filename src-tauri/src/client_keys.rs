@@ -0,0 +1,243 @@
+use crate::auth_manager;
+use crate::secure_store;
+use crate::types::ClientKeyInfo;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+const CLIENT_KEYS_FILE: &str = "codeforwarder-client-keys.json";
+const CLIENT_KEY_PREFIX: &str = "cfk-";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredClientKey {
+    id: String,
+    name: String,
+    /// Encrypted with `secure_store`.
+    key: String,
+    created_at: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ClientKeysFile {
+    keys: Vec<StoredClientKey>,
+}
+
+/// A decrypted client key, kept in memory by ThinkingProxy.
+#[derive(Debug, Clone)]
+pub struct ClientKey {
+    pub id: String,
+    pub name: String,
+    pub key: String,
+    pub created_at: String,
+}
+
+impl ClientKey {
+    pub fn info(&self) -> ClientKeyInfo {
+        ClientKeyInfo {
+            id: self.id.clone(),
+            name: self.name.clone(),
+            key_preview: preview(&self.key),
+            created_at: self.created_at.clone(),
+        }
+    }
+}
+
+/// Client identity attached to usage events.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientIdentity {
+    pub id: String,
+    pub name: String,
+}
+
+/// Live client-key state used by ThinkingProxy for every inference request.
+#[derive(Debug, Clone, Default)]
+pub struct ClientAuthConfig {
+    /// When set, requests without a known key are rejected with 401.
+    pub required: bool,
+    pub keys: Vec<ClientKey>,
+}
+
+impl ClientAuthConfig {
+    pub fn load(required: bool) -> Self {
+        let keys = load_client_keys().unwrap_or_else(|e| {
+            log::error!("[ClientKeys] Failed to load client keys: {}", e);
+            Vec::new()
+        });
+        Self { required, keys }
+    }
+
+    /// Matches `Authorization: Bearer <key>` or `x-api-key: <key>` against the issued keys.
+    pub fn identify(&self, headers: &hyper::HeaderMap) -> Option<ClientIdentity> {
+        if self.keys.is_empty() {
+            return None;
+        }
+
+        let bearer = headers
+            .get(hyper::header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.trim())
+            .map(|v| {
+                v.strip_prefix("Bearer ")
+                    .or_else(|| v.strip_prefix("bearer "))
+                    .unwrap_or(v)
+                    .trim()
+            });
+        let api_key = headers
+            .get("x-api-key")
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.trim());
+
+        [bearer, api_key]
+            .into_iter()
+            .flatten()
+            .filter(|candidate| !candidate.is_empty())
+            .find_map(|candidate| self.keys.iter().find(|k| k.key == candidate))
+            .map(|k| ClientIdentity {
+                id: k.id.clone(),
+                name: k.name.clone(),
+            })
+    }
+}
+
+fn client_keys_path() -> PathBuf {
+    auth_manager::get_auth_dir().join(CLIENT_KEYS_FILE)
+}
+
+fn preview(key: &str) -> String {
+    let tail: String = key
+        .chars()
+        .rev()
+        .take(4)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect();
+    format!("{}...{}", CLIENT_KEY_PREFIX, tail)
+}
+
+fn read_file() -> Result<ClientKeysFile, String> {
+    let path = client_keys_path();
+    if !path.exists() {
+        return Ok(ClientKeysFile::default());
+    }
+    let contents =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read client keys: {}", e))?;
+    serde_json::from_str(&contents).map_err(|e| format!("Failed to parse client keys: {}", e))
+}
+
+fn write_file(file: &ClientKeysFile) -> Result<(), String> {
+    let rendered = serde_json::to_string_pretty(file)
+        .map_err(|e| format!("Failed to serialize client keys: {}", e))?;
+    fs::write(client_keys_path(), rendered)
+        .map_err(|e| format!("Failed to write client keys: {}", e))
+}
+
+pub fn load_client_keys() -> Result<Vec<ClientKey>, String> {
+    let file = read_file()?;
+    let mut keys = Vec::with_capacity(file.keys.len());
+    for stored in file.keys {
+        match secure_store::decrypt_secret(&stored.key) {
+            Ok(key) if !key.is_empty() => keys.push(ClientKey {
+                id: stored.id,
+                name: stored.name,
+                key,
+                created_at: stored.created_at,
+            }),
+            Ok(_) => {}
+            Err(e) => log::warn!(
+                "[ClientKeys] Failed to decrypt key '{}', skipping: {}",
+                stored.name,
+                e
+            ),
+        }
+    }
+    Ok(keys)
+}
+
+/// Issues a new key. The returned `ClientKey` is the only time the full key is exposed.
+pub fn create_client_key(name: &str) -> Result<ClientKey, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Client key name cannot be empty".to_string());
+    }
+
+    let mut file = read_file()?;
+    if file.keys.iter().any(|k| k.name.eq_ignore_ascii_case(name)) {
+        return Err(format!("A client key named '{}' already exists", name));
+    }
+
+    let key = format!("{}{}", CLIENT_KEY_PREFIX, Uuid::new_v4().simple());
+    let client_key = ClientKey {
+        id: Uuid::new_v4().to_string(),
+        name: name.to_string(),
+        key,
+        created_at: Utc::now().to_rfc3339(),
+    };
+    file.keys.push(StoredClientKey {
+        id: client_key.id.clone(),
+        name: client_key.name.clone(),
+        key: secure_store::encrypt_secret(&client_key.key)?,
+        created_at: client_key.created_at.clone(),
+    });
+    write_file(&file)?;
+
+    Ok(client_key)
+}
+
+pub fn delete_client_key(id: &str) -> Result<(), String> {
+    let mut file = read_file()?;
+    let before = file.keys.len();
+    file.keys.retain(|k| k.id != id);
+    if file.keys.len() == before {
+        return Err(format!("Client key not found: {}", id));
+    }
+    write_file(&file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ClientAuthConfig {
+        ClientAuthConfig {
+            required: true,
+            keys: vec![ClientKey {
+                id: "id-1".to_string(),
+                name: "Factory".to_string(),
+                key: "cfk-abc123".to_string(),
+                created_at: String::new(),
+            }],
+        }
+    }
+
+    #[test]
+    fn identify_accepts_bearer_and_x_api_key() {
+        let config = config();
+
+        let mut headers = hyper::HeaderMap::new();
+        headers.insert("authorization", "Bearer cfk-abc123".parse().unwrap());
+        assert_eq!(
+            config.identify(&headers).map(|c| c.name),
+            Some("Factory".to_string())
+        );
+
+        let mut headers = hyper::HeaderMap::new();
+        headers.insert("authorization", "Bearer dummy".parse().unwrap());
+        headers.insert("x-api-key", "cfk-abc123".parse().unwrap());
+        assert_eq!(
+            config.identify(&headers).map(|c| c.id),
+            Some("id-1".to_string())
+        );
+
+        let mut headers = hyper::HeaderMap::new();
+        headers.insert("x-api-key", "cfk-wrong".parse().unwrap());
+        assert!(config.identify(&headers).is_none());
+    }
+
+    #[test]
+    fn preview_hides_all_but_the_tail() {
+        assert_eq!(preview("cfk-0123456789abcdef"), "cfk-...cdef");
+    }
+}
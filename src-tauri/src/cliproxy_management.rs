@@ -4,7 +4,6 @@ use reqwest::header::{HeaderMap, HeaderValue};
 use std::sync::OnceLock;
 use std::time::Duration;
 
-const MANAGEMENT_BASE_URL: &str = "http://127.0.0.1:8318";
 const MANAGEMENT_TIMEOUT_SECS: u64 = 5;
 
 fn shared_client() -> Result<&'static reqwest::Client, String> {
@@ -47,6 +46,7 @@ fn management_headers() -> Result<HeaderMap, String> {
 }
 
 pub async fn fetch_provider_model_definitions(
+    backend_port: u16,
     channel: &str,
 ) -> Result<ProviderModelDefinitionsResponse, String> {
     let channel = sanitize_channel(channel)?;
@@ -54,8 +54,8 @@ pub async fn fetch_provider_model_definitions(
     let headers = management_headers()?;
 
     let url = format!(
-        "{}/v0/management/model-definitions/{}",
-        MANAGEMENT_BASE_URL, channel
+        "http://127.0.0.1:{}/v0/management/model-definitions/{}",
+        backend_port, channel
     );
 
     let resp = client
@@ -1,17 +1,28 @@
 use crate::auth_manager;
 use crate::binary_manager;
+use crate::budgets;
+use crate::client_keys::{self, ClientAuthConfig};
 use crate::cliproxy_management;
 use crate::config_manager;
 use crate::factory_settings;
+use crate::failover;
+use crate::model_rules;
+use crate::pricing::{self, PricingTable};
+use crate::request_capture;
+use crate::retries;
+use crate::server_logs::LogFilter;
 use crate::server_manager::ServerManager;
+use crate::server_supervisor;
 use crate::settings;
-use crate::thinking_proxy::ThinkingProxy;
+use crate::thinking_proxy::{ThinkingProxy, MAX_DRAIN_TIMEOUT_SECS};
+use crate::timeouts;
 use crate::tray;
 use crate::types::*;
-use crate::usage_tracker::{UsageRangeQuery, UsageTracker};
+use crate::usage_tracker::{UsageQuery, UsageTracker};
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use tauri::{Emitter, State};
 use tauri_plugin_autostart::ManagerExt as AutoStartManagerExt;
 use tokio::sync::{Mutex, RwLock};
@@ -44,9 +55,9 @@ pub async fn get_server_state(
     sm.refresh_running_status().await;
     let tp = state.thinking_proxy.read().await;
     Ok(ServerState {
-        is_running: sm.is_running() && tp.is_running(),
-        proxy_port: 8317,
-        backend_port: 8318,
+        is_running: sm.is_ready() && tp.is_running(),
+        proxy_port: tp.proxy_port,
+        backend_port: tp.target_port,
         binary_available: binary_manager::is_binary_available_for_app(&app),
         binary_downloading: state.binary_downloading.load(Ordering::Relaxed),
     })
@@ -55,55 +66,69 @@ pub async fn get_server_state(
 #[tauri::command]
 pub async fn start_server(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<(), String> {
     let _lifecycle_guard = state.lifecycle_lock.lock().await;
+    start_server_locked(&app, &state).await
+}
 
+/// (Re)starts the proxy and backend from the saved settings. Callers hold `lifecycle_lock`.
+async fn start_server_locked(app: &tauri::AppHandle, state: &AppState) -> Result<(), String> {
     let app_for_binary = app.clone();
     let binary_path =
         run_blocking(move || binary_manager::ensure_binary_installed(&app_for_binary)).await?;
 
-    let settings = settings::load_settings(&app);
+    let settings = settings::load_settings(app);
+    settings.validate_network()?;
     let app_for_config = app.clone();
     let enabled_providers = settings.enabled_providers.clone();
+    let backend_port = settings.backend_port;
     let config_path = run_blocking(move || {
-        config_manager::get_merged_config_path(&app_for_config, &enabled_providers)
+        config_manager::get_merged_config_path(&app_for_config, &enabled_providers, backend_port)
     })
     .await?;
     let config_path_str = config_path.to_string_lossy().to_string();
     let binary_path_str = binary_path.to_string_lossy().to_string();
 
     // Always perform a clean restart so stale background processes cannot block startup.
-    {
-        let mut tp = state.thinking_proxy.write().await;
-        tp.stop().await;
-    }
+    let drain = state.thinking_proxy.write().await.begin_stop();
+    drain.wait().await;
     {
         let mut sm = state.server_manager.write().await;
         sm.stop().await;
     }
     ServerManager::kill_orphaned_processes().await;
-    ServerManager::cleanup_port_conflicts_for_restart().await?;
+    ServerManager::cleanup_port_conflicts_for_restart(&[settings.proxy_port, backend_port]).await?;
 
     // Start thinking proxy first
     {
         let mut tp = state.thinking_proxy.write().await;
+        tp.apply_settings(&settings);
         tp.start()
             .await
             .map_err(|e| format!("Failed to start thinking proxy: {}", e))?;
     }
 
     // Then start the backend server
-    {
+    let generation = {
         let mut sm = state.server_manager.write().await;
         sm.start(&config_path_str, &binary_path_str).await?;
+        sm.generation()
+    };
+    if let Err(e) = ServerManager::wait_until_ready(&state.server_manager, backend_port).await {
+        let drain = state.thinking_proxy.write().await.begin_stop();
+        drain.wait().await;
+        state.server_manager.write().await.stop().await;
+        return Err(e);
     }
+    state.thinking_proxy.read().await.refresh_model_catalog();
+    server_supervisor::watch(app, generation);
 
     // Update tray state
-    tray::update_tray_state(&app, true);
+    tray::update_tray_state(app, Some(settings.proxy_port));
 
     // Emit status change
     let server_state = ServerState {
         is_running: true,
-        proxy_port: 8317,
-        backend_port: 8318,
+        proxy_port: settings.proxy_port,
+        backend_port: settings.backend_port,
         binary_available: true,
         binary_downloading: false,
     };
@@ -117,10 +142,12 @@ pub async fn stop_server(app: tauri::AppHandle, state: State<'_, AppState>) -> R
     let _lifecycle_guard = state.lifecycle_lock.lock().await;
 
     // Stop thinking proxy first
-    {
+    let (proxy_port, backend_port, drain) = {
         let mut tp = state.thinking_proxy.write().await;
-        tp.stop().await;
-    }
+        let drain = tp.begin_stop();
+        (tp.proxy_port, tp.target_port, drain)
+    };
+    drain.wait().await;
 
     // Then stop backend
     {
@@ -129,13 +156,13 @@ pub async fn stop_server(app: tauri::AppHandle, state: State<'_, AppState>) -> R
     }
 
     // Update tray state
-    tray::update_tray_state(&app, false);
+    tray::update_tray_state(&app, None);
 
     // Emit status change
     let server_state = ServerState {
         is_running: false,
-        proxy_port: 8317,
-        backend_port: 8318,
+        proxy_port,
+        backend_port,
         binary_available: binary_manager::is_binary_available_for_app(&app),
         binary_downloading: false,
     };
@@ -144,6 +171,34 @@ pub async fn stop_server(app: tauri::AppHandle, state: State<'_, AppState>) -> R
     Ok(())
 }
 
+#[tauri::command]
+pub async fn get_server_logs(
+    state: State<'_, AppState>,
+    level: Option<String>,
+    contains: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<ServerLogLine>, String> {
+    let filter = LogFilter::new(level.as_deref(), contains.as_deref(), limit);
+    let sm = state.server_manager.read().await;
+    Ok(sm.get_logs(&filter).await)
+}
+
+#[tauri::command]
+pub async fn set_server_log_persistence(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    enabled: bool,
+) -> Result<(), String> {
+    {
+        let sm = state.server_manager.read().await;
+        sm.set_log_persistence(enabled)?;
+    }
+
+    let mut current = settings::load_settings(&app);
+    current.persist_server_logs = enabled;
+    settings::save_settings(&app, &current)
+}
+
 #[tauri::command]
 pub async fn get_auth_accounts() -> Result<HashMap<String, ServiceAccounts>, String> {
     let accounts = tokio::task::spawn_blocking(auth_manager::scan_auth_directory)
@@ -169,8 +224,9 @@ pub async fn run_auth(
     let settings = settings::load_settings(&app);
     let app_for_config = app.clone();
     let enabled_providers = settings.enabled_providers.clone();
+    let backend_port = settings.backend_port;
     let config_path = run_blocking(move || {
-        config_manager::get_merged_config_path(&app_for_config, &enabled_providers)
+        config_manager::get_merged_config_path(&app_for_config, &enabled_providers, backend_port)
     })
     .await?;
     let config_path_str = config_path.to_string_lossy().to_string();
@@ -221,8 +277,10 @@ pub async fn set_provider_enabled(
     // Regenerate config (hot reload)
     let app_for_config = app.clone();
     let enabled_providers = current.enabled_providers.clone();
+    let backend_port = current.backend_port;
     run_blocking(move || {
-        config_manager::get_merged_config_path(&app_for_config, &enabled_providers).map(|_| ())
+        config_manager::get_merged_config_path(&app_for_config, &enabled_providers, backend_port)
+            .map(|_| ())
     })
     .await?;
 
@@ -267,6 +325,85 @@ pub async fn set_vercel_config(
     Ok(())
 }
 
+async fn reload_client_auth(app: &tauri::AppHandle, state: &State<'_, AppState>) {
+    let required = settings::load_settings(app).require_client_api_key;
+    let config = run_blocking(move || Ok(ClientAuthConfig::load(required)))
+        .await
+        .unwrap_or_default();
+    let client_auth = state.thinking_proxy.read().await.client_auth.clone();
+    *client_auth.write().await = config;
+}
+
+#[tauri::command]
+pub async fn list_client_keys() -> Result<Vec<ClientKeyInfo>, String> {
+    run_blocking(|| {
+        Ok(client_keys::load_client_keys()?
+            .iter()
+            .map(|k| k.info())
+            .collect())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn create_client_key(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    name: String,
+) -> Result<CreatedClientKey, String> {
+    let created = run_blocking(move || client_keys::create_client_key(&name)).await?;
+    reload_client_auth(&app, &state).await;
+    Ok(CreatedClientKey {
+        id: created.id,
+        name: created.name,
+        key: created.key,
+        created_at: created.created_at,
+    })
+}
+
+#[tauri::command]
+pub async fn delete_client_key(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<(), String> {
+    let required = settings::load_settings(&app).require_client_api_key;
+    run_blocking(move || {
+        if required && client_keys::load_client_keys()?.len() <= 1 {
+            return Err(
+                "Turn off \"Require client API key\" before deleting the last key".to_string(),
+            );
+        }
+        client_keys::delete_client_key(&id)
+    })
+    .await?;
+    reload_client_auth(&app, &state).await;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_require_client_api_key(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    enabled: bool,
+) -> Result<(), String> {
+    if enabled {
+        let has_keys = run_blocking(|| Ok(!client_keys::load_client_keys()?.is_empty())).await?;
+        if !has_keys {
+            return Err("Create a client key before requiring one".to_string());
+        }
+    }
+
+    let mut current = settings::load_settings(&app);
+    current.require_client_api_key = enabled;
+    if !enabled {
+        current.validate_network()?;
+    }
+    settings::save_settings(&app, &current)?;
+    reload_client_auth(&app, &state).await;
+    Ok(())
+}
+
 #[tauri::command]
 pub fn set_launch_at_login(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
     if enabled {
@@ -286,6 +423,50 @@ pub fn set_launch_at_login(app: tauri::AppHandle, enabled: bool) -> Result<(), S
     Ok(())
 }
 
+/// Persists the listen address and ports. A running server is restarted on them right away;
+/// otherwise they apply on the next start. Factory models installed by CodeForwarder move to
+/// a new proxy port once nothing is left listening on the old one.
+#[tauri::command]
+pub async fn set_network_config(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    bind_address: String,
+    proxy_port: u16,
+    backend_port: u16,
+) -> Result<(), String> {
+    let _lifecycle_guard = state.lifecycle_lock.lock().await;
+    let mut current = settings::load_settings(&app);
+    let previous_port = current.proxy_port;
+    current.bind_address = bind_address.trim().to_string();
+    current.proxy_port = proxy_port;
+    current.backend_port = backend_port;
+    current.validate_network()?;
+    settings::save_settings(&app, &current)?;
+
+    // Restarting here means Factory never points at a port the proxy isn't listening on.
+    if state.thinking_proxy.read().await.is_running() {
+        start_server_locked(&app, &state).await?;
+    }
+
+    if previous_port != proxy_port {
+        let _guard = state.factory_settings_lock.lock().await;
+        match run_blocking(move || factory_settings::move_proxy_port(previous_port, proxy_port))
+            .await
+        {
+            Ok(0) => {}
+            Ok(moved) => log::info!(
+                "[Settings] Moved {} Factory models from port {} to {}",
+                moved,
+                previous_port,
+                proxy_port
+            ),
+            Err(e) => log::warn!("[Settings] Failed to move Factory models: {}", e),
+        }
+    }
+
+    Ok(())
+}
+
 #[tauri::command]
 pub fn check_binary(app: tauri::AppHandle) -> Result<bool, String> {
     Ok(binary_manager::is_binary_available_for_app(&app))
@@ -298,18 +479,22 @@ pub async fn download_binary(
 ) -> Result<String, String> {
     state.binary_downloading.store(true, Ordering::SeqCst);
 
-    let is_running = {
+    let (is_running, proxy_port, backend_port) = {
         let mut sm = state.server_manager.write().await;
         sm.refresh_running_status().await;
         let tp = state.thinking_proxy.read().await;
-        sm.is_running() && tp.is_running()
+        (
+            sm.is_ready() && tp.is_running(),
+            tp.proxy_port,
+            tp.target_port,
+        )
     };
     app.emit(
         "server_status_changed",
         ServerState {
             is_running,
-            proxy_port: 8317,
-            backend_port: 8318,
+            proxy_port,
+            backend_port,
             binary_available: binary_manager::is_binary_available_for_app(&app),
             binary_downloading: true,
         },
@@ -324,11 +509,15 @@ pub async fn download_binary(
 
     state.binary_downloading.store(false, Ordering::SeqCst);
 
-    let is_running = {
+    let (is_running, proxy_port, backend_port) = {
         let mut sm = state.server_manager.write().await;
         sm.refresh_running_status().await;
         let tp = state.thinking_proxy.read().await;
-        sm.is_running() && tp.is_running()
+        (
+            sm.is_ready() && tp.is_running(),
+            tp.proxy_port,
+            tp.target_port,
+        )
     };
     let binary_available = result
         .as_ref()
@@ -338,8 +527,8 @@ pub async fn download_binary(
         "server_status_changed",
         ServerState {
             is_running,
-            proxy_port: 8317,
-            backend_port: 8318,
+            proxy_port,
+            backend_port,
             binary_available,
             binary_downloading: false,
         },
@@ -359,11 +548,12 @@ pub async fn open_auth_folder() -> Result<(), String> {
 }
 
 #[tauri::command]
-pub fn copy_server_url() -> Result<(), String> {
+pub fn copy_server_url(app: tauri::AppHandle) -> Result<(), String> {
+    let url = settings::load_settings(&app).proxy_url();
     let mut clipboard =
         arboard::Clipboard::new().map_err(|e| format!("Failed to access clipboard: {}", e))?;
     clipboard
-        .set_text("http://localhost:8317")
+        .set_text(url)
         .map_err(|e| format!("Failed to copy to clipboard: {}", e))?;
     Ok(())
 }
@@ -382,61 +572,463 @@ pub async fn sync_theme_icons(
     tray::set_theme_override(&app, Some(theme));
     tray::update_main_window_icon(&app);
 
-    let is_running = {
+    let running_port = {
         let mut sm = state.server_manager.write().await;
         sm.refresh_running_status().await;
         let tp = state.thinking_proxy.read().await;
-        sm.is_running() && tp.is_running()
+        (sm.is_ready() && tp.is_running()).then_some(tp.proxy_port)
     };
-    tray::update_tray_state(&app, is_running);
+    tray::update_tray_state(&app, running_port);
 
     Ok(())
 }
 
 #[tauri::command]
 pub async fn get_usage_dashboard(
+    app: tauri::AppHandle,
     state: State<'_, AppState>,
-    range: Option<String>,
+    query: Option<UsageDashboardQuery>,
 ) -> Result<UsageDashboardPayload, String> {
-    let range = range.unwrap_or_else(|| "7d".to_string());
-    let parsed_range = UsageRangeQuery::from_input(&range);
+    let query = UsageQuery::resolve(&query.unwrap_or_default(), chrono::Utc::now())?;
+    let pricing = PricingTable::new(&settings::load_settings(&app).pricing_overrides);
     let dashboard = state
         .usage_tracker
-        .get_usage_dashboard(parsed_range)
+        .get_usage_dashboard(query, pricing)
         .await?;
     Ok(UsageDashboardPayload { dashboard })
 }
 
+#[tauri::command]
+pub async fn export_usage(
+    state: State<'_, AppState>,
+    request: UsageExportRequest,
+) -> Result<UsageExportResult, String> {
+    state.usage_tracker.export_usage(request).await
+}
+
+#[tauri::command]
+pub async fn open_usage_exports_folder() -> Result<(), String> {
+    run_blocking(|| {
+        let exports_dir = auth_manager::get_auth_dir().join("exports");
+        std::fs::create_dir_all(&exports_dir)
+            .map_err(|e| format!("Failed to create exports folder: {}", e))?;
+        open::that(&exports_dir).map_err(|e| format!("Failed to open exports folder: {}", e))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn get_usage_budgets(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Vec<UsageBudgetStatus>, String> {
+    let budgets = settings::load_settings(&app).usage_budgets;
+    let now = chrono::Utc::now();
+    let used = state
+        .usage_tracker
+        .budget_usage(budgets.clone(), now)
+        .await?;
+    Ok(budgets
+        .into_iter()
+        .zip(used)
+        .map(|(budget, used)| UsageBudgetStatus {
+            resets_at: budgets::period_reset(budget.period, now).to_rfc3339(),
+            budget,
+            used,
+        })
+        .collect())
+}
+
+/// Adds a budget (empty `id`) or replaces the one with the same id.
+#[tauri::command]
+pub async fn save_usage_budget(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    budget: UsageBudget,
+) -> Result<UsageBudget, String> {
+    budgets::validate_budget(&budget)?;
+    let mut budget = budget;
+    budget.target = budget.target.trim().to_string();
+
+    let mut current = settings::load_settings(&app);
+    if budget.id.is_empty() {
+        budget.id = uuid::Uuid::new_v4().to_string();
+        current.usage_budgets.push(budget.clone());
+    } else {
+        let existing = current
+            .usage_budgets
+            .iter_mut()
+            .find(|b| b.id == budget.id)
+            .ok_or_else(|| format!("Budget not found: {}", budget.id))?;
+        *existing = budget.clone();
+    }
+    settings::save_settings(&app, &current)?;
+    apply_usage_budgets(&state, current.usage_budgets).await;
+    Ok(budget)
+}
+
+#[tauri::command]
+pub async fn delete_usage_budget(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<(), String> {
+    let mut current = settings::load_settings(&app);
+    let before = current.usage_budgets.len();
+    current.usage_budgets.retain(|b| b.id != id);
+    if current.usage_budgets.len() == before {
+        return Err(format!("Budget not found: {}", id));
+    }
+    settings::save_settings(&app, &current)?;
+    apply_usage_budgets(&state, current.usage_budgets).await;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_usage_storage_stats(
+    state: State<'_, AppState>,
+) -> Result<UsageStorageStats, String> {
+    state.usage_tracker.get_storage_stats().await
+}
+
+#[tauri::command]
+pub async fn set_usage_retention(
+    app: tauri::AppHandle,
+    retention: UsageRetention,
+) -> Result<(), String> {
+    if retention.usage_json_days > 0
+        && retention.raw_event_days > 0
+        && retention.usage_json_days > retention.raw_event_days
+    {
+        return Err("usage_json retention cannot be longer than raw event retention".to_string());
+    }
+    let mut current = settings::load_settings(&app);
+    current.usage_retention = retention;
+    settings::save_settings(&app, &current)
+}
+
+/// Applies the saved retention policy now instead of waiting for the daily run.
+#[tauri::command]
+pub async fn run_usage_maintenance(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<UsageMaintenanceReport, String> {
+    let retention = settings::load_settings(&app).usage_retention;
+    state.usage_tracker.run_maintenance(retention).await
+}
+
+// ---------------------------------------------------------------------------
+// Model rules
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub fn get_model_rules(app: tauri::AppHandle) -> Vec<ModelRewriteRule> {
+    settings::load_settings(&app).model_rules
+}
+
+/// Replaces the ordered rule list; rules without an id get one.
+#[tauri::command]
+pub async fn set_model_rules(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    rules: Vec<ModelRewriteRule>,
+) -> Result<Vec<ModelRewriteRule>, String> {
+    let mut rules = rules;
+    for rule in rules.iter_mut() {
+        model_rules::validate_rule(rule)?;
+        rule.pattern = rule.pattern.trim().to_string();
+        rule.target_model = rule.target_model.trim().to_string();
+        if rule.id.is_empty() {
+            rule.id = uuid::Uuid::new_v4().to_string();
+        }
+    }
+
+    let mut current = settings::load_settings(&app);
+    current.model_rules = rules.clone();
+    settings::save_settings(&app, &current)?;
+    let model_rewriter = state.thinking_proxy.read().await.model_rewriter.clone();
+    model_rewriter.set_rules(&rules);
+    Ok(rules)
+}
+
+/// Shows which saved rule, if any, would apply to a model name.
+#[tauri::command]
+pub async fn preview_model_rewrite(
+    state: State<'_, AppState>,
+    model: String,
+) -> Result<Option<ModelRewritePreview>, String> {
+    let model_rewriter = state.thinking_proxy.read().await.model_rewriter.clone();
+    Ok(model_rewriter.preview(model.trim()))
+}
+
+// ---------------------------------------------------------------------------
+// Proxy timeouts and retries
+// ---------------------------------------------------------------------------
+
+/// Saves upstream timeouts and applies them to new requests right away. Overrides without an
+/// id get one.
+#[tauri::command]
+pub async fn set_proxy_timeouts(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    timeouts: ProxyTimeouts,
+) -> Result<ProxyTimeouts, String> {
+    let mut timeouts = timeouts;
+    timeouts::validate(&timeouts)?;
+    for rule in timeouts.overrides.iter_mut() {
+        rule.pattern = rule.pattern.trim().to_string();
+        if rule.id.is_empty() {
+            rule.id = uuid::Uuid::new_v4().to_string();
+        }
+    }
+
+    let mut current = settings::load_settings(&app);
+    current.proxy_timeouts = timeouts.clone();
+    settings::save_settings(&app, &current)?;
+    let timeout_policy = state.thinking_proxy.read().await.timeout_policy.clone();
+    timeout_policy.set_settings(&timeouts);
+    Ok(timeouts)
+}
+
+/// Saves retry settings for rate-limited or overloaded requests; they apply right away.
+#[tauri::command]
+pub async fn set_proxy_retries(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    retries: ProxyRetries,
+) -> Result<(), String> {
+    retries::validate(&retries)?;
+    let mut current = settings::load_settings(&app);
+    current.proxy_retries = retries;
+    settings::save_settings(&app, &current)?;
+    let retry_policy = state.thinking_proxy.read().await.retry_policy.clone();
+    retry_policy.set_settings(retries);
+    Ok(())
+}
+
+/// Saves how long stopping the proxy waits for in-flight requests.
+#[tauri::command]
+pub async fn set_shutdown_drain(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    secs: u32,
+) -> Result<(), String> {
+    if secs > MAX_DRAIN_TIMEOUT_SECS {
+        return Err(format!(
+            "Drain timeout must be at most {} seconds",
+            MAX_DRAIN_TIMEOUT_SECS
+        ));
+    }
+    let mut current = settings::load_settings(&app);
+    current.shutdown_drain_secs = secs;
+    settings::save_settings(&app, &current)?;
+    state.thinking_proxy.write().await.drain_timeout = Duration::from_secs(secs as u64);
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Failover chains
+// ---------------------------------------------------------------------------
+
+/// Saves failover chains and applies them to new requests right away. Chains without an id
+/// get one.
+#[tauri::command]
+pub async fn set_failover_chains(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    chains: Vec<FailoverChain>,
+) -> Result<Vec<FailoverChain>, String> {
+    let mut chains = chains;
+    for chain in chains.iter_mut() {
+        failover::validate_chain(chain)?;
+        chain.pattern = chain.pattern.trim().to_string();
+        for step in chain.steps.iter_mut() {
+            step.model = step.model.trim().to_string();
+        }
+        if chain.id.is_empty() {
+            chain.id = uuid::Uuid::new_v4().to_string();
+        }
+    }
+
+    let mut current = settings::load_settings(&app);
+    current.failover_chains = chains.clone();
+    settings::save_settings(&app, &current)?;
+    let failover_router = state.thinking_proxy.read().await.failover_router.clone();
+    failover_router.set_chains(&chains);
+    Ok(chains)
+}
+
+// ---------------------------------------------------------------------------
+// Request capture
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub async fn set_request_capture_settings(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    capture: RequestCaptureSettings,
+) -> Result<(), String> {
+    if capture.max_body_kb == 0 || capture.max_storage_mb == 0 {
+        return Err("Capture limits must be greater than zero".to_string());
+    }
+    let mut current = settings::load_settings(&app);
+    current.request_capture = capture;
+    settings::save_settings(&app, &current)?;
+    let capture_store = state.thinking_proxy.read().await.capture_store.clone();
+    capture_store.set_settings(capture);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn list_request_captures(
+    state: State<'_, AppState>,
+    limit: Option<u32>,
+) -> Result<Vec<RequestCaptureSummary>, String> {
+    let capture_store = state.thinking_proxy.read().await.capture_store.clone();
+    capture_store.list(limit).await
+}
+
+#[tauri::command]
+pub async fn get_request_capture(
+    state: State<'_, AppState>,
+    request_id: String,
+) -> Result<RequestCapture, String> {
+    let capture_store = state.thinking_proxy.read().await.capture_store.clone();
+    capture_store
+        .get(request_id.clone())
+        .await?
+        .ok_or_else(|| format!("Capture not found: {}", request_id))
+}
+
+/// Re-sends a captured request through the running proxy, authenticated as the original
+/// client when it used an issued client key.
+#[tauri::command]
+pub async fn replay_request_capture(
+    state: State<'_, AppState>,
+    request_id: String,
+) -> Result<RequestReplayResult, String> {
+    let (capture_store, client_auth, proxy_url) = {
+        let tp = state.thinking_proxy.read().await;
+        if !tp.is_running() {
+            return Err("Proxy is not running".to_string());
+        }
+        let proxy_url = AppSettings {
+            bind_address: tp.bind_address.clone(),
+            proxy_port: tp.proxy_port,
+            ..Default::default()
+        }
+        .proxy_url();
+        (tp.capture_store.clone(), tp.client_auth.clone(), proxy_url)
+    };
+    let capture = capture_store
+        .get(request_id.clone())
+        .await?
+        .ok_or_else(|| format!("Capture not found: {}", request_id))?;
+    let client_key = client_auth
+        .read()
+        .await
+        .keys
+        .iter()
+        .find(|key| !capture.client_id.is_empty() && key.id == capture.client_id)
+        .map(|key| key.key.clone());
+    let max_body_bytes = capture_store.settings().max_body_kb as usize * 1024;
+    log::info!(
+        "[RequestCapture] Replaying {} {} ({})",
+        capture.summary.method,
+        capture.summary.path,
+        request_id
+    );
+    request_capture::replay(&capture, &proxy_url, client_key.as_deref(), max_body_bytes).await
+}
+
+#[tauri::command]
+pub async fn clear_request_captures(state: State<'_, AppState>) -> Result<usize, String> {
+    let capture_store = state.thinking_proxy.read().await.capture_store.clone();
+    capture_store.clear().await
+}
+
+async fn apply_usage_budgets(state: &State<'_, AppState>, budgets: Vec<UsageBudget>) {
+    let budget_guard = state.thinking_proxy.read().await.budget_guard.clone();
+    budget_guard.set_budgets(budgets);
+}
+
+#[tauri::command]
+pub fn get_model_pricing(app: tauri::AppHandle) -> Vec<ModelPriceEntry> {
+    PricingTable::new(&settings::load_settings(&app).pricing_overrides).entries()
+}
+
+/// Sets or clears (`price: None`) the price override for a model prefix.
+#[tauri::command]
+pub fn set_model_price_override(
+    app: tauri::AppHandle,
+    model: String,
+    price: Option<ModelPrice>,
+) -> Result<(), String> {
+    let model = pricing::normalize_model(&model);
+    if model.is_empty() {
+        return Err("Model name cannot be empty".to_string());
+    }
+
+    let mut current = settings::load_settings(&app);
+    match price {
+        Some(price) => {
+            pricing::validate_price(&price)?;
+            current.pricing_overrides.insert(model, price);
+        }
+        None => {
+            current.pricing_overrides.remove(&model);
+        }
+    }
+    settings::save_settings(&app, &current)
+}
+
 // ---------------------------------------------------------------------------
 // Models / Custom Models (Factory)
 // ---------------------------------------------------------------------------
 
 #[tauri::command]
 pub async fn get_provider_model_definitions(
+    state: State<'_, AppState>,
     channel: String,
 ) -> Result<ProviderModelDefinitionsResponse, String> {
-    cliproxy_management::fetch_provider_model_definitions(&channel).await
+    let backend_port = state.thinking_proxy.read().await.target_port;
+    cliproxy_management::fetch_provider_model_definitions(backend_port, &channel).await
+}
+
+/// The port the proxy last ran on and the saved one, which differ while the server is stopped
+/// after a port change. Factory entries on either belong to CodeForwarder; `set_network_config`
+/// has already moved the existing ones to the saved port.
+async fn factory_proxy_ports(app: &tauri::AppHandle, state: &AppState) -> (u16, u16) {
+    let running = state.thinking_proxy.read().await.proxy_port;
+    (running, settings::load_settings(app).proxy_port)
 }
 
 #[tauri::command]
 pub async fn list_factory_custom_models(
+    app: tauri::AppHandle,
     state: State<'_, AppState>,
 ) -> Result<FactoryCustomModelsState, String> {
+    let (running, saved) = factory_proxy_ports(&app, &state).await;
     let _guard = state.factory_settings_lock.lock().await;
-    run_blocking(move || factory_settings::list_factory_custom_models()).await
+    run_blocking(move || factory_settings::list_factory_custom_models(&[running, saved])).await
 }
 
 #[tauri::command]
 pub async fn remove_factory_custom_models(
+    app: tauri::AppHandle,
     state: State<'_, AppState>,
     ids: Vec<String>,
 ) -> Result<FactoryCustomModelsRemoveResult, String> {
+    let (running, saved) = factory_proxy_ports(&app, &state).await;
     let _guard = state.factory_settings_lock.lock().await;
-    run_blocking(move || factory_settings::remove_factory_custom_models(ids)).await
+    run_blocking(move || factory_settings::remove_factory_custom_models(ids, &[running, saved]))
+        .await
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn update_factory_custom_model(
+    app: tauri::AppHandle,
     state: State<'_, AppState>,
     id: String,
     model: Option<String>,
@@ -445,26 +1037,39 @@ pub async fn update_factory_custom_model(
     no_image_support: Option<bool>,
     provider: Option<String>,
 ) -> Result<FactoryCustomModelRow, String> {
+    let (running, saved) = factory_proxy_ports(&app, &state).await;
     let _guard = state.factory_settings_lock.lock().await;
     run_blocking(move || {
-        factory_settings::update_factory_custom_model(
+        let row = factory_settings::update_factory_custom_model(
             &id,
             model,
             base_url,
             display_name,
             no_image_support,
             provider,
-        )
+            &[running, saved],
+        )?;
+        // Keep entries on the saved port, like the ones set_network_config moved.
+        factory_settings::move_proxy_port(running, saved)?;
+        Ok(row)
     })
     .await
 }
 
 #[tauri::command]
 pub async fn install_agent_models(
+    app: tauri::AppHandle,
     state: State<'_, AppState>,
     agent_key: String,
     models: Vec<FactoryCustomModelInput>,
 ) -> Result<AgentInstallResult, String> {
+    let (running, saved) = factory_proxy_ports(&app, &state).await;
     let _guard = state.factory_settings_lock.lock().await;
-    run_blocking(move || factory_settings::install_agent_models(&agent_key, models)).await
+    run_blocking(move || {
+        let result = factory_settings::install_agent_models(&agent_key, models, &[running, saved])?;
+        // The install dialog builds baseUrls from the running port.
+        factory_settings::move_proxy_port(running, saved)?;
+        Ok(result)
+    })
+    .await
 }
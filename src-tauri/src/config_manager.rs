@@ -5,6 +5,9 @@ use std::path::PathBuf;
 use crate::auth_manager;
 use crate::managed_key;
 
+/// Copy of `resources/config.yaml` for runs without a Tauri resource dir (headless mode).
+pub const DEFAULT_BASE_CONFIG: &str = include_str!("../resources/config.yaml");
+
 pub fn get_base_config_path(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
     use tauri::Manager;
     let resource_dir = app_handle
@@ -17,9 +20,22 @@ pub fn get_base_config_path(app_handle: &tauri::AppHandle) -> Result<PathBuf, St
 pub fn get_merged_config_path(
     app_handle: &tauri::AppHandle,
     enabled_providers: &HashMap<String, bool>,
+    backend_port: u16,
 ) -> Result<PathBuf, String> {
-    let auth_dir = auth_manager::get_auth_dir();
     let base_config_path = get_base_config_path(app_handle)?;
+    let base_config = fs::read_to_string(&base_config_path)
+        .map_err(|e| format!("Failed to read base config: {}", e))?;
+    write_merged_config(&base_config, enabled_providers, backend_port)
+}
+
+/// Merges `base_config` with app-managed sections and writes `merged-config.yaml`
+/// into the auth dir.
+pub fn write_merged_config(
+    base_config: &str,
+    enabled_providers: &HashMap<String, bool>,
+    backend_port: u16,
+) -> Result<PathBuf, String> {
+    let auth_dir = auth_manager::get_auth_dir();
 
     // Scan for zai-*.json files and extract api_key values
     let mut zai_keys: Vec<String> = Vec::new();
@@ -93,15 +109,23 @@ pub fn get_merged_config_path(
         .map(|(key, _)| key.clone())
         .collect();
 
-    // Read and parse the base config.
-    let base_config = fs::read_to_string(&base_config_path)
-        .map_err(|e| format!("Failed to read base config: {}", e))?;
-    let mut root: serde_yaml::Value = serde_yaml::from_str(&base_config)
+    // Parse the base config.
+    let mut root: serde_yaml::Value = serde_yaml::from_str(base_config)
         .map_err(|e| format!("Failed to parse base config YAML: {}", e))?;
     let root_map = root
         .as_mapping_mut()
         .ok_or_else(|| "Base config root must be a YAML mapping".to_string())?;
 
+    // The backend always listens on loopback; only its port follows settings.
+    root_map.insert(
+        serde_yaml::Value::String("port".to_string()),
+        serde_yaml::Value::Number(backend_port.into()),
+    );
+    root_map.insert(
+        serde_yaml::Value::String("host".to_string()),
+        serde_yaml::Value::String("127.0.0.1".to_string()),
+    );
+
     // Inject managed local-only management key.
     let management_key = managed_key::get_or_create_management_key()
         .map_err(|e| format!("Failed to load managed remote-management key: {}", e))?;
@@ -0,0 +1,202 @@
+use crate::types::ErrorCategory;
+use serde_json::Value;
+
+/// Bytes of a failed upstream response kept for classification.
+pub const MAX_ERROR_BODY_BYTES: usize = 8 * 1024;
+const MAX_ERROR_MESSAGE_CHARS: usize = 500;
+
+/// Category and a short message for a failed request, stored with its usage event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorDetails {
+    pub category: ErrorCategory,
+    pub message: Option<String>,
+}
+
+impl ErrorDetails {
+    pub fn new(category: ErrorCategory, message: impl Into<String>) -> Self {
+        Self {
+            category,
+            message: Some(truncate(message.into())),
+        }
+    }
+}
+
+fn truncate(message: String) -> String {
+    let message = message.trim();
+    match message.char_indices().nth(MAX_ERROR_MESSAGE_CHARS) {
+        Some((index, _)) => format!("{}...", &message[..index]),
+        None => message.to_string(),
+    }
+}
+
+/// `(type/code/status, message)` from Anthropic (`{"error":{"type","message"}}`), OpenAI
+/// (`{"error":{"code","type","message"}}`), Gemini (`{"error":{"status","message"}}`, sometimes
+/// wrapped in an array) and plain `{"message"}`/`{"detail"}` bodies.
+fn parse_error_body(body: &[u8]) -> (Vec<String>, Option<String>) {
+    let Ok(json) = serde_json::from_slice::<Value>(body) else {
+        let text = String::from_utf8_lossy(body).trim().to_string();
+        return (Vec::new(), (!text.is_empty()).then_some(text));
+    };
+    let json = match json {
+        Value::Array(items) => items.into_iter().next().unwrap_or(Value::Null),
+        other => other,
+    };
+    let error = json.get("error").unwrap_or(&json);
+    let error = if error.is_string() { &json } else { error };
+
+    let codes = ["type", "code", "status"]
+        .iter()
+        .filter_map(|key| error.get(key))
+        .filter_map(|value| match value {
+            Value::String(s) => Some(s.to_ascii_lowercase()),
+            Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        })
+        .collect();
+    let message = ["message", "detail", "error"]
+        .iter()
+        .filter_map(|key| error.get(key).or_else(|| json.get(key)))
+        .find_map(|value| value.as_str())
+        .map(str::to_string);
+    (codes, message)
+}
+
+/// Classifies a failed upstream response from its status, `Retry-After` and body.
+pub fn classify_response(status_code: u16, has_retry_after: bool, body: &[u8]) -> ErrorDetails {
+    let (codes, message) = parse_error_body(body);
+    let haystack = codes
+        .iter()
+        .map(String::as_str)
+        .chain(message.as_deref())
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_ascii_lowercase();
+    let mentions = |needles: &[&str]| needles.iter().any(|n| haystack.contains(n));
+
+    let category = match status_code {
+        401 => ErrorCategory::AuthExpired,
+        403 if mentions(&[
+            "auth",
+            "token",
+            "credential",
+            "permission",
+            "unauthenticated",
+        ]) =>
+        {
+            ErrorCategory::AuthExpired
+        }
+        402 => ErrorCategory::QuotaExceeded,
+        429 if mentions(&[
+            "insufficient_quota",
+            "usage_limit",
+            "quota exceeded for quota metric",
+            "billing",
+            "credit",
+            "per day",
+            "daily",
+        ]) && !has_retry_after =>
+        {
+            ErrorCategory::QuotaExceeded
+        }
+        429 if mentions(&["insufficient_quota", "usage_limit_reached"]) => {
+            ErrorCategory::QuotaExceeded
+        }
+        429 => ErrorCategory::RateLimited,
+        408 | 504 => ErrorCategory::Timeout,
+        500..=599 => ErrorCategory::Upstream5xx,
+        _ if mentions(&["timeout", "timed out", "deadline_exceeded"]) => ErrorCategory::Timeout,
+        _ => ErrorCategory::ClientError,
+    };
+
+    let fallback = || {
+        codes
+            .first()
+            .cloned()
+            .unwrap_or_else(|| format!("HTTP {}", status_code))
+    };
+    ErrorDetails::new(category, message.unwrap_or_else(fallback))
+}
+
+/// Classifies a request that never got an upstream response.
+pub fn classify_transport_error(error: &(dyn std::error::Error + 'static)) -> ErrorDetails {
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(error);
+    let mut timed_out = false;
+    while let Some(err) = source {
+        if let Some(reqwest_err) = err.downcast_ref::<reqwest::Error>() {
+            timed_out |= reqwest_err.is_timeout();
+        }
+        timed_out |= err.to_string().to_ascii_lowercase().contains("timed out");
+        source = err.source();
+    }
+    let category = if timed_out {
+        ErrorCategory::Timeout
+    } else {
+        ErrorCategory::BackendUnavailable
+    };
+    ErrorDetails::new(category, error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn category(status: u16, retry_after: bool, body: &str) -> ErrorCategory {
+        classify_response(status, retry_after, body.as_bytes()).category
+    }
+
+    #[test]
+    fn classifies_provider_error_bodies() {
+        let anthropic_rate = r#"{"type":"error","error":{"type":"rate_limit_error","message":"Number of request tokens has exceeded your per-minute rate limit"}}"#;
+        assert_eq!(
+            category(429, true, anthropic_rate),
+            ErrorCategory::RateLimited
+        );
+        let details = classify_response(429, true, anthropic_rate.as_bytes());
+        assert!(details
+            .message
+            .unwrap()
+            .starts_with("Number of request tokens"));
+
+        let openai_quota = r#"{"error":{"message":"You exceeded your current quota","type":"insufficient_quota","code":"insufficient_quota"}}"#;
+        assert_eq!(
+            category(429, false, openai_quota),
+            ErrorCategory::QuotaExceeded
+        );
+        let codex_limit = r#"{"error":{"type":"usage_limit_reached","message":"The usage limit has been reached"}}"#;
+        assert_eq!(
+            category(429, true, codex_limit),
+            ErrorCategory::QuotaExceeded
+        );
+
+        let gemini_auth = r#"[{"error":{"code":401,"message":"Request had invalid authentication credentials.","status":"UNAUTHENTICATED"}}]"#;
+        assert_eq!(
+            category(401, false, gemini_auth),
+            ErrorCategory::AuthExpired
+        );
+        let forbidden =
+            r#"{"error":{"type":"permission_error","message":"OAuth token has expired"}}"#;
+        assert_eq!(category(403, false, forbidden), ErrorCategory::AuthExpired);
+
+        let overloaded =
+            r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#;
+        assert_eq!(category(529, false, overloaded), ErrorCategory::Upstream5xx);
+        assert_eq!(category(504, false, ""), ErrorCategory::Timeout);
+
+        let invalid =
+            r#"{"error":{"type":"invalid_request_error","message":"prompt is too long"}}"#;
+        assert_eq!(category(400, false, invalid), ErrorCategory::ClientError);
+        assert_eq!(
+            classify_response(404, false, b"").message.as_deref(),
+            Some("HTTP 404")
+        );
+    }
+
+    #[test]
+    fn truncates_long_messages() {
+        let details = ErrorDetails::new(ErrorCategory::ClientError, "x".repeat(2_000));
+        assert_eq!(
+            details.message.unwrap().len(),
+            MAX_ERROR_MESSAGE_CHARS + "...".len()
+        );
+    }
+}
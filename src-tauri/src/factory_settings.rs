@@ -160,33 +160,23 @@ fn write_json_atomic(path: &Path, value: &Value, create_backup: bool) -> Result<
     }
 }
 
-fn is_proxy_base_url(base_url: &str) -> bool {
+fn is_proxy_base_url(base_url: &str, proxy_ports: &[u16]) -> bool {
     let trimmed = base_url.trim();
     if trimmed.is_empty() {
         return false;
     }
 
-    let lower = trimmed.to_ascii_lowercase();
-    if lower.starts_with("http://localhost:8317")
-        || lower.starts_with("https://localhost:8317")
-        || lower.starts_with("http://127.0.0.1:8317")
-        || lower.starts_with("https://127.0.0.1:8317")
-        || lower.starts_with("http://0.0.0.0:8317")
-        || lower.starts_with("https://0.0.0.0:8317")
+    let Ok(url) = Url::parse(trimmed) else {
+        return false;
+    };
+    if !url
+        .port_or_known_default()
+        .is_some_and(|port| proxy_ports.contains(&port))
     {
-        return true;
-    }
-
-    if let Ok(url) = Url::parse(trimmed) {
-        let port = url.port_or_known_default().unwrap_or(0);
-        if port != 8317 {
-            return false;
-        }
-        let host = url.host_str().unwrap_or("").to_ascii_lowercase();
-        return host == "localhost" || host == "127.0.0.1" || host == "0.0.0.0" || host == "::1";
+        return false;
     }
-
-    false
+    let host = url.host_str().unwrap_or("").to_ascii_lowercase();
+    host == "localhost" || host == "127.0.0.1" || host == "0.0.0.0" || host == "[::1]"
 }
 
 fn session_default_model_id(root: &Value) -> Option<String> {
@@ -201,6 +191,7 @@ fn session_default_model_id(root: &Value) -> Option<String> {
 fn parse_custom_model_row(
     entry: &Value,
     default_id: Option<&str>,
+    proxy_ports: &[u16],
 ) -> Option<FactoryCustomModelRow> {
     let Some(obj) = entry.as_object() else {
         return None;
@@ -239,7 +230,7 @@ fn parse_custom_model_row(
         .and_then(|v| v.as_bool())
         .unwrap_or(false);
     let index = obj.get("index").and_then(|v| v.as_i64());
-    let is_proxy = is_proxy_base_url(&base_url);
+    let is_proxy = is_proxy_base_url(&base_url, proxy_ports);
     let is_session_default = default_id.map(|d| d == id).unwrap_or(false);
 
     Some(FactoryCustomModelRow {
@@ -263,7 +254,10 @@ fn parse_custom_model_row(
     })
 }
 
-fn list_factory_custom_models_at_path(path: &Path) -> Result<FactoryCustomModelsState, String> {
+fn list_factory_custom_models_at_path(
+    path: &Path,
+    proxy_ports: &[u16],
+) -> Result<FactoryCustomModelsState, String> {
     let factory_settings_path = path.to_string_lossy().to_string();
     if !path.exists() {
         return Ok(FactoryCustomModelsState {
@@ -284,7 +278,7 @@ fn list_factory_custom_models_at_path(path: &Path) -> Result<FactoryCustomModels
 
     let mut models: Vec<FactoryCustomModelRow> = Vec::new();
     for entry in existing {
-        if let Some(row) = parse_custom_model_row(&entry, default_ref) {
+        if let Some(row) = parse_custom_model_row(&entry, default_ref, proxy_ports) {
             models.push(row);
         }
     }
@@ -304,14 +298,15 @@ fn list_factory_custom_models_at_path(path: &Path) -> Result<FactoryCustomModels
     })
 }
 
-pub fn list_factory_custom_models() -> Result<FactoryCustomModelsState, String> {
+pub fn list_factory_custom_models(proxy_ports: &[u16]) -> Result<FactoryCustomModelsState, String> {
     let path = factory_settings_path()?;
-    list_factory_custom_models_at_path(&path)
+    list_factory_custom_models_at_path(&path, proxy_ports)
 }
 
 fn remove_factory_custom_models_at_path(
     path: &Path,
     ids: Vec<String>,
+    proxy_ports: &[u16],
 ) -> Result<FactoryCustomModelsRemoveResult, String> {
     let factory_settings_path = path.to_string_lossy().to_string();
 
@@ -374,7 +369,7 @@ fn remove_factory_custom_models_at_path(
             if id_set.contains(entry_id) {
                 found.insert(entry_id.to_string());
                 let base_url = entry.get("baseUrl").and_then(|v| v.as_str()).unwrap_or("");
-                if is_proxy_base_url(base_url) {
+                if is_proxy_base_url(base_url, proxy_ports) {
                     removed += 1;
                     continue;
                 }
@@ -402,11 +397,13 @@ fn remove_factory_custom_models_at_path(
 
 pub fn remove_factory_custom_models(
     ids: Vec<String>,
+    proxy_ports: &[u16],
 ) -> Result<FactoryCustomModelsRemoveResult, String> {
     let path = factory_settings_path()?;
-    remove_factory_custom_models_at_path(&path, ids)
+    remove_factory_custom_models_at_path(&path, ids, proxy_ports)
 }
 
+#[allow(clippy::too_many_arguments)]
 fn update_factory_custom_model_at_path(
     path: &Path,
     id: &str,
@@ -415,6 +412,7 @@ fn update_factory_custom_model_at_path(
     display_name: Option<String>,
     no_image_support: Option<bool>,
     provider: Option<String>,
+    proxy_ports: &[u16],
 ) -> Result<FactoryCustomModelRow, String> {
     let id = id.trim();
     if id.is_empty() {
@@ -459,7 +457,7 @@ fn update_factory_custom_model_at_path(
             .get("baseUrl")
             .and_then(|v| v.as_str())
             .unwrap_or("");
-        if !is_proxy_base_url(current_base_url) {
+        if !is_proxy_base_url(current_base_url, proxy_ports) {
             return Err("Refusing to edit a non-proxy model".to_string());
         }
 
@@ -468,11 +466,12 @@ fn update_factory_custom_model_at_path(
             if next_trimmed.is_empty() {
                 return Err("baseUrl cannot be empty".to_string());
             }
-            if !is_proxy_base_url(next_trimmed) {
-                return Err(
-                    "Refusing to set baseUrl to a non-proxy endpoint (must be localhost:8317)"
-                        .to_string(),
-                );
+            if !is_proxy_base_url(next_trimmed, proxy_ports) {
+                let ports: Vec<String> = proxy_ports.iter().map(u16::to_string).collect();
+                return Err(format!(
+                    "Refusing to set baseUrl to a non-proxy endpoint (must be localhost:{})",
+                    ports.join(" or localhost:")
+                ));
             }
         }
 
@@ -556,10 +555,11 @@ fn update_factory_custom_model_at_path(
         write_json_atomic(path, &root, true)?;
     }
 
-    parse_custom_model_row(&updated_entry, default_ref)
+    parse_custom_model_row(&updated_entry, default_ref, proxy_ports)
         .ok_or("Updated custom model could not be parsed".to_string())
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn update_factory_custom_model(
     id: &str,
     model: Option<String>,
@@ -567,6 +567,7 @@ pub fn update_factory_custom_model(
     display_name: Option<String>,
     no_image_support: Option<bool>,
     provider: Option<String>,
+    proxy_ports: &[u16],
 ) -> Result<FactoryCustomModelRow, String> {
     let path = factory_settings_path()?;
     update_factory_custom_model_at_path(
@@ -577,6 +578,7 @@ pub fn update_factory_custom_model(
         display_name,
         no_image_support,
         provider,
+        proxy_ports,
     )
 }
 
@@ -584,6 +586,7 @@ fn install_agent_models_at_path(
     path: &Path,
     agent_key: &str,
     models: Vec<FactoryCustomModelInput>,
+    proxy_ports: &[u16],
 ) -> Result<AgentInstallResult, String> {
     let agent_key = agent_key.trim().to_ascii_lowercase();
     if agent_key.is_empty() {
@@ -627,7 +630,7 @@ fn install_agent_models_at_path(
             || base_url.is_empty()
             || provider.is_empty()
             || display_name.is_empty()
-            || !is_proxy_base_url(base_url)
+            || !is_proxy_base_url(base_url, proxy_ports)
         {
             skipped_invalid += 1;
             continue;
@@ -699,9 +702,57 @@ fn install_agent_models_at_path(
 pub fn install_agent_models(
     agent_key: &str,
     models: Vec<FactoryCustomModelInput>,
+    proxy_ports: &[u16],
 ) -> Result<AgentInstallResult, String> {
     let path = factory_settings_path()?;
-    install_agent_models_at_path(&path, agent_key, models)
+    install_agent_models_at_path(&path, agent_key, models, proxy_ports)
+}
+
+/// Points CodeForwarder-owned entries (localhost `baseUrl`s on `from_port`) at `to_port`.
+/// Returns how many entries were rewritten.
+fn move_proxy_port_at_path(path: &Path, from_port: u16, to_port: u16) -> Result<usize, String> {
+    if from_port == to_port || !path.exists() {
+        return Ok(0);
+    }
+
+    let mut root = read_json_file(path)?;
+    let Some(entries) = root.get_mut("customModels").and_then(|v| v.as_array_mut()) else {
+        return Ok(0);
+    };
+
+    let mut moved = 0usize;
+    for entry in entries.iter_mut() {
+        let Some(base_url) = entry.get("baseUrl").and_then(|v| v.as_str()) else {
+            continue;
+        };
+        let base_url = base_url.trim();
+        if !is_proxy_base_url(base_url, &[from_port]) {
+            continue;
+        }
+        let Ok(mut url) = Url::parse(base_url) else {
+            continue;
+        };
+        if url.set_port(Some(to_port)).is_err() {
+            continue;
+        }
+        let mut next = url.to_string();
+        // Url adds a trailing slash to a bare host; keep the entry's own form.
+        if url.path() == "/" && !base_url.ends_with('/') {
+            next.pop();
+        }
+        entry["baseUrl"] = Value::String(next);
+        moved += 1;
+    }
+
+    if moved > 0 {
+        write_json_atomic(path, &root, true)?;
+    }
+    Ok(moved)
+}
+
+pub fn move_proxy_port(from_port: u16, to_port: u16) -> Result<usize, String> {
+    let path = factory_settings_path()?;
+    move_proxy_port_at_path(&path, from_port, to_port)
 }
 
 #[cfg(test)]
@@ -757,7 +808,7 @@ mod tests {
             },
         ];
 
-        let res = install_agent_models_at_path(&path, "droid", models).unwrap();
+        let res = install_agent_models_at_path(&path, "droid", models, &[8317]).unwrap();
         assert_eq!(res.total_requested, 2);
         assert_eq!(res.added, 1);
         assert_eq!(res.skipped_duplicates, 1);
@@ -797,7 +848,7 @@ mod tests {
         });
         fs::write(&path, serde_json::to_vec_pretty(&settings).unwrap()).unwrap();
 
-        let state = list_factory_custom_models_at_path(&path).unwrap();
+        let state = list_factory_custom_models_at_path(&path, &[8317]).unwrap();
         assert_eq!(state.models.len(), 2);
         assert_eq!(
             state.session_default_model.as_deref(),
@@ -825,13 +876,20 @@ mod tests {
         });
         fs::write(&path, serde_json::to_vec_pretty(&settings).unwrap()).unwrap();
 
-        let err = remove_factory_custom_models_at_path(&path, vec!["custom:proxy-0".to_string()])
-            .unwrap_err();
+        let err = remove_factory_custom_models_at_path(
+            &path,
+            vec!["custom:proxy-0".to_string()],
+            &[8317],
+        )
+        .unwrap_err();
         assert!(err.contains("session default"));
 
-        let res =
-            remove_factory_custom_models_at_path(&path, vec!["custom:external-1".to_string()])
-                .unwrap();
+        let res = remove_factory_custom_models_at_path(
+            &path,
+            vec!["custom:external-1".to_string()],
+            &[8317],
+        )
+        .unwrap();
         assert_eq!(res.removed, 0);
         assert_eq!(res.skipped_non_proxy, 1);
 
@@ -850,6 +908,7 @@ mod tests {
                 "custom:proxy-0".to_string(),
                 "custom:external-1".to_string(),
             ],
+            &[8317],
         )
         .unwrap();
         assert_eq!(res2.removed, 1);
@@ -892,6 +951,7 @@ mod tests {
             Some("New".to_string()),
             None,
             None,
+            &[8317],
         )
         .unwrap_err();
         assert!(err.contains("non-proxy"));
@@ -904,6 +964,7 @@ mod tests {
             Some("Proxy Updated".to_string()),
             Some(true),
             Some("openai".to_string()),
+            &[8317],
         )
         .unwrap();
         assert_eq!(updated.display_name, "Proxy Updated");
@@ -912,4 +973,67 @@ mod tests {
 
         let _ = fs::remove_dir_all(path.parent().unwrap().parent().unwrap());
     }
+
+    #[test]
+    fn proxy_base_url_follows_configured_port() {
+        assert!(is_proxy_base_url("http://localhost:8317/v1", &[8317]));
+        assert!(is_proxy_base_url("http://127.0.0.1:9317", &[9317]));
+        assert!(is_proxy_base_url("http://[::1]:9317/v1", &[9317]));
+        assert!(!is_proxy_base_url("http://localhost:8317/v1", &[9317]));
+        assert!(!is_proxy_base_url("http://localhost:83170", &[8317]));
+        assert!(!is_proxy_base_url("https://example.com:8317", &[8317]));
+        // Until a restart, both the running and the newly saved port count.
+        assert!(is_proxy_base_url("http://localhost:9317/v1", &[8317, 9317]));
+    }
+
+    #[test]
+    fn move_proxy_port_rewrites_only_proxy_entries() {
+        let path = make_temp_settings_path();
+        ensure_parent_dir(&path).unwrap();
+
+        let settings = serde_json::json!({
+            "customModels": [
+                {"id": "custom:proxy-0", "model": "gpt-4.1", "index": 0, "baseUrl": "http://localhost:8317/v1", "apiKey": "dummy", "displayName": "Proxy", "noImageSupport": false, "provider": "openai"},
+                {"id": "custom:proxy-1", "model": "claude-sonnet-4-5", "index": 1, "baseUrl": "http://127.0.0.1:8317", "apiKey": "dummy", "displayName": "Proxy", "noImageSupport": false, "provider": "anthropic"},
+                {"id": "custom:external-2", "model": "kimi-k2.5", "index": 2, "baseUrl": "https://example.com:8317/v1", "apiKey": "sk-REDACTED", "displayName": "External", "noImageSupport": false, "provider": "generic-chat-completion-api"}
+            ]
+        });
+        fs::write(&path, serde_json::to_vec_pretty(&settings).unwrap()).unwrap();
+
+        assert_eq!(move_proxy_port_at_path(&path, 8317, 8317).unwrap(), 0);
+        assert_eq!(move_proxy_port_at_path(&path, 8317, 9317).unwrap(), 2);
+        let state = list_factory_custom_models_at_path(&path, &[9317]).unwrap();
+        let urls: Vec<(&str, bool)> = state
+            .models
+            .iter()
+            .map(|m| (m.base_url.as_str(), m.is_proxy))
+            .collect();
+        assert_eq!(
+            urls,
+            [
+                ("http://localhost:9317/v1", true),
+                ("http://127.0.0.1:9317", true),
+                ("https://example.com:8317/v1", false),
+            ]
+        );
+
+        // Moved entries are matched on the new port, so a reinstall doesn't duplicate them.
+        let res = install_agent_models_at_path(
+            &path,
+            "droid",
+            vec![FactoryCustomModelInput {
+                model: "gpt-4.1".to_string(),
+                base_url: "http://localhost:9317/v1".to_string(),
+                api_key: "dummy".to_string(),
+                display_name: "GPT".to_string(),
+                no_image_support: false,
+                provider: "openai".to_string(),
+            }],
+            &[9317],
+        )
+        .unwrap();
+        assert_eq!(res.skipped_duplicates, 1);
+
+        let _ = fs::remove_dir_all(path.parent().unwrap().parent().unwrap());
+    }
 }
@@ -0,0 +1,261 @@
+use crate::model_rules::glob_matcher;
+use crate::types::{FailoverChain, FailoverTarget};
+use bytes::Bytes;
+use regex::Regex;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+/// Longest `Retry-After` honored when cooling a route down.
+const MAX_COOLDOWN_SECS: u64 = 10 * 60;
+
+/// One upstream try: where to send the request and which model to ask for.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FailoverRoute {
+    pub target: FailoverTarget,
+    pub model: String,
+}
+
+/// Statuses that move a request on to the next route of its chain.
+pub fn is_failover_status(status_code: u16) -> bool {
+    status_code == 429 || status_code == 529 || (500..600).contains(&status_code)
+}
+
+/// Parses `Retry-After` as delay seconds or an HTTP date.
+pub fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    let value = headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let secs = (at.timestamp() - chrono::Utc::now().timestamp()).max(0);
+    Some(Duration::from_secs(secs as u64))
+}
+
+/// Replaces the `model` field of a JSON request body.
+pub fn set_model(body: &Bytes, model: &str) -> Option<Bytes> {
+    let mut json = serde_json::from_slice::<Value>(body).ok()?;
+    json.as_object_mut()?
+        .insert("model".to_string(), Value::String(model.to_string()));
+    serde_json::to_vec(&json).ok().map(Bytes::from)
+}
+
+/// Replaces each `*` in `template` with the text the matching `*` of the pattern captured.
+fn fill_template(template: &str, captures: &regex::Captures) -> String {
+    let mut groups = captures.iter().skip(1).flatten();
+    let mut model = String::with_capacity(template.len());
+    for ch in template.chars() {
+        match ch {
+            '*' => model.push_str(groups.next().map(|m| m.as_str()).unwrap_or_default()),
+            _ => model.push(ch),
+        }
+    }
+    model
+}
+
+pub fn validate_chain(chain: &FailoverChain) -> Result<(), String> {
+    if chain.pattern.trim().is_empty() {
+        return Err("Failover chain pattern cannot be empty".to_string());
+    }
+    if chain.steps.is_empty() {
+        return Err(format!(
+            "Failover chain '{}' needs at least one fallback",
+            chain.pattern.trim()
+        ));
+    }
+    if chain.steps.iter().any(|step| step.model.trim().is_empty()) {
+        return Err(format!(
+            "Failover chain '{}' has a fallback without a model",
+            chain.pattern.trim()
+        ));
+    }
+    glob_matcher(&chain.pattern).map(|_| ())
+}
+
+/// Live failover chains used by ThinkingProxy, plus routes cooling down after `Retry-After`.
+#[derive(Default)]
+pub struct FailoverRouter {
+    chains: RwLock<Vec<(Regex, FailoverChain)>>,
+    cooldowns: Mutex<HashMap<FailoverRoute, Instant>>,
+}
+
+impl FailoverRouter {
+    /// Replaces the chains. Chains that fail to compile are skipped with a warning.
+    pub fn set_chains(&self, chains: &[FailoverChain]) {
+        let compiled = chains
+            .iter()
+            .filter(|chain| chain.enabled)
+            .filter_map(|chain| match glob_matcher(&chain.pattern) {
+                Ok(matcher) => Some((matcher, chain.clone())),
+                Err(e) => {
+                    log::warn!(
+                        "[ThinkingProxy] Skipping failover chain {}: {}",
+                        chain.id,
+                        e
+                    );
+                    None
+                }
+            })
+            .collect();
+        if let Ok(mut current) = self.chains.write() {
+            *current = compiled;
+        }
+    }
+
+    /// Fallback routes for `model` from the first matching chain, empty when none matches.
+    pub fn plan(&self, model: &str) -> Vec<FailoverRoute> {
+        let Ok(chains) = self.chains.read() else {
+            return Vec::new();
+        };
+        chains
+            .iter()
+            .find_map(|(matcher, chain)| {
+                let captures = matcher.captures(model)?;
+                Some(
+                    chain
+                        .steps
+                        .iter()
+                        .map(|step| FailoverRoute {
+                            target: step.target,
+                            model: fill_template(step.model.trim(), &captures),
+                        })
+                        .collect(),
+                )
+            })
+            .unwrap_or_default()
+    }
+
+    /// Skips `route` until `delay` has passed.
+    pub fn cool_down(&self, route: &FailoverRoute, delay: Duration) {
+        let delay = delay.min(Duration::from_secs(MAX_COOLDOWN_SECS));
+        if let Ok(mut cooldowns) = self.cooldowns.lock() {
+            cooldowns.insert(route.clone(), Instant::now() + delay);
+        }
+    }
+
+    pub fn is_cooling_down(&self, route: &FailoverRoute) -> bool {
+        let Ok(mut cooldowns) = self.cooldowns.lock() else {
+            return false;
+        };
+        let now = Instant::now();
+        cooldowns.retain(|_, until| *until > now);
+        cooldowns.contains_key(route)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::FailoverStep;
+
+    fn chain(pattern: &str, steps: &[(FailoverTarget, &str)]) -> FailoverChain {
+        FailoverChain {
+            id: pattern.to_string(),
+            enabled: true,
+            pattern: pattern.to_string(),
+            steps: steps
+                .iter()
+                .map(|(target, model)| FailoverStep {
+                    target: *target,
+                    model: model.to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn plan_fills_wildcards_from_the_first_matching_chain() {
+        let router = FailoverRouter::default();
+        router.set_chains(&[
+            chain(
+                "claude-opus-*",
+                &[
+                    (FailoverTarget::Backend, "gemini-claude-opus-*"),
+                    (FailoverTarget::Vercel, "claude-opus-*"),
+                ],
+            ),
+            chain("claude-*", &[(FailoverTarget::Backend, "gpt-5")]),
+        ]);
+
+        let plan = router.plan("claude-opus-4-5-20251101");
+        assert_eq!(
+            plan,
+            vec![
+                FailoverRoute {
+                    target: FailoverTarget::Backend,
+                    model: "gemini-claude-opus-4-5-20251101".to_string(),
+                },
+                FailoverRoute {
+                    target: FailoverTarget::Vercel,
+                    model: "claude-opus-4-5-20251101".to_string(),
+                },
+            ]
+        );
+        assert_eq!(router.plan("claude-sonnet-4-5")[0].model, "gpt-5");
+        assert!(router.plan("gpt-5").is_empty());
+    }
+
+    #[test]
+    fn retry_after_cools_a_route_down() {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(
+            reqwest::header::RETRY_AFTER,
+            reqwest::header::HeaderValue::from_static("30"),
+        );
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(30)));
+        headers.insert(
+            reqwest::header::RETRY_AFTER,
+            reqwest::header::HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+
+        let router = FailoverRouter::default();
+        let route = FailoverRoute {
+            target: FailoverTarget::Backend,
+            model: "claude-opus-4-5".to_string(),
+        };
+        router.cool_down(&route, Duration::from_secs(30));
+        assert!(router.is_cooling_down(&route));
+        router.cool_down(&route, Duration::ZERO);
+        assert!(!router.is_cooling_down(&route));
+    }
+
+    #[test]
+    fn only_rate_limits_and_server_errors_fail_over() {
+        for status in [429, 500, 502, 503, 529] {
+            assert!(is_failover_status(status), "{}", status);
+        }
+        for status in [200, 400, 401, 404, 413] {
+            assert!(!is_failover_status(status), "{}", status);
+        }
+    }
+
+    #[test]
+    fn validate_requires_pattern_and_fallback_models() {
+        assert!(validate_chain(&chain("claude-*", &[(FailoverTarget::Backend, "gpt-5")])).is_ok());
+        assert_eq!(
+            validate_chain(&chain(" ", &[(FailoverTarget::Backend, "gpt-5")])).unwrap_err(),
+            "Failover chain pattern cannot be empty"
+        );
+        assert_eq!(
+            validate_chain(&chain("claude-*", &[])).unwrap_err(),
+            "Failover chain 'claude-*' needs at least one fallback"
+        );
+        assert_eq!(
+            validate_chain(&chain(
+                "claude-*",
+                &[
+                    (FailoverTarget::Backend, "gpt-5"),
+                    (FailoverTarget::Vercel, " ")
+                ]
+            ))
+            .unwrap_err(),
+            "Failover chain 'claude-*' has a fallback without a model"
+        );
+    }
+}
@@ -0,0 +1,350 @@
+use crate::auth_manager;
+use crate::binary_manager;
+use crate::budgets::BudgetGuard;
+use crate::client_keys::ClientAuthConfig;
+use crate::config_manager;
+use crate::model_catalog;
+use crate::model_rules::ModelRewriter;
+use crate::request_capture::RequestCaptureStore;
+use crate::server_manager::ServerManager;
+use crate::thinking_proxy::ThinkingProxy;
+use crate::types::{AppSettings, VercelGatewayConfig};
+use crate::usage_tracker::{self, UsageTracker};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+const HEADLESS_CONFIG_FILE: &str = "codeforwarder-headless.json";
+const BACKEND_HEALTH_CHECK_INTERVAL_SECS: u64 = 5;
+
+/// Settings file used instead of `tauri-plugin-store` when running without a window.
+///
+/// Accepts every `AppSettings` field at the top level (plaintext `vercel_api_key`),
+/// plus optional overrides for the runtime binary and base config.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HeadlessConfig {
+    #[serde(flatten)]
+    pub settings: AppSettings,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub binary_path: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_config_path: Option<PathBuf>,
+}
+
+pub fn default_config_path() -> PathBuf {
+    auth_manager::get_auth_dir().join(HEADLESS_CONFIG_FILE)
+}
+
+fn pid_file() -> PathBuf {
+    let base = dirs::data_local_dir()
+        .or_else(dirs::home_dir)
+        .unwrap_or_else(std::env::temp_dir);
+    base.join("codeforwarder").join("headless.pid")
+}
+
+/// PID of a running headless instance, if one recorded itself.
+pub fn load_pid() -> Option<u32> {
+    let text = std::fs::read_to_string(pid_file()).ok()?;
+    text.trim().parse::<u32>().ok()
+}
+
+fn persist_pid() {
+    let path = pid_file();
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    let _ = std::fs::write(path, std::process::id().to_string());
+}
+
+pub fn clear_pid() {
+    let _ = std::fs::remove_file(pid_file());
+}
+
+pub fn load_config(path: &Path) -> Result<HeadlessConfig, String> {
+    if !path.exists() {
+        log::info!(
+            "[Headless] No config at {}, using defaults",
+            path.to_string_lossy()
+        );
+        return Ok(HeadlessConfig::default());
+    }
+
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read headless config: {}", e))?;
+    serde_json::from_str(&contents).map_err(|e| format!("Failed to parse headless config: {}", e))
+}
+
+pub fn save_config(path: &Path, config: &HeadlessConfig) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+    let serialized = serde_json::to_vec_pretty(config)
+        .map_err(|e| format!("Failed to serialize headless config: {}", e))?;
+    std::fs::write(path, serialized).map_err(|e| format!("Failed to write headless config: {}", e))
+}
+
+pub fn write_merged_config(config: &HeadlessConfig) -> Result<PathBuf, String> {
+    let base_config = match &config.base_config_path {
+        Some(path) => std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read base config: {}", e))?,
+        None => config_manager::DEFAULT_BASE_CONFIG.to_string(),
+    };
+    config_manager::write_merged_config(
+        &base_config,
+        &config.settings.enabled_providers,
+        config.settings.backend_port,
+    )
+}
+
+/// Writes the merged backend config and resolves the runtime binary.
+/// Returns `(merged_config_path, binary_path)`.
+pub fn prepare_runtime(config: &HeadlessConfig) -> Result<(PathBuf, PathBuf), String> {
+    let merged_config_path = write_merged_config(config)?;
+    let binary_path = binary_manager::ensure_binary_installed_from(config.binary_path.clone())?;
+    Ok((merged_config_path, binary_path))
+}
+
+pub fn parse_config_arg(args: &[String]) -> Result<Option<PathBuf>, String> {
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--config" {
+            let value = iter
+                .next()
+                .ok_or_else(|| "--config requires a path".to_string())?;
+            return Ok(Some(PathBuf::from(value)));
+        }
+        if let Some(value) = arg.strip_prefix("--config=") {
+            return Ok(Some(PathBuf::from(value)));
+        }
+    }
+    Ok(None)
+}
+
+/// Runs ThinkingProxy + CLIProxyAPIPlus without tray or webview until SIGTERM/Ctrl-C.
+/// Returns the process exit code.
+pub fn run(args: &[String]) -> i32 {
+    let runtime = match tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            log::error!("[Headless] Failed to build tokio runtime: {}", e);
+            return 1;
+        }
+    };
+
+    match runtime.block_on(run_async(args)) {
+        Ok(()) => 0,
+        Err(e) => {
+            log::error!("[Headless] {}", e);
+            1
+        }
+    }
+}
+
+async fn run_async(args: &[String]) -> Result<(), String> {
+    let config_path = parse_config_arg(args)?.unwrap_or_else(default_config_path);
+    let config = load_config(&config_path)?;
+    config.settings.validate_network()?;
+
+    let (config, (merged_config_path, binary_path)) = tokio::task::spawn_blocking(move || {
+        let prepared = prepare_runtime(&config);
+        prepared.map(|paths| (config, paths))
+    })
+    .await
+    .map_err(|e| format!("Failed to join runtime preparation task: {}", e))??;
+    let settings = config.settings;
+    let backend_port = settings.backend_port;
+
+    let usage_tracker = Arc::new(UsageTracker::new()?);
+    let retention = settings.usage_retention;
+    tokio::spawn(usage_tracker::run_maintenance_loop(
+        usage_tracker.clone(),
+        move || retention,
+    ));
+    let vercel_config = Arc::new(RwLock::new(VercelGatewayConfig {
+        enabled: settings.vercel_gateway_enabled,
+        api_key: settings.vercel_api_key.clone(),
+    }));
+
+    ServerManager::kill_orphaned_processes().await;
+    ServerManager::cleanup_port_conflicts_for_restart(&[settings.proxy_port, backend_port]).await?;
+
+    let server_manager = ServerManager::new();
+    if settings.persist_server_logs {
+        if let Err(e) = server_manager.set_log_persistence(true) {
+            log::warn!("[Headless] Failed to enable server log persistence: {}", e);
+        }
+    }
+    let client_auth = Arc::new(RwLock::new(ClientAuthConfig::load(
+        settings.require_client_api_key,
+    )));
+    let mut thinking_proxy = ThinkingProxy::new(
+        vercel_config,
+        client_auth,
+        usage_tracker,
+        Arc::new(BudgetGuard::new(settings.usage_budgets.clone())),
+        Arc::new(RequestCaptureStore::new(settings.request_capture)?),
+        Arc::new(ModelRewriter::new(&settings.model_rules)),
+        server_manager.readiness(),
+    );
+    thinking_proxy.apply_settings(&settings);
+    thinking_proxy
+        .start()
+        .await
+        .map_err(|e| format!("Failed to start thinking proxy: {}", e))?;
+
+    let server_manager = RwLock::new(server_manager);
+    let started = server_manager
+        .write()
+        .await
+        .start(
+            &merged_config_path.to_string_lossy(),
+            &binary_path.to_string_lossy(),
+        )
+        .await;
+    let started = match started {
+        Ok(()) => ServerManager::wait_until_ready(&server_manager, backend_port).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = started {
+        thinking_proxy.stop().await;
+        server_manager.write().await.stop().await;
+        return Err(e);
+    }
+    thinking_proxy.refresh_model_catalog();
+
+    // Added or removed accounts change which models the backend serves, as in the app.
+    let catalog = thinking_proxy.model_catalog.clone();
+    let runtime = tokio::runtime::Handle::current();
+    let auth_watcher = auth_manager::watch_auth_dir(move || {
+        log::info!("[Headless] Auth directory changed, refreshing the model catalog");
+        let catalog = catalog.clone();
+        runtime.spawn(async move {
+            tokio::time::sleep(Duration::from_secs(
+                model_catalog::ACCOUNT_CHANGE_REFRESH_DELAY_SECS,
+            ))
+            .await;
+            catalog.refresh(backend_port).await;
+        });
+    });
+    if let Err(e) = &auth_watcher {
+        log::warn!("[Headless] {}", e);
+    }
+
+    persist_pid();
+    log::info!(
+        "[Headless] Serving on {} (backend port {})",
+        settings.proxy_url(),
+        backend_port
+    );
+
+    let backend_exited = wait_for_shutdown(&server_manager).await;
+
+    log::info!("[Headless] Shutting down");
+    thinking_proxy.stop().await;
+    server_manager.write().await.stop().await;
+    if load_pid() == Some(std::process::id()) {
+        clear_pid();
+    }
+
+    if backend_exited {
+        return Err("Backend process exited unexpectedly".to_string());
+    }
+    Ok(())
+}
+
+/// Waits for SIGTERM/Ctrl-C, or for the backend to exit on its own (returns `true`).
+async fn wait_for_shutdown(server_manager: &RwLock<ServerManager>) -> bool {
+    let mut health_check =
+        tokio::time::interval(Duration::from_secs(BACKEND_HEALTH_CHECK_INTERVAL_SECS));
+    health_check.tick().await;
+
+    let signal = shutdown_signal();
+    tokio::pin!(signal);
+
+    loop {
+        tokio::select! {
+            _ = &mut signal => return false,
+            _ = health_check.tick() => {
+                if !server_manager.write().await.refresh_running_status().await {
+                    return true;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+async fn shutdown_signal() {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut terminate = match signal(SignalKind::terminate()) {
+        Ok(terminate) => terminate,
+        Err(e) => {
+            log::warn!("[Headless] Failed to install SIGTERM handler: {}", e);
+            let _ = tokio::signal::ctrl_c().await;
+            return;
+        }
+    };
+
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => log::info!("[Headless] Received Ctrl-C"),
+        _ = terminate.recv() => log::info!("[Headless] Received SIGTERM"),
+    }
+}
+
+#[cfg(not(unix))]
+async fn shutdown_signal() {
+    let _ = tokio::signal::ctrl_c().await;
+    log::info!("[Headless] Received Ctrl-C");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_config_arg_supports_both_forms() {
+        let args = vec![
+            "--headless".to_string(),
+            "--config".to_string(),
+            "/tmp/a.json".to_string(),
+        ];
+        assert_eq!(
+            parse_config_arg(&args).unwrap(),
+            Some(PathBuf::from("/tmp/a.json"))
+        );
+
+        let args = vec!["--config=/tmp/b.json".to_string()];
+        assert_eq!(
+            parse_config_arg(&args).unwrap(),
+            Some(PathBuf::from("/tmp/b.json"))
+        );
+
+        assert_eq!(parse_config_arg(&["--headless".to_string()]).unwrap(), None);
+        assert!(parse_config_arg(&["--config".to_string()]).is_err());
+    }
+
+    #[test]
+    fn headless_config_fills_missing_fields_with_defaults() {
+        let config: HeadlessConfig = serde_json::from_str(
+            r#"{"proxy_port": 9317, "enabled_providers": {"qwen": false}, "binary_path": "/opt/cli-proxy-api-plus"}"#,
+        )
+        .unwrap();
+        assert_eq!(config.settings.proxy_port, 9317);
+        assert_eq!(config.settings.backend_port, 8318);
+        assert_eq!(config.settings.bind_address, "127.0.0.1");
+        assert_eq!(config.settings.enabled_providers.get("qwen"), Some(&false));
+        assert_eq!(
+            config.binary_path,
+            Some(PathBuf::from("/opt/cli-proxy-api-plus"))
+        );
+        assert!(config.base_config_path.is_none());
+    }
+}
@@ -1,16 +1,33 @@
 mod auth_manager;
 mod binary_manager;
+mod budgets;
+mod cli;
+mod client_keys;
 mod cliproxy_management;
 mod commands;
 mod config_manager;
+mod error_taxonomy;
 mod factory_settings;
+mod failover;
+mod headless;
 mod managed_key;
+mod model_catalog;
+mod model_rules;
+mod pricing;
+mod reasoning;
+mod request_capture;
+mod retries;
 mod secure_store;
+mod server_logs;
 mod server_manager;
+mod server_supervisor;
 mod settings;
 mod thinking_proxy;
+mod timeouts;
 mod tray;
 mod types;
+mod usage_export;
+mod usage_latency;
 mod usage_tracker;
 
 use commands::AppState;
@@ -64,12 +81,41 @@ pub fn run() {
             commands::set_provider_enabled,
             commands::set_vercel_config,
             commands::set_launch_at_login,
+            commands::set_network_config,
+            commands::get_server_logs,
+            commands::set_server_log_persistence,
+            commands::list_client_keys,
+            commands::create_client_key,
+            commands::delete_client_key,
+            commands::set_require_client_api_key,
             commands::check_binary,
             commands::download_binary,
             commands::open_auth_folder,
             commands::copy_server_url,
             commands::sync_theme_icons,
             commands::get_usage_dashboard,
+            commands::get_model_pricing,
+            commands::set_model_price_override,
+            commands::export_usage,
+            commands::open_usage_exports_folder,
+            commands::get_usage_budgets,
+            commands::save_usage_budget,
+            commands::delete_usage_budget,
+            commands::get_usage_storage_stats,
+            commands::set_usage_retention,
+            commands::run_usage_maintenance,
+            commands::set_proxy_timeouts,
+            commands::set_proxy_retries,
+            commands::set_shutdown_drain,
+            commands::set_failover_chains,
+            commands::set_request_capture_settings,
+            commands::list_request_captures,
+            commands::get_request_capture,
+            commands::replay_request_capture,
+            commands::clear_request_captures,
+            commands::get_model_rules,
+            commands::set_model_rules,
+            commands::preview_model_rewrite,
             commands::get_provider_model_definitions,
             commands::list_factory_custom_models,
             commands::install_agent_models,
@@ -96,7 +142,19 @@ pub fn run() {
             }));
 
             // Create managers
-            let server_manager = Arc::new(RwLock::new(ServerManager::new()));
+            let server_manager = ServerManager::new();
+            let log_handle = app_handle.clone();
+            server_manager.set_log_listener(Some(Arc::new(move |line| {
+                use tauri::Emitter;
+                log_handle.emit("server_log_line", line).ok();
+            })));
+            if app_settings.persist_server_logs {
+                if let Err(e) = server_manager.set_log_persistence(true) {
+                    log::error!("[Setup] Failed to enable server log persistence: {}", e);
+                }
+            }
+            let backend_ready = server_manager.readiness();
+            let server_manager = Arc::new(RwLock::new(server_manager));
             let usage_tracker = match UsageTracker::new() {
                 Ok(tracker) => Arc::new(tracker),
                 Err(e) => {
@@ -104,10 +162,61 @@ pub fn run() {
                     return Err(Box::new(std::io::Error::other(e)));
                 }
             };
-            let thinking_proxy = Arc::new(RwLock::new(ThinkingProxy::new(
+            let client_auth = Arc::new(RwLock::new(client_keys::ClientAuthConfig::load(
+                app_settings.require_client_api_key,
+            )));
+            let budget_guard = Arc::new(budgets::BudgetGuard::new(
+                app_settings.usage_budgets.clone(),
+            ));
+            let alert_handle = app_handle.clone();
+            budget_guard.set_alert_listener(Some(Arc::new(move |alert| {
+                use tauri::Emitter;
+                use tauri_plugin_notification::NotificationExt;
+                let title = match alert.level {
+                    types::BudgetAlertLevel::Soft => "CodeForwarder budget warning",
+                    types::BudgetAlertLevel::Hard => "CodeForwarder budget exhausted",
+                };
+                let body = format!(
+                    "{}: {} of {} used",
+                    budgets::describe(&alert.budget),
+                    alert.used,
+                    alert.limit
+                );
+                if let Err(e) = alert_handle
+                    .notification()
+                    .builder()
+                    .title(title)
+                    .body(body)
+                    .show()
+                {
+                    log::warn!("[Budgets] Failed to show notification: {}", e);
+                }
+                alert_handle.emit("budget_alert", alert).ok();
+            })));
+            let capture_store =
+                match request_capture::RequestCaptureStore::new(app_settings.request_capture) {
+                    Ok(store) => Arc::new(store),
+                    Err(e) => {
+                        log::error!("[Setup] Failed to initialize request capture: {}", e);
+                        return Err(Box::new(std::io::Error::other(e)));
+                    }
+                };
+            let mut proxy = ThinkingProxy::new(
                 vercel_config,
+                client_auth,
                 usage_tracker.clone(),
-            )));
+                budget_guard,
+                capture_store,
+                Arc::new(model_rules::ModelRewriter::new(&app_settings.model_rules)),
+                backend_ready,
+            );
+            proxy.apply_settings(&app_settings);
+            let drain_handle = app_handle.clone();
+            proxy.set_drain_listener(Some(Arc::new(move |event| {
+                use tauri::Emitter;
+                drain_handle.emit("proxy_draining", event).ok();
+            })));
+            let thinking_proxy = Arc::new(RwLock::new(proxy));
             let lifecycle_lock = Arc::new(Mutex::new(()));
             let factory_settings_lock = Arc::new(Mutex::new(()));
             let binary_downloading = Arc::new(AtomicBool::new(false));
@@ -122,6 +231,12 @@ pub fn run() {
                 factory_settings_lock: factory_settings_lock.clone(),
             });
 
+            let retention_handle = app_handle.clone();
+            tauri::async_runtime::spawn(usage_tracker::run_maintenance_loop(
+                usage_tracker.clone(),
+                move || settings::load_settings(&retention_handle).usage_retention,
+            ));
+
             // Setup system tray
             tray::setup_tray(&app_handle)?;
             tray::update_main_window_icon(&app_handle);
@@ -150,9 +265,14 @@ pub fn run() {
                     log::info!("[Setup] Binary available, auto-starting server...");
 
                     let app_settings = settings::load_settings(&auto_start_handle);
+                    if let Err(e) = app_settings.validate_network() {
+                        log::error!("[Setup] Invalid network settings: {}", e);
+                        return;
+                    }
                     let config_path = build_merged_config_path(
                         auto_start_handle.clone(),
                         app_settings.enabled_providers.clone(),
+                        app_settings.backend_port,
                     )
                     .await;
 
@@ -172,17 +292,18 @@ pub fn run() {
                                 };
                             let binary_path_str = binary_path.to_string_lossy().to_string();
 
-                            {
-                                let mut tp = tp.write().await;
-                                tp.stop().await;
-                            }
+                            let drain = tp.write().await.begin_stop();
+                            drain.wait().await;
                             {
                                 let mut sm = sm.write().await;
                                 sm.stop().await;
                             }
                             ServerManager::kill_orphaned_processes().await;
-                            if let Err(e) =
-                                ServerManager::cleanup_port_conflicts_for_restart().await
+                            if let Err(e) = ServerManager::cleanup_port_conflicts_for_restart(&[
+                                app_settings.proxy_port,
+                                app_settings.backend_port,
+                            ])
+                            .await
                             {
                                 log::error!("[Setup] Failed to clear stale listeners: {}", e);
                                 return;
@@ -191,6 +312,7 @@ pub fn run() {
                             // Start thinking proxy
                             {
                                 let mut tp = tp.write().await;
+                                tp.apply_settings(&app_settings);
                                 if let Err(e) = tp.start().await {
                                     log::error!("[Setup] Failed to start thinking proxy: {}", e);
                                     return;
@@ -198,17 +320,34 @@ pub fn run() {
                             }
 
                             // Start backend server
-                            {
+                            let generation = {
                                 let mut sm = sm.write().await;
                                 if let Err(e) = sm.start(&config_path_str, &binary_path_str).await {
                                     log::error!("[Setup] Failed to start server: {}", e);
-                                    let mut tp = tp.write().await;
-                                    tp.stop().await;
+                                    drop(sm);
+                                    let drain = tp.write().await.begin_stop();
+                                    drain.wait().await;
                                     return;
                                 }
+                                sm.generation()
+                            };
+                            if let Err(e) =
+                                ServerManager::wait_until_ready(&sm, app_settings.backend_port)
+                                    .await
+                            {
+                                log::error!("[Setup] Server failed readiness check: {}", e);
+                                let drain = tp.write().await.begin_stop();
+                                drain.wait().await;
+                                sm.write().await.stop().await;
+                                return;
                             }
+                            tp.read().await.refresh_model_catalog();
+                            server_supervisor::watch(&auto_start_handle, generation);
 
-                            tray::update_tray_state(&auto_start_handle, true);
+                            tray::update_tray_state(
+                                &auto_start_handle,
+                                Some(app_settings.proxy_port),
+                            );
 
                             use tauri::Emitter;
                             auto_start_handle
@@ -216,8 +355,8 @@ pub fn run() {
                                     "server_status_changed",
                                     types::ServerState {
                                         is_running: true,
-                                        proxy_port: 8317,
-                                        backend_port: 8318,
+                                        proxy_port: app_settings.proxy_port,
+                                        backend_port: app_settings.backend_port,
                                         binary_available: true,
                                         binary_downloading: false,
                                     },
@@ -255,23 +394,25 @@ pub fn run() {
                     };
 
                     if is_running {
-                        {
+                        let (proxy_port, backend_port, drain) = {
                             let mut tp = tp.write().await;
-                            tp.stop().await;
-                        }
+                            let drain = tp.begin_stop();
+                            (tp.proxy_port, tp.target_port, drain)
+                        };
+                        drain.wait().await;
                         {
                             let mut sm = sm.write().await;
                             sm.stop().await;
                         }
-                        tray::update_tray_state(&handle, false);
+                        tray::update_tray_state(&handle, None);
                         use tauri::Emitter;
                         handle
                             .emit(
                                 "server_status_changed",
                                 types::ServerState {
                                     is_running: false,
-                                    proxy_port: 8317,
-                                    backend_port: 8318,
+                                    proxy_port,
+                                    backend_port,
                                     binary_available: binary_manager::is_binary_available_for_app(
                                         &handle,
                                     ),
@@ -281,8 +422,16 @@ pub fn run() {
                             .ok();
                     } else {
                         let s = settings::load_settings(&handle);
-                        match build_merged_config_path(handle.clone(), s.enabled_providers.clone())
-                            .await
+                        if let Err(e) = s.validate_network() {
+                            log::error!("Invalid network settings: {}", e);
+                            return;
+                        }
+                        match build_merged_config_path(
+                            handle.clone(),
+                            s.enabled_providers.clone(),
+                            s.backend_port,
+                        )
+                        .await
                         {
                             Ok(config_path) => {
                                 let config_str = config_path.to_string_lossy().to_string();
@@ -296,17 +445,19 @@ pub fn run() {
                                     };
                                 let bin_str = binary_path.to_string_lossy().to_string();
 
-                                {
-                                    let mut tp = tp.write().await;
-                                    tp.stop().await;
-                                }
+                                let drain = tp.write().await.begin_stop();
+                                drain.wait().await;
                                 {
                                     let mut sm = sm.write().await;
                                     sm.stop().await;
                                 }
                                 ServerManager::kill_orphaned_processes().await;
                                 if let Err(e) =
-                                    ServerManager::cleanup_port_conflicts_for_restart().await
+                                    ServerManager::cleanup_port_conflicts_for_restart(&[
+                                        s.proxy_port,
+                                        s.backend_port,
+                                    ])
+                                    .await
                                 {
                                     log::error!("Failed to clear stale listeners: {}", e);
                                     return;
@@ -314,29 +465,43 @@ pub fn run() {
 
                                 {
                                     let mut tp = tp.write().await;
+                                    tp.apply_settings(&s);
                                     if let Err(e) = tp.start().await {
                                         log::error!("Failed to start thinking proxy: {}", e);
                                         return;
                                     }
                                 }
-                                {
+                                let generation = {
                                     let mut sm = sm.write().await;
                                     if let Err(e) = sm.start(&config_str, &bin_str).await {
                                         log::error!("Failed to start server: {}", e);
-                                        let mut tp = tp.write().await;
-                                        tp.stop().await;
+                                        drop(sm);
+                                        let drain = tp.write().await.begin_stop();
+                                        drain.wait().await;
                                         return;
                                     }
+                                    sm.generation()
+                                };
+                                if let Err(e) =
+                                    ServerManager::wait_until_ready(&sm, s.backend_port).await
+                                {
+                                    log::error!("Server failed readiness check: {}", e);
+                                    let drain = tp.write().await.begin_stop();
+                                    drain.wait().await;
+                                    sm.write().await.stop().await;
+                                    return;
                                 }
-                                tray::update_tray_state(&handle, true);
+                                tp.read().await.refresh_model_catalog();
+                                server_supervisor::watch(&handle, generation);
+                                tray::update_tray_state(&handle, Some(s.proxy_port));
                                 use tauri::Emitter;
                                 handle
                                     .emit(
                                         "server_status_changed",
                                         types::ServerState {
                                             is_running: true,
-                                            proxy_port: 8317,
-                                            backend_port: 8318,
+                                            proxy_port: s.proxy_port,
+                                            backend_port: s.backend_port,
                                             binary_available: true,
                                             binary_downloading: false,
                                         },
@@ -364,10 +529,8 @@ pub fn run() {
                 tauri::async_runtime::spawn(async move {
                     let _lifecycle_guard = lifecycle_lock.lock().await;
 
-                    {
-                        let mut tp = tp.write().await;
-                        tp.stop().await;
-                    }
+                    let drain = tp.write().await.begin_stop();
+                    drain.wait().await;
                     {
                         let mut sm = sm.write().await;
                         sm.stop().await;
@@ -379,9 +542,11 @@ pub fn run() {
             });
 
             // Handle copy URL from tray
+            let copy_url_handle = app_handle.clone();
             app.listen("tray_copy_url_clicked", move |_| {
+                let url = settings::load_settings(&copy_url_handle).proxy_url();
                 if let Ok(mut clipboard) = arboard::Clipboard::new() {
-                    clipboard.set_text("http://localhost:8317").ok();
+                    clipboard.set_text(url).ok();
                 }
             });
 
@@ -411,12 +576,25 @@ pub fn run() {
         .expect("error while running tauri application");
 }
 
+/// Entry point for `--headless`: runs the proxy pair without tray or webview.
+pub fn run_headless(args: &[String]) -> i32 {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    headless::run(args)
+}
+
+/// Entry point for the `codeforwarder-cli` binary.
+pub fn run_cli(args: &[String]) -> i32 {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+    cli::run(args)
+}
+
 async fn build_merged_config_path(
     app_handle: tauri::AppHandle,
     enabled_providers: std::collections::HashMap<String, bool>,
+    backend_port: u16,
 ) -> Result<std::path::PathBuf, String> {
     tokio::task::spawn_blocking(move || {
-        config_manager::get_merged_config_path(&app_handle, &enabled_providers)
+        config_manager::get_merged_config_path(&app_handle, &enabled_providers, backend_port)
     })
     .await
     .map_err(|e| format!("Failed to join config generation task: {}", e))?
@@ -431,23 +609,29 @@ async fn build_runtime_binary_path(
 }
 
 fn setup_auth_watcher(app_handle: tauri::AppHandle) {
-    use notify_debouncer_mini::new_debouncer;
     use std::time::Duration;
 
-    let auth_dir = auth_manager::get_auth_dir();
-
     let handle = app_handle.clone();
-    let mut debouncer = new_debouncer(Duration::from_millis(500), move |_res| {
+    let _debouncer = auth_manager::watch_auth_dir(move || {
         log::info!("[FileWatcher] Auth directory changed, emitting event");
         use tauri::Emitter;
         handle.emit("auth_accounts_changed", ()).ok();
-    })
-    .expect("Failed to create file watcher");
 
-    debouncer
-        .watcher()
-        .watch(&auth_dir, notify::RecursiveMode::NonRecursive)
-        .expect("Failed to watch auth directory");
+        // Added or removed accounts change which models the backend serves.
+        let handle = handle.clone();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(
+                model_catalog::ACCOUNT_CHANGE_REFRESH_DELAY_SECS,
+            ))
+            .await;
+            let state = handle.state::<AppState>();
+            let tp = state.thinking_proxy.read().await;
+            if tp.is_running() {
+                tp.refresh_model_catalog();
+            }
+        });
+    })
+    .expect("Failed to watch auth directory");
 
     // Keep the debouncer alive for the lifetime of the app
     loop {
@@ -5,5 +5,10 @@
 )]
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--headless") {
+        std::process::exit(codeforwarder_lib::run_headless(&args));
+    }
+
     codeforwarder_lib::run()
 }
@@ -0,0 +1,79 @@
+use crate::cliproxy_management;
+use crate::types::{ProviderModelInfo, ThinkingSupport};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// Management API channels whose model definitions are cached.
+const CATALOG_CHANNELS: [&str; 6] = [
+    "claude",
+    "codex",
+    "gemini",
+    "qwen",
+    "github-copilot",
+    "antigravity",
+];
+
+/// Delay before reloading after the auth directory changes, so the backend has picked up
+/// the new accounts.
+pub const ACCOUNT_CHANGE_REFRESH_DELAY_SECS: u64 = 2;
+
+/// Model definitions advertised by the backend, keyed by lowercase model id. Empty until the
+/// first refresh; lookups then fall back to passing requests through unclamped.
+#[derive(Default)]
+pub struct ModelCatalog {
+    models: RwLock<HashMap<String, ProviderModelInfo>>,
+}
+
+impl ModelCatalog {
+    pub fn model(&self, model: &str) -> Option<ProviderModelInfo> {
+        let models = self.models.read().ok()?;
+        models.get(&model.to_ascii_lowercase()).cloned()
+    }
+
+    pub fn thinking_support(&self, model: &str) -> Option<ThinkingSupport> {
+        self.model(model).and_then(|info| info.thinking)
+    }
+
+    pub fn replace(&self, models: Vec<ProviderModelInfo>) {
+        let mut by_id = HashMap::with_capacity(models.len());
+        for info in models {
+            by_id.entry(info.id.to_ascii_lowercase()).or_insert(info);
+        }
+        if let Ok(mut current) = self.models.write() {
+            *current = by_id;
+        }
+    }
+
+    /// Fetches every channel from the management API. Channels that fail keep no entries;
+    /// the catalog is left untouched when all of them fail.
+    pub async fn refresh(&self, backend_port: u16) -> usize {
+        let mut models = Vec::new();
+        let mut fetched_any = false;
+        for channel in CATALOG_CHANNELS {
+            match cliproxy_management::fetch_provider_model_definitions(backend_port, channel).await
+            {
+                Ok(response) => {
+                    fetched_any = true;
+                    models.extend(response.models);
+                }
+                Err(e) => {
+                    log::warn!("[ModelCatalog] Failed to fetch '{}' models: {}", channel, e);
+                }
+            }
+        }
+        if !fetched_any {
+            return 0;
+        }
+        let count = models.len();
+        self.replace(models);
+        log::info!("[ModelCatalog] Cached {} model definitions", count);
+        count
+    }
+}
+
+/// Refreshes the catalog without holding up the caller.
+pub fn refresh_in_background(catalog: Arc<ModelCatalog>, backend_port: u16) {
+    tokio::spawn(async move {
+        catalog.refresh(backend_port).await;
+    });
+}
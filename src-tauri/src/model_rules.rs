@@ -0,0 +1,258 @@
+use crate::types::{ModelMatchKind, ModelRewritePreview, ModelRewriteRule};
+use bytes::Bytes;
+use regex::{Regex, RegexBuilder};
+use serde_json::Value;
+use std::sync::RwLock;
+
+/// Fields a rule may not inject, since they are owned by routing or by the rule itself.
+const RESERVED_FIELDS: [&str; 2] = ["model", "stream"];
+
+fn compile(rule: &ModelRewriteRule) -> Result<Regex, String> {
+    let pattern = rule.pattern.trim();
+    let source = match rule.match_kind {
+        ModelMatchKind::Exact => format!("^{}$", regex::escape(pattern)),
+        ModelMatchKind::Glob => glob_source(pattern),
+        ModelMatchKind::Regex => format!("^(?:{})$", pattern),
+    };
+    build_matcher(&source, pattern)
+}
+
+fn glob_source(pattern: &str) -> String {
+    let mut source = String::from("^");
+    for ch in pattern.chars() {
+        match ch {
+            '*' => source.push_str("(.*)"),
+            '?' => source.push('.'),
+            _ => source.push_str(&regex::escape(&ch.to_string())),
+        }
+    }
+    source.push('$');
+    source
+}
+
+fn build_matcher(source: &str, pattern: &str) -> Result<Regex, String> {
+    RegexBuilder::new(source)
+        .case_insensitive(true)
+        .build()
+        .map_err(|e| format!("Invalid pattern '{}': {}", pattern, e))
+}
+
+/// Case-insensitive matcher for a whole-string glob, as used by model rules. Each `*` is a
+/// capture group.
+pub fn glob_matcher(pattern: &str) -> Result<Regex, String> {
+    let pattern = pattern.trim();
+    build_matcher(&glob_source(pattern), pattern)
+}
+
+pub fn validate_rule(rule: &ModelRewriteRule) -> Result<(), String> {
+    if rule.pattern.trim().is_empty() {
+        return Err("Rule pattern cannot be empty".to_string());
+    }
+    if rule.target_model.trim().is_empty() && rule.inject.is_empty() {
+        return Err("Set a target model, fields to inject, or both".to_string());
+    }
+    if let Some(field) = RESERVED_FIELDS
+        .iter()
+        .find(|field| rule.inject.contains_key(**field))
+    {
+        return Err(format!("'{}' cannot be injected by a rule", field));
+    }
+    compile(rule).map(|_| ())
+}
+
+struct CompiledRule {
+    rule: ModelRewriteRule,
+    matcher: Regex,
+}
+
+impl CompiledRule {
+    fn rewrite(&self, model: &str) -> Option<String> {
+        if !self.matcher.is_match(model) {
+            return None;
+        }
+        let target = self.rule.target_model.trim();
+        Some(match (target.is_empty(), self.rule.match_kind) {
+            (true, _) => model.to_string(),
+            (false, ModelMatchKind::Regex) => self.matcher.replace(model, target).into_owned(),
+            (false, _) => target.to_string(),
+        })
+    }
+}
+
+/// Live model rules used by ThinkingProxy. The first enabled matching rule wins.
+#[derive(Default)]
+pub struct ModelRewriter {
+    rules: RwLock<Vec<CompiledRule>>,
+}
+
+impl ModelRewriter {
+    pub fn new(rules: &[ModelRewriteRule]) -> Self {
+        let rewriter = Self::default();
+        rewriter.set_rules(rules);
+        rewriter
+    }
+
+    /// Replaces the rule set. Rules that fail to compile are skipped with a warning.
+    pub fn set_rules(&self, rules: &[ModelRewriteRule]) {
+        let compiled = rules
+            .iter()
+            .filter(|rule| rule.enabled)
+            .filter_map(|rule| match compile(rule) {
+                Ok(matcher) => Some(CompiledRule {
+                    rule: rule.clone(),
+                    matcher,
+                }),
+                Err(e) => {
+                    log::warn!("[ModelRules] Skipping rule {}: {}", rule.id, e);
+                    None
+                }
+            })
+            .collect();
+        if let Ok(mut current) = self.rules.write() {
+            *current = compiled;
+        }
+    }
+
+    pub fn preview(&self, model: &str) -> Option<ModelRewritePreview> {
+        let rules = self.rules.read().ok()?;
+        rules.iter().find_map(|compiled| {
+            compiled
+                .rewrite(model)
+                .map(|rewritten| ModelRewritePreview {
+                    rule_id: compiled.rule.id.clone(),
+                    model: rewritten,
+                    inject: compiled.rule.inject.clone(),
+                })
+        })
+    }
+
+    /// Rewrites the `model` of a JSON request body and injects the rule's fields. The flag is
+    /// set when a `thinking` field was injected. `None` when the body has no model or no rule
+    /// matches.
+    pub fn apply(&self, body: &Bytes) -> Option<(Bytes, bool)> {
+        let mut json = serde_json::from_slice::<Value>(body).ok()?;
+        let model = json.get("model")?.as_str()?.to_string();
+        let preview = self.preview(&model)?;
+        let injects_thinking = preview.inject.contains_key("thinking");
+        let object = json.as_object_mut()?;
+        object.insert("model".to_string(), Value::String(preview.model.clone()));
+        for (key, value) in preview.inject {
+            object.insert(key, value);
+        }
+        log::info!(
+            "[ModelRules] Rule {} rewrote model '{}' -> '{}'",
+            preview.rule_id,
+            model,
+            preview.model
+        );
+        let body = serde_json::to_vec(&json).ok()?;
+        Some((Bytes::from(body), injects_thinking))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rule(kind: ModelMatchKind, pattern: &str, target: &str) -> ModelRewriteRule {
+        ModelRewriteRule {
+            id: pattern.to_string(),
+            enabled: true,
+            match_kind: kind,
+            pattern: pattern.to_string(),
+            target_model: target.to_string(),
+            inject: serde_json::Map::new(),
+        }
+    }
+
+    #[test]
+    fn first_matching_rule_rewrites_model_and_injects_fields() {
+        let mut fast = rule(ModelMatchKind::Exact, "fast", "claude-haiku-4-5");
+        fast.inject.insert("temperature".to_string(), json!(0.2));
+        let rewriter = ModelRewriter::new(&[
+            fast,
+            rule(
+                ModelMatchKind::Exact,
+                "deep",
+                "claude-opus-4-5-thinking-16000",
+            ),
+            rule(ModelMatchKind::Glob, "gpt-5*-mini", "gpt-5-mini"),
+            rule(ModelMatchKind::Regex, r"sonnet-(\d+)", "claude-sonnet-$1-5"),
+        ]);
+
+        let body = Bytes::from(json!({"model": "FAST", "max_tokens": 10}).to_string());
+        let (rewritten, injects_thinking) = rewriter.apply(&body).unwrap();
+        assert!(!injects_thinking);
+        let rewritten: Value = serde_json::from_slice(&rewritten).unwrap();
+        assert_eq!(rewritten["model"], "claude-haiku-4-5");
+        assert_eq!(rewritten["temperature"], json!(0.2));
+        assert_eq!(rewritten["max_tokens"], 10);
+
+        let model = |name: &str| rewriter.preview(name).map(|p| p.model);
+        assert_eq!(
+            model("deep").as_deref(),
+            Some("claude-opus-4-5-thinking-16000")
+        );
+        assert_eq!(model("gpt-5.1-codex-mini").as_deref(), Some("gpt-5-mini"));
+        assert_eq!(model("sonnet-4").as_deref(), Some("claude-sonnet-4-5"));
+        assert_eq!(model("claude-sonnet-4-5"), None);
+        assert!(rewriter
+            .apply(&Bytes::from_static(b"{\"model\":\"other\"}"))
+            .is_none());
+    }
+
+    #[test]
+    fn disabled_and_invalid_rules_are_skipped() {
+        let mut disabled = rule(ModelMatchKind::Exact, "fast", "disabled");
+        disabled.enabled = false;
+        let rewriter = ModelRewriter::new(&[
+            disabled,
+            rule(ModelMatchKind::Regex, "(", "broken"),
+            rule(ModelMatchKind::Glob, "fa*", "claude-haiku-4-5"),
+            rule(ModelMatchKind::Exact, "fast", "shadowed"),
+        ]);
+        let preview = rewriter.preview("fast").unwrap();
+        assert_eq!(preview.rule_id, "fa*");
+        assert_eq!(preview.model, "claude-haiku-4-5");
+    }
+
+    #[test]
+    fn validate_requires_pattern_and_an_effect() {
+        assert_eq!(
+            validate_rule(&rule(ModelMatchKind::Exact, " ", "claude-haiku-4-5")).unwrap_err(),
+            "Rule pattern cannot be empty"
+        );
+        assert_eq!(
+            validate_rule(&rule(ModelMatchKind::Exact, "fast", "")).unwrap_err(),
+            "Set a target model, fields to inject, or both"
+        );
+
+        let mut inject_only = rule(ModelMatchKind::Exact, "fast", "");
+        inject_only
+            .inject
+            .insert("temperature".to_string(), json!(0.2));
+        assert!(validate_rule(&inject_only).is_ok());
+    }
+
+    #[test]
+    fn validate_refuses_reserved_inject_fields() {
+        for field in RESERVED_FIELDS {
+            let mut reserved = rule(ModelMatchKind::Exact, "fast", "claude-haiku-4-5");
+            reserved.inject.insert(field.to_string(), json!("x"));
+            assert_eq!(
+                validate_rule(&reserved).unwrap_err(),
+                format!("'{}' cannot be injected by a rule", field)
+            );
+        }
+    }
+
+    #[test]
+    fn validate_reports_the_invalid_pattern() {
+        let err = validate_rule(&rule(ModelMatchKind::Regex, "(", "x")).unwrap_err();
+        assert!(err.starts_with("Invalid pattern '(':"), "{}", err);
+        // Glob and exact patterns are escaped, so regex syntax in them is literal.
+        assert!(validate_rule(&rule(ModelMatchKind::Glob, "gpt-(5*", "x")).is_ok());
+        assert!(validate_rule(&rule(ModelMatchKind::Exact, "gpt-[5", "x")).is_ok());
+    }
+}
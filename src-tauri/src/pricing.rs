@@ -0,0 +1,240 @@
+use crate::types::{ModelPrice, ModelPriceEntry};
+use std::collections::HashMap;
+
+/// Public list prices in USD per million tokens, matched by longest model-name prefix.
+const DEFAULT_PRICES: &[(&str, ModelPrice)] = &[
+    // Anthropic
+    ("claude-opus-4-5", price(5.0, 25.0, Some(0.5), Some(6.25))),
+    ("claude-opus-4", price(15.0, 75.0, Some(1.5), Some(18.75))),
+    ("claude-sonnet-4", price(3.0, 15.0, Some(0.3), Some(3.75))),
+    ("claude-haiku-4-5", price(1.0, 5.0, Some(0.1), Some(1.25))),
+    ("claude-3-7-sonnet", price(3.0, 15.0, Some(0.3), Some(3.75))),
+    ("claude-3-5-sonnet", price(3.0, 15.0, Some(0.3), Some(3.75))),
+    ("claude-3-5-haiku", price(0.8, 4.0, Some(0.08), Some(1.0))),
+    // OpenAI
+    ("gpt-5", price(1.25, 10.0, Some(0.125), None)),
+    ("gpt-5-mini", price(0.25, 2.0, Some(0.025), None)),
+    ("gpt-5-nano", price(0.05, 0.4, Some(0.005), None)),
+    ("gpt-4-1", price(2.0, 8.0, Some(0.5), None)),
+    ("gpt-4-1-mini", price(0.4, 1.6, Some(0.1), None)),
+    ("gpt-4-1-nano", price(0.1, 0.4, Some(0.025), None)),
+    ("gpt-4o", price(2.5, 10.0, Some(1.25), None)),
+    ("gpt-4o-mini", price(0.15, 0.6, Some(0.075), None)),
+    ("o3", price(2.0, 8.0, Some(0.5), None)),
+    ("o3-mini", price(1.1, 4.4, Some(0.55), None)),
+    ("o4-mini", price(1.1, 4.4, Some(0.275), None)),
+    // Google
+    ("gemini-3-pro", price(2.0, 12.0, Some(0.2), None)),
+    ("gemini-2-5-pro", price(1.25, 10.0, Some(0.31), None)),
+    ("gemini-2-5-flash", price(0.3, 2.5, Some(0.075), None)),
+    ("gemini-2-5-flash-lite", price(0.1, 0.4, Some(0.025), None)),
+    // Others
+    ("glm-4-6", price(0.6, 2.2, Some(0.11), None)),
+    ("qwen3-coder-plus", price(1.0, 5.0, None, None)),
+];
+
+/// `(input, output, cache_read, cache_write)`; missing cache prices fall back to input.
+const fn price(
+    input: f64,
+    output: f64,
+    cache_read: Option<f64>,
+    cache_write: Option<f64>,
+) -> ModelPrice {
+    ModelPrice {
+        input,
+        output,
+        cache_read,
+        cache_write,
+    }
+}
+
+/// Token counts for one model, as summed from usage events.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokenCounts {
+    pub input: i64,
+    pub output: i64,
+    /// Cache reads.
+    pub cached: i64,
+    pub cache_write: i64,
+}
+
+/// Lowercases, drops any `vendor/` prefix and maps dots to dashes so
+/// `anthropic/claude-opus-4.5` and `claude-opus-4-5` resolve alike.
+pub fn normalize_model(model: &str) -> String {
+    let trimmed = model.trim();
+    let base = trimmed.rsplit('/').next().unwrap_or(trimmed);
+    base.to_ascii_lowercase().replace('.', "-")
+}
+
+/// Anthropic reports `input_tokens` excluding cache reads/writes; OpenAI and Gemini
+/// include cached tokens in the prompt count.
+fn input_includes_cached(model: &str) -> bool {
+    !normalize_model(model).starts_with("claude")
+}
+
+fn longest_prefix<'a, T>(
+    model: &str,
+    candidates: impl Iterator<Item = (&'a str, T)>,
+) -> Option<(&'a str, T)> {
+    candidates
+        .filter(|(prefix, _)| model.starts_with(*prefix))
+        .max_by_key(|(prefix, _)| prefix.len())
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PricingTable {
+    overrides: HashMap<String, ModelPrice>,
+}
+
+impl PricingTable {
+    pub fn new(overrides: &HashMap<String, ModelPrice>) -> Self {
+        Self {
+            overrides: overrides
+                .iter()
+                .map(|(model, price)| (normalize_model(model), *price))
+                .collect(),
+        }
+    }
+
+    /// Overrides win over built-in prices; within each, the longest matching prefix wins.
+    pub fn lookup(&self, model: &str) -> Option<ModelPrice> {
+        let model = normalize_model(model);
+        if let Some((_, price)) =
+            longest_prefix(&model, self.overrides.iter().map(|(k, v)| (k.as_str(), *v)))
+        {
+            return Some(price);
+        }
+        longest_prefix(
+            &model,
+            DEFAULT_PRICES
+                .iter()
+                .map(|(prefix, price)| (*prefix, *price)),
+        )
+        .map(|(_, price)| price)
+    }
+
+    /// Estimated API cost in USD, or `None` when the model has no known price.
+    pub fn estimate(&self, model: &str, tokens: TokenCounts) -> Option<f64> {
+        let price = self.lookup(model)?;
+        let uncached_input = if input_includes_cached(model) {
+            (tokens.input - tokens.cached).max(0)
+        } else {
+            tokens.input
+        };
+        let cache_read = price.cache_read.unwrap_or(price.input);
+        let cache_write = price.cache_write.unwrap_or(price.input);
+
+        let cost = uncached_input as f64 * price.input
+            + tokens.cached.max(0) as f64 * cache_read
+            + tokens.cache_write.max(0) as f64 * cache_write
+            + tokens.output.max(0) as f64 * price.output;
+        Some(cost / 1_000_000.0)
+    }
+
+    /// Built-in prices merged with overrides, for display and editing.
+    pub fn entries(&self) -> Vec<ModelPriceEntry> {
+        let mut entries: Vec<ModelPriceEntry> = DEFAULT_PRICES
+            .iter()
+            .filter(|(prefix, ..)| !self.overrides.contains_key(*prefix))
+            .map(|(prefix, price)| ModelPriceEntry {
+                model: prefix.to_string(),
+                price: *price,
+                is_override: false,
+            })
+            .collect();
+        entries.extend(self.overrides.iter().map(|(model, price)| ModelPriceEntry {
+            model: model.clone(),
+            price: *price,
+            is_override: true,
+        }));
+        entries.sort_by(|a, b| a.model.cmp(&b.model));
+        entries
+    }
+}
+
+pub fn validate_price(price: &ModelPrice) -> Result<(), String> {
+    let values = [
+        Some(price.input),
+        Some(price.output),
+        price.cache_read,
+        price.cache_write,
+    ];
+    if values.iter().flatten().any(|v| !v.is_finite() || *v < 0.0) {
+        return Err("Prices must be non-negative numbers".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup_prefers_longest_prefix_and_overrides() {
+        let table = PricingTable::default();
+        assert_eq!(table.lookup("gpt-5-mini-2025-08-07").unwrap().input, 0.25);
+        assert_eq!(table.lookup("gpt-5.1-codex").unwrap().input, 1.25);
+        assert_eq!(
+            table.lookup("anthropic/claude-opus-4.5").unwrap().input,
+            5.0
+        );
+        assert_eq!(
+            table.lookup("claude-opus-4-1-20250805").unwrap().input,
+            15.0
+        );
+        assert!(table.lookup("some-local-model").is_none());
+
+        let overrides = HashMap::from([(
+            "claude-opus".to_string(),
+            ModelPrice {
+                input: 1.0,
+                output: 2.0,
+                cache_read: None,
+                cache_write: None,
+            },
+        )]);
+        let table = PricingTable::new(&overrides);
+        assert_eq!(table.lookup("claude-opus-4-5").unwrap().input, 1.0);
+    }
+
+    #[test]
+    fn lookup_prices_o3_mini_separately_from_o3() {
+        let table = PricingTable::default();
+        assert_eq!(table.lookup("o3-mini-2025-01-31").unwrap().input, 1.1);
+        assert_eq!(table.lookup("o3-mini").unwrap().output, 4.4);
+        assert_eq!(table.lookup("o3-2025-04-16").unwrap().input, 2.0);
+    }
+
+    #[test]
+    fn estimate_handles_cache_reads_and_writes_per_provider() {
+        let table = PricingTable::default();
+
+        // Anthropic: input excludes cache tokens.
+        let claude = table
+            .estimate(
+                "claude-sonnet-4-5",
+                TokenCounts {
+                    input: 1_000_000,
+                    output: 1_000_000,
+                    cached: 1_000_000,
+                    cache_write: 1_000_000,
+                },
+            )
+            .unwrap();
+        assert!((claude - (3.0 + 15.0 + 0.3 + 3.75)).abs() < 1e-9);
+
+        // OpenAI: cached tokens are part of the prompt count.
+        let gpt = table
+            .estimate(
+                "gpt-5",
+                TokenCounts {
+                    input: 1_000_000,
+                    output: 0,
+                    cached: 400_000,
+                    cache_write: 0,
+                },
+            )
+            .unwrap();
+        assert!((gpt - (0.6 * 1.25 + 0.4 * 0.125)).abs() < 1e-9);
+    }
+}
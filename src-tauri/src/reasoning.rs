@@ -0,0 +1,301 @@
+use crate::model_catalog::ModelCatalog;
+use crate::types::ThinkingSupport;
+use bytes::Bytes;
+use serde_json::{json, Value};
+
+const REASONING_SUFFIX: &str = "-reasoning-";
+const THINKING_SUFFIX: &str = "-thinking-";
+const DYNAMIC_BUDGET: i64 = -1;
+
+/// Effort levels from least to most reasoning.
+const EFFORT_LEVELS: [&str; 6] = ["none", "minimal", "low", "medium", "high", "xhigh"];
+
+fn effort_rank(level: &str) -> Option<usize> {
+    EFFORT_LEVELS.iter().position(|known| *known == level)
+}
+
+/// Picks the closest level the model supports, preferring the strongest level that does not
+/// exceed the requested one.
+pub fn clamp_level(support: Option<&ThinkingSupport>, requested: &str) -> String {
+    let Some(levels) = support
+        .and_then(|s| s.levels.as_ref())
+        .filter(|levels| !levels.is_empty())
+    else {
+        return requested.to_string();
+    };
+    if levels.iter().any(|level| level == requested) {
+        return requested.to_string();
+    }
+    let requested_rank = effort_rank(requested).unwrap_or(0);
+    let mut ranked: Vec<(usize, &String)> = levels
+        .iter()
+        .filter_map(|level| effort_rank(level).map(|rank| (rank, level)))
+        .collect();
+    ranked.sort_by_key(|(rank, _)| *rank);
+    ranked
+        .iter()
+        .rev()
+        .find(|(rank, _)| *rank <= requested_rank)
+        .or_else(|| ranked.first())
+        .map(|(_, level)| (*level).clone())
+        .unwrap_or_else(|| requested.to_string())
+}
+
+/// Clamps a thinking budget into the model's `[min, max]`. `0` disables thinking and `-1`
+/// asks for a dynamic budget; both fall back to the nearest bound when not allowed.
+pub fn clamp_budget(support: Option<&ThinkingSupport>, requested: i64) -> i64 {
+    let Some(support) = support else {
+        return requested;
+    };
+    if requested == DYNAMIC_BUDGET {
+        if support.dynamic_allowed.unwrap_or(true) {
+            return requested;
+        }
+        return support.max.or(support.min).unwrap_or(requested);
+    }
+    if requested == 0 && support.zero_allowed.unwrap_or(false) {
+        return 0;
+    }
+    let mut budget = requested;
+    if let Some(min) = support.min {
+        budget = budget.max(min);
+    }
+    if let Some(max) = support.max {
+        budget = budget.min(max);
+    }
+    budget
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BodyDialect {
+    Anthropic,
+    OpenAiChat,
+    OpenAiResponses,
+    Gemini,
+}
+
+fn body_dialect(path: &str, json: &Value) -> BodyDialect {
+    if json.get("contents").is_some() {
+        BodyDialect::Gemini
+    } else if path.ends_with("/messages") {
+        BodyDialect::Anthropic
+    } else if path.ends_with("/responses") {
+        BodyDialect::OpenAiResponses
+    } else {
+        BodyDialect::OpenAiChat
+    }
+}
+
+/// Returns the model without its suffix and the suffix value, when `model` ends with
+/// `<suffix><value>`.
+fn split_suffix<'a>(model: &'a str, suffix: &str) -> Option<(&'a str, &'a str)> {
+    let pos = model.rfind(suffix)?;
+    let value = &model[pos + suffix.len()..];
+    (pos > 0 && !value.is_empty()).then(|| (&model[..pos], value))
+}
+
+fn is_gemini_model(model: &str) -> bool {
+    model.starts_with("gemini-") && !model.starts_with("gemini-claude-")
+}
+
+fn apply_effort(json: &mut Value, dialect: BodyDialect, effort: &str) {
+    match dialect {
+        BodyDialect::OpenAiResponses => {
+            if !json.get("reasoning").is_some_and(Value::is_object) {
+                json["reasoning"] = json!({});
+            }
+            json["reasoning"]["effort"] = Value::String(effort.to_string());
+        }
+        _ => json["reasoning_effort"] = Value::String(effort.to_string()),
+    }
+}
+
+fn apply_budget(json: &mut Value, dialect: BodyDialect, budget: i64) {
+    let include_thoughts = budget != 0;
+    match dialect {
+        BodyDialect::Gemini => {
+            if !json.get("generationConfig").is_some_and(Value::is_object) {
+                json["generationConfig"] = json!({});
+            }
+            json["generationConfig"]["thinkingConfig"] = json!({
+                "thinkingBudget": budget,
+                "includeThoughts": include_thoughts,
+            });
+        }
+        BodyDialect::Anthropic => {
+            json["thinking"] = if budget == 0 {
+                json!({ "type": "disabled" })
+            } else {
+                json!({ "type": "enabled", "budget_tokens": budget })
+            };
+        }
+        BodyDialect::OpenAiChat | BodyDialect::OpenAiResponses => {
+            json["extra_body"] = json!({
+                "google": {
+                    "thinking_config": {
+                        "thinking_budget": budget,
+                        "include_thoughts": include_thoughts,
+                    }
+                }
+            });
+        }
+    }
+}
+
+/// Rewrites OpenAI/Codex and Gemini reasoning suffixes into request fields, mirroring the
+/// Claude `-thinking-<budget>` handling:
+/// - `gpt-5-codex-reasoning-high` -> `gpt-5-codex` with `reasoning.effort` (Responses API) or
+///   `reasoning_effort` (Chat Completions).
+/// - `gemini-2.5-pro-thinking-8192` -> `gemini-2.5-pro` with a thinking budget in the shape of
+///   the request body; `-thinking-auto` asks for a dynamic budget.
+///
+/// Levels and budgets are clamped to the model's advertised `ThinkingSupport` when the catalog
+/// knows it. `None` when the body has no such suffix.
+pub fn process_reasoning_suffix(body: &Bytes, path: &str, catalog: &ModelCatalog) -> Option<Bytes> {
+    let mut json = serde_json::from_slice::<Value>(body).ok()?;
+    let model = json.get("model")?.as_str()?.to_string();
+    let dialect = body_dialect(path, &json);
+
+    if model.starts_with("claude-") || model.starts_with("gemini-claude-") {
+        return None;
+    }
+
+    if let Some((clean_model, level)) = split_suffix(&model, REASONING_SUFFIX) {
+        let level = level.to_ascii_lowercase();
+        effort_rank(&level)?;
+        let support = catalog.thinking_support(clean_model);
+        let effort = clamp_level(support.as_ref(), &level);
+        if effort != level {
+            log::info!(
+                "[ThinkingProxy] Adjusted reasoning effort for '{}' from {} to {}",
+                clean_model,
+                level,
+                effort
+            );
+        }
+        json["model"] = Value::String(clean_model.to_string());
+        apply_effort(&mut json, dialect, &effort);
+        log::info!(
+            "[ThinkingProxy] Transformed model '{}' -> '{}' with reasoning effort {}",
+            model,
+            clean_model,
+            effort
+        );
+    } else if is_gemini_model(&model) {
+        let (clean_model, raw_budget) = split_suffix(&model, THINKING_SUFFIX)?;
+        let requested = if raw_budget.eq_ignore_ascii_case("auto") {
+            DYNAMIC_BUDGET
+        } else {
+            raw_budget.parse::<i64>().ok().filter(|b| *b >= 0)?
+        };
+        let support = catalog.thinking_support(clean_model);
+        let budget = clamp_budget(support.as_ref(), requested);
+        if budget != requested {
+            log::info!(
+                "[ThinkingProxy] Adjusted thinking budget for '{}' from {} to {}",
+                clean_model,
+                requested,
+                budget
+            );
+        }
+        json["model"] = Value::String(clean_model.to_string());
+        apply_budget(&mut json, dialect, budget);
+        log::info!(
+            "[ThinkingProxy] Transformed model '{}' -> '{}' with thinking budget {}",
+            model,
+            clean_model,
+            budget
+        );
+    } else {
+        return None;
+    }
+
+    serde_json::to_vec(&json).ok().map(Bytes::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn support(min: i64, max: i64, levels: &[&str]) -> ThinkingSupport {
+        ThinkingSupport {
+            min: Some(min),
+            max: Some(max),
+            zero_allowed: Some(false),
+            dynamic_allowed: Some(false),
+            levels: Some(levels.iter().map(|l| l.to_string()).collect()),
+        }
+    }
+
+    fn rewrite(body: Value, path: &str) -> Option<Value> {
+        let bytes = Bytes::from(body.to_string());
+        process_reasoning_suffix(&bytes, path, &ModelCatalog::default())
+            .map(|b| serde_json::from_slice(&b).unwrap())
+    }
+
+    #[test]
+    fn reasoning_suffix_sets_effort_for_the_request_dialect() {
+        let chat = rewrite(
+            json!({"model": "gpt-5-reasoning-high", "messages": []}),
+            "/v1/chat/completions",
+        )
+        .unwrap();
+        assert_eq!(chat["model"], "gpt-5");
+        assert_eq!(chat["reasoning_effort"], "high");
+
+        let responses = rewrite(
+            json!({"model": "gpt-5-codex-reasoning-low", "input": "hi", "reasoning": {"summary": "auto"}}),
+            "/v1/responses",
+        )
+        .unwrap();
+        assert_eq!(responses["model"], "gpt-5-codex");
+        assert_eq!(
+            responses["reasoning"],
+            json!({"summary": "auto", "effort": "low"})
+        );
+
+        let gemini = rewrite(
+            json!({"model": "gemini-2.5-pro-thinking-8192", "messages": []}),
+            "/v1/chat/completions",
+        )
+        .unwrap();
+        assert_eq!(gemini["model"], "gemini-2.5-pro");
+        assert_eq!(
+            gemini["extra_body"]["google"]["thinking_config"]["thinking_budget"],
+            8192
+        );
+
+        let native = rewrite(
+            json!({"model": "gemini-2.5-flash-thinking-auto", "contents": []}),
+            "/v1/generate",
+        )
+        .unwrap();
+        assert_eq!(
+            native["generationConfig"]["thinkingConfig"]["thinkingBudget"],
+            -1
+        );
+
+        assert!(rewrite(json!({"model": "gpt-5-reasoning-extreme"}), "/v1/responses").is_none());
+        assert!(rewrite(
+            json!({"model": "claude-opus-4-5-thinking-100"}),
+            "/v1/messages"
+        )
+        .is_none());
+        assert!(rewrite(json!({"model": "gemini-2.5-pro"}), "/v1/chat/completions").is_none());
+    }
+
+    #[test]
+    fn levels_and_budgets_are_clamped_to_thinking_support() {
+        let codex = support(0, 0, &["low", "medium", "high"]);
+        assert_eq!(clamp_level(Some(&codex), "medium"), "medium");
+        assert_eq!(clamp_level(Some(&codex), "xhigh"), "high");
+        assert_eq!(clamp_level(Some(&codex), "minimal"), "low");
+        assert_eq!(clamp_level(None, "xhigh"), "xhigh");
+
+        let gemini = support(128, 32768, &[]);
+        assert_eq!(clamp_budget(Some(&gemini), 100_000), 32768);
+        assert_eq!(clamp_budget(Some(&gemini), 0), 128);
+        assert_eq!(clamp_budget(Some(&gemini), -1), 32768);
+        assert_eq!(clamp_budget(None, 100_000), 100_000);
+    }
+}
@@ -0,0 +1,585 @@
+use crate::auth_manager;
+use crate::types::{
+    CapturedHeader, RequestCapture, RequestCaptureSettings, RequestCaptureSummary,
+    RequestReplayResult,
+};
+use chrono::{TimeZone, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+const REDACTED: &str = "[redacted]";
+const MAX_LISTED_CAPTURES: u32 = 500;
+const REPLAY_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// Headers never forwarded on replay, besides redacted ones.
+const REPLAY_SKIPPED_HEADERS: [&str; 5] = [
+    "host",
+    "content-length",
+    "connection",
+    "transfer-encoding",
+    "accept-encoding",
+];
+
+fn is_secret_header(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    matches!(
+        name.as_str(),
+        "authorization" | "proxy-authorization" | "cookie" | "set-cookie"
+    ) || name.ends_with("-token")
+        || [
+            "api-key",
+            "apikey",
+            "secret",
+            "session",
+            "password",
+            "signature",
+        ]
+        .iter()
+        .any(|needle| name.contains(needle))
+}
+
+/// Headers in capture form, with credential values replaced by `[redacted]`.
+pub fn sanitize_headers(headers: &hyper::HeaderMap) -> Vec<CapturedHeader> {
+    headers
+        .iter()
+        .map(|(name, value)| CapturedHeader {
+            name: name.as_str().to_string(),
+            value: if is_secret_header(name.as_str()) {
+                REDACTED.to_string()
+            } else {
+                String::from_utf8_lossy(value.as_bytes()).into_owned()
+            },
+        })
+        .collect()
+}
+
+/// Identifies the request a capture belongs to.
+#[derive(Debug, Clone, Default)]
+pub struct CaptureMeta {
+    pub request_id: String,
+    pub method: String,
+    pub path: String,
+    pub provider: String,
+    pub model: String,
+    pub client_id: String,
+    pub client_label: String,
+}
+
+/// Leading bytes of a body, plus its full size.
+#[derive(Debug, Clone, Default)]
+pub struct BodySample {
+    bytes: Vec<u8>,
+    total_bytes: i64,
+    limit: usize,
+}
+
+impl BodySample {
+    fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            ..Default::default()
+        }
+    }
+
+    pub fn observe(&mut self, chunk: &[u8]) {
+        self.total_bytes += chunk.len() as i64;
+        let take = self.limit.saturating_sub(self.bytes.len()).min(chunk.len());
+        self.bytes.extend_from_slice(&chunk[..take]);
+    }
+
+    fn is_truncated(&self) -> bool {
+        self.total_bytes > self.bytes.len() as i64
+    }
+
+    fn text(&self) -> String {
+        String::from_utf8_lossy(&self.bytes).into_owned()
+    }
+}
+
+/// Response half of a capture, filled while the body streams to the client.
+#[derive(Debug, Clone)]
+pub struct CapturedResponse {
+    pub headers: Vec<CapturedHeader>,
+    pub body: BodySample,
+}
+
+/// Request half of a capture, held by ThinkingProxy until the response is known.
+#[derive(Clone)]
+pub struct CaptureDraft {
+    store: Arc<RequestCaptureStore>,
+    meta: CaptureMeta,
+    request_headers: Vec<CapturedHeader>,
+    /// `None` for bodies streamed upstream without buffering.
+    request_body: Option<BodySample>,
+    request_bytes: i64,
+    max_body_bytes: usize,
+}
+
+impl CaptureDraft {
+    pub fn response(&self, headers: &hyper::HeaderMap) -> CapturedResponse {
+        CapturedResponse {
+            headers: sanitize_headers(headers),
+            body: BodySample::new(self.max_body_bytes),
+        }
+    }
+
+    /// Stores the pair in the background. `response` is `None` when the proxy answered itself.
+    pub fn finish(self, status_code: u16, duration_ms: i64, response: Option<CapturedResponse>) {
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = self.store.insert(&self, status_code, duration_ms, response) {
+                log::warn!("[RequestCapture] Failed to store capture: {}", e);
+            }
+        });
+    }
+}
+
+/// SQLite store for sanitized request/response pairs, capped by total size.
+pub struct RequestCaptureStore {
+    db_path: PathBuf,
+    settings: RwLock<RequestCaptureSettings>,
+}
+
+impl RequestCaptureStore {
+    pub fn new(settings: RequestCaptureSettings) -> Result<Self, String> {
+        Self::open(
+            auth_manager::get_auth_dir().join("codeforwarder-captures.db"),
+            settings,
+        )
+    }
+
+    pub(crate) fn open(db_path: PathBuf, settings: RequestCaptureSettings) -> Result<Self, String> {
+        let store = Self {
+            db_path,
+            settings: RwLock::new(settings),
+        };
+        store.init_schema()?;
+        Ok(store)
+    }
+
+    fn open_connection(path: &Path) -> Result<Connection, String> {
+        let conn = Connection::open(path).map_err(|e| {
+            format!(
+                "Failed to open capture database at {}: {}",
+                path.display(),
+                e
+            )
+        })?;
+        conn.execute_batch(
+            r#"
+            PRAGMA journal_mode = WAL;
+            PRAGMA synchronous = NORMAL;
+            "#,
+        )
+        .map_err(|e| format!("Failed to configure capture database: {}", e))?;
+        Ok(conn)
+    }
+
+    fn init_schema(&self) -> Result<(), String> {
+        let conn = Self::open_connection(&self.db_path)?;
+        conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS request_captures (
+              request_id TEXT PRIMARY KEY,
+              captured_at INTEGER NOT NULL,
+              method TEXT NOT NULL,
+              path TEXT NOT NULL,
+              provider TEXT NOT NULL,
+              model TEXT NOT NULL,
+              client_id TEXT NOT NULL,
+              client_label TEXT NOT NULL,
+              status_code INTEGER NOT NULL,
+              duration_ms INTEGER NOT NULL,
+              request_headers TEXT NOT NULL,
+              request_body TEXT,
+              request_body_bytes INTEGER NOT NULL,
+              request_body_truncated INTEGER NOT NULL,
+              response_headers TEXT NOT NULL,
+              response_body TEXT,
+              response_body_bytes INTEGER NOT NULL,
+              response_body_truncated INTEGER NOT NULL,
+              size_bytes INTEGER NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_request_captures_captured_at
+              ON request_captures(captured_at);
+            "#,
+        )
+        .map_err(|e| format!("Failed to initialize capture schema: {}", e))?;
+        Ok(())
+    }
+
+    pub fn settings(&self) -> RequestCaptureSettings {
+        *self.settings.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn set_settings(&self, settings: RequestCaptureSettings) {
+        *self.settings.write().unwrap_or_else(|e| e.into_inner()) = settings;
+    }
+
+    /// Starts a capture when capturing is enabled. `body` is the buffered body as forwarded.
+    pub fn begin(
+        self: &Arc<Self>,
+        meta: CaptureMeta,
+        headers: &hyper::HeaderMap,
+        body: Option<&[u8]>,
+        request_bytes: i64,
+    ) -> Option<CaptureDraft> {
+        let settings = self.settings();
+        if !settings.enabled {
+            return None;
+        }
+        let max_body_bytes = settings.max_body_kb as usize * 1024;
+        let request_body = body.map(|body| {
+            let mut sample = BodySample::new(max_body_bytes);
+            sample.observe(body);
+            sample
+        });
+        Some(CaptureDraft {
+            store: self.clone(),
+            meta,
+            request_headers: sanitize_headers(headers),
+            request_body,
+            request_bytes,
+            max_body_bytes,
+        })
+    }
+
+    fn insert(
+        &self,
+        draft: &CaptureDraft,
+        status_code: u16,
+        duration_ms: i64,
+        response: Option<CapturedResponse>,
+    ) -> Result<(), String> {
+        let request_headers = serde_json::to_string(&draft.request_headers)
+            .map_err(|e| format!("Failed to serialize request headers: {}", e))?;
+        let response_headers =
+            serde_json::to_string(&response.as_ref().map(|r| &r.headers).unwrap_or(&Vec::new()))
+                .map_err(|e| format!("Failed to serialize response headers: {}", e))?;
+        let request_body = draft.request_body.as_ref().map(BodySample::text);
+        let response_body = response.as_ref().map(|r| r.body.text());
+        let size_bytes = (request_headers.len()
+            + response_headers.len()
+            + request_body.as_ref().map_or(0, String::len)
+            + response_body.as_ref().map_or(0, String::len)) as i64;
+        let max_storage_bytes = self.settings().max_storage_mb as i64 * 1024 * 1024;
+
+        let conn = Self::open_connection(&self.db_path)?;
+        let tx = conn
+            .unchecked_transaction()
+            .map_err(|e| format!("Failed to start capture transaction: {}", e))?;
+        tx.execute(
+            r#"
+            INSERT OR REPLACE INTO request_captures (
+              request_id, captured_at, method, path, provider, model, client_id, client_label,
+              status_code, duration_ms, request_headers, request_body, request_body_bytes,
+              request_body_truncated, response_headers, response_body, response_body_bytes,
+              response_body_truncated, size_bytes
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+            params![
+                draft.meta.request_id,
+                Utc::now().timestamp(),
+                draft.meta.method,
+                draft.meta.path,
+                draft.meta.provider,
+                draft.meta.model,
+                draft.meta.client_id,
+                draft.meta.client_label,
+                status_code as i64,
+                duration_ms,
+                request_headers,
+                request_body,
+                draft.request_bytes,
+                draft
+                    .request_body
+                    .as_ref()
+                    .is_some_and(BodySample::is_truncated),
+                response_headers,
+                response_body,
+                response.as_ref().map_or(0, |r| r.body.total_bytes),
+                response.as_ref().is_some_and(|r| r.body.is_truncated()),
+                size_bytes,
+            ],
+        )
+        .map_err(|e| format!("Failed to insert capture: {}", e))?;
+        // Keep the newest captures that fit within the cap.
+        tx.execute(
+            r#"
+            DELETE FROM request_captures
+            WHERE request_id IN (
+              SELECT request_id FROM (
+                SELECT
+                  request_id,
+                  SUM(size_bytes) OVER (ORDER BY captured_at DESC, rowid DESC) AS running
+                FROM request_captures
+              )
+              WHERE running > ?1
+            )
+            "#,
+            [max_storage_bytes],
+        )
+        .map_err(|e| format!("Failed to evict old captures: {}", e))?;
+        tx.commit()
+            .map_err(|e| format!("Failed to commit capture: {}", e))?;
+        Ok(())
+    }
+
+    fn summary_from_row(row: &Row<'_>) -> rusqlite::Result<RequestCaptureSummary> {
+        Ok(RequestCaptureSummary {
+            request_id: row.get(0)?,
+            captured_at: Utc
+                .timestamp_opt(row.get::<_, i64>(1)?, 0)
+                .single()
+                .map(|dt| dt.to_rfc3339())
+                .unwrap_or_default(),
+            method: row.get(2)?,
+            path: row.get(3)?,
+            provider: row.get(4)?,
+            model: row.get(5)?,
+            client_label: row.get(6)?,
+            status_code: row.get(7)?,
+            duration_ms: row.get(8)?,
+            size_bytes: row.get(9)?,
+        })
+    }
+
+    /// Newest first.
+    pub async fn list(&self, limit: Option<u32>) -> Result<Vec<RequestCaptureSummary>, String> {
+        let db_path = self.db_path.clone();
+        let limit = limit.unwrap_or(100).clamp(1, MAX_LISTED_CAPTURES);
+        tokio::task::spawn_blocking(move || {
+            let conn = Self::open_connection(&db_path)?;
+            let mut stmt = conn
+                .prepare(
+                    r#"
+                    SELECT request_id, captured_at, method, path, provider, model, client_label,
+                           status_code, duration_ms, size_bytes
+                    FROM request_captures
+                    ORDER BY captured_at DESC, rowid DESC
+                    LIMIT ?1
+                    "#,
+                )
+                .map_err(|e| format!("Failed to prepare capture list query: {}", e))?;
+            let rows = stmt
+                .query_map([limit], Self::summary_from_row)
+                .map_err(|e| format!("Failed to query captures: {}", e))?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| format!("Failed to read capture rows: {}", e))?;
+            Ok(rows)
+        })
+        .await
+        .map_err(|e| format!("Failed to join capture list task: {}", e))?
+    }
+
+    pub async fn get(&self, request_id: String) -> Result<Option<RequestCapture>, String> {
+        let db_path = self.db_path.clone();
+        tokio::task::spawn_blocking(move || {
+            let conn = Self::open_connection(&db_path)?;
+            conn.query_row(
+                r#"
+                SELECT request_id, captured_at, method, path, provider, model, client_label,
+                       status_code, duration_ms, size_bytes, client_id, request_headers,
+                       request_body, request_body_bytes, request_body_truncated,
+                       response_headers, response_body, response_body_bytes,
+                       response_body_truncated
+                FROM request_captures
+                WHERE request_id = ?1
+                "#,
+                [request_id],
+                |row| {
+                    let headers = |i: usize| -> rusqlite::Result<Vec<CapturedHeader>> {
+                        Ok(serde_json::from_str(&row.get::<_, String>(i)?).unwrap_or_default())
+                    };
+                    Ok(RequestCapture {
+                        summary: Self::summary_from_row(row)?,
+                        client_id: row.get(10)?,
+                        request_headers: headers(11)?,
+                        request_body: row.get(12)?,
+                        request_body_bytes: row.get(13)?,
+                        request_body_truncated: row.get(14)?,
+                        response_headers: headers(15)?,
+                        response_body: row.get(16)?,
+                        response_body_bytes: row.get(17)?,
+                        response_body_truncated: row.get(18)?,
+                    })
+                },
+            )
+            .optional()
+            .map_err(|e| format!("Failed to load capture: {}", e))
+        })
+        .await
+        .map_err(|e| format!("Failed to join capture query task: {}", e))?
+    }
+
+    /// Deletes all captures and returns how many were removed.
+    pub async fn clear(&self) -> Result<usize, String> {
+        let db_path = self.db_path.clone();
+        tokio::task::spawn_blocking(move || {
+            let conn = Self::open_connection(&db_path)?;
+            let removed = conn
+                .execute("DELETE FROM request_captures", [])
+                .map_err(|e| format!("Failed to clear captures: {}", e))?;
+            conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE); VACUUM;")
+                .map_err(|e| format!("Failed to compact capture database: {}", e))?;
+            Ok(removed)
+        })
+        .await
+        .map_err(|e| format!("Failed to join capture clear task: {}", e))?
+    }
+}
+
+/// Sends a captured request through the running proxy, so it takes the current routing,
+/// thinking rewrite and budgets. Redacted credentials are dropped; `client_key` stands in
+/// for them when the original caller used an issued client key.
+pub async fn replay(
+    capture: &RequestCapture,
+    proxy_url: &str,
+    client_key: Option<&str>,
+    max_body_bytes: usize,
+) -> Result<RequestReplayResult, String> {
+    if capture.request_body_truncated {
+        return Err(
+            "Captured request body was truncated; raise the capture body limit to replay it"
+                .to_string(),
+        );
+    }
+    if capture.request_body.is_none() && capture.request_body_bytes > 0 {
+        return Err("Request body was streamed upstream and not captured".to_string());
+    }
+
+    let method = reqwest::Method::from_bytes(capture.summary.method.as_bytes())
+        .map_err(|e| format!("Invalid captured method: {}", e))?;
+    let mut headers = reqwest::header::HeaderMap::new();
+    for header in &capture.request_headers {
+        let name = header.name.to_ascii_lowercase();
+        if header.value == REDACTED || REPLAY_SKIPPED_HEADERS.contains(&name.as_str()) {
+            continue;
+        }
+        if let (Ok(name), Ok(value)) = (
+            reqwest::header::HeaderName::from_bytes(name.as_bytes()),
+            reqwest::header::HeaderValue::from_str(&header.value),
+        ) {
+            headers.append(name, value);
+        }
+    }
+    if let Some(key) = client_key {
+        let value = reqwest::header::HeaderValue::from_str(&format!("Bearer {}", key))
+            .map_err(|e| format!("Invalid client key: {}", e))?;
+        headers.insert(reqwest::header::AUTHORIZATION, value);
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(REPLAY_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to build replay client: {}", e))?;
+    let started_at = Instant::now();
+    let response = client
+        .request(method, format!("{}{}", proxy_url, capture.summary.path))
+        .headers(headers)
+        .body(capture.request_body.clone().unwrap_or_default())
+        .send()
+        .await
+        .map_err(|e| format!("Failed to replay request: {}", e))?;
+    let status_code = response.status().as_u16();
+    let response_headers = sanitize_headers(response.headers());
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| format!("Failed to read replay response: {}", e))?;
+    let mut body = BodySample::new(max_body_bytes);
+    body.observe(&bytes);
+
+    Ok(RequestReplayResult {
+        status_code,
+        duration_ms: started_at.elapsed().as_millis() as i64,
+        response_headers,
+        response_body: body.text(),
+        response_body_truncated: body.is_truncated(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_db_path() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("cf-captures-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join("captures.db")
+    }
+
+    #[test]
+    fn sanitize_headers_redacts_credentials() {
+        let mut headers = hyper::HeaderMap::new();
+        headers.insert("authorization", "Bearer secret".parse().unwrap());
+        headers.insert("x-api-key", "sk-ant".parse().unwrap());
+        headers.insert("x-goog-api-key", "AIza".parse().unwrap());
+        headers.insert("anthropic-beta", "interleaved-thinking".parse().unwrap());
+        headers.insert("x-ratelimit-remaining-tokens", "100".parse().unwrap());
+
+        let sanitized = sanitize_headers(&headers);
+        let value = |name: &str| {
+            sanitized
+                .iter()
+                .find(|h| h.name == name)
+                .map(|h| h.value.as_str())
+        };
+        assert_eq!(value("authorization"), Some(REDACTED));
+        assert_eq!(value("x-api-key"), Some(REDACTED));
+        assert_eq!(value("x-goog-api-key"), Some(REDACTED));
+        assert_eq!(value("anthropic-beta"), Some("interleaved-thinking"));
+        assert_eq!(value("x-ratelimit-remaining-tokens"), Some("100"));
+    }
+
+    #[tokio::test]
+    async fn stores_truncated_pairs_and_evicts_oldest_over_cap() {
+        let store = Arc::new(
+            RequestCaptureStore::open(
+                temp_db_path(),
+                RequestCaptureSettings {
+                    enabled: true,
+                    max_body_kb: 1,
+                    max_storage_mb: 1,
+                },
+            )
+            .unwrap(),
+        );
+        let body = vec![b'a'; 4096];
+        for i in 0..700 {
+            let meta = CaptureMeta {
+                request_id: format!("r{}", i),
+                method: "POST".to_string(),
+                path: "/v1/messages".to_string(),
+                ..Default::default()
+            };
+            let draft = store
+                .begin(meta, &hyper::HeaderMap::new(), Some(&body), 4096)
+                .unwrap();
+            let mut response = draft.response(&hyper::HeaderMap::new());
+            response.body.observe(&[b'b'; 2048]);
+            store.insert(&draft, 200, 5, Some(response)).unwrap();
+        }
+
+        let capture = store.get("r699".to_string()).await.unwrap().unwrap();
+        assert_eq!(capture.request_body.as_ref().map(String::len), Some(1024));
+        assert!(capture.request_body_truncated);
+        assert_eq!(capture.response_body_bytes, 2048);
+        assert!(capture.response_body_truncated);
+
+        // ~2 KB per capture against a 1 MB cap keeps roughly the newest 500.
+        let listed = store.list(Some(MAX_LISTED_CAPTURES)).await.unwrap();
+        assert_eq!(listed.len(), MAX_LISTED_CAPTURES as usize);
+        assert_eq!(listed[0].request_id, "r699");
+        assert!(store.get("r100".to_string()).await.unwrap().is_none());
+        assert!(store.get("r300".to_string()).await.unwrap().is_some());
+
+        store.set_settings(RequestCaptureSettings::default());
+        assert!(store
+            .begin(CaptureMeta::default(), &hyper::HeaderMap::new(), None, 0)
+            .is_none());
+    }
+}
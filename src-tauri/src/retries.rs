@@ -0,0 +1,193 @@
+use crate::failover::retry_after;
+use crate::types::ProxyRetries;
+use std::sync::RwLock;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Upper bounds for configured retries.
+const MAX_ATTEMPTS: u32 = 10;
+const MAX_DELAY_MS: u64 = 5 * 60 * 1000;
+
+/// Anthropic rate-limit families; each has `-remaining` and `-reset` (RFC 3339) headers.
+const ANTHROPIC_RATELIMITS: [&str; 4] = [
+    "anthropic-ratelimit-requests",
+    "anthropic-ratelimit-tokens",
+    "anthropic-ratelimit-input-tokens",
+    "anthropic-ratelimit-output-tokens",
+];
+
+/// Rate limited (`429`) and overloaded (`529`) responses.
+pub fn is_retry_status(status_code: u16) -> bool {
+    status_code == 429 || status_code == 529
+}
+
+pub fn validate(retries: &ProxyRetries) -> Result<(), String> {
+    if retries.max_attempts == 0 || retries.max_attempts > MAX_ATTEMPTS {
+        return Err(format!(
+            "Retry attempts must be between 1 and {}",
+            MAX_ATTEMPTS
+        ));
+    }
+    if retries.max_delay_ms > MAX_DELAY_MS {
+        return Err(format!(
+            "Retry delay must be at most {} seconds",
+            MAX_DELAY_MS / 1000
+        ));
+    }
+    if retries.base_delay_ms > retries.max_delay_ms {
+        return Err("Base retry delay cannot exceed the maximum delay".to_string());
+    }
+    Ok(())
+}
+
+/// Time until the latest exhausted Anthropic rate limit resets, if any limit is exhausted.
+fn anthropic_reset(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    let header = |name: String| headers.get(name).and_then(|v| v.to_str().ok());
+    let now = chrono::Utc::now();
+    ANTHROPIC_RATELIMITS
+        .iter()
+        .filter(|family| header(format!("{}-remaining", family)).map(str::trim) == Some("0"))
+        .filter_map(|family| header(format!("{}-reset", family)))
+        .filter_map(|reset| chrono::DateTime::parse_from_rfc3339(reset.trim()).ok())
+        .map(|reset| {
+            (reset.with_timezone(&chrono::Utc) - now)
+                .num_milliseconds()
+                .max(0)
+        })
+        .max()
+        .map(|ms| Duration::from_millis(ms as u64))
+}
+
+/// Exponential backoff for the retry after `attempt`, with jitter over its upper half.
+fn backoff(retries: &ProxyRetries, attempt: u32) -> Duration {
+    let exponent = attempt.saturating_sub(1).min(20);
+    let ceiling = retries
+        .base_delay_ms
+        .saturating_mul(1 << exponent)
+        .min(retries.max_delay_ms);
+    let half = ceiling / 2;
+    // A v4 UUID is random bits, which is all the jitter needs.
+    let jitter = (Uuid::new_v4().as_u128() % (half as u128 + 1)) as u64;
+    Duration::from_millis(ceiling - half + jitter)
+}
+
+/// Live retry settings used by ThinkingProxy.
+#[derive(Default)]
+pub struct RetryPolicy {
+    settings: RwLock<ProxyRetries>,
+}
+
+impl RetryPolicy {
+    pub fn set_settings(&self, settings: ProxyRetries) {
+        if let Ok(mut current) = self.settings.write() {
+            *current = settings;
+        }
+    }
+
+    /// How long to wait before sending the request again after `attempt` attempts got
+    /// `status_code`, or `None` to relay the response. Upstream's `Retry-After` or exhausted
+    /// Anthropic rate limits take precedence over backoff; waits beyond `max_delay_ms` are
+    /// not retried.
+    pub fn delay_for(
+        &self,
+        attempt: u32,
+        status_code: u16,
+        headers: &reqwest::header::HeaderMap,
+    ) -> Option<Duration> {
+        let settings = match self.settings.read() {
+            Ok(settings) => *settings,
+            Err(poisoned) => *poisoned.into_inner(),
+        };
+        if !is_retry_status(status_code) || attempt >= settings.max_attempts {
+            return None;
+        }
+        let delay = retry_after(headers)
+            .or_else(|| anthropic_reset(headers))
+            .unwrap_or_else(|| backoff(&settings, attempt));
+        (delay <= Duration::from_millis(settings.max_delay_ms)).then_some(delay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::{HeaderMap, HeaderValue};
+
+    #[test]
+    fn delay_follows_backoff_and_upstream_hints() {
+        let policy = RetryPolicy::default();
+        policy.set_settings(ProxyRetries {
+            max_attempts: 3,
+            base_delay_ms: 1000,
+            max_delay_ms: 10_000,
+        });
+        let empty = HeaderMap::new();
+
+        let first = policy.delay_for(1, 529, &empty).unwrap();
+        assert!(first >= Duration::from_millis(500) && first <= Duration::from_millis(1000));
+        let second = policy.delay_for(2, 429, &empty).unwrap();
+        assert!(second >= Duration::from_millis(1000) && second <= Duration::from_millis(2000));
+        assert_eq!(policy.delay_for(3, 529, &empty), None);
+        assert_eq!(policy.delay_for(1, 500, &empty), None);
+
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after", HeaderValue::from_static("4"));
+        assert_eq!(
+            policy.delay_for(1, 429, &headers),
+            Some(Duration::from_secs(4))
+        );
+        headers.insert("retry-after", HeaderValue::from_static("60"));
+        assert_eq!(policy.delay_for(1, 429, &headers), None);
+
+        let reset = (chrono::Utc::now() + chrono::Duration::seconds(3)).to_rfc3339();
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "anthropic-ratelimit-tokens-remaining",
+            HeaderValue::from_static("0"),
+        );
+        headers.insert(
+            "anthropic-ratelimit-tokens-reset",
+            HeaderValue::from_str(&reset).unwrap(),
+        );
+        let delay = policy.delay_for(1, 429, &headers).unwrap();
+        assert!(delay > Duration::from_secs(2) && delay <= Duration::from_secs(3));
+    }
+
+    #[test]
+    fn validate_bounds_attempts() {
+        let retries = |max_attempts| ProxyRetries {
+            max_attempts,
+            ..ProxyRetries::default()
+        };
+        assert!(validate(&retries(1)).is_ok());
+        assert!(validate(&retries(10)).is_ok());
+        for attempts in [0, 11] {
+            assert_eq!(
+                validate(&retries(attempts)),
+                Err("Retry attempts must be between 1 and 10".to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn validate_bounds_delays() {
+        assert!(validate(&ProxyRetries::default()).is_ok());
+        let too_long = ProxyRetries {
+            max_delay_ms: MAX_DELAY_MS + 1,
+            ..ProxyRetries::default()
+        };
+        assert_eq!(
+            validate(&too_long),
+            Err("Retry delay must be at most 300 seconds".to_string())
+        );
+        let inverted = ProxyRetries {
+            base_delay_ms: 2000,
+            max_delay_ms: 1000,
+            ..ProxyRetries::default()
+        };
+        assert_eq!(
+            validate(&inverted),
+            Err("Base retry delay cannot exceed the maximum delay".to_string())
+        );
+    }
+}
@@ -0,0 +1,250 @@
+use crate::types::ServerLogLine;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+const LOG_FILE_NAME: &str = "backend.log";
+const MAX_LOG_FILE_BYTES: u64 = 5 * 1024 * 1024;
+const MAX_ROTATED_FILES: usize = 3;
+
+pub fn log_dir() -> PathBuf {
+    crate::auth_manager::get_auth_dir().join("logs")
+}
+
+// ---------------------------------------------------------------------------
+// Levels
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warn => "warn",
+            LogLevel::Error => "error",
+        }
+    }
+
+    pub fn from_str_loose(s: &str) -> Option<LogLevel> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "debug" | "trace" => Some(LogLevel::Debug),
+            "info" => Some(LogLevel::Info),
+            "warn" | "warning" => Some(LogLevel::Warn),
+            "error" | "fatal" | "panic" => Some(LogLevel::Error),
+            _ => None,
+        }
+    }
+}
+
+/// Best-effort level detection for cli-proxy-api-plus output (`[info]`, `level=warn`, ...).
+/// Lines without a recognizable marker fall back to `default`.
+pub fn detect_level(line: &str, default: LogLevel) -> LogLevel {
+    let lower = line.to_ascii_lowercase();
+    for (start, _) in lower.match_indices('[') {
+        let rest = &lower[start + 1..];
+        if let Some(end) = rest.find(']') {
+            if let Some(level) = LogLevel::from_str_loose(&rest[..end]) {
+                return level;
+            }
+        }
+    }
+    if let Some(pos) = lower.find("level=") {
+        let value: String = lower[pos + "level=".len()..]
+            .trim_start_matches('"')
+            .chars()
+            .take_while(|c| c.is_ascii_alphabetic())
+            .collect();
+        if let Some(level) = LogLevel::from_str_loose(&value) {
+            return level;
+        }
+    }
+    default
+}
+
+// ---------------------------------------------------------------------------
+// Filtering
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Default, Clone)]
+pub struct LogFilter {
+    pub min_level: Option<LogLevel>,
+    pub contains: Option<String>,
+    pub limit: Option<usize>,
+}
+
+impl LogFilter {
+    pub fn new(level: Option<&str>, contains: Option<&str>, limit: Option<usize>) -> Self {
+        Self {
+            min_level: level.and_then(LogLevel::from_str_loose),
+            contains: contains
+                .map(|s| s.trim().to_lowercase())
+                .filter(|s| !s.is_empty()),
+            limit,
+        }
+    }
+
+    pub fn matches(&self, line: &ServerLogLine) -> bool {
+        if let Some(min_level) = self.min_level {
+            let level = LogLevel::from_str_loose(&line.level).unwrap_or(LogLevel::Info);
+            if level < min_level {
+                return false;
+            }
+        }
+        if let Some(needle) = &self.contains {
+            if !line.message.to_lowercase().contains(needle) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Applies the filter to lines in chronological order, keeping the newest `limit` matches.
+    pub fn apply<'a>(&self, lines: impl Iterator<Item = &'a ServerLogLine>) -> Vec<ServerLogLine> {
+        let mut matched: Vec<ServerLogLine> = lines.filter(|l| self.matches(l)).cloned().collect();
+        if let Some(limit) = self.limit {
+            if matched.len() > limit {
+                matched.drain(..matched.len() - limit);
+            }
+        }
+        matched
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Rotating file sink
+// ---------------------------------------------------------------------------
+
+pub struct RotatingLogFile {
+    dir: PathBuf,
+    file: File,
+    written: u64,
+}
+
+impl RotatingLogFile {
+    pub fn open(dir: PathBuf) -> Result<Self, String> {
+        fs::create_dir_all(&dir).map_err(|e| format!("Failed to create log directory: {}", e))?;
+        let path = dir.join(LOG_FILE_NAME);
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| format!("Failed to open log file: {}", e))?;
+        let written = file.metadata().map(|m| m.len()).unwrap_or(0);
+        Ok(Self { dir, file, written })
+    }
+
+    pub fn write_line(&mut self, line: &ServerLogLine) {
+        let rendered = format!(
+            "{} [{}] [{}] {}\n",
+            line.timestamp, line.level, line.source, line.message
+        );
+        if self.written + rendered.len() as u64 > MAX_LOG_FILE_BYTES {
+            if let Err(e) = self.rotate() {
+                log::warn!("[ServerLogs] Failed to rotate backend log: {}", e);
+            }
+        }
+        if self.file.write_all(rendered.as_bytes()).is_ok() {
+            self.written += rendered.len() as u64;
+        }
+    }
+
+    fn rotate(&mut self) -> Result<(), String> {
+        let _ = self.file.flush();
+        for index in (1..MAX_ROTATED_FILES).rev() {
+            let from = self.dir.join(format!("{}.{}", LOG_FILE_NAME, index));
+            if from.exists() {
+                let to = self.dir.join(format!("{}.{}", LOG_FILE_NAME, index + 1));
+                let _ = fs::rename(&from, &to);
+            }
+        }
+        let current = self.dir.join(LOG_FILE_NAME);
+        fs::rename(&current, self.dir.join(format!("{}.1", LOG_FILE_NAME)))
+            .map_err(|e| format!("Failed to rotate log file: {}", e))?;
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&current)
+            .map_err(|e| format!("Failed to reopen log file: {}", e))?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(level: &str, message: &str) -> ServerLogLine {
+        ServerLogLine {
+            timestamp: "2026-01-01T00:00:00Z".to_string(),
+            level: level.to_string(),
+            source: "stdout".to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn detect_level_reads_common_markers() {
+        assert_eq!(
+            detect_level(
+                "[2026-01-01 10:00:00] [error] upstream failed",
+                LogLevel::Info
+            ),
+            LogLevel::Error
+        );
+        assert_eq!(
+            detect_level("time=now level=warning msg=slow", LogLevel::Info),
+            LogLevel::Warn
+        );
+        assert_eq!(
+            detect_level("[main.go:12] plain line", LogLevel::Info),
+            LogLevel::Info
+        );
+        assert_eq!(detect_level("no markers", LogLevel::Warn), LogLevel::Warn);
+    }
+
+    #[test]
+    fn filter_applies_level_substring_and_limit() {
+        let lines = vec![
+            line("debug", "token refresh"),
+            line("info", "Token refreshed"),
+            line("warn", "token expiring"),
+            line("error", "quota exceeded"),
+        ];
+
+        let filter = LogFilter::new(Some("info"), Some("TOKEN"), None);
+        let out = filter.apply(lines.iter());
+        assert_eq!(out.len(), 2);
+        assert_eq!(out[0].message, "Token refreshed");
+
+        let filter = LogFilter::new(None, None, Some(1));
+        let out = filter.apply(lines.iter());
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].message, "quota exceeded");
+    }
+
+    #[test]
+    fn rotating_log_file_rotates_when_full() {
+        let dir = std::env::temp_dir().join(format!(
+            "codeforwarder-server-logs-test-{}",
+            uuid::Uuid::new_v4()
+        ));
+        let mut file = RotatingLogFile::open(dir.clone()).unwrap();
+        file.written = MAX_LOG_FILE_BYTES;
+        file.write_line(&line("info", "after rotation"));
+
+        assert!(dir.join(format!("{}.1", LOG_FILE_NAME)).exists());
+        let current = fs::read_to_string(dir.join(LOG_FILE_NAME)).unwrap();
+        assert!(current.contains("after rotation"));
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -1,20 +1,22 @@
-use crate::types::AuthCommand;
-use chrono::Utc;
+use crate::server_logs::{self, LogFilter, LogLevel, RotatingLogFile};
+use crate::types::{AuthCommand, ServerLogLine};
+use chrono::{SecondsFormat, Utc};
 use log;
 use std::collections::HashMap;
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
 use tokio::process::{Child, Command};
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, RwLock};
 use uuid::Uuid;
 
 #[cfg(target_os = "windows")]
 const CREATE_NO_WINDOW: u32 = 0x08000000;
 #[cfg(target_os = "windows")]
 const CLI_PROXY_IMAGE_NAME: &str = "cli-proxy-api-plus.exe";
-const PROXY_PORT: u16 = 8317;
-const BACKEND_PORT: u16 = 8318;
 
 fn apply_hidden_process_flags(_cmd: &mut Command) {
     #[cfg(target_os = "windows")]
@@ -82,7 +84,6 @@ impl<T> RingBuffer<T> {
         self.tail = (self.tail + 1) % capacity;
     }
 
-    #[cfg(test)]
     pub fn elements(&self) -> Vec<&T> {
         let capacity = self.storage.len();
         if self.count == 0 {
@@ -105,11 +106,93 @@ impl<T> RingBuffer<T> {
 // ---------------------------------------------------------------------------
 
 const MAX_LOG_LINES: usize = 1000;
+const READINESS_TIMEOUT: Duration = Duration::from_secs(30);
+const READINESS_POLL_INTERVAL: Duration = Duration::from_millis(250);
+const PROBE_CONNECT_TIMEOUT: Duration = Duration::from_millis(500);
+const PROBE_REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+fn probe_client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .no_proxy()
+            .timeout(PROBE_REQUEST_TIMEOUT)
+            .build()
+            .expect("Failed to build readiness probe client")
+    })
+}
+
+/// Called for every captured log line, e.g. to forward it to the UI.
+pub type LogListener = Arc<dyn Fn(&ServerLogLine) + Send + Sync>;
+
+/// Fan-out for backend log lines: in-memory ring buffer, optional file, optional listener.
+/// Cloned into the stdout/stderr reader tasks.
+#[derive(Clone)]
+struct LogPipeline {
+    buffer: Arc<Mutex<RingBuffer<ServerLogLine>>>,
+    file: Arc<std::sync::Mutex<Option<RotatingLogFile>>>,
+    listener: Arc<std::sync::RwLock<Option<LogListener>>>,
+}
+
+impl LogPipeline {
+    fn new() -> Self {
+        Self {
+            buffer: Arc::new(Mutex::new(RingBuffer::new(MAX_LOG_LINES))),
+            file: Arc::new(std::sync::Mutex::new(None)),
+            listener: Arc::new(std::sync::RwLock::new(None)),
+        }
+    }
+
+    async fn push(&self, source: &str, level: LogLevel, message: &str) {
+        let line = ServerLogLine {
+            timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            level: level.as_str().to_string(),
+            source: source.to_string(),
+            message: message.to_string(),
+        };
+
+        if let Ok(mut file) = self.file.lock() {
+            if let Some(file) = file.as_mut() {
+                file.write_line(&line);
+            }
+        }
+        let listener = self.listener.read().ok().and_then(|l| l.clone());
+        if let Some(listener) = listener {
+            listener(&line);
+        }
+
+        self.buffer.lock().await.append(line);
+    }
+
+    fn spawn_reader<R>(&self, reader: R, source: &'static str, default_level: LogLevel)
+    where
+        R: tokio::io::AsyncRead + Unpin + Send + 'static,
+    {
+        let pipeline = self.clone();
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(reader).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                if !line.is_empty() {
+                    let level = server_logs::detect_level(&line, default_level);
+                    pipeline.push(source, level, &line).await;
+                }
+            }
+        });
+    }
+}
 
 pub struct ServerManager {
     child: Option<Child>,
     is_running: bool,
-    log_buffer: Arc<Mutex<RingBuffer<String>>>,
+    logs: LogPipeline,
+    /// Bumped on every successful start and every stop, so a supervisor can tell
+    /// whether the process it is watching is still the current one.
+    generation: u64,
+    /// `(config_path, binary_path)` of the last successful start, reused by `restart`.
+    launch: Option<(String, String)>,
+    /// Set once the readiness probe passes; shared with ThinkingProxy to gate traffic.
+    ready: Arc<AtomicBool>,
 }
 
 impl ServerManager {
@@ -117,7 +200,10 @@ impl ServerManager {
         Self {
             child: None,
             is_running: false,
-            log_buffer: Arc::new(Mutex::new(RingBuffer::new(MAX_LOG_LINES))),
+            logs: LogPipeline::new(),
+            generation: 0,
+            launch: None,
+            ready: Arc::new(AtomicBool::new(false)),
         }
     }
 
@@ -127,6 +213,24 @@ impl ServerManager {
         self.is_running
     }
 
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Whether the backend is running and has passed the readiness probe.
+    pub fn is_ready(&self) -> bool {
+        self.is_running && self.ready.load(Ordering::SeqCst)
+    }
+
+    /// Shared readiness flag, handed to ThinkingProxy.
+    pub fn readiness(&self) -> Arc<AtomicBool> {
+        self.ready.clone()
+    }
+
+    pub fn mark_ready(&self) {
+        self.ready.store(true, Ordering::SeqCst);
+    }
+
     pub async fn refresh_running_status(&mut self) -> bool {
         if !self.is_running {
             return false;
@@ -143,6 +247,7 @@ impl ServerManager {
             }
         } else {
             self.is_running = false;
+            self.ready.store(false, Ordering::SeqCst);
             clear_managed_pid();
             return false;
         }
@@ -150,11 +255,12 @@ impl ServerManager {
         if let Some(status) = exited_status {
             self.child = None;
             self.is_running = false;
+            self.ready.store(false, Ordering::SeqCst);
             clear_managed_pid();
-            self.add_log(&format!(
-                "Server exited unexpectedly with status: {}",
-                status
-            ))
+            self.add_log(
+                LogLevel::Error,
+                &format!("Server exited unexpectedly with status: {}", status),
+            )
             .await;
             return false;
         }
@@ -162,9 +268,13 @@ impl ServerManager {
         if let Some(err) = wait_error {
             self.child = None;
             self.is_running = false;
+            self.ready.store(false, Ordering::SeqCst);
             clear_managed_pid();
-            self.add_log(&format!("Failed to check server process state: {}", err))
-                .await;
+            self.add_log(
+                LogLevel::Error,
+                &format!("Failed to check server process state: {}", err),
+            )
+            .await;
             return false;
         }
 
@@ -173,11 +283,38 @@ impl ServerManager {
 
     // -- logging ------------------------------------------------------------
 
-    pub async fn add_log(&self, message: &str) {
-        let timestamp = Utc::now().format("%H:%M:%S").to_string();
-        let log_line = format!("[{}] {}", timestamp, message);
-        let mut buf = self.log_buffer.lock().await;
-        buf.append(log_line);
+    pub async fn add_log(&self, level: LogLevel, message: &str) {
+        self.logs.push("manager", level, message).await;
+    }
+
+    pub async fn get_logs(&self, filter: &LogFilter) -> Vec<ServerLogLine> {
+        let buffer = self.logs.buffer.lock().await;
+        filter.apply(buffer.elements().into_iter())
+    }
+
+    pub fn set_log_listener(&self, listener: Option<LogListener>) {
+        if let Ok(mut current) = self.logs.listener.write() {
+            *current = listener;
+        }
+    }
+
+    /// Starts or stops mirroring log lines to `<auth dir>/logs/backend.log` (rotated).
+    pub fn set_log_persistence(&self, enabled: bool) -> Result<(), String> {
+        let next = if enabled {
+            Some(RotatingLogFile::open(server_logs::log_dir())?)
+        } else {
+            None
+        };
+        let mut file = self
+            .logs
+            .file
+            .lock()
+            .map_err(|_| "Log file lock poisoned".to_string())?;
+        if enabled && file.is_some() {
+            return Ok(());
+        }
+        *file = next;
+        Ok(())
     }
 
     // -- start / stop -------------------------------------------------------
@@ -209,44 +346,24 @@ impl ServerManager {
 
         self.child = Some(child);
         self.is_running = true;
+        self.ready.store(false, Ordering::SeqCst);
+        self.generation += 1;
+        self.launch = Some((config_path.to_string(), binary_path.to_string()));
         if let Some(pid) = self.child.as_ref().and_then(|c| c.id()) {
             persist_managed_pid(pid);
         }
-        self.add_log(&format!("Server started (binary={})", binary_path))
-            .await;
+        self.add_log(
+            LogLevel::Info,
+            &format!("Server started (binary={})", binary_path),
+        )
+        .await;
 
-        // Spawn stdout reader
+        // Spawn stdout/stderr readers
         if let Some(stdout) = stdout {
-            let buf = Arc::clone(&self.log_buffer);
-            tokio::spawn(async move {
-                let reader = BufReader::new(stdout);
-                let mut lines = reader.lines();
-                while let Ok(Some(line)) = lines.next_line().await {
-                    if !line.is_empty() {
-                        let ts = Utc::now().format("%H:%M:%S").to_string();
-                        let entry = format!("[{}] {}", ts, line);
-                        let mut b = buf.lock().await;
-                        b.append(entry);
-                    }
-                }
-            });
+            self.logs.spawn_reader(stdout, "stdout", LogLevel::Info);
         }
-
-        // Spawn stderr reader
         if let Some(stderr) = stderr {
-            let buf = Arc::clone(&self.log_buffer);
-            tokio::spawn(async move {
-                let reader = BufReader::new(stderr);
-                let mut lines = reader.lines();
-                while let Ok(Some(line)) = lines.next_line().await {
-                    if !line.is_empty() {
-                        let ts = Utc::now().format("%H:%M:%S").to_string();
-                        let entry = format!("[{}] WARN: {}", ts, line);
-                        let mut b = buf.lock().await;
-                        b.append(entry);
-                    }
-                }
-            });
+            self.logs.spawn_reader(stderr, "stderr", LogLevel::Warn);
         }
 
         Ok(())
@@ -254,7 +371,7 @@ impl ServerManager {
 
     pub async fn stop(&mut self) {
         if let Some(mut child) = self.child.take() {
-            self.add_log("Stopping server...").await;
+            self.add_log(LogLevel::Info, "Stopping server...").await;
 
             #[cfg(target_os = "windows")]
             let child_pid = child.id();
@@ -273,23 +390,101 @@ impl ServerManager {
 
             match wait_result {
                 Ok(Ok(status)) => {
-                    self.add_log(&format!("Server stopped with status: {}", status))
-                        .await;
+                    self.add_log(
+                        LogLevel::Info,
+                        &format!("Server stopped with status: {}", status),
+                    )
+                    .await;
                 }
                 Ok(Err(e)) => {
-                    self.add_log(&format!("Error waiting for server: {}", e))
+                    self.add_log(LogLevel::Error, &format!("Error waiting for server: {}", e))
                         .await;
                 }
                 Err(_) => {
-                    self.add_log("Server did not stop within 2s timeout").await;
+                    self.add_log(LogLevel::Warn, "Server did not stop within 2s timeout")
+                        .await;
                 }
             }
         }
 
         self.is_running = false;
+        self.ready.store(false, Ordering::SeqCst);
+        self.generation += 1;
         clear_managed_pid();
     }
 
+    /// Starts the backend again with the config and binary of the last successful start.
+    pub async fn restart(&mut self) -> Result<(), String> {
+        let (config_path, binary_path) = self
+            .launch
+            .clone()
+            .ok_or_else(|| "Server has not been started yet".to_string())?;
+        self.start(&config_path, &binary_path).await
+    }
+
+    // -- readiness ----------------------------------------------------------
+
+    /// One readiness check: a TCP connect, then any HTTP response from the backend root.
+    pub async fn probe_backend(port: u16) -> bool {
+        let addr = std::net::SocketAddr::from(([127, 0, 0, 1], port));
+        match tokio::time::timeout(PROBE_CONNECT_TIMEOUT, TcpStream::connect(addr)).await {
+            Ok(Ok(_)) => {}
+            _ => return false,
+        }
+
+        probe_client()
+            .get(format!("http://127.0.0.1:{}/", port))
+            .send()
+            .await
+            .is_ok()
+    }
+
+    /// Polls the backend until it answers, marking it ready. Fails if the process exits,
+    /// is replaced by another start/stop, or does not answer within `READINESS_TIMEOUT`.
+    pub async fn wait_until_ready(server_manager: &RwLock<Self>, port: u16) -> Result<(), String> {
+        let generation = server_manager.read().await.generation();
+        let deadline = Instant::now() + READINESS_TIMEOUT;
+
+        loop {
+            {
+                let mut sm = server_manager.write().await;
+                if sm.generation() != generation {
+                    return Err("Server was restarted while waiting for readiness".to_string());
+                }
+                if !sm.refresh_running_status().await {
+                    return Err("Server exited before becoming ready".to_string());
+                }
+            }
+
+            if Self::probe_backend(port).await {
+                let sm = server_manager.read().await;
+                if sm.generation() != generation {
+                    return Err("Server was restarted while waiting for readiness".to_string());
+                }
+                sm.mark_ready();
+                sm.add_log(LogLevel::Info, &format!("Server ready on port {}", port))
+                    .await;
+                return Ok(());
+            }
+
+            if Instant::now() >= deadline {
+                let message = format!(
+                    "Server did not become ready on port {} within {}s",
+                    port,
+                    READINESS_TIMEOUT.as_secs()
+                );
+                server_manager
+                    .read()
+                    .await
+                    .add_log(LogLevel::Warn, &message)
+                    .await;
+                return Err(message);
+            }
+
+            tokio::time::sleep(READINESS_POLL_INTERVAL).await;
+        }
+    }
+
     // -- auth commands ------------------------------------------------------
 
     pub async fn run_auth_command(
@@ -539,12 +734,12 @@ impl ServerManager {
         }
     }
 
-    pub async fn cleanup_port_conflicts_for_restart() -> Result<(), String> {
+    pub async fn cleanup_port_conflicts_for_restart(ports: &[u16]) -> Result<(), String> {
         #[cfg(target_os = "windows")]
         let listeners = list_tcp_listeners().await?;
 
         #[cfg(not(target_os = "windows"))]
-        let listeners = list_port_listeners_unix().await?;
+        let listeners = list_port_listeners_unix(ports).await?;
 
         if listeners.is_empty() {
             return Ok(());
@@ -553,7 +748,7 @@ impl ServerManager {
         let current_pid = std::process::id();
         let mut pid_to_ports: HashMap<u32, Vec<u16>> = HashMap::new();
         for (port, pid, _process_name) in listeners {
-            if (port == PROXY_PORT || port == BACKEND_PORT) && pid != current_pid {
+            if ports.contains(&port) && pid != current_pid {
                 pid_to_ports.entry(pid).or_default().push(port);
                 #[cfg(not(target_os = "windows"))]
                 {
@@ -848,7 +1043,7 @@ async fn ss_list_port_listeners_unix(ports: &[u16]) -> Result<Vec<(u16, u32, Str
 }
 
 #[cfg(not(target_os = "windows"))]
-async fn list_port_listeners_unix() -> Result<Vec<(u16, u32, String)>, String> {
+async fn list_port_listeners_unix(ports: &[u16]) -> Result<Vec<(u16, u32, String)>, String> {
     // Prefer `lsof` when available since it is narrowly scoped per-port, and fall back to `ss`
     // (commonly available on Ubuntu) when `lsof` is missing.
     let lsof_available = Command::new("lsof").arg("-v").output().await.is_ok();
@@ -856,7 +1051,7 @@ async fn list_port_listeners_unix() -> Result<Vec<(u16, u32, String)>, String> {
         use std::collections::BTreeMap;
 
         let mut pid_to_ports: BTreeMap<u32, Vec<u16>> = BTreeMap::new();
-        for port in [PROXY_PORT, BACKEND_PORT] {
+        for &port in ports {
             match lsof_pids_listening_on_tcp_port(port).await {
                 Ok(pids) => {
                     for pid in pids {
@@ -883,7 +1078,7 @@ async fn list_port_listeners_unix() -> Result<Vec<(u16, u32, String)>, String> {
         return Ok(out);
     }
 
-    ss_list_port_listeners_unix(&[PROXY_PORT, BACKEND_PORT]).await
+    ss_list_port_listeners_unix(ports).await
 }
 
 #[cfg(target_os = "windows")]
@@ -1088,4 +1283,25 @@ mod tests {
         let text = "123\n456 \n\n789\nnot-a-pid\n";
         assert_eq!(parse_lsof_pids(text), vec![123, 456, 789]);
     }
+
+    #[tokio::test]
+    async fn probe_backend_requires_an_http_response() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buf = [0u8; 1024];
+                let _ = tokio::io::AsyncReadExt::read(&mut stream, &mut buf).await;
+                let _ = stream
+                    .write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n")
+                    .await;
+            }
+        });
+        assert!(ServerManager::probe_backend(port).await);
+
+        let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let closed_port = closed.local_addr().unwrap().port();
+        drop(closed);
+        assert!(!ServerManager::probe_backend(closed_port).await);
+    }
 }
@@ -0,0 +1,301 @@
+use crate::binary_manager;
+use crate::commands::AppState;
+use crate::server_logs::{LogFilter, LogLevel};
+use crate::server_manager::ServerManager;
+use crate::tray;
+use crate::types::{ServerCrashEvent, ServerState};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(2);
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// Crashes closer together than this count as "rapid".
+const RAPID_CRASH_WINDOW: Duration = Duration::from_secs(300);
+/// Give up after this many rapid crashes.
+const MAX_RAPID_CRASHES: usize = 5;
+const CRASH_LOG_LINES: usize = 20;
+const NOTIFICATION_LOG_LINES: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrashDecision {
+    Restart { attempt: u32, delay: Duration },
+    GiveUp { crashes: u32 },
+}
+
+/// Sliding window of recent crashes; decides between another restart and giving up.
+#[derive(Debug, Default)]
+pub struct CrashTracker {
+    recent: VecDeque<Instant>,
+}
+
+impl CrashTracker {
+    pub fn record(&mut self, now: Instant) -> CrashDecision {
+        while let Some(oldest) = self.recent.front() {
+            if now.duration_since(*oldest) >= RAPID_CRASH_WINDOW {
+                self.recent.pop_front();
+            } else {
+                break;
+            }
+        }
+        self.recent.push_back(now);
+
+        let crashes = self.recent.len();
+        if crashes >= MAX_RAPID_CRASHES {
+            return CrashDecision::GiveUp {
+                crashes: crashes as u32,
+            };
+        }
+
+        let exponent = (crashes - 1).min(16) as u32;
+        let delay = INITIAL_BACKOFF
+            .saturating_mul(2u32.pow(exponent))
+            .min(MAX_BACKOFF);
+        CrashDecision::Restart {
+            attempt: crashes as u32,
+            delay,
+        }
+    }
+}
+
+/// Watches the backend started as `generation` and restarts it when it dies.
+/// Exits as soon as the backend is stopped or restarted by someone else.
+pub fn watch(app: &AppHandle, generation: u64) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        supervise(app, generation).await;
+    });
+}
+
+async fn supervise(app: AppHandle, mut generation: u64) {
+    let state = app.state::<AppState>();
+    let server_manager = state.server_manager.clone();
+    let thinking_proxy = state.thinking_proxy.clone();
+    let lifecycle_lock = state.lifecycle_lock.clone();
+    let backend_port = thinking_proxy.read().await.target_port;
+    let mut crashes = CrashTracker::default();
+
+    loop {
+        tokio::time::sleep(CHECK_INTERVAL).await;
+
+        let is_ready = {
+            let mut sm = server_manager.write().await;
+            if sm.generation() != generation {
+                return;
+            }
+            if sm.refresh_running_status().await {
+                Some(sm.is_ready())
+            } else {
+                None
+            }
+        };
+        match is_ready {
+            Some(true) => continue,
+            // Alive but never answered after a restart: keep probing so traffic resumes
+            // as soon as it does.
+            Some(false) => {
+                if ServerManager::probe_backend(backend_port).await {
+                    let sm = server_manager.read().await;
+                    if sm.generation() == generation {
+                        sm.mark_ready();
+                        drop(sm);
+                        emit_status(&app, true).await;
+                    }
+                }
+                continue;
+            }
+            None => {}
+        }
+
+        let decision = crashes.record(Instant::now());
+        let last_logs = {
+            let sm = server_manager.read().await;
+            sm.get_logs(&LogFilter {
+                limit: Some(CRASH_LOG_LINES),
+                ..LogFilter::default()
+            })
+            .await
+        };
+        emit_status(&app, false).await;
+
+        match decision {
+            CrashDecision::Restart { attempt, delay } => {
+                log::warn!(
+                    "[Supervisor] Backend crashed (attempt {}), restarting in {:?}",
+                    attempt,
+                    delay
+                );
+                notify(
+                    &app,
+                    "CodeForwarder backend crashed",
+                    &format!("Restarting in {}s...", delay.as_secs().max(1)),
+                );
+                app.emit(
+                    "server_crashed",
+                    ServerCrashEvent {
+                        crash_count: attempt,
+                        restarting: true,
+                        retry_in_ms: Some(delay.as_millis() as u64),
+                        last_logs,
+                    },
+                )
+                .ok();
+
+                tokio::time::sleep(delay).await;
+
+                let restarted = {
+                    let _lifecycle_guard = lifecycle_lock.lock().await;
+                    let mut sm = server_manager.write().await;
+                    if sm.generation() != generation {
+                        return;
+                    }
+                    match sm.restart().await {
+                        Ok(()) => Some(sm.generation()),
+                        Err(e) => {
+                            // Left not running; the next check records it as another crash.
+                            sm.add_log(LogLevel::Error, &format!("Restart failed: {}", e))
+                                .await;
+                            None
+                        }
+                    }
+                };
+
+                // Readiness is awaited without the lifecycle lock so user stop/start
+                // calls are not held up by a warming backend; if they replace the
+                // process meanwhile, the generation check reports it as stale.
+                if let Some(restarted) = restarted {
+                    generation = restarted;
+                    match ServerManager::wait_until_ready(&server_manager, backend_port).await {
+                        Ok(()) => {
+                            log::info!("[Supervisor] Backend restarted");
+                            thinking_proxy.read().await.refresh_model_catalog();
+                            emit_status(&app, true).await;
+                        }
+                        Err(e) => log::warn!("[Supervisor] Restarted backend not ready: {}", e),
+                    }
+                }
+            }
+            CrashDecision::GiveUp { crashes } => {
+                log::error!(
+                    "[Supervisor] Backend crashed {} times within {:?}, giving up",
+                    crashes,
+                    RAPID_CRASH_WINDOW
+                );
+
+                {
+                    let _lifecycle_guard = lifecycle_lock.lock().await;
+                    if server_manager.read().await.generation() == generation {
+                        let drain = thinking_proxy.write().await.begin_stop();
+                        drain.wait().await;
+                    }
+                }
+
+                let tail: Vec<&str> = last_logs
+                    .iter()
+                    .rev()
+                    .take(NOTIFICATION_LOG_LINES)
+                    .rev()
+                    .map(|line| line.message.as_str())
+                    .collect();
+                notify(
+                    &app,
+                    &format!("CodeForwarder backend crashed {} times", crashes),
+                    &format!("Automatic restart stopped.\n{}", tail.join("\n")),
+                );
+                app.emit(
+                    "server_crashed",
+                    ServerCrashEvent {
+                        crash_count: crashes,
+                        restarting: false,
+                        retry_in_ms: None,
+                        last_logs,
+                    },
+                )
+                .ok();
+                return;
+            }
+        }
+    }
+}
+
+async fn emit_status(app: &AppHandle, is_running: bool) {
+    let state = app.state::<AppState>();
+    let (proxy_port, backend_port) = {
+        let tp = state.thinking_proxy.read().await;
+        (tp.proxy_port, tp.target_port)
+    };
+
+    tray::update_tray_state(app, is_running.then_some(proxy_port));
+    app.emit(
+        "server_status_changed",
+        ServerState {
+            is_running,
+            proxy_port,
+            backend_port,
+            binary_available: binary_manager::is_binary_available_for_app(app),
+            binary_downloading: false,
+        },
+    )
+    .ok();
+}
+
+fn notify(app: &AppHandle, title: &str, body: &str) {
+    if let Err(e) = app.notification().builder().title(title).body(body).show() {
+        log::warn!("[Supervisor] Failed to show notification: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_then_gives_up_after_rapid_crashes() {
+        let mut tracker = CrashTracker::default();
+        let start = Instant::now();
+
+        let delays: Vec<CrashDecision> = (0..MAX_RAPID_CRASHES as u64)
+            .map(|i| tracker.record(start + Duration::from_secs(i)))
+            .collect();
+
+        assert_eq!(
+            delays[0],
+            CrashDecision::Restart {
+                attempt: 1,
+                delay: Duration::from_secs(1)
+            }
+        );
+        assert_eq!(
+            delays[2],
+            CrashDecision::Restart {
+                attempt: 3,
+                delay: Duration::from_secs(4)
+            }
+        );
+        assert_eq!(
+            delays[MAX_RAPID_CRASHES - 1],
+            CrashDecision::GiveUp {
+                crashes: MAX_RAPID_CRASHES as u32
+            }
+        );
+    }
+
+    #[test]
+    fn crashes_outside_window_are_forgotten() {
+        let mut tracker = CrashTracker::default();
+        let start = Instant::now();
+        tracker.record(start);
+        tracker.record(start + Duration::from_secs(1));
+
+        let later = start + RAPID_CRASH_WINDOW + Duration::from_secs(10);
+        assert_eq!(
+            tracker.record(later),
+            CrashDecision::Restart {
+                attempt: 1,
+                delay: INITIAL_BACKOFF
+            }
+        );
+    }
+}
@@ -58,7 +58,21 @@ pub fn save_settings(app: &tauri::AppHandle, settings: &AppSettings) -> Result<(
         "vercel_gateway_enabled": settings.vercel_gateway_enabled,
         "vercel_api_key": encrypted_key,
         "vercel_api_key_encrypted": !settings.vercel_api_key.is_empty(),
-        "launch_at_login": settings.launch_at_login
+        "launch_at_login": settings.launch_at_login,
+        "bind_address": settings.bind_address,
+        "proxy_port": settings.proxy_port,
+        "backend_port": settings.backend_port,
+        "persist_server_logs": settings.persist_server_logs,
+        "require_client_api_key": settings.require_client_api_key,
+        "pricing_overrides": settings.pricing_overrides,
+        "usage_budgets": settings.usage_budgets,
+        "usage_retention": settings.usage_retention,
+        "request_capture": settings.request_capture,
+        "model_rules": settings.model_rules,
+        "proxy_timeouts": settings.proxy_timeouts,
+        "proxy_retries": settings.proxy_retries,
+        "failover_chains": settings.failover_chains,
+        "shutdown_drain_secs": settings.shutdown_drain_secs
     });
 
     store.set("settings", value);
@@ -1,22 +1,45 @@
 use bytes::Bytes;
-use http_body_util::Full;
+use futures_util::StreamExt;
+use http_body_util::{combinators::UnsyncBoxBody, BodyDataStream, BodyExt, Full, StreamBody};
+use hyper::body::Frame;
 use hyper::server::conn::http1;
 use hyper::service::service_fn;
 use hyper::{Request, Response, StatusCode};
 use hyper_util::rt::TokioIo;
+use hyper_util::server::graceful::GracefulShutdown;
 use std::{
-    sync::{Arc, OnceLock},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, OnceLock,
+    },
     time::{Duration, Instant},
 };
 use tokio::net::TcpListener;
 use tokio::sync::RwLock;
+use tokio::task::JoinSet;
 
-use crate::types::VercelGatewayConfig;
+use crate::budgets::{BudgetGuard, BudgetRejection};
+use crate::client_keys::{ClientAuthConfig, ClientIdentity};
+use crate::error_taxonomy::{
+    classify_response, classify_transport_error, ErrorDetails, MAX_ERROR_BODY_BYTES,
+};
+use crate::failover::{is_failover_status, retry_after, set_model, FailoverRoute, FailoverRouter};
+use crate::model_catalog::{self, ModelCatalog};
+use crate::model_rules::ModelRewriter;
+use crate::reasoning::{clamp_budget, process_reasoning_suffix};
+use crate::request_capture::{CaptureDraft, CaptureMeta, CapturedResponse, RequestCaptureStore};
+use crate::retries::RetryPolicy;
+use crate::timeouts::{RequestTimeouts, TimeoutPolicy, UpstreamTimeout};
+use crate::types::{
+    AppSettings, ErrorCategory, FailoverTarget, ProxyDrainEvent, VercelGatewayConfig,
+    DEFAULT_BACKEND_PORT, DEFAULT_BIND_ADDRESS, DEFAULT_PROXY_PORT,
+};
 use crate::usage_tracker::{UsageEvent, UsageTracker};
 use chrono::Utc;
 use uuid::Uuid;
 
-const HARD_TOKEN_CAP: i64 = 32000;
+/// Output token cap for models the catalog doesn't know.
+const DEFAULT_OUTPUT_TOKEN_CAP: i64 = 32000;
 const MINIMUM_HEADROOM: i64 = 1024;
 const HEADROOM_RATIO: f64 = 0.1;
 const VERCEL_GATEWAY_HOST: &str = "ai-gateway.vercel.sh";
@@ -26,11 +49,61 @@ const HTTP_CONNECT_TIMEOUT_SECS: u64 = 5;
 const HTTP_READ_TIMEOUT_SECS: u64 = 90;
 const BACKEND_FORWARD_RETRY_ATTEMPTS: usize = 3;
 const BACKEND_FORWARD_RETRY_DELAY_MS: u64 = 200;
+const MAX_USAGE_SCAN_BYTES: usize = 4 * 1024 * 1024;
+const BACKEND_WARMUP_RETRY_AFTER_SECS: u64 = 2;
+/// Status recorded for requests the client abandoned (nginx's "client closed request").
+const CLIENT_CLOSED_REQUEST: u16 = 499;
+/// How long a failed attempt's error body may take to read before failing over anyway.
+const FAILOVER_ERROR_BODY_TIMEOUT_SECS: u64 = 5;
+/// Upper bound for the configured drain timeout.
+pub const MAX_DRAIN_TIMEOUT_SECS: u32 = 300;
+/// Slack `stop()` allows past the drain timeout for the remaining connections to be closed.
+const SHUTDOWN_GRACE_SECS: u64 = 2;
+
+/// Called with drain progress while the proxy stops.
+pub type DrainListener = Arc<dyn Fn(&ProxyDrainEvent) + Send + Sync>;
+
+type ProxyBody = UnsyncBoxBody<Bytes, Box<dyn std::error::Error + Send + Sync>>;
 
 struct ForwardOutcome {
-    response: Response<Full<Bytes>>,
+    upstream: reqwest::Response,
     status_code: u16,
-    body: Bytes,
+    /// Longest gap allowed between body chunks.
+    read_timeout: Option<Duration>,
+}
+
+/// Request body forwarded upstream. Buffered bodies can be replayed for retries; streamed
+/// bodies are handed to the upstream request once and never held in memory.
+enum ForwardBody {
+    Buffered(Bytes),
+    Streaming(Option<reqwest::Body>),
+}
+
+impl ForwardBody {
+    fn streaming(incoming: hyper::body::Incoming) -> Self {
+        Self::Streaming(Some(reqwest::Body::wrap_stream(BodyDataStream::new(
+            incoming,
+        ))))
+    }
+
+    fn buffered(&self) -> Option<&Bytes> {
+        match self {
+            Self::Buffered(bytes) => Some(bytes),
+            Self::Streaming(_) => None,
+        }
+    }
+
+    fn is_replayable(&self) -> bool {
+        matches!(self, Self::Buffered(_))
+    }
+
+    /// Body for the next upstream attempt, or `None` once a streamed body has been sent.
+    fn next_attempt(&mut self) -> Option<reqwest::Body> {
+        match self {
+            Self::Buffered(bytes) => Some(reqwest::Body::from(bytes.clone())),
+            Self::Streaming(body) => body.take(),
+        }
+    }
 }
 
 #[derive(Default)]
@@ -39,6 +112,7 @@ struct TokenUsage {
     output_tokens: Option<i64>,
     total_tokens: Option<i64>,
     cached_tokens: Option<i64>,
+    cache_write_tokens: Option<i64>,
     reasoning_tokens: Option<i64>,
     usage_json: Option<String>,
     account_hint: Option<String>,
@@ -54,15 +128,37 @@ struct TrackingSeed {
     model: String,
     account_key: String,
     account_label: String,
+    client_id: String,
+    client_label: String,
     request_bytes: i64,
+    /// Set when request capture is enabled.
+    capture: Option<CaptureDraft>,
+    /// Set on every attempt once a request fails over.
+    parent_request_id: Option<String>,
 }
 
 pub struct ThinkingProxy {
+    pub bind_address: String,
     pub proxy_port: u16,
     pub target_port: u16,
     pub vercel_config: Arc<RwLock<VercelGatewayConfig>>,
+    pub client_auth: Arc<RwLock<ClientAuthConfig>>,
     pub usage_tracker: Arc<UsageTracker>,
-    shutdown_tx: Option<tokio::sync::oneshot::Sender<()>>,
+    pub budget_guard: Arc<BudgetGuard>,
+    pub capture_store: Arc<RequestCaptureStore>,
+    pub model_rewriter: Arc<ModelRewriter>,
+    /// Model definitions from the backend, refreshed once it is ready.
+    pub model_catalog: Arc<ModelCatalog>,
+    pub timeout_policy: Arc<TimeoutPolicy>,
+    pub failover_router: Arc<FailoverRouter>,
+    pub retry_policy: Arc<RetryPolicy>,
+    /// Owned by ServerManager; false while the backend is starting or restarting.
+    backend_ready: Arc<AtomicBool>,
+    /// How long `stop()` lets in-flight requests finish before closing them.
+    pub drain_timeout: Duration,
+    drain_listener: Option<DrainListener>,
+    /// Carries the drain timeout to the serve task.
+    shutdown_tx: Option<tokio::sync::oneshot::Sender<Duration>>,
     serve_task: Option<tokio::task::JoinHandle<()>>,
     pub is_running: bool,
 }
@@ -70,58 +166,116 @@ pub struct ThinkingProxy {
 impl ThinkingProxy {
     pub fn new(
         vercel_config: Arc<RwLock<VercelGatewayConfig>>,
+        client_auth: Arc<RwLock<ClientAuthConfig>>,
         usage_tracker: Arc<UsageTracker>,
+        budget_guard: Arc<BudgetGuard>,
+        capture_store: Arc<RequestCaptureStore>,
+        model_rewriter: Arc<ModelRewriter>,
+        backend_ready: Arc<AtomicBool>,
     ) -> Self {
+        // Budget checks reuse cached usage, so keep it current as events are written.
+        let guard = budget_guard.clone();
+        usage_tracker.set_event_listener(Some(Arc::new(move |event| guard.record_event(event))));
         Self {
-            proxy_port: 8317,
-            target_port: 8318,
+            bind_address: DEFAULT_BIND_ADDRESS.to_string(),
+            proxy_port: DEFAULT_PROXY_PORT,
+            target_port: DEFAULT_BACKEND_PORT,
             vercel_config,
+            client_auth,
             usage_tracker,
+            budget_guard,
+            capture_store,
+            model_rewriter,
+            model_catalog: Arc::new(ModelCatalog::default()),
+            timeout_policy: Arc::new(TimeoutPolicy::default()),
+            failover_router: Arc::new(FailoverRouter::default()),
+            retry_policy: Arc::new(RetryPolicy::default()),
+            backend_ready,
+            drain_timeout: Duration::from_secs(AppSettings::default().shutdown_drain_secs as u64),
+            drain_listener: None,
             shutdown_tx: None,
             serve_task: None,
             is_running: false,
         }
     }
 
+    /// Applies the proxy's share of the settings. The listen address and ports take effect on
+    /// the next `start()`; upstream timeouts, retries, failover chains and the drain timeout
+    /// apply right away.
+    pub fn apply_settings(&mut self, settings: &AppSettings) {
+        self.bind_address = settings.bind_address.trim().to_string();
+        self.proxy_port = settings.proxy_port;
+        self.target_port = settings.backend_port;
+        self.drain_timeout = Duration::from_secs(settings.shutdown_drain_secs as u64);
+        self.timeout_policy.set_settings(&settings.proxy_timeouts);
+        self.retry_policy.set_settings(settings.proxy_retries);
+        self.failover_router.set_chains(&settings.failover_chains);
+    }
+
+    pub fn set_drain_listener(&mut self, listener: Option<DrainListener>) {
+        self.drain_listener = listener;
+    }
+
     pub async fn start(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         if self.is_running {
             log::info!("[ThinkingProxy] Already running");
             return Ok(());
         }
 
-        let addr = format!("127.0.0.1:{}", self.proxy_port);
-        let listener = TcpListener::bind(&addr).await?;
-        log::info!("[ThinkingProxy] Listening on port {}", self.proxy_port);
+        let ip: std::net::IpAddr = self.bind_address.parse()?;
+        let addr = std::net::SocketAddr::new(ip, self.proxy_port);
+        let listener = TcpListener::bind(addr).await?;
+        log::info!("[ThinkingProxy] Listening on {}", addr);
 
-        let (shutdown_tx, mut shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let (shutdown_tx, mut shutdown_rx) = tokio::sync::oneshot::channel::<Duration>();
         self.shutdown_tx = Some(shutdown_tx);
         self.is_running = true;
 
         let vercel_config = self.vercel_config.clone();
+        let client_auth = self.client_auth.clone();
         let usage_tracker = self.usage_tracker.clone();
+        let budget_guard = self.budget_guard.clone();
+        let capture_store = self.capture_store.clone();
+        let model_rewriter = self.model_rewriter.clone();
+        let model_catalog = self.model_catalog.clone();
+        let timeout_policy = self.timeout_policy.clone();
+        let failover_router = self.failover_router.clone();
+        let retry_policy = self.retry_policy.clone();
+        let backend_ready = self.backend_ready.clone();
         let target_port = self.target_port;
+        let drain_listener = self.drain_listener.clone();
 
         let serve_task = tokio::spawn(async move {
-            loop {
+            let graceful = GracefulShutdown::new();
+            let mut connections = JoinSet::new();
+            let drain_timeout = loop {
                 tokio::select! {
                     result = listener.accept() => {
                         match result {
                             Ok((stream, _addr)) => {
                                 let io = TokioIo::new(stream);
-                                let vc = vercel_config.clone();
-                                let tracker = usage_tracker.clone();
-                                tokio::spawn(async move {
-                                    let svc = service_fn(move |req| {
-                                        let vc = vc.clone();
-                                        let tracker = tracker.clone();
-                                        async move {
-                                            handle_request(req, vc, target_port, tracker).await
-                                        }
-                                    });
-                                    if let Err(e) = http1::Builder::new()
-                                        .serve_connection(io, svc)
-                                        .await
-                                    {
+                                let ctx = RequestContext {
+                                    vercel_config: vercel_config.clone(),
+                                    client_auth: client_auth.clone(),
+                                    usage_tracker: usage_tracker.clone(),
+                                    budget_guard: budget_guard.clone(),
+                                    capture_store: capture_store.clone(),
+                                    model_rewriter: model_rewriter.clone(),
+                                    model_catalog: model_catalog.clone(),
+                                    timeout_policy: timeout_policy.clone(),
+                                    failover_router: failover_router.clone(),
+                                    retry_policy: retry_policy.clone(),
+                                    backend_ready: backend_ready.clone(),
+                                    target_port,
+                                };
+                                let svc = service_fn(move |req| {
+                                    let ctx = ctx.clone();
+                                    async move { handle_request(req, ctx).await }
+                                });
+                                let conn = graceful
+                                    .watch(http1::Builder::new().serve_connection(io, svc));
+                                connections.spawn(async move {
+                                    if let Err(e) = conn.await {
                                         log::error!("[ThinkingProxy] Connection error: {}", e);
                                     }
                                 });
@@ -131,24 +285,63 @@ impl ThinkingProxy {
                             }
                         }
                     }
-                    _ = &mut shutdown_rx => {
+                    Some(_) = connections.join_next(), if !connections.is_empty() => {}
+                    drain_timeout = &mut shutdown_rx => {
                         log::info!("[ThinkingProxy] Shutdown signal received");
-                        break;
+                        break drain_timeout.unwrap_or_default();
                     }
                 }
-            }
+            };
+            // Closing the listener refuses new connections while open ones drain.
+            drop(listener);
+            drain_connections(graceful, connections, drain_timeout, drain_listener).await;
         });
         self.serve_task = Some(serve_task);
 
         Ok(())
     }
 
-    pub async fn stop(&mut self) {
+    /// Stops accepting connections right away and returns the drain of in-flight requests.
+    /// Callers holding the proxy lock should release it before waiting on the drain, which
+    /// can take up to `drain_timeout`.
+    pub fn begin_stop(&mut self) -> ProxyDrain {
         if let Some(tx) = self.shutdown_tx.take() {
-            let _ = tx.send(());
+            let _ = tx.send(self.drain_timeout);
         }
-        if let Some(handle) = self.serve_task.take() {
-            match tokio::time::timeout(Duration::from_secs(2), handle).await {
+        self.is_running = false;
+        ProxyDrain {
+            serve_task: self.serve_task.take(),
+            timeout: self.drain_timeout,
+        }
+    }
+
+    /// `begin_stop` and wait for the drain, for callers that own the proxy.
+    pub async fn stop(&mut self) {
+        self.begin_stop().wait().await;
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.is_running
+    }
+
+    /// Reloads the model catalog from the backend. Call once the backend is ready.
+    pub fn refresh_model_catalog(&self) {
+        model_catalog::refresh_in_background(self.model_catalog.clone(), self.target_port);
+    }
+}
+
+/// A proxy that has stopped accepting connections while its open ones finish.
+pub struct ProxyDrain {
+    serve_task: Option<tokio::task::JoinHandle<()>>,
+    timeout: Duration,
+}
+
+impl ProxyDrain {
+    /// Waits until in-flight requests are done or closed at the drain timeout.
+    pub async fn wait(self) {
+        if let Some(handle) = self.serve_task {
+            let wait = self.timeout + Duration::from_secs(SHUTDOWN_GRACE_SECS);
+            match tokio::time::timeout(wait, handle).await {
                 Ok(Ok(())) => {}
                 Ok(Err(e)) => {
                     log::warn!("[ThinkingProxy] Proxy task join error: {}", e);
@@ -158,31 +351,189 @@ impl ThinkingProxy {
                 }
             }
         }
-        self.is_running = false;
         log::info!("[ThinkingProxy] Stopped");
     }
+}
 
-    pub fn is_running(&self) -> bool {
-        self.is_running
+/// Lets open connections finish their current request, reporting progress every second, and
+/// aborts whatever is still running once `timeout` has passed.
+async fn drain_connections(
+    graceful: GracefulShutdown,
+    mut connections: JoinSet<()>,
+    timeout: Duration,
+    listener: Option<DrainListener>,
+) {
+    while connections.try_join_next().is_some() {}
+    if connections.is_empty() {
+        return;
+    }
+    log::info!(
+        "[ThinkingProxy] Draining {} connection(s) for up to {}s",
+        connections.len(),
+        timeout.as_secs()
+    );
+
+    let deadline = tokio::time::Instant::now() + timeout;
+    let notify = |in_flight: usize| {
+        if let Some(listener) = &listener {
+            let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+            listener(&ProxyDrainEvent {
+                in_flight,
+                remaining_secs: remaining.as_secs_f64().ceil() as u64,
+            });
+        }
+    };
+    // Idle keep-alive connections close as soon as shutdown is signalled and busy ones right
+    // after their current response, so every connection still open is a request in flight.
+    let shutdown = graceful.shutdown();
+    tokio::pin!(shutdown);
+    let mut progress = tokio::time::interval(Duration::from_secs(1));
+    loop {
+        tokio::select! {
+            _ = &mut shutdown => {
+                log::info!("[ThinkingProxy] All in-flight requests finished");
+                break;
+            }
+            _ = progress.tick() => {
+                while connections.try_join_next().is_some() {}
+                notify(connections.len());
+            }
+            _ = tokio::time::sleep_until(deadline) => {
+                while connections.try_join_next().is_some() {}
+                log::warn!(
+                    "[ThinkingProxy] Closing {} connection(s) still in flight after {}s",
+                    connections.len(),
+                    timeout.as_secs()
+                );
+                connections.shutdown().await;
+                break;
+            }
+        }
     }
+    notify(0);
+}
+
+fn full_body(body: impl Into<Bytes>) -> ProxyBody {
+    Full::new(body.into())
+        .map_err(|never| match never {})
+        .boxed_unsync()
 }
 
-fn make_response(status: StatusCode, body: &str) -> Response<Full<Bytes>> {
+fn make_response(status: StatusCode, body: &str) -> Response<ProxyBody> {
     Response::builder()
         .status(status)
         .header("Content-Type", "text/plain")
         .header("Connection", "close")
-        .body(Full::new(Bytes::from(body.to_string())))
+        .body(full_body(body.to_string()))
+        .unwrap()
+}
+
+fn make_warming_up_response() -> Response<ProxyBody> {
+    Response::builder()
+        .status(StatusCode::SERVICE_UNAVAILABLE)
+        .header("Content-Type", "text/plain")
+        .header("Retry-After", BACKEND_WARMUP_RETRY_AFTER_SECS.to_string())
+        .header("Connection", "close")
+        .body(full_body(
+            "Service Unavailable - Local backend is starting, retry shortly",
+        ))
+        .unwrap()
+}
+
+/// Error body shape expected by the calling SDK, so budget rejections surface as
+/// ordinary rate-limit errors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ErrorDialect {
+    Anthropic,
+    OpenAi,
+    Gemini,
+}
+
+fn error_dialect_for_path(path: &str) -> ErrorDialect {
+    if path.contains(":generateContent")
+        || path.contains(":streamGenerateContent")
+        || path.contains("/v1beta/")
+    {
+        ErrorDialect::Gemini
+    } else if path.ends_with("/messages") || path.ends_with("/messages/count_tokens") {
+        ErrorDialect::Anthropic
+    } else {
+        ErrorDialect::OpenAi
+    }
+}
+
+fn budget_error_body(dialect: ErrorDialect, message: &str) -> serde_json::Value {
+    match dialect {
+        ErrorDialect::Anthropic => serde_json::json!({
+            "type": "error",
+            "error": { "type": "rate_limit_error", "message": message }
+        }),
+        ErrorDialect::OpenAi => serde_json::json!({
+            "error": {
+                "message": message,
+                "type": "insufficient_quota",
+                "param": null,
+                "code": "budget_exceeded"
+            }
+        }),
+        ErrorDialect::Gemini => serde_json::json!({
+            "error": { "code": 429, "message": message, "status": "RESOURCE_EXHAUSTED" }
+        }),
+    }
+}
+
+fn auth_error_body(dialect: ErrorDialect, message: &str) -> serde_json::Value {
+    match dialect {
+        ErrorDialect::Anthropic => serde_json::json!({
+            "type": "error",
+            "error": { "type": "authentication_error", "message": message }
+        }),
+        ErrorDialect::OpenAi => serde_json::json!({
+            "error": {
+                "message": message,
+                "type": "invalid_request_error",
+                "param": null,
+                "code": "invalid_api_key"
+            }
+        }),
+        ErrorDialect::Gemini => serde_json::json!({
+            "error": { "code": 401, "message": message, "status": "UNAUTHENTICATED" }
+        }),
+    }
+}
+
+fn make_unauthorized_response(path: &str) -> Response<ProxyBody> {
+    let body = auth_error_body(
+        error_dialect_for_path(path),
+        "Missing or invalid CodeForwarder client key",
+    );
+    Response::builder()
+        .status(StatusCode::UNAUTHORIZED)
+        .header("Content-Type", "application/json")
+        .header("Connection", "close")
+        .body(full_body(body.to_string()))
+        .unwrap()
+}
+
+fn make_budget_exceeded_response(path: &str, rejection: &BudgetRejection) -> Response<ProxyBody> {
+    let body = budget_error_body(error_dialect_for_path(path), &rejection.message());
+    let retry_after = (rejection.resets_at - Utc::now()).num_seconds().max(1);
+    Response::builder()
+        .status(StatusCode::TOO_MANY_REQUESTS)
+        .header("Content-Type", "application/json")
+        .header("Retry-After", retry_after.to_string())
+        .header("Connection", "close")
+        .body(full_body(body.to_string()))
         .unwrap()
 }
 
-fn make_redirect(location: &str) -> Response<Full<Bytes>> {
+fn make_redirect(location: &str) -> Response<ProxyBody> {
     Response::builder()
         .status(StatusCode::FOUND)
         .header("Location", location)
         .header("Content-Length", "0")
         .header("Connection", "close")
-        .body(Full::new(Bytes::new()))
+        .body(full_body(Bytes::new()))
         .unwrap()
 }
 
@@ -201,12 +552,41 @@ fn shared_http_client() -> &'static reqwest::Client {
     })
 }
 
-async fn handle_request(
-    req: Request<hyper::body::Incoming>,
+/// Shared proxy state handed to every request.
+#[derive(Clone)]
+struct RequestContext {
     vercel_config: Arc<RwLock<VercelGatewayConfig>>,
-    target_port: u16,
+    client_auth: Arc<RwLock<ClientAuthConfig>>,
     usage_tracker: Arc<UsageTracker>,
-) -> Result<Response<Full<Bytes>>, hyper::Error> {
+    budget_guard: Arc<BudgetGuard>,
+    capture_store: Arc<RequestCaptureStore>,
+    model_rewriter: Arc<ModelRewriter>,
+    model_catalog: Arc<ModelCatalog>,
+    timeout_policy: Arc<TimeoutPolicy>,
+    failover_router: Arc<FailoverRouter>,
+    retry_policy: Arc<RetryPolicy>,
+    backend_ready: Arc<AtomicBool>,
+    target_port: u16,
+}
+
+async fn handle_request(
+    req: Request<hyper::body::Incoming>,
+    ctx: RequestContext,
+) -> Result<Response<ProxyBody>, hyper::Error> {
+    let RequestContext {
+        vercel_config,
+        client_auth,
+        usage_tracker,
+        budget_guard,
+        capture_store,
+        model_rewriter,
+        model_catalog,
+        timeout_policy,
+        failover_router,
+        retry_policy,
+        backend_ready,
+        target_port,
+    } = ctx.clone();
     let request_started_at = Instant::now();
     let method = req.method().clone();
     let uri = req.uri().clone();
@@ -215,19 +595,7 @@ async fn handle_request(
 
     log::info!("[ThinkingProxy] Incoming request: {} {}", method, path);
 
-    // Collect request body
-    use http_body_util::BodyExt;
-    let body_bytes = match req.into_body().collect().await {
-        Ok(collected) => collected.to_bytes(),
-        Err(e) => {
-            log::error!("[ThinkingProxy] Error reading request body: {}", e);
-            return Ok(make_response(
-                StatusCode::BAD_REQUEST,
-                "Invalid request body",
-            ));
-        }
-    };
-    let body_string = String::from_utf8_lossy(&body_bytes).to_string();
+    let incoming = req.into_body();
 
     // 1. Amp CLI login redirects
     if path.starts_with("/auth/cli-login") || path.starts_with("/api/auth/cli-login") {
@@ -260,79 +628,177 @@ async fn handle_request(
     let is_provider_path = rewritten_path.starts_with("/api/provider/");
     let is_cli_proxy_path =
         rewritten_path.starts_with("/v1/") || rewritten_path.starts_with("/api/v1/");
-    let is_inference_request = is_provider_path || is_cli_proxy_path;
     if !is_provider_path && !is_cli_proxy_path {
         log::info!(
             "[ThinkingProxy] Amp management request, forwarding to ampcode.com: {}",
             rewritten_path
         );
-        return Ok(
-            forward_to_amp(&method, &rewritten_path, &headers, &body_string)
-                .await
-                .unwrap_or_else(|e| {
-                    log::error!("[ThinkingProxy] Amp forward error: {}", e);
-                    make_response(
-                        StatusCode::BAD_GATEWAY,
-                        "Bad Gateway - Could not connect to ampcode.com",
-                    )
-                }),
-        );
+        let body = reqwest::Body::wrap_stream(BodyDataStream::new(incoming));
+        return Ok(forward_to_amp(&method, &rewritten_path, &headers, body)
+            .await
+            .unwrap_or_else(|e| {
+                log::error!("[ThinkingProxy] Amp forward error: {}", e);
+                make_response(
+                    StatusCode::BAD_GATEWAY,
+                    "Bad Gateway - Could not connect to ampcode.com",
+                )
+            }));
     }
 
-    // 4. Process thinking parameter for POST requests
-    let mut modified_body = body_string.clone();
+    // 4. Client keys: identify the calling tool, and reject unknown callers when required
+    let client = {
+        let auth = client_auth.read().await;
+        let client = auth.identify(&headers);
+        if client.is_none() && auth.required {
+            log::warn!(
+                "[ThinkingProxy] Rejecting request without a valid client key: {}",
+                path
+            );
+            return Ok(make_unauthorized_response(&rewritten_path));
+        }
+        client
+    };
+
+    // 5. Buffer JSON POST bodies as raw bytes so the thinking rewrite and usage tracking can
+    // inspect them. Anything else (multipart uploads, audio, ...) is streamed upstream untouched.
     let mut thinking_enabled = false;
+    let (mut forward_body, request_bytes) = if should_inspect_body(&method, &headers) {
+        let body_bytes = match incoming.collect().await {
+            Ok(collected) => collected.to_bytes(),
+            Err(e) => {
+                log::error!("[ThinkingProxy] Error reading request body: {}", e);
+                return Ok(make_response(
+                    StatusCode::BAD_REQUEST,
+                    "Invalid request body",
+                ));
+            }
+        };
+        let request_bytes = body_bytes.len() as i64;
+        let mut body = body_bytes;
+        if !body.is_empty() {
+            // User rules run first, so an alias can target a `-thinking-N` model.
+            let mut injected_thinking = false;
+            if let Some((rewritten, injects_thinking)) = model_rewriter.apply(&body) {
+                body = rewritten;
+                injected_thinking = injects_thinking;
+            }
+            let (new_body, is_thinking) = process_thinking_parameter(&body, &model_catalog);
+            body = new_body;
+            if let Some(new_body) = process_reasoning_suffix(&body, &rewritten_path, &model_catalog)
+            {
+                body = new_body;
+            }
+            thinking_enabled = is_thinking || injected_thinking;
+        }
+        (ForwardBody::Buffered(body), request_bytes)
+    } else {
+        (
+            ForwardBody::streaming(incoming),
+            content_length_hint(&headers),
+        )
+    };
 
-    if method == hyper::Method::POST && !body_string.is_empty() {
-        let (new_body, is_thinking) = process_thinking_parameter(&body_string);
-        modified_body = new_body;
-        thinking_enabled = is_thinking;
+    let buffered_body = forward_body.buffered().map(|b| b.as_ref());
+    let mut seed = build_tracking_seed(
+        &method,
+        &rewritten_path,
+        &headers,
+        buffered_body.unwrap_or_default(),
+        request_bytes,
+        request_started_at,
+        client.as_ref(),
+    );
+    seed.capture = capture_store.begin(
+        CaptureMeta {
+            request_id: seed.request_id.clone(),
+            method: seed.method.clone(),
+            path: seed.path.clone(),
+            provider: seed.provider.clone(),
+            model: seed.model.clone(),
+            client_id: seed.client_id.clone(),
+            client_label: seed.client_label.clone(),
+        },
+        &headers,
+        buffered_body,
+        request_bytes,
+    );
+
+    // 6. Usage budgets: hard limits refuse the request before it reaches any upstream
+    if let Some(rejection) = budget_guard
+        .check(
+            &usage_tracker,
+            &seed.provider,
+            &seed.model,
+            &seed.account_key,
+        )
+        .await
+    {
+        log::warn!("[ThinkingProxy] {}", rejection.message());
+        record_failed_request(
+            usage_tracker.clone(),
+            Some(seed),
+            429,
+            ErrorDetails::new(ErrorCategory::QuotaExceeded, rejection.message()),
+        );
+        return Ok(make_budget_exceeded_response(&rewritten_path, &rejection));
     }
 
-    let tracking_seed = if is_inference_request {
-        Some(build_tracking_seed(
-            &method,
-            &rewritten_path,
-            &headers,
-            &modified_body,
-            body_bytes.len() as i64,
-            request_started_at,
-        ))
+    // 7. Failover chains: walk the fallback routes for this model until one answers
+    let failover = forward_body
+        .buffered()
+        .filter(|_| method == hyper::Method::POST)
+        .map(|body| (body.clone(), failover_router.plan(&seed.model)))
+        .filter(|(_, fallbacks)| !fallbacks.is_empty());
+    if let Some((body, fallbacks)) = failover {
+        let request = FailoverRequest {
+            method: &method,
+            path: &rewritten_path,
+            headers: &headers,
+            body,
+            thinking_enabled,
+            seed,
+        };
+        return Ok(forward_with_failover(request, fallbacks, &ctx).await);
+    }
+
+    let timeouts = timeout_policy.resolve(&rewritten_path, &seed.model);
+    let mut tracking_seed = InFlightSeed::new(&usage_tracker, seed);
+
+    // 8. Vercel gateway routing
+    let vc = vercel_config.read().await;
+    let vercel_body = if vc.is_active() && method == hyper::Method::POST {
+        forward_body
+            .buffered()
+            .filter(|body| is_claude_model_request(body))
+            .cloned()
     } else {
         None
     };
-
-    // 5. Vercel gateway routing
-    let vc = vercel_config.read().await;
-    if vc.is_active() && method == hyper::Method::POST && is_claude_model_request(&modified_body) {
+    if let Some(vercel_body) = vercel_body {
         let api_key = vc.api_key.clone();
         drop(vc);
         log::info!("[ThinkingProxy] Routing Claude request via Vercel AI Gateway");
-        let result = forward_to_vercel(
-            &method,
-            "/v1/messages",
-            &headers,
-            &modified_body,
-            thinking_enabled,
-            &api_key,
-        )
+        let result = retry_on_status(&retry_policy, || {
+            forward_to_vercel(
+                &method,
+                "/v1/messages",
+                &headers,
+                vercel_body.clone(),
+                thinking_enabled,
+                &api_key,
+                &timeouts,
+            )
+        })
         .await;
 
         return Ok(match result {
-            Ok(outcome) => {
-                record_usage_if_needed(
-                    usage_tracker.clone(),
-                    tracking_seed,
-                    outcome.status_code,
-                    outcome.body,
-                );
-                outcome.response
-            }
+            Ok(outcome) => stream_outcome(outcome, &usage_tracker, tracking_seed.take()),
             Err(e) => {
                 log::error!("[ThinkingProxy] Vercel forward error: {}", e);
-                record_usage_if_needed(usage_tracker.clone(), tracking_seed, 502, Bytes::new());
-                make_response(
-                    StatusCode::BAD_GATEWAY,
+                upstream_failure(
+                    &usage_tracker,
+                    tracking_seed.take(),
+                    e.as_ref(),
                     "Bad Gateway - Could not connect to Vercel AI Gateway",
                 )
             }
@@ -340,16 +806,52 @@ async fn handle_request(
     }
     drop(vc);
 
-    // 6. Default: forward to local backend on target_port
-    let result = forward_to_backend_with_retry(
-        &method,
-        &rewritten_path,
-        &headers,
-        &modified_body,
-        thinking_enabled,
-        target_port,
-    )
-    .await;
+    // 9. Default: forward to local backend on target_port, once it has passed readiness
+    if !backend_ready.load(Ordering::SeqCst) {
+        log::info!("[ThinkingProxy] Backend not ready, returning 503");
+        record_failed_request(
+            usage_tracker,
+            tracking_seed.take(),
+            503,
+            ErrorDetails::new(
+                ErrorCategory::BackendUnavailable,
+                "Backend is still starting up",
+            ),
+        );
+        return Ok(make_warming_up_response());
+    }
+    let replayable_post = forward_body
+        .buffered()
+        .filter(|_| method == hyper::Method::POST)
+        .cloned();
+    let result = match replayable_post {
+        Some(body) => {
+            retry_on_status(&retry_policy, || {
+                forward_buffered_to_backend(
+                    &method,
+                    &rewritten_path,
+                    &headers,
+                    body.clone(),
+                    thinking_enabled,
+                    target_port,
+                    &timeouts,
+                )
+            })
+            .await
+        }
+        None => {
+            forward_to_backend_with_retry(
+                &method,
+                &rewritten_path,
+                &headers,
+                &mut forward_body,
+                thinking_enabled,
+                target_port,
+                &timeouts,
+            )
+            .await
+        }
+    };
 
     match result {
         Ok(outcome) => {
@@ -357,6 +859,7 @@ async fn handle_request(
             if outcome.status_code == StatusCode::NOT_FOUND.as_u16()
                 && !path.starts_with("/api/")
                 && !path.starts_with("/v1/")
+                && forward_body.is_replayable()
             {
                 let new_path = format!("/api{}", path);
                 log::info!(
@@ -368,50 +871,327 @@ async fn handle_request(
                     &method,
                     &new_path,
                     &headers,
-                    &modified_body,
+                    &mut forward_body,
                     thinking_enabled,
                     target_port,
+                    &timeouts,
                 )
                 .await;
                 return Ok(match retry_result {
                     Ok(retry_outcome) => {
-                        record_usage_if_needed(
-                            usage_tracker.clone(),
-                            tracking_seed,
-                            retry_outcome.status_code,
-                            retry_outcome.body,
-                        );
-                        retry_outcome.response
+                        stream_outcome(retry_outcome, &usage_tracker, tracking_seed.take())
                     }
                     Err(e) => {
                         log::error!("[ThinkingProxy] Backend retry error: {}", e);
-                        record_usage_if_needed(
-                            usage_tracker.clone(),
-                            tracking_seed,
-                            502,
-                            Bytes::new(),
-                        );
                         let response_message =
                             format!("Bad Gateway - Local backend unavailable: {}", e);
-                        make_response(StatusCode::BAD_GATEWAY, &response_message)
+                        upstream_failure(
+                            &usage_tracker,
+                            tracking_seed.take(),
+                            e.as_ref(),
+                            &response_message,
+                        )
                     }
                 });
             }
-            record_usage_if_needed(
-                usage_tracker.clone(),
-                tracking_seed,
-                outcome.status_code,
-                outcome.body,
-            );
-            Ok(outcome.response)
+            Ok(stream_outcome(
+                outcome,
+                &usage_tracker,
+                tracking_seed.take(),
+            ))
         }
         Err(e) => {
             log::error!("[ThinkingProxy] Backend forward error: {}", e);
-            record_usage_if_needed(usage_tracker, tracking_seed, 502, Bytes::new());
             let response_message = format!("Bad Gateway - Local backend unavailable: {}", e);
-            Ok(make_response(StatusCode::BAD_GATEWAY, &response_message))
+            Ok(upstream_failure(
+                &usage_tracker,
+                tracking_seed.take(),
+                e.as_ref(),
+                &response_message,
+            ))
+        }
+    }
+}
+
+/// A buffered request about to walk its failover chain.
+struct FailoverRequest<'a> {
+    method: &'a hyper::Method,
+    path: &'a str,
+    headers: &'a hyper::HeaderMap,
+    body: Bytes,
+    thinking_enabled: bool,
+    seed: TrackingSeed,
+}
+
+/// Sends the request on its normal route, then on each fallback while upstream answers with
+/// 429, 529, a 5xx or a connection error. Nothing reaches the client until an attempt is
+/// final. Every attempt is recorded as its own usage event, all sharing the original request
+/// id as parent. Routes cooling down after `Retry-After`, over a hard budget or currently
+/// unusable are skipped; the last route is sent regardless of cooldowns, and answered by the
+/// proxy when it is unusable or over budget.
+async fn forward_with_failover(
+    request: FailoverRequest<'_>,
+    fallbacks: Vec<FailoverRoute>,
+    ctx: &RequestContext,
+) -> Response<ProxyBody> {
+    let FailoverRequest {
+        method,
+        path,
+        headers,
+        body,
+        thinking_enabled,
+        mut seed,
+    } = request;
+    let vercel_api_key = {
+        let vc = ctx.vercel_config.read().await;
+        vc.is_active().then(|| vc.api_key.clone())
+    };
+    let primary = FailoverRoute {
+        target: if vercel_api_key.is_some() && is_claude_model_request(&body) {
+            FailoverTarget::Vercel
+        } else {
+            FailoverTarget::Backend
+        },
+        model: seed.model.clone(),
+    };
+    // The capture belongs to whichever attempt is relayed to the client.
+    let mut capture = seed.capture.take();
+    seed.parent_request_id = Some(seed.request_id.clone());
+
+    let routes: Vec<FailoverRoute> = std::iter::once(primary).chain(fallbacks).collect();
+    let last = routes.len() - 1;
+    for (index, route) in routes.into_iter().enumerate() {
+        let is_last = index == last;
+        let mut attempt_seed = seed.clone();
+        let attempt_body = if index == 0 {
+            body.clone()
+        } else {
+            attempt_seed.request_id = Uuid::new_v4().to_string();
+            attempt_seed.started_at = Instant::now();
+            attempt_seed.provider = infer_provider_from_path_and_model(path, &route.model);
+            attempt_seed.model = route.model.clone();
+            set_model(&body, &route.model).unwrap_or_else(|| body.clone())
+        };
+        let unavailable = match route.target {
+            FailoverTarget::Backend => (!ctx.backend_ready.load(Ordering::SeqCst))
+                .then_some("Backend is still starting up"),
+            FailoverTarget::Vercel if vercel_api_key.is_none() => {
+                Some("Vercel AI Gateway is not configured")
+            }
+            FailoverTarget::Vercel if !is_claude_model_request(&attempt_body) => {
+                Some("Vercel AI Gateway only serves Claude models")
+            }
+            FailoverTarget::Vercel => None,
+        };
+        if !is_last && (unavailable.is_some() || ctx.failover_router.is_cooling_down(&route)) {
+            log::info!(
+                "[ThinkingProxy] Skipping failover route {:?} '{}'",
+                route.target,
+                route.model
+            );
+            continue;
+        }
+        // The primary route was checked before forwarding; fallbacks may be budgeted too.
+        if index > 0 {
+            let rejection = ctx
+                .budget_guard
+                .check(
+                    &ctx.usage_tracker,
+                    &attempt_seed.provider,
+                    &attempt_seed.model,
+                    &attempt_seed.account_key,
+                )
+                .await;
+            if let Some(rejection) = rejection {
+                log::warn!("[ThinkingProxy] {}", rejection.message());
+                if !is_last {
+                    continue;
+                }
+                attempt_seed.capture = capture.take();
+                record_failed_request(
+                    ctx.usage_tracker.clone(),
+                    Some(attempt_seed),
+                    429,
+                    ErrorDetails::new(ErrorCategory::QuotaExceeded, rejection.message()),
+                );
+                return make_budget_exceeded_response(path, &rejection);
+            }
+        }
+        if let Some(reason) = unavailable {
+            attempt_seed.capture = capture.take();
+            record_failed_request(
+                ctx.usage_tracker.clone(),
+                Some(attempt_seed),
+                503,
+                ErrorDetails::new(ErrorCategory::BackendUnavailable, reason),
+            );
+            return match route.target {
+                FailoverTarget::Backend => make_warming_up_response(),
+                FailoverTarget::Vercel => make_response(
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    &format!("Service Unavailable - {}", reason),
+                ),
+            };
+        }
+
+        let timeouts = ctx.timeout_policy.resolve(path, &route.model);
+        let mut in_flight = InFlightSeed::new(&ctx.usage_tracker, attempt_seed);
+        let result = match route.target {
+            FailoverTarget::Vercel => {
+                log::info!(
+                    "[ThinkingProxy] Routing '{}' via Vercel AI Gateway",
+                    route.model
+                );
+                retry_on_status(&ctx.retry_policy, || {
+                    forward_to_vercel(
+                        method,
+                        "/v1/messages",
+                        headers,
+                        attempt_body.clone(),
+                        thinking_enabled,
+                        vercel_api_key.as_deref().unwrap_or_default(),
+                        &timeouts,
+                    )
+                })
+                .await
+            }
+            FailoverTarget::Backend => {
+                retry_on_status(&ctx.retry_policy, || {
+                    forward_buffered_to_backend(
+                        method,
+                        path,
+                        headers,
+                        attempt_body.clone(),
+                        thinking_enabled,
+                        ctx.target_port,
+                        &timeouts,
+                    )
+                })
+                .await
+            }
+        };
+        match result {
+            Ok(outcome) if is_last || !is_failover_status(outcome.status_code) => {
+                let seed = in_flight.take().map(|seed| TrackingSeed {
+                    capture: capture.take(),
+                    ..seed
+                });
+                return stream_outcome(outcome, &ctx.usage_tracker, seed);
+            }
+            Ok(outcome) => {
+                log::warn!(
+                    "[ThinkingProxy] {:?} '{}' returned {}, failing over",
+                    route.target,
+                    route.model,
+                    outcome.status_code
+                );
+                if let Some(delay) = retry_after(outcome.upstream.headers()) {
+                    ctx.failover_router.cool_down(&route, delay);
+                }
+                if let Some(seed) = in_flight.take() {
+                    record_discarded_response(&ctx.usage_tracker, seed, outcome).await;
+                }
+            }
+            Err(e) if is_last => {
+                log::error!("[ThinkingProxy] Failover forward error: {}", e);
+                let seed = in_flight.take().map(|seed| TrackingSeed {
+                    capture: capture.take(),
+                    ..seed
+                });
+                let response_message = format!("Bad Gateway - Upstream unavailable: {}", e);
+                return upstream_failure(&ctx.usage_tracker, seed, e.as_ref(), &response_message);
+            }
+            Err(e) => {
+                log::warn!(
+                    "[ThinkingProxy] {:?} '{}' failed: {}, failing over",
+                    route.target,
+                    route.model,
+                    e
+                );
+                let details = classify_transport_error(e.as_ref());
+                let status_code = if details.category == ErrorCategory::Timeout {
+                    504
+                } else {
+                    502
+                };
+                record_failed_request(
+                    ctx.usage_tracker.clone(),
+                    in_flight.take(),
+                    status_code,
+                    details,
+                );
+            }
+        }
+    }
+    make_response(
+        StatusCode::BAD_GATEWAY,
+        "Bad Gateway - No upstream route left",
+    )
+}
+
+/// Records an upstream error response the client never sees, reading only enough of the
+/// body to classify the error.
+async fn record_discarded_response(
+    usage_tracker: &Arc<UsageTracker>,
+    seed: TrackingSeed,
+    outcome: ForwardOutcome,
+) {
+    let mut recorder = UsageRecorder::new(
+        usage_tracker.clone(),
+        seed,
+        outcome.status_code,
+        outcome.upstream.headers(),
+    );
+    let mut chunks = outcome.upstream.bytes_stream();
+    let limit = Duration::from_secs(FAILOVER_ERROR_BODY_TIMEOUT_SECS);
+    while recorder.response_bytes < MAX_ERROR_BODY_BYTES as i64 {
+        match tokio::time::timeout(limit, chunks.next()).await {
+            Ok(Some(Ok(bytes))) => recorder.observe(&bytes),
+            _ => break,
         }
     }
+    recorder.complete = true;
+}
+
+/// Records a failed upstream call and answers the client: `504` when upstream timed out,
+/// `502` with `bad_gateway_message` otherwise.
+fn upstream_failure(
+    usage_tracker: &Arc<UsageTracker>,
+    seed: Option<TrackingSeed>,
+    error: &(dyn std::error::Error + Send + Sync + 'static),
+    bad_gateway_message: &str,
+) -> Response<ProxyBody> {
+    let details = classify_transport_error(error);
+    if details.category == ErrorCategory::Timeout {
+        record_failed_request(usage_tracker.clone(), seed, 504, details);
+        let message = format!("Gateway Timeout - {}", error);
+        return make_response(StatusCode::GATEWAY_TIMEOUT, &message);
+    }
+    record_failed_request(usage_tracker.clone(), seed, 502, details);
+    make_response(StatusCode::BAD_GATEWAY, bad_gateway_message)
+}
+
+/// Only JSON POST bodies are parsed (thinking rewrite, model/account attribution).
+/// A missing content type is treated as JSON for compatibility with minimal clients.
+fn should_inspect_body(method: &hyper::Method, headers: &hyper::HeaderMap) -> bool {
+    if *method != hyper::Method::POST {
+        return false;
+    }
+    match headers
+        .get(hyper::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+    {
+        Some(content_type) => content_type.to_ascii_lowercase().contains("json"),
+        None => true,
+    }
+}
+
+fn content_length_hint(headers: &hyper::HeaderMap) -> i64 {
+    headers
+        .get(hyper::header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<i64>().ok())
+        .unwrap_or(0)
 }
 
 fn is_retryable_backend_error(method: &hyper::Method, message: &str) -> bool {
@@ -436,18 +1216,32 @@ async fn forward_to_backend_with_retry(
     method: &hyper::Method,
     path: &str,
     headers: &hyper::HeaderMap,
-    body: &str,
+    body: &mut ForwardBody,
     thinking_enabled: bool,
     target_port: u16,
+    timeouts: &RequestTimeouts,
 ) -> Result<ForwardOutcome, Box<dyn std::error::Error + Send + Sync>> {
     let mut attempts = 0usize;
 
     loop {
         attempts += 1;
-        match forward_to_backend(method, path, headers, body, thinking_enabled, target_port).await {
-            Ok(outcome) => return Ok(outcome),
-            Err(e) => {
-                if attempts >= BACKEND_FORWARD_RETRY_ATTEMPTS {
+        let Some(attempt_body) = body.next_attempt() else {
+            return Err("Request body was already streamed upstream".into());
+        };
+        match forward_to_backend(
+            method,
+            path,
+            headers,
+            attempt_body,
+            thinking_enabled,
+            target_port,
+            timeouts,
+        )
+        .await
+        {
+            Ok(outcome) => return Ok(outcome),
+            Err(e) => {
+                if attempts >= BACKEND_FORWARD_RETRY_ATTEMPTS || !body.is_replayable() {
                     return Err(e);
                 }
 
@@ -468,13 +1262,71 @@ async fn forward_to_backend_with_retry(
     }
 }
 
+/// `forward_to_backend_with_retry` for a body the caller keeps, so the request can be sent
+/// again.
+async fn forward_buffered_to_backend(
+    method: &hyper::Method,
+    path: &str,
+    headers: &hyper::HeaderMap,
+    body: Bytes,
+    thinking_enabled: bool,
+    target_port: u16,
+    timeouts: &RequestTimeouts,
+) -> Result<ForwardOutcome, Box<dyn std::error::Error + Send + Sync>> {
+    let mut body = ForwardBody::Buffered(body);
+    forward_to_backend_with_retry(
+        method,
+        path,
+        headers,
+        &mut body,
+        thinking_enabled,
+        target_port,
+        timeouts,
+    )
+    .await
+}
+
+/// Sends a replayable request again while upstream answers `429`/`529` and the retry policy
+/// allows it. This happens before any of the response is relayed, so a discarded attempt is
+/// simply dropped.
+async fn retry_on_status<F, Fut>(
+    retry_policy: &RetryPolicy,
+    mut send: F,
+) -> Result<ForwardOutcome, Box<dyn std::error::Error + Send + Sync>>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<
+        Output = Result<ForwardOutcome, Box<dyn std::error::Error + Send + Sync>>,
+    >,
+{
+    let mut attempt = 1;
+    loop {
+        let outcome = send().await?;
+        let Some(delay) =
+            retry_policy.delay_for(attempt, outcome.status_code, outcome.upstream.headers())
+        else {
+            return Ok(outcome);
+        };
+        log::warn!(
+            "[ThinkingProxy] Upstream returned {} on attempt {}. Retrying in {}ms...",
+            outcome.status_code,
+            attempt,
+            delay.as_millis()
+        );
+        drop(outcome);
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
 fn build_tracking_seed(
     method: &hyper::Method,
     rewritten_path: &str,
     headers: &hyper::HeaderMap,
-    body: &str,
+    body: &[u8],
     request_bytes: i64,
     started_at: Instant,
+    client: Option<&ClientIdentity>,
 ) -> TrackingSeed {
     let model = extract_model_from_body(body).unwrap_or_else(|| "unknown".to_string());
     let provider = infer_provider_from_path_and_model(rewritten_path, &model);
@@ -490,21 +1342,118 @@ fn build_tracking_seed(
         model,
         account_key: account_key.clone(),
         account_label: account_key,
+        client_id: client.map(|c| c.id.clone()).unwrap_or_default(),
+        client_label: client.map(|c| c.name.clone()).unwrap_or_default(),
         request_bytes,
+        capture: None,
+        parent_request_id: None,
     }
 }
 
-fn record_usage_if_needed(
+/// Holds a request's tracking seed while the proxy waits on upstream. When the client
+/// disconnects, hyper drops the request future, which aborts the upstream call; the seed is
+/// then still here and gets recorded as cancelled.
+struct InFlightSeed {
+    usage_tracker: Arc<UsageTracker>,
+    seed: Option<TrackingSeed>,
+}
+
+impl InFlightSeed {
+    fn new(usage_tracker: &Arc<UsageTracker>, seed: TrackingSeed) -> Self {
+        Self {
+            usage_tracker: usage_tracker.clone(),
+            seed: Some(seed),
+        }
+    }
+
+    /// Hands the seed to whatever answers the client.
+    fn take(&mut self) -> Option<TrackingSeed> {
+        self.seed.take()
+    }
+}
+
+impl Drop for InFlightSeed {
+    fn drop(&mut self) {
+        let Some(seed) = self.seed.take() else {
+            return;
+        };
+        log::info!(
+            "[ThinkingProxy] Client disconnected while waiting on upstream, cancelled {} {}",
+            seed.method,
+            seed.path
+        );
+        record_failed_request(
+            self.usage_tracker.clone(),
+            Some(seed),
+            CLIENT_CLOSED_REQUEST,
+            ErrorDetails::new(
+                ErrorCategory::Cancelled,
+                "Client disconnected before upstream responded",
+            ),
+        );
+    }
+}
+
+/// Records a request the proxy answered itself, without relaying an upstream response.
+fn record_failed_request(
     usage_tracker: Arc<UsageTracker>,
     seed: Option<TrackingSeed>,
     status_code: u16,
-    response_body: Bytes,
+    error: ErrorDetails,
 ) {
     let Some(mut seed) = seed else {
         return;
     };
 
-    let mut usage = extract_token_usage(&response_body);
+    if let Some(capture) = seed.capture.take() {
+        capture.finish(
+            status_code,
+            seed.started_at.elapsed().as_millis() as i64,
+            None,
+        );
+    }
+    persist_usage_event(
+        usage_tracker,
+        seed,
+        status_code,
+        TokenUsage::default(),
+        0,
+        ResponseTiming::default(),
+        Some(error),
+    );
+}
+
+/// Milliseconds from request start to upstream headers and to the first generated token.
+#[derive(Debug, Clone, Copy, Default)]
+struct ResponseTiming {
+    ttfb_ms: Option<i64>,
+    ttft_ms: Option<i64>,
+}
+
+/// Output tokens per second over the generation window: after the first token for
+/// streams, the whole request otherwise.
+fn output_tokens_per_sec(
+    output_tokens: Option<i64>,
+    duration_ms: i64,
+    timing: ResponseTiming,
+) -> Option<f64> {
+    let output_tokens = output_tokens.filter(|tokens| *tokens > 0)?;
+    let window_ms = duration_ms - timing.ttft_ms.unwrap_or(0);
+    if window_ms <= 0 {
+        return None;
+    }
+    Some(output_tokens as f64 * 1000.0 / window_ms as f64)
+}
+
+fn persist_usage_event(
+    usage_tracker: Arc<UsageTracker>,
+    mut seed: TrackingSeed,
+    status_code: u16,
+    mut usage: TokenUsage,
+    response_bytes: i64,
+    timing: ResponseTiming,
+    error: Option<ErrorDetails>,
+) {
     if seed.account_key == "unknown" {
         if let Some(account_hint) = usage.account_hint.take() {
             if !account_hint.trim().is_empty() {
@@ -514,6 +1463,7 @@ fn record_usage_if_needed(
         }
     }
 
+    let duration_ms = seed.started_at.elapsed().as_millis() as i64;
     let event = UsageEvent {
         request_id: seed.request_id,
         timestamp_utc: Utc::now().timestamp(),
@@ -523,16 +1473,25 @@ fn record_usage_if_needed(
         model: seed.model,
         account_key: seed.account_key,
         account_label: seed.account_label,
+        client_id: seed.client_id,
+        client_label: seed.client_label,
         status_code: status_code as i64,
-        duration_ms: seed.started_at.elapsed().as_millis() as i64,
+        duration_ms,
+        ttfb_ms: timing.ttfb_ms,
+        ttft_ms: timing.ttft_ms,
+        output_tokens_per_sec: output_tokens_per_sec(usage.output_tokens, duration_ms, timing),
         request_bytes: seed.request_bytes,
-        response_bytes: response_body.len() as i64,
+        response_bytes,
         input_tokens: usage.input_tokens,
         output_tokens: usage.output_tokens,
         total_tokens: usage.total_tokens,
         cached_tokens: usage.cached_tokens,
+        cache_write_tokens: usage.cache_write_tokens,
         reasoning_tokens: usage.reasoning_tokens,
         usage_json: usage.usage_json,
+        error_category: error.as_ref().map(|error| error.category),
+        error_message: error.and_then(|error| error.message),
+        parent_request_id: seed.parent_request_id,
     };
 
     tokio::spawn(async move {
@@ -542,8 +1501,245 @@ fn record_usage_if_needed(
     });
 }
 
-fn extract_model_from_body(body: &str) -> Option<String> {
-    let json: serde_json::Value = serde_json::from_str(body).ok()?;
+/// Streams an upstream response to the client, recording usage once the body has been
+/// fully relayed (or abandoned by the client).
+fn stream_outcome(
+    outcome: ForwardOutcome,
+    usage_tracker: &Arc<UsageTracker>,
+    seed: Option<TrackingSeed>,
+) -> Response<ProxyBody> {
+    let recorder = seed.map(|seed| {
+        UsageRecorder::new(
+            usage_tracker.clone(),
+            seed,
+            outcome.status_code,
+            outcome.upstream.headers(),
+        )
+    });
+    build_proxy_response(outcome.upstream, recorder, outcome.read_timeout)
+}
+
+fn is_event_stream(headers: &reqwest::header::HeaderMap) -> bool {
+    headers
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_ascii_lowercase().contains("text/event-stream"))
+        .unwrap_or(false)
+}
+
+/// Observes response chunks as they pass through the proxy and persists the usage event
+/// when dropped, which happens once the body is finished or the client goes away.
+struct UsageRecorder {
+    usage_tracker: Arc<UsageTracker>,
+    seed: Option<TrackingSeed>,
+    status_code: u16,
+    scanner: UsageScanner,
+    response_bytes: i64,
+    timing: ResponseTiming,
+    has_retry_after: bool,
+    /// Leading bytes of an error response, classified once the body is done.
+    error_body: Option<Vec<u8>>,
+    capture: Option<(CaptureDraft, CapturedResponse)>,
+    /// Set when the body stopped on a timeout; recorded instead of the upstream status.
+    timeout: Option<ErrorDetails>,
+    /// Set once the upstream body has ended or failed. A recorder dropped before that means
+    /// the client went away mid-response.
+    complete: bool,
+}
+
+impl UsageRecorder {
+    fn new(
+        usage_tracker: Arc<UsageTracker>,
+        mut seed: TrackingSeed,
+        status_code: u16,
+        headers: &reqwest::header::HeaderMap,
+    ) -> Self {
+        // Created as soon as upstream headers arrive, which is the time to first byte.
+        let timing = ResponseTiming {
+            ttfb_ms: Some(seed.started_at.elapsed().as_millis() as i64),
+            ttft_ms: None,
+        };
+        let capture = seed.capture.take().map(|draft| {
+            let response = draft.response(headers);
+            (draft, response)
+        });
+        // hyper never polls these bodies, so dropping them unread is not a disconnect.
+        let complete = seed.method == "HEAD" || status_code == 204 || status_code == 304;
+        Self {
+            usage_tracker,
+            seed: Some(seed),
+            status_code,
+            scanner: UsageScanner::new(is_event_stream(headers)),
+            response_bytes: 0,
+            timing,
+            has_retry_after: headers.contains_key(reqwest::header::RETRY_AFTER),
+            error_body: (status_code >= 400).then(Vec::new),
+            capture,
+            timeout: None,
+            complete,
+        }
+    }
+
+    /// The response was cut off by a read or total timeout after headers were relayed.
+    fn timed_out(&mut self, error: &dyn std::fmt::Display) {
+        self.status_code = StatusCode::GATEWAY_TIMEOUT.as_u16();
+        self.error_body = None;
+        self.timeout = Some(ErrorDetails::new(ErrorCategory::Timeout, error.to_string()));
+        self.complete = true;
+    }
+
+    fn observe(&mut self, chunk: &[u8]) {
+        self.response_bytes += chunk.len() as i64;
+        if let Some(body) = self.error_body.as_mut() {
+            let take = MAX_ERROR_BODY_BYTES
+                .saturating_sub(body.len())
+                .min(chunk.len());
+            body.extend_from_slice(&chunk[..take]);
+        }
+        if let Some((_, response)) = self.capture.as_mut() {
+            response.body.observe(chunk);
+        }
+        self.scanner.observe(chunk);
+        if self.timing.ttft_ms.is_none() && self.scanner.saw_output {
+            self.timing.ttft_ms = self
+                .seed
+                .as_ref()
+                .map(|seed| seed.started_at.elapsed().as_millis() as i64);
+        }
+    }
+}
+
+impl Drop for UsageRecorder {
+    fn drop(&mut self) {
+        let Some(seed) = self.seed.take() else {
+            return;
+        };
+        let cancelled = !self.complete;
+        if cancelled {
+            log::info!(
+                "[ThinkingProxy] Client disconnected mid-response, cancelled {} {}",
+                seed.method,
+                seed.path
+            );
+            self.status_code = CLIENT_CLOSED_REQUEST;
+        }
+        if let Some((draft, response)) = self.capture.take() {
+            draft.finish(
+                self.status_code,
+                seed.started_at.elapsed().as_millis() as i64,
+                Some(response),
+            );
+        }
+        // Partial usage still counts: streams report input tokens up front.
+        let usage = std::mem::take(&mut self.scanner).finish();
+        let error = if cancelled {
+            Some(ErrorDetails::new(
+                ErrorCategory::Cancelled,
+                "Client disconnected before the response finished",
+            ))
+        } else {
+            self.timeout.take().or_else(|| {
+                self.error_body
+                    .take()
+                    .map(|body| classify_response(self.status_code, self.has_retry_after, &body))
+            })
+        };
+        persist_usage_event(
+            self.usage_tracker.clone(),
+            seed,
+            self.status_code,
+            usage,
+            self.response_bytes,
+            self.timing,
+            error,
+        );
+    }
+}
+
+/// Incrementally extracts token usage from a response body.
+///
+/// SSE bodies are parsed line by line so only the current partial line is held in memory.
+/// Other bodies are buffered up to `MAX_USAGE_SCAN_BYTES` and parsed once complete.
+#[derive(Default)]
+struct UsageScanner {
+    is_event_stream: bool,
+    pending_line: Vec<u8>,
+    buffered: Vec<u8>,
+    overflowed: bool,
+    aggregate: TokenUsage,
+    saw_usage: bool,
+    /// Set once an SSE event carries generated content.
+    saw_output: bool,
+}
+
+impl UsageScanner {
+    fn new(is_event_stream: bool) -> Self {
+        Self {
+            is_event_stream,
+            ..Default::default()
+        }
+    }
+
+    fn observe(&mut self, chunk: &[u8]) {
+        if self.is_event_stream {
+            self.pending_line.extend_from_slice(chunk);
+            while let Some(pos) = self.pending_line.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = self.pending_line.drain(..=pos).collect();
+                self.consume_sse_line(&line);
+            }
+            if self.pending_line.len() > MAX_USAGE_SCAN_BYTES {
+                self.pending_line.clear();
+            }
+            return;
+        }
+
+        if self.overflowed {
+            return;
+        }
+        if self.buffered.len() + chunk.len() > MAX_USAGE_SCAN_BYTES {
+            self.overflowed = true;
+            self.buffered = Vec::new();
+            return;
+        }
+        self.buffered.extend_from_slice(chunk);
+    }
+
+    fn consume_sse_line(&mut self, line: &[u8]) {
+        let text = String::from_utf8_lossy(line);
+        let Some(json) = parse_sse_data(&text) else {
+            return;
+        };
+        if !self.saw_output && sse_event_has_output(&json) {
+            self.saw_output = true;
+        }
+        if let Some(parsed) = extract_usage_from_json_value(&json) {
+            self.saw_usage = true;
+            merge_usage(&mut self.aggregate, parsed);
+        }
+    }
+
+    fn finish(mut self) -> TokenUsage {
+        if !self.is_event_stream {
+            if self.overflowed {
+                return TokenUsage::default();
+            }
+            return extract_token_usage(&self.buffered);
+        }
+
+        if !self.pending_line.is_empty() {
+            let line = std::mem::take(&mut self.pending_line);
+            self.consume_sse_line(&line);
+        }
+        if self.saw_usage {
+            self.aggregate
+        } else {
+            TokenUsage::default()
+        }
+    }
+}
+
+fn extract_model_from_body(body: &[u8]) -> Option<String> {
+    let json: serde_json::Value = serde_json::from_slice(body).ok()?;
     json.get("model")
         .and_then(|v| v.as_str())
         .map(|s| s.to_string())
@@ -585,7 +1781,7 @@ fn infer_provider_from_path_and_model(path: &str, model: &str) -> String {
     "unknown".to_string()
 }
 
-fn extract_account_hint(headers: &hyper::HeaderMap, body: &str) -> Option<String> {
+fn extract_account_hint(headers: &hyper::HeaderMap, body: &[u8]) -> Option<String> {
     let header_keys = [
         "x-codeforwarder-account",
         "x-codeforwarder-account-id",
@@ -603,7 +1799,7 @@ fn extract_account_hint(headers: &hyper::HeaderMap, body: &str) -> Option<String
         }
     }
 
-    let json: serde_json::Value = serde_json::from_str(body).ok()?;
+    let json: serde_json::Value = serde_json::from_slice(body).ok()?;
     for key in ["auth_index", "account_id", "account", "account_key"] {
         if let Some(value) = json.get(key) {
             if let Some(s) = value.as_str() {
@@ -635,19 +1831,9 @@ fn extract_token_usage(response_body: &[u8]) -> TokenUsage {
     let mut aggregate = TokenUsage::default();
     let mut saw_usage = false;
     for line in text.lines() {
-        let line = line.trim();
-        if !line.starts_with("data:") {
-            continue;
-        }
-        let payload = line.trim_start_matches("data:").trim();
-        if payload.is_empty() || payload == "[DONE]" {
-            continue;
-        }
-        if let Ok(json) = serde_json::from_str::<serde_json::Value>(payload) {
-            if let Some(parsed) = extract_usage_from_json_value(&json) {
-                saw_usage = true;
-                merge_usage(&mut aggregate, parsed);
-            }
+        if let Some(parsed) = parse_sse_usage_line(line) {
+            saw_usage = true;
+            merge_usage(&mut aggregate, parsed);
         }
     }
 
@@ -658,10 +1844,68 @@ fn extract_token_usage(response_body: &[u8]) -> TokenUsage {
     }
 }
 
+fn parse_sse_usage_line(line: &str) -> Option<TokenUsage> {
+    extract_usage_from_json_value(&parse_sse_data(line)?)
+}
+
+fn parse_sse_data(line: &str) -> Option<serde_json::Value> {
+    let line = line.trim();
+    if !line.starts_with("data:") {
+        return None;
+    }
+    let payload = line.trim_start_matches("data:").trim();
+    if payload.is_empty() || payload == "[DONE]" {
+        return None;
+    }
+    serde_json::from_str::<serde_json::Value>(payload).ok()
+}
+
+/// Whether a streamed event carries generated text, reasoning or tool-call content, as
+/// opposed to start/ping/usage-only events. Covers Anthropic, OpenAI chat/responses and Gemini.
+fn sse_event_has_output(json: &serde_json::Value) -> bool {
+    let event_type = json.get("type").and_then(|v| v.as_str()).unwrap_or("");
+    if event_type == "content_block_delta" || event_type.ends_with(".delta") {
+        return true;
+    }
+    let has_chat_delta = json
+        .get("choices")
+        .and_then(|v| v.as_array())
+        .map(|choices| {
+            choices.iter().any(|choice| {
+                let Some(delta) = choice.get("delta") else {
+                    return false;
+                };
+                ["content", "reasoning_content"].iter().any(|key| {
+                    delta
+                        .get(key)
+                        .and_then(|v| v.as_str())
+                        .is_some_and(|s| !s.is_empty())
+                }) || delta.get("tool_calls").is_some_and(|v| !v.is_null())
+            })
+        })
+        .unwrap_or(false);
+    if has_chat_delta {
+        return true;
+    }
+    json.get("candidates")
+        .and_then(|v| v.as_array())
+        .map(|candidates| {
+            candidates.iter().any(|candidate| {
+                candidate
+                    .pointer("/content/parts")
+                    .and_then(|v| v.as_array())
+                    .is_some_and(|parts| !parts.is_empty())
+            })
+        })
+        .unwrap_or(false)
+}
+
 fn merge_usage(target: &mut TokenUsage, source: TokenUsage) {
     target.input_tokens = sum_optional_i64(target.input_tokens, source.input_tokens);
     target.output_tokens = sum_optional_i64(target.output_tokens, source.output_tokens);
     target.cached_tokens = sum_optional_i64(target.cached_tokens, source.cached_tokens);
+    target.cache_write_tokens =
+        sum_optional_i64(target.cache_write_tokens, source.cache_write_tokens);
     target.reasoning_tokens = sum_optional_i64(target.reasoning_tokens, source.reasoning_tokens);
     target.total_tokens = sum_optional_i64(target.total_tokens, source.total_tokens);
     if target.usage_json.is_none() {
@@ -733,13 +1977,14 @@ fn parse_usage_object(value: &serde_json::Value) -> Option<TokenUsage> {
         .or_else(|| {
             find_number_in_object_deep(value, &["total_tokens", "totalTokenCount", "tokens"])
         });
+    // Cache reads and cache writes are priced differently, so they are kept apart.
     let cached_tokens = find_number_in_object(
         obj,
         &[
             "cached_tokens",
             "cached_input_tokens",
             "cache_read_input_tokens",
-            "cache_creation_input_tokens",
+            "cachedContentTokenCount",
         ],
     )
     .or_else(|| {
@@ -749,10 +1994,12 @@ fn parse_usage_object(value: &serde_json::Value) -> Option<TokenUsage> {
                 "cached_tokens",
                 "cached_input_tokens",
                 "cache_read_input_tokens",
-                "cache_creation_input_tokens",
+                "cachedContentTokenCount",
             ],
         )
     });
+    let cache_write_tokens = find_number_in_object(obj, &["cache_creation_input_tokens"])
+        .or_else(|| find_number_in_object_deep(value, &["cache_creation_input_tokens"]));
     let reasoning_tokens = find_number_in_object(
         obj,
         &["reasoning_tokens", "thinking_tokens", "reasoningTokenCount"],
@@ -772,6 +2019,7 @@ fn parse_usage_object(value: &serde_json::Value) -> Option<TokenUsage> {
         && output_tokens.is_none()
         && total_tokens.is_none()
         && cached_tokens.is_none()
+        && cache_write_tokens.is_none()
         && reasoning_tokens.is_none()
         && account_hint.is_none()
     {
@@ -788,6 +2036,7 @@ fn parse_usage_object(value: &serde_json::Value) -> Option<TokenUsage> {
         output_tokens,
         total_tokens,
         cached_tokens,
+        cache_write_tokens,
         reasoning_tokens,
         usage_json: serde_json::to_string(value).ok(),
         account_hint,
@@ -874,8 +2123,8 @@ fn find_number_in_object_deep(value: &serde_json::Value, keys: &[&str]) -> Optio
     }
 }
 
-fn is_claude_model_request(body: &str) -> bool {
-    let Ok(json) = serde_json::from_str::<serde_json::Value>(body) else {
+fn is_claude_model_request(body: &[u8]) -> bool {
+    let Ok(json) = serde_json::from_slice::<serde_json::Value>(body) else {
         return false;
     };
     let Some(model) = json.get("model").and_then(|m| m.as_str()) else {
@@ -885,10 +2134,11 @@ fn is_claude_model_request(body: &str) -> bool {
 }
 
 /// Processes the JSON body to add thinking parameter if model name has a thinking suffix.
+/// The budget and max tokens are clamped to the model's limits from the catalog.
 /// Returns (modified_body, thinking_enabled).
-fn process_thinking_parameter(body: &str) -> (String, bool) {
-    let Ok(mut json) = serde_json::from_str::<serde_json::Value>(body) else {
-        return (body.to_string(), false);
+fn process_thinking_parameter(body: &Bytes, catalog: &ModelCatalog) -> (Bytes, bool) {
+    let Ok(mut json) = serde_json::from_slice::<serde_json::Value>(body) else {
+        return (body.clone(), false);
     };
 
     let Some(model) = json
@@ -896,12 +2146,12 @@ fn process_thinking_parameter(body: &str) -> (String, bool) {
         .and_then(|m| m.as_str())
         .map(|s| s.to_string())
     else {
-        return (body.to_string(), false);
+        return (body.clone(), false);
     };
 
     // Only process Claude models (including gemini-claude variants)
     if !model.starts_with("claude-") && !model.starts_with("gemini-claude-") {
-        return (body.to_string(), false);
+        return (body.clone(), false);
     }
 
     // Check for thinking suffix pattern: -thinking-NUMBER
@@ -923,7 +2173,15 @@ fn process_thinking_parameter(body: &str) -> (String, bool) {
                     model[..thinking_pos].to_string()
                 };
 
-                let effective_budget = budget.min(HARD_TOKEN_CAP - 1);
+                let model_info = catalog.model(&clean_model);
+                let output_cap = model_info
+                    .as_ref()
+                    .and_then(|info| info.max_completion_tokens)
+                    .filter(|cap| *cap > 1)
+                    .unwrap_or(DEFAULT_OUTPUT_TOKEN_CAP);
+                let thinking_support = model_info.and_then(|info| info.thinking);
+                let effective_budget =
+                    clamp_budget(thinking_support.as_ref(), budget).min(output_cap - 1);
                 if effective_budget != budget {
                     log::info!(
                         "[ThinkingProxy] Adjusted thinking budget from {} to {} to stay within limits",
@@ -944,9 +2202,9 @@ fn process_thinking_parameter(body: &str) -> (String, bool) {
                 let token_headroom =
                     MINIMUM_HEADROOM.max((effective_budget as f64 * HEADROOM_RATIO) as i64);
                 let desired_max_tokens = effective_budget + token_headroom;
-                let mut required_max_tokens = desired_max_tokens.min(HARD_TOKEN_CAP);
+                let mut required_max_tokens = desired_max_tokens.min(output_cap);
                 if required_max_tokens <= effective_budget {
-                    required_max_tokens = (effective_budget + 1).min(HARD_TOKEN_CAP);
+                    required_max_tokens = (effective_budget + 1).min(output_cap);
                 }
 
                 let has_max_output_tokens = json.get("max_output_tokens").is_some();
@@ -983,8 +2241,8 @@ fn process_thinking_parameter(body: &str) -> (String, bool) {
                     effective_budget
                 );
 
-                if let Ok(modified) = serde_json::to_string(&json) {
-                    return (modified, true);
+                if let Ok(modified) = serde_json::to_vec(&json) {
+                    return (Bytes::from(modified), true);
                 }
             } else {
                 // Invalid budget (non-positive) - strip suffix, no thinking
@@ -999,8 +2257,8 @@ fn process_thinking_parameter(body: &str) -> (String, bool) {
                     model,
                     clean_model
                 );
-                if let Ok(modified) = serde_json::to_string(&json) {
-                    return (modified, true);
+                if let Ok(modified) = serde_json::to_vec(&json) {
+                    return (Bytes::from(modified), true);
                 }
             }
         } else {
@@ -1016,8 +2274,8 @@ fn process_thinking_parameter(body: &str) -> (String, bool) {
                 model,
                 clean_model
             );
-            if let Ok(modified) = serde_json::to_string(&json) {
-                return (modified, true);
+            if let Ok(modified) = serde_json::to_vec(&json) {
+                return (Bytes::from(modified), true);
             }
         }
     } else if model.ends_with("-thinking") || model.contains("-thinking(") {
@@ -1027,10 +2285,10 @@ fn process_thinking_parameter(body: &str) -> (String, bool) {
             "[ThinkingProxy] Detected thinking model '{}' - enabling beta header, passing through to backend",
             model
         );
-        return (body.to_string(), true);
+        return (body.clone(), true);
     }
 
-    (body.to_string(), false)
+    (body.clone(), false)
 }
 
 /// Build a reqwest header map from hyper headers, excluding hop-by-hop headers.
@@ -1053,14 +2311,14 @@ fn build_forwarding_headers(
     out
 }
 
-/// Build a hyper Response from a reqwest response (status, headers, body).
+/// Build a hyper Response from a reqwest response, streaming the body chunk by chunk.
 fn build_proxy_response(
-    status: reqwest::StatusCode,
-    resp_headers: &reqwest::header::HeaderMap,
-    body: Bytes,
-) -> Response<Full<Bytes>> {
-    let mut builder = Response::builder().status(status.as_u16());
-    for (name, value) in resp_headers.iter() {
+    upstream: reqwest::Response,
+    recorder: Option<UsageRecorder>,
+    read_timeout: Option<Duration>,
+) -> Response<ProxyBody> {
+    let mut builder = Response::builder().status(upstream.status().as_u16());
+    for (name, value) in upstream.headers().iter() {
         // Skip hop-by-hop headers
         let name_lower = name.as_str().to_lowercase();
         if name_lower == "transfer-encoding" || name_lower == "connection" {
@@ -1068,7 +2326,79 @@ fn build_proxy_response(
         }
         builder = builder.header(name.as_str(), value.as_bytes());
     }
-    builder.body(Full::new(body)).unwrap()
+    builder
+        .body(stream_upstream_body(upstream, recorder, read_timeout))
+        .unwrap()
+}
+
+/// Relay the upstream body without buffering it. Each chunk is handed to the recorder (if any)
+/// before being forwarded, so usage is extracted while the response streams. The body ends
+/// with an error when no chunk arrives within `read_timeout`. When the client disconnects,
+/// hyper drops this body, which closes the upstream connection and stops generation.
+fn stream_upstream_body(
+    upstream: reqwest::Response,
+    recorder: Option<UsageRecorder>,
+    read_timeout: Option<Duration>,
+) -> ProxyBody {
+    // hyper stops polling once Content-Length bytes are written, so the end of the stream is
+    // never observed for sized bodies.
+    let content_length = upstream.content_length();
+    let chunks = upstream.bytes_stream().boxed();
+    let stream = futures_util::stream::unfold(
+        (chunks, recorder, false),
+        move |(mut chunks, mut recorder, finished)| async move {
+            if finished {
+                return None;
+            }
+            let next = match read_timeout {
+                Some(limit) => match tokio::time::timeout(limit, chunks.next()).await {
+                    Ok(next) => next,
+                    Err(_) => {
+                        let e = UpstreamTimeout {
+                            waited: limit,
+                            stage: "between response chunks",
+                        };
+                        log::warn!("[ThinkingProxy] Upstream stream error: {}", e);
+                        if let Some(recorder) = recorder.as_mut() {
+                            recorder.timed_out(&e);
+                        }
+                        let e = Box::new(e) as Box<dyn std::error::Error + Send + Sync>;
+                        return Some((Err(e), (chunks, recorder, true)));
+                    }
+                },
+                None => chunks.next().await,
+            };
+            let Some(next) = next else {
+                if let Some(recorder) = recorder.as_mut() {
+                    recorder.complete = true;
+                }
+                return None;
+            };
+            match next {
+                Ok(bytes) => {
+                    if let Some(recorder) = recorder.as_mut() {
+                        recorder.observe(&bytes);
+                        if content_length.is_some_and(|len| recorder.response_bytes >= len as i64) {
+                            recorder.complete = true;
+                        }
+                    }
+                    Some((Ok(Frame::data(bytes)), (chunks, recorder, false)))
+                }
+                Err(e) => {
+                    log::warn!("[ThinkingProxy] Upstream stream error: {}", e);
+                    if let Some(recorder) = recorder.as_mut() {
+                        if e.is_timeout() {
+                            recorder.timed_out(&e);
+                        }
+                        recorder.complete = true;
+                    }
+                    let e = Box::new(e) as Box<dyn std::error::Error + Send + Sync>;
+                    Some((Err(e), (chunks, recorder, true)))
+                }
+            }
+        },
+    );
+    StreamBody::new(stream).boxed_unsync()
 }
 
 /// Forward a request to ampcode.com and rewrite Location headers / cookie domains in the response.
@@ -1076,8 +2406,8 @@ async fn forward_to_amp(
     method: &hyper::Method,
     path: &str,
     headers: &hyper::HeaderMap,
-    body: &str,
-) -> Result<Response<Full<Bytes>>, Box<dyn std::error::Error + Send + Sync>> {
+    body: reqwest::Body,
+) -> Result<Response<ProxyBody>, Box<dyn std::error::Error + Send + Sync>> {
     let client = shared_http_client();
     let url = format!("https://ampcode.com{}", path);
 
@@ -1092,19 +2422,14 @@ async fn forward_to_amp(
     let resp = client
         .request(reqwest_method, &url)
         .headers(fwd_headers)
-        .body(body.to_string())
+        .body(body)
         .send()
         .await?;
 
-    let status = resp.status();
-    let resp_headers = resp.headers().clone();
-    let resp_body = resp.bytes().await?;
-
     // Rewrite response: Location headers and cookie domains
-    // We need to rewrite both headers and body for Set-Cookie
-    let mut builder = Response::builder().status(status.as_u16());
+    let mut builder = Response::builder().status(resp.status().as_u16());
 
-    for (name, value) in resp_headers.iter() {
+    for (name, value) in resp.headers().iter() {
         let name_lower = name.as_str().to_lowercase();
         if name_lower == "transfer-encoding" || name_lower == "connection" {
             continue;
@@ -1124,7 +2449,9 @@ async fn forward_to_amp(
         }
     }
 
-    Ok(builder.body(Full::new(resp_body)).unwrap())
+    Ok(builder
+        .body(stream_upstream_body(resp, None, None))
+        .unwrap())
 }
 
 /// Rewrite Location header values from ampcode.com responses.
@@ -1157,11 +2484,11 @@ async fn forward_to_vercel(
     method: &hyper::Method,
     path: &str,
     headers: &hyper::HeaderMap,
-    body: &str,
+    body: Bytes,
     thinking_enabled: bool,
     api_key: &str,
+    timeouts: &RequestTimeouts,
 ) -> Result<ForwardOutcome, Box<dyn std::error::Error + Send + Sync>> {
-    let client = shared_http_client();
     let url = format!("https://{}{}", VERCEL_GATEWAY_HOST, path);
 
     let excluded = [
@@ -1221,21 +2548,17 @@ async fn forward_to_vercel(
     }
 
     let reqwest_method = reqwest::Method::from_bytes(method.as_str().as_bytes())?;
-    let resp = client
+    let request = timeouts
+        .client
         .request(reqwest_method, &url)
         .headers(fwd_headers)
-        .body(body.to_string())
-        .send()
-        .await?;
-
-    let status = resp.status();
-    let resp_headers = resp.headers().clone();
-    let resp_body = resp.bytes().await?;
+        .body(body);
+    let resp = timeouts.send(request).await?;
 
     Ok(ForwardOutcome {
-        response: build_proxy_response(status, &resp_headers, resp_body.clone()),
-        status_code: status.as_u16(),
-        body: resp_body,
+        status_code: resp.status().as_u16(),
+        upstream: resp,
+        read_timeout: timeouts.read,
     })
 }
 
@@ -1244,11 +2567,11 @@ async fn forward_to_backend(
     method: &hyper::Method,
     path: &str,
     headers: &hyper::HeaderMap,
-    body: &str,
+    body: reqwest::Body,
     thinking_enabled: bool,
     target_port: u16,
+    timeouts: &RequestTimeouts,
 ) -> Result<ForwardOutcome, Box<dyn std::error::Error + Send + Sync>> {
-    let client = shared_http_client();
     let url = format!("http://127.0.0.1:{}{}", target_port, path);
 
     let excluded = [
@@ -1294,34 +2617,212 @@ async fn forward_to_backend(
     }
 
     let reqwest_method = reqwest::Method::from_bytes(method.as_str().as_bytes())?;
-    let resp = client
+    let request = timeouts
+        .client
         .request(reqwest_method, &url)
         .headers(fwd_headers)
-        .body(body.to_string())
-        .send()
-        .await?;
-
-    let status = resp.status();
-    let resp_headers = resp.headers().clone();
-    let resp_body = resp.bytes().await?;
+        .body(body);
+    let resp = timeouts.send(request).await?;
 
     Ok(ForwardOutcome {
-        response: build_proxy_response(status, &resp_headers, resp_body.clone()),
-        status_code: status.as_u16(),
-        body: resp_body,
+        status_code: resp.status().as_u16(),
+        upstream: resp,
+        read_timeout: timeouts.read,
     })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::client_keys::ClientKey;
+    use crate::types::{
+        BudgetMetric, BudgetPeriod, BudgetScope, FailoverChain, FailoverStep, ModelMatchKind,
+        ModelRewriteRule, ProxyRetries, ProxyTimeouts, RequestCaptureSettings, TimeoutOverride,
+        TimeoutScope, UsageBudget,
+    };
+    use crate::usage_tracker::RecordedEvent;
+    use std::sync::atomic::AtomicUsize;
+
+    /// A running ThinkingProxy in front of a local upstream, with its own usage database.
+    struct TestProxy {
+        proxy: ThinkingProxy,
+        port: u16,
+        usage_tracker: Arc<UsageTracker>,
+        dir: std::path::PathBuf,
+    }
+
+    impl TestProxy {
+        async fn start(upstream_port: u16, settings: AppSettings) -> Self {
+            Self::start_with(
+                upstream_port,
+                settings,
+                ClientAuthConfig::default(),
+                Arc::new(AtomicBool::new(true)),
+            )
+            .await
+        }
+
+        async fn start_with(
+            upstream_port: u16,
+            settings: AppSettings,
+            client_auth: ClientAuthConfig,
+            backend_ready: Arc<AtomicBool>,
+        ) -> Self {
+            let (usage_tracker, dir) = temp_usage_tracker();
+            let capture_store =
+                RequestCaptureStore::open(dir.join("captures.db"), settings.request_capture)
+                    .unwrap();
+            let mut proxy = ThinkingProxy::new(
+                Arc::new(RwLock::new(VercelGatewayConfig::default())),
+                Arc::new(RwLock::new(client_auth)),
+                usage_tracker.clone(),
+                Arc::new(BudgetGuard::new(settings.usage_budgets.clone())),
+                Arc::new(capture_store),
+                Arc::new(ModelRewriter::new(&settings.model_rules)),
+                backend_ready,
+            );
+            let port = std::net::TcpListener::bind("127.0.0.1:0")
+                .unwrap()
+                .local_addr()
+                .unwrap()
+                .port();
+            proxy.apply_settings(&AppSettings {
+                bind_address: "127.0.0.1".to_string(),
+                proxy_port: port,
+                backend_port: upstream_port,
+                ..settings
+            });
+            proxy.start().await.unwrap();
+            Self {
+                proxy,
+                port,
+                usage_tracker,
+                dir,
+            }
+        }
+
+        async fn post(&self, path: &str, body: serde_json::Value) -> reqwest::Response {
+            self.request(path).json(&body).send().await.unwrap()
+        }
+
+        fn request(&self, path: &str) -> reqwest::RequestBuilder {
+            reqwest::Client::new().post(format!("http://127.0.0.1:{}{}", self.port, path))
+        }
+
+        async fn events(&self, count: usize) -> Vec<RecordedEvent> {
+            wait_for_events(&self.usage_tracker, count).await
+        }
+
+        async fn stop(mut self) {
+            self.proxy.stop().await;
+            std::fs::remove_dir_all(&self.dir).ok();
+        }
+    }
+
+    /// Usage events are written in the background; waits until `count` have landed.
+    async fn wait_for_events(usage_tracker: &UsageTracker, count: usize) -> Vec<RecordedEvent> {
+        for _ in 0..200 {
+            let events = usage_tracker.recorded_events();
+            if events.len() >= count {
+                return events;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("expected {} usage events", count);
+    }
+
+    fn temp_usage_tracker() -> (Arc<UsageTracker>, std::path::PathBuf) {
+        let dir = std::env::temp_dir().join(format!("codeforwarder-proxy-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let usage_tracker = Arc::new(UsageTracker::open(dir.join("usage.db")).unwrap());
+        (usage_tracker, dir)
+    }
+
+    fn test_seed() -> TrackingSeed {
+        build_tracking_seed(
+            &hyper::Method::POST,
+            "/v1/messages",
+            &hyper::HeaderMap::new(),
+            br#"{"model":"claude-sonnet-4-5"}"#,
+            30,
+            Instant::now(),
+            None,
+        )
+    }
+
+    const MESSAGE_START: &[u8] = b"event: message_start\ndata: {\"type\":\"message_start\",\"message\":{\"usage\":{\"input_tokens\":42,\"output_tokens\":1}}}\n\n";
+
+    /// Serves `handler` on a local port, standing in for the backend.
+    async fn spawn_upstream<F, Fut>(handler: F) -> u16
+    where
+        F: Fn(Request<hyper::body::Incoming>) -> Fut + Clone + Send + 'static,
+        Fut: std::future::Future<Output = Response<ProxyBody>> + Send + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let handler = handler.clone();
+                tokio::spawn(async move {
+                    let svc = service_fn(move |req| {
+                        let response = handler(req);
+                        async move { Ok::<_, std::convert::Infallible>(response.await) }
+                    });
+                    let _ = http1::Builder::new()
+                        .serve_connection(TokioIo::new(stream), svc)
+                        .await;
+                });
+            }
+        });
+        port
+    }
+
+    async fn request_model(req: Request<hyper::body::Incoming>) -> String {
+        let body = req.into_body().collect().await.unwrap().to_bytes();
+        extract_model_from_body(&body).unwrap_or_default()
+    }
+
+    async fn request_json(req: Request<hyper::body::Incoming>) -> serde_json::Value {
+        let body = req.into_body().collect().await.unwrap().to_bytes();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    fn json_response(status: u16, body: serde_json::Value) -> Response<ProxyBody> {
+        Response::builder()
+            .status(status)
+            .header("Content-Type", "application/json")
+            .body(full_body(body.to_string()))
+            .unwrap()
+    }
+
+    fn test_client_auth() -> ClientAuthConfig {
+        ClientAuthConfig {
+            required: true,
+            keys: vec![ClientKey {
+                id: "factory-key".to_string(),
+                name: "Factory".to_string(),
+                key: "cf-factory".to_string(),
+                created_at: String::new(),
+            }],
+        }
+    }
+
+    fn no_retries() -> ProxyRetries {
+        ProxyRetries {
+            max_attempts: 1,
+            ..ProxyRetries::default()
+        }
+    }
 
     #[test]
     fn test_process_thinking_parameter_claude_with_budget() {
         let body = r#"{"model":"claude-opus-4-5-20251101-thinking-5000","max_tokens":1024}"#;
-        let (result, enabled) = process_thinking_parameter(body);
+        let (result, enabled) = process_thinking_parameter(
+            &Bytes::from_static(body.as_bytes()),
+            &ModelCatalog::default(),
+        );
         assert!(enabled);
-        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&result).unwrap();
         assert_eq!(json["model"], "claude-opus-4-5-20251101");
         assert_eq!(json["thinking"]["type"], "enabled");
         assert_eq!(json["thinking"]["budget_tokens"], 5000);
@@ -1330,9 +2831,12 @@ mod tests {
     #[test]
     fn test_process_thinking_parameter_gemini_claude_with_budget() {
         let body = r#"{"model":"gemini-claude-opus-4-5-thinking-10000","max_tokens":1024}"#;
-        let (result, enabled) = process_thinking_parameter(body);
+        let (result, enabled) = process_thinking_parameter(
+            &Bytes::from_static(body.as_bytes()),
+            &ModelCatalog::default(),
+        );
         assert!(enabled);
-        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&result).unwrap();
         assert_eq!(json["model"], "gemini-claude-opus-4-5-thinking");
         assert_eq!(json["thinking"]["type"], "enabled");
         assert_eq!(json["thinking"]["budget_tokens"], 10000);
@@ -1341,43 +2845,61 @@ mod tests {
     #[test]
     fn test_process_thinking_parameter_no_suffix() {
         let body = r#"{"model":"claude-opus-4-5-20251101","max_tokens":1024}"#;
-        let (result, enabled) = process_thinking_parameter(body);
+        let (result, enabled) = process_thinking_parameter(
+            &Bytes::from_static(body.as_bytes()),
+            &ModelCatalog::default(),
+        );
         assert!(!enabled);
-        assert_eq!(result, body);
+        assert_eq!(result, body.as_bytes());
     }
 
     #[test]
     fn test_process_thinking_parameter_thinking_only_suffix() {
         let body = r#"{"model":"gemini-claude-opus-4-5-thinking","max_tokens":1024}"#;
-        let (result, enabled) = process_thinking_parameter(body);
+        let (result, enabled) = process_thinking_parameter(
+            &Bytes::from_static(body.as_bytes()),
+            &ModelCatalog::default(),
+        );
         assert!(enabled);
         // Body should be unchanged, just beta header enabled
-        assert_eq!(result, body);
+        assert_eq!(result, body.as_bytes());
     }
 
     #[test]
     fn test_process_thinking_parameter_non_claude_model() {
         let body = r#"{"model":"gpt-4","max_tokens":1024}"#;
-        let (result, enabled) = process_thinking_parameter(body);
+        let (result, enabled) = process_thinking_parameter(
+            &Bytes::from_static(body.as_bytes()),
+            &ModelCatalog::default(),
+        );
         assert!(!enabled);
-        assert_eq!(result, body);
+        assert_eq!(result, body.as_bytes());
     }
 
     #[test]
     fn test_process_thinking_parameter_hard_cap() {
         let body = r#"{"model":"claude-opus-4-5-20251101-thinking-99999","max_tokens":1024}"#;
-        let (result, enabled) = process_thinking_parameter(body);
+        let (result, enabled) = process_thinking_parameter(
+            &Bytes::from_static(body.as_bytes()),
+            &ModelCatalog::default(),
+        );
         assert!(enabled);
-        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
-        assert_eq!(json["thinking"]["budget_tokens"], HARD_TOKEN_CAP - 1);
+        let json: serde_json::Value = serde_json::from_slice(&result).unwrap();
+        assert_eq!(
+            json["thinking"]["budget_tokens"],
+            DEFAULT_OUTPUT_TOKEN_CAP - 1
+        );
     }
 
     #[test]
     fn test_process_thinking_parameter_adjusts_max_tokens() {
         let body = r#"{"model":"claude-sonnet-4-5-20250929-thinking-5000","max_tokens":100}"#;
-        let (result, enabled) = process_thinking_parameter(body);
+        let (result, enabled) = process_thinking_parameter(
+            &Bytes::from_static(body.as_bytes()),
+            &ModelCatalog::default(),
+        );
         assert!(enabled);
-        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&result).unwrap();
         // max_tokens should be bumped since 100 <= 5000
         let max_tokens = json["max_tokens"].as_i64().unwrap();
         assert!(max_tokens > 5000);
@@ -1408,12 +2930,14 @@ mod tests {
 
     #[test]
     fn test_is_claude_model_request() {
-        assert!(is_claude_model_request(r#"{"model":"claude-opus-4-5"}"#));
         assert!(is_claude_model_request(
-            r#"{"model":"gemini-claude-opus-4-5-thinking"}"#
+            r#"{"model":"claude-opus-4-5"}"#.as_bytes()
+        ));
+        assert!(is_claude_model_request(
+            r#"{"model":"gemini-claude-opus-4-5-thinking"}"#.as_bytes()
         ));
-        assert!(!is_claude_model_request(r#"{"model":"gpt-4"}"#));
-        assert!(!is_claude_model_request(r#"{"invalid":"json"}"#));
+        assert!(!is_claude_model_request(r#"{"model":"gpt-4"}"#.as_bytes()));
+        assert!(!is_claude_model_request(r#"{"invalid":"json"}"#.as_bytes()));
     }
 
     #[test]
@@ -1430,7 +2954,10 @@ mod tests {
             &hyper::Method::GET,
             "operation timed out"
         ));
-        assert!(is_retryable_backend_error(&hyper::Method::GET, "broken pipe"));
+        assert!(is_retryable_backend_error(
+            &hyper::Method::GET,
+            "broken pipe"
+        ));
         assert!(!is_retryable_backend_error(
             &hyper::Method::POST,
             "invalid header value"
@@ -1460,4 +2987,1034 @@ mod tests {
         assert_eq!(usage.reasoning_tokens, Some(31));
         assert_eq!(usage.total_tokens, Some(150));
     }
+
+    #[test]
+    fn test_extract_usage_separates_anthropic_cache_reads_and_writes() {
+        let payload = serde_json::json!({
+            "usage": {
+                "input_tokens": 10,
+                "cache_creation_input_tokens": 2000,
+                "cache_read_input_tokens": 500,
+                "output_tokens": 20
+            }
+        });
+
+        let usage = extract_usage_from_json_value(&payload).expect("expected usage");
+        assert_eq!(usage.cached_tokens, Some(500));
+        assert_eq!(usage.cache_write_tokens, Some(2000));
+    }
+
+    #[test]
+    fn test_usage_scanner_handles_sse_split_across_chunks() {
+        let mut scanner = UsageScanner::new(true);
+        scanner.observe(b"event: message_start\ndata: {\"type\":\"message_start\",\"message\":{\"usage\":{\"input_");
+        scanner.observe(b"tokens\":12,\"output_tokens\":1}}}\n\n");
+        scanner.observe(b"data: {\"type\":\"message_delta\",\"usage\":{\"output_tokens\":40}}\n");
+        scanner.observe(b"data: [DONE]");
+
+        let usage = scanner.finish();
+        assert_eq!(usage.input_tokens, Some(12));
+        assert_eq!(usage.output_tokens, Some(41));
+    }
+
+    #[test]
+    fn test_usage_scanner_detects_first_output_event() {
+        let mut scanner = UsageScanner::new(true);
+        scanner.observe(b"data: {\"type\":\"message_start\",\"message\":{}}\n");
+        scanner.observe(b"data: {\"type\":\"ping\"}\n");
+        assert!(!scanner.saw_output);
+        scanner.observe(b"data: {\"type\":\"content_block_delta\",\"delta\":{\"text\":\"Hi\"}}\n");
+        assert!(scanner.saw_output);
+    }
+
+    #[test]
+    fn test_chat_completion_delta_with_content_is_output() {
+        assert!(sse_event_has_output(&serde_json::json!({
+            "choices": [{"delta": {"content": "Hi"}}]
+        })));
+        assert!(!sse_event_has_output(&serde_json::json!({
+            "choices": [{"delta": {"role": "assistant", "content": ""}}]
+        })));
+    }
+
+    #[test]
+    fn test_responses_text_delta_is_output() {
+        assert!(sse_event_has_output(&serde_json::json!({
+            "type": "response.output_text.delta", "delta": "Hi"
+        })));
+    }
+
+    #[test]
+    fn test_gemini_candidate_text_is_output() {
+        assert!(sse_event_has_output(&serde_json::json!({
+            "candidates": [{"content": {"parts": [{"text": "Hi"}]}}]
+        })));
+    }
+
+    #[test]
+    fn test_output_tokens_per_sec_counts_from_first_token() {
+        let timing = ResponseTiming {
+            ttfb_ms: Some(200),
+            ttft_ms: Some(500),
+        };
+        assert_eq!(output_tokens_per_sec(Some(100), 2_500, timing), Some(50.0));
+        assert_eq!(output_tokens_per_sec(None, 2_500, timing), None);
+        assert_eq!(output_tokens_per_sec(Some(100), 500, timing), None);
+    }
+
+    #[test]
+    fn test_usage_scanner_buffers_json_bodies() {
+        let mut scanner = UsageScanner::new(false);
+        scanner.observe(br#"{"id":"msg_1","usage":{"input_"#);
+        scanner.observe(br#"tokens":7,"output_tokens":3}}"#);
+
+        let usage = scanner.finish();
+        assert_eq!(usage.input_tokens, Some(7));
+        assert_eq!(usage.output_tokens, Some(3));
+        assert_eq!(usage.total_tokens, Some(10));
+    }
+
+    #[test]
+    fn test_process_thinking_parameter_uses_catalog_limits() {
+        let catalog = ModelCatalog::default();
+        catalog.replace(vec![serde_json::from_value(serde_json::json!({
+            "id": "claude-opus-4-5-20251101",
+            "max_completion_tokens": 64000,
+            "thinking": { "min": 1024, "max": 60000 }
+        }))
+        .unwrap()]);
+
+        let body = r#"{"model":"claude-opus-4-5-20251101-thinking-50000","max_tokens":1024}"#;
+        let (result, _) =
+            process_thinking_parameter(&Bytes::from_static(body.as_bytes()), &catalog);
+        let json: serde_json::Value = serde_json::from_slice(&result).unwrap();
+        assert_eq!(json["thinking"]["budget_tokens"], 50000);
+        assert_eq!(json["max_tokens"], 55000);
+
+        let body = r#"{"model":"claude-opus-4-5-20251101-thinking-99999","max_tokens":1024}"#;
+        let (result, _) =
+            process_thinking_parameter(&Bytes::from_static(body.as_bytes()), &catalog);
+        let json: serde_json::Value = serde_json::from_slice(&result).unwrap();
+        assert_eq!(json["thinking"]["budget_tokens"], 60000);
+        assert_eq!(json["max_tokens"], 64000);
+
+        let body = r#"{"model":"claude-opus-4-5-20251101-thinking-100","max_tokens":1024}"#;
+        let (result, _) =
+            process_thinking_parameter(&Bytes::from_static(body.as_bytes()), &catalog);
+        let json: serde_json::Value = serde_json::from_slice(&result).unwrap();
+        assert_eq!(json["thinking"]["budget_tokens"], 1024);
+    }
+
+    #[test]
+    fn test_process_thinking_parameter_preserves_binary_body() {
+        let body = Bytes::from_static(&[0xff, 0xfe, 0x00, 0x42, 0x80]);
+        let (result, enabled) = process_thinking_parameter(&body, &ModelCatalog::default());
+        assert!(!enabled);
+        assert_eq!(result, body);
+    }
+
+    #[test]
+    fn test_budget_errors_match_the_request_dialect() {
+        assert_eq!(
+            error_dialect_for_path("/v1/messages"),
+            ErrorDialect::Anthropic
+        );
+        assert_eq!(
+            error_dialect_for_path("/api/provider/anthropic/v1/messages"),
+            ErrorDialect::Anthropic
+        );
+        assert_eq!(
+            error_dialect_for_path("/v1/chat/completions"),
+            ErrorDialect::OpenAi
+        );
+        assert_eq!(
+            error_dialect_for_path(
+                "/api/provider/google/v1beta/models/gemini-2.5-pro:generateContent"
+            ),
+            ErrorDialect::Gemini
+        );
+
+        let anthropic = budget_error_body(ErrorDialect::Anthropic, "over budget");
+        assert_eq!(anthropic["type"], "error");
+        assert_eq!(anthropic["error"]["type"], "rate_limit_error");
+        let openai = budget_error_body(ErrorDialect::OpenAi, "over budget");
+        assert_eq!(openai["error"]["message"], "over budget");
+        assert_eq!(openai["error"]["code"], "budget_exceeded");
+    }
+
+    #[test]
+    fn test_should_inspect_body_only_for_json_posts() {
+        let mut headers = hyper::HeaderMap::new();
+        assert!(should_inspect_body(&hyper::Method::POST, &headers));
+        assert!(!should_inspect_body(&hyper::Method::GET, &headers));
+
+        headers.insert(
+            hyper::header::CONTENT_TYPE,
+            "application/json; charset=utf-8".parse().unwrap(),
+        );
+        assert!(should_inspect_body(&hyper::Method::POST, &headers));
+
+        headers.insert(
+            hyper::header::CONTENT_TYPE,
+            "multipart/form-data; boundary=abc".parse().unwrap(),
+        );
+        assert!(!should_inspect_body(&hyper::Method::POST, &headers));
+    }
+
+    #[tokio::test]
+    async fn test_drain_closes_requests_still_running_at_timeout() {
+        use tokio::io::AsyncWriteExt;
+
+        let graceful = GracefulShutdown::new();
+        let mut connections = JoinSet::new();
+        let (mut client, server) = tokio::io::duplex(1024);
+        let svc = service_fn(|_req: Request<hyper::body::Incoming>| async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok::<_, std::convert::Infallible>(Response::new(Full::new(Bytes::new())))
+        });
+        let conn =
+            graceful.watch(http1::Builder::new().serve_connection(TokioIo::new(server), svc));
+        connections.spawn(async move {
+            let _ = conn.await;
+        });
+        client
+            .write_all(b"GET / HTTP/1.1\r\nhost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let reported = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = reported.clone();
+        let listener: DrainListener = Arc::new(move |event: &ProxyDrainEvent| {
+            sink.lock().unwrap().push(event.in_flight);
+        });
+        let started = Instant::now();
+        drain_connections(
+            graceful,
+            connections,
+            Duration::from_millis(300),
+            Some(listener),
+        )
+        .await;
+
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(*reported.lock().unwrap(), vec![1, 0]);
+    }
+
+    #[tokio::test]
+    async fn test_stop_refuses_new_connections_while_draining() {
+        let received = Arc::new(tokio::sync::Notify::new());
+        let release = Arc::new(tokio::sync::Notify::new());
+        let (upstream_received, upstream_release) = (received.clone(), release.clone());
+        let upstream = spawn_upstream(move |_req| {
+            let (received, release) = (upstream_received.clone(), upstream_release.clone());
+            async move {
+                received.notify_one();
+                release.notified().await;
+                json_response(200, serde_json::json!({"ok": true}))
+            }
+        })
+        .await;
+        let mut proxy = TestProxy::start(upstream, AppSettings::default()).await;
+        let port = proxy.port;
+        let in_flight = tokio::spawn(async move {
+            reqwest::Client::new()
+                .post(format!("http://127.0.0.1:{}/v1/messages", port))
+                .json(&serde_json::json!({"model": "claude-sonnet-4-5"}))
+                .send()
+                .await
+                .map(|response| response.status())
+        });
+        received.notified().await;
+
+        let drain = proxy.proxy.begin_stop();
+        let mut refused = false;
+        for _ in 0..100 {
+            if tokio::net::TcpStream::connect(("127.0.0.1", port))
+                .await
+                .is_err()
+            {
+                refused = true;
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(refused, "new connections should be refused once stopping");
+
+        // The request that was already running still gets its response.
+        release.notify_one();
+        assert_eq!(in_flight.await.unwrap().unwrap(), 200);
+        drain.wait().await;
+        proxy.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_warming_backend_answers_503_until_ready() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let upstream_calls = calls.clone();
+        let upstream = spawn_upstream(move |_req| {
+            upstream_calls.fetch_add(1, Ordering::SeqCst);
+            async { json_response(200, serde_json::json!({"ok": true})) }
+        })
+        .await;
+        let backend_ready = Arc::new(AtomicBool::new(false));
+        let proxy = TestProxy::start_with(
+            upstream,
+            AppSettings::default(),
+            ClientAuthConfig::default(),
+            backend_ready.clone(),
+        )
+        .await;
+        let request = serde_json::json!({"model": "claude-sonnet-4-5"});
+
+        let response = proxy.post("/v1/messages", request.clone()).await;
+        assert_eq!(response.status(), 503);
+        assert_eq!(
+            response.headers()["retry-after"],
+            BACKEND_WARMUP_RETRY_AFTER_SECS.to_string().as_str()
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        backend_ready.store(true, Ordering::SeqCst);
+        let response = proxy.post("/v1/messages", request).await;
+        assert_eq!(response.status(), 200);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        proxy.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_client_key_rejects_unknown_callers_in_their_dialect() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let upstream_calls = calls.clone();
+        let upstream = spawn_upstream(move |_req| {
+            upstream_calls.fetch_add(1, Ordering::SeqCst);
+            async { json_response(200, serde_json::json!({"ok": true})) }
+        })
+        .await;
+        let proxy = TestProxy::start_with(
+            upstream,
+            AppSettings::default(),
+            test_client_auth(),
+            Arc::new(AtomicBool::new(true)),
+        )
+        .await;
+
+        let response = proxy
+            .post(
+                "/v1/messages",
+                serde_json::json!({"model": "claude-sonnet-4-5"}),
+            )
+            .await;
+        assert_eq!(response.status(), 401);
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["error"]["type"], "authentication_error");
+
+        let response = proxy
+            .request("/v1/chat/completions")
+            .header("x-api-key", "cf-wrong")
+            .json(&serde_json::json!({"model": "gpt-5"}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 401);
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["error"]["code"], "invalid_api_key");
+
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+        assert!(proxy.usage_tracker.recorded_events().is_empty());
+
+        proxy.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_client_key_attributes_usage_to_the_client() {
+        let upstream =
+            spawn_upstream(|_req| async { json_response(200, serde_json::json!({"ok": true})) })
+                .await;
+        let proxy = TestProxy::start_with(
+            upstream,
+            AppSettings::default(),
+            test_client_auth(),
+            Arc::new(AtomicBool::new(true)),
+        )
+        .await;
+
+        let response = proxy
+            .request("/v1/messages")
+            .bearer_auth("cf-factory")
+            .json(&serde_json::json!({"model": "claude-sonnet-4-5"}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        response.bytes().await.unwrap();
+
+        let events = proxy.events(1).await;
+        assert_eq!(events[0].client_id, "factory-key");
+        assert_eq!(events[0].client_label, "Factory");
+
+        proxy.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_model_rules_apply_before_thinking_suffix_and_usage() {
+        let received = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let upstream_received = received.clone();
+        let upstream = spawn_upstream(move |req| {
+            let received = upstream_received.clone();
+            async move {
+                let body = request_json(req).await;
+                received.lock().unwrap().push(body);
+                json_response(200, serde_json::json!({"ok": true}))
+            }
+        })
+        .await;
+        let rule = |pattern: &str, target: &str| ModelRewriteRule {
+            id: pattern.to_string(),
+            enabled: true,
+            match_kind: ModelMatchKind::Exact,
+            pattern: pattern.to_string(),
+            target_model: target.to_string(),
+            inject: serde_json::Map::new(),
+        };
+        let mut deep = rule("deep", "claude-opus-4-5-thinking-10000");
+        deep.inject
+            .insert("temperature".to_string(), serde_json::json!(1));
+        let proxy = TestProxy::start(
+            upstream,
+            AppSettings {
+                model_rules: vec![deep, rule("deep", "claude-haiku-4-5")],
+                proxy_retries: no_retries(),
+                ..AppSettings::default()
+            },
+        )
+        .await;
+
+        let response = proxy
+            .post(
+                "/v1/messages",
+                serde_json::json!({"model": "deep", "max_tokens": 100}),
+            )
+            .await;
+        assert_eq!(response.status(), 200);
+
+        // The alias resolves to a `-thinking-N` model, which is then split into the base model
+        // and a thinking budget before the request is sent.
+        let body = received.lock().unwrap()[0].clone();
+        assert_eq!(body["model"], "claude-opus-4-5");
+        assert_eq!(body["thinking"]["budget_tokens"], 10_000);
+        assert!(body["max_tokens"].as_i64().unwrap() > 10_000);
+        assert_eq!(body["temperature"], 1);
+        // Usage is attributed to the model that was actually requested upstream.
+        let events = proxy.events(1).await;
+        assert_eq!(events[0].model, "claude-opus-4-5");
+
+        proxy.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_capture_stores_the_rewritten_body_with_redacted_headers() {
+        let upstream =
+            spawn_upstream(|_req| async { json_response(200, serde_json::json!({"id": "msg_1"})) })
+                .await;
+        let proxy = TestProxy::start(
+            upstream,
+            AppSettings {
+                request_capture: RequestCaptureSettings {
+                    enabled: true,
+                    ..RequestCaptureSettings::default()
+                },
+                ..AppSettings::default()
+            },
+        )
+        .await;
+
+        let response = proxy
+            .request("/v1/messages")
+            .header("x-api-key", "sk-ant-secret")
+            .header("anthropic-beta", "interleaved-thinking")
+            .json(&serde_json::json!({
+                "model": "claude-opus-4-5-thinking-2048",
+                "max_tokens": 100
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        response.bytes().await.unwrap();
+
+        let request_id = proxy.events(1).await[0].request_id.clone();
+        let mut capture = None;
+        for _ in 0..200 {
+            capture = proxy
+                .proxy
+                .capture_store
+                .get(request_id.clone())
+                .await
+                .unwrap();
+            if capture.is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let capture = capture.expect("capture should be stored");
+
+        let body: serde_json::Value =
+            serde_json::from_str(capture.request_body.as_deref().unwrap()).unwrap();
+        assert_eq!(body["model"], "claude-opus-4-5");
+        assert_eq!(body["thinking"]["budget_tokens"], 2048);
+        let header = |name: &str| {
+            capture
+                .request_headers
+                .iter()
+                .find(|h| h.name == name)
+                .map(|h| h.value.as_str())
+        };
+        assert_eq!(header("x-api-key"), Some("[redacted]"));
+        assert_eq!(header("anthropic-beta"), Some("interleaved-thinking"));
+        assert_eq!(capture.summary.status_code, 200);
+        assert_eq!(capture.response_body.as_deref(), Some(r#"{"id":"msg_1"}"#));
+
+        proxy.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_reasoning_suffix_on_chat_completions_sets_reasoning_effort() {
+        let received = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let upstream_received = received.clone();
+        let upstream = spawn_upstream(move |req| {
+            let received = upstream_received.clone();
+            async move {
+                let body = request_json(req).await;
+                received.lock().unwrap().push(body);
+                json_response(200, serde_json::json!({"ok": true}))
+            }
+        })
+        .await;
+        let proxy = TestProxy::start(upstream, AppSettings::default()).await;
+
+        let response = proxy
+            .post(
+                "/v1/chat/completions",
+                serde_json::json!({"model": "gpt-5-reasoning-high", "messages": []}),
+            )
+            .await;
+        assert_eq!(response.status(), 200);
+
+        let body = received.lock().unwrap()[0].clone();
+        assert_eq!(body["model"], "gpt-5");
+        assert_eq!(body["reasoning_effort"], "high");
+
+        proxy.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_reasoning_suffix_on_responses_sets_clamped_reasoning_effort() {
+        let received = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let upstream_received = received.clone();
+        let upstream = spawn_upstream(move |req| {
+            let received = upstream_received.clone();
+            async move {
+                let body = request_json(req).await;
+                received.lock().unwrap().push(body);
+                json_response(200, serde_json::json!({"ok": true}))
+            }
+        })
+        .await;
+        let proxy = TestProxy::start(upstream, AppSettings::default()).await;
+        proxy
+            .proxy
+            .model_catalog
+            .replace(vec![serde_json::from_value(serde_json::json!({
+                "id": "gpt-5-codex",
+                "thinking": { "levels": ["low", "medium", "high"] }
+            }))
+            .unwrap()]);
+
+        let response = proxy
+            .post(
+                "/v1/responses",
+                serde_json::json!({
+                    "model": "gpt-5-codex-reasoning-xhigh",
+                    "input": "hi",
+                    "reasoning": {"summary": "auto"}
+                }),
+            )
+            .await;
+        assert_eq!(response.status(), 200);
+
+        // The catalog tops out at `high`, and existing reasoning settings are kept.
+        let body = received.lock().unwrap()[0].clone();
+        assert_eq!(body["model"], "gpt-5-codex");
+        assert_eq!(
+            body["reasoning"],
+            serde_json::json!({"summary": "auto", "effort": "high"})
+        );
+
+        proxy.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_timeout_override_answers_504_and_records_timeout() {
+        let upstream = spawn_upstream(|req| async move {
+            if request_model(req).await == "slow-headers" {
+                tokio::time::sleep(Duration::from_secs(10)).await;
+                return json_response(200, serde_json::json!({"ok": true}));
+            }
+            // Headers and a first event arrive right away, then the stream stalls.
+            let chunks = futures_util::stream::once(async {
+                Ok::<_, Box<dyn std::error::Error + Send + Sync>>(Frame::data(Bytes::from_static(
+                    MESSAGE_START,
+                )))
+            })
+            .chain(futures_util::stream::pending());
+            Response::builder()
+                .header("Content-Type", "text/event-stream")
+                .body(BodyExt::boxed_unsync(StreamBody::new(chunks)))
+                .unwrap()
+        })
+        .await;
+        let proxy = TestProxy::start(
+            upstream,
+            AppSettings {
+                proxy_timeouts: ProxyTimeouts {
+                    read_secs: 300,
+                    overrides: vec![TimeoutOverride {
+                        id: "slow".to_string(),
+                        scope: TimeoutScope::Model,
+                        pattern: "slow-*".to_string(),
+                        read_secs: Some(1),
+                        total_secs: None,
+                    }],
+                    ..ProxyTimeouts::default()
+                },
+                proxy_retries: no_retries(),
+                ..AppSettings::default()
+            },
+        )
+        .await;
+
+        let response = proxy
+            .post("/v1/messages", serde_json::json!({"model": "slow-headers"}))
+            .await;
+        assert_eq!(response.status(), 504);
+        let events = proxy.events(1).await;
+        assert_eq!(events[0].status_code, 504);
+        assert_eq!(events[0].error_category.as_deref(), Some("timeout"));
+
+        // Once streaming, the client already has a 200; the stall ends the body and the
+        // event records the timeout with the usage seen so far.
+        let response = proxy
+            .post(
+                "/v1/messages",
+                serde_json::json!({"model": "slow-stream", "stream": true}),
+            )
+            .await;
+        assert_eq!(response.status(), 200);
+        let _ = tokio::time::timeout(Duration::from_secs(5), response.bytes())
+            .await
+            .expect("stalled stream should be closed by the read timeout");
+        let events = proxy.events(2).await;
+        let stalled = events.iter().find(|e| e.model == "slow-stream").unwrap();
+        assert_eq!(stalled.status_code, 504);
+        assert_eq!(stalled.error_category.as_deref(), Some("timeout"));
+        assert_eq!(stalled.input_tokens, Some(42));
+
+        proxy.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_retries_overloaded_but_not_after_streaming_starts() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let upstream_calls = calls.clone();
+        let upstream = spawn_upstream(move |_req| {
+            let attempt = upstream_calls.fetch_add(1, Ordering::SeqCst) + 1;
+            async move {
+                if attempt == 1 {
+                    return json_response(529, serde_json::json!({"error": "overloaded"}));
+                }
+                // The stream breaks after its first event; the client already has the headers.
+                let chunks = futures_util::stream::once(async {
+                    Ok(Frame::data(Bytes::from_static(MESSAGE_START)))
+                })
+                .chain(futures_util::stream::once(async {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    Err("connection reset".into())
+                }));
+                Response::builder()
+                    .header("Content-Type", "text/event-stream")
+                    .body(BodyExt::boxed_unsync(StreamBody::new(chunks)))
+                    .unwrap()
+            }
+        })
+        .await;
+        let proxy = TestProxy::start(
+            upstream,
+            AppSettings {
+                proxy_retries: ProxyRetries {
+                    max_attempts: 3,
+                    base_delay_ms: 10,
+                    max_delay_ms: 100,
+                },
+                ..AppSettings::default()
+            },
+        )
+        .await;
+
+        let response = proxy
+            .post(
+                "/v1/messages",
+                serde_json::json!({"model": "claude-sonnet-4-5", "stream": true}),
+            )
+            .await;
+        assert_eq!(response.status(), 200);
+        assert!(response.bytes().await.is_err());
+
+        // Only the 529 was retried; the broken stream is relayed as is.
+        let events = proxy.events(1).await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].status_code, 200);
+        assert_eq!(events[0].input_tokens, Some(42));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        proxy.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_failover_records_each_attempt_under_one_parent() {
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let upstream_seen = seen.clone();
+        let upstream = spawn_upstream(move |req| {
+            let seen = upstream_seen.clone();
+            async move {
+                let model = request_model(req).await;
+                seen.lock().unwrap().push(model.clone());
+                if model == "primary" {
+                    let mut response =
+                        json_response(529, serde_json::json!({"error": "overloaded"}));
+                    response
+                        .headers_mut()
+                        .insert("retry-after", hyper::header::HeaderValue::from_static("30"));
+                    response
+                } else {
+                    json_response(200, serde_json::json!({"model": model}))
+                }
+            }
+        })
+        .await;
+        let proxy = TestProxy::start(
+            upstream,
+            AppSettings {
+                proxy_retries: no_retries(),
+                failover_chains: vec![FailoverChain {
+                    id: "primary".to_string(),
+                    enabled: true,
+                    pattern: "primary".to_string(),
+                    steps: vec![FailoverStep {
+                        target: FailoverTarget::Backend,
+                        model: "fallback".to_string(),
+                    }],
+                }],
+                ..AppSettings::default()
+            },
+        )
+        .await;
+
+        let response = proxy
+            .post("/v1/messages", serde_json::json!({"model": "primary"}))
+            .await;
+        assert_eq!(response.status(), 200);
+        response.bytes().await.unwrap();
+        assert_eq!(*seen.lock().unwrap(), ["primary", "fallback"]);
+
+        let events = proxy.events(2).await;
+        let primary = events.iter().find(|e| e.model == "primary").unwrap();
+        let fallback = events.iter().find(|e| e.model == "fallback").unwrap();
+        assert_eq!(primary.status_code, 529);
+        assert_eq!(fallback.status_code, 200);
+        assert_ne!(primary.request_id, fallback.request_id);
+        assert_eq!(
+            primary.parent_request_id.as_deref(),
+            Some(primary.request_id.as_str())
+        );
+        assert_eq!(fallback.parent_request_id, primary.parent_request_id);
+
+        // Retry-After cooled the primary route down, so the next request skips it.
+        let response = proxy
+            .post("/v1/messages", serde_json::json!({"model": "primary"}))
+            .await;
+        assert_eq!(response.status(), 200);
+        assert_eq!(*seen.lock().unwrap(), ["primary", "fallback", "fallback"]);
+
+        proxy.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_failover_skips_fallbacks_over_hard_budget() {
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let upstream_seen = seen.clone();
+        let upstream = spawn_upstream(move |req| {
+            let seen = upstream_seen.clone();
+            async move {
+                let model = request_model(req).await;
+                seen.lock().unwrap().push(model.clone());
+                if model.starts_with("primary") {
+                    json_response(529, serde_json::json!({"error": "overloaded"}))
+                } else {
+                    json_response(200, serde_json::json!({"model": model}))
+                }
+            }
+        })
+        .await;
+        let chain = |pattern: &str, models: &[&str]| FailoverChain {
+            id: pattern.to_string(),
+            enabled: true,
+            pattern: pattern.to_string(),
+            steps: models
+                .iter()
+                .map(|model| FailoverStep {
+                    target: FailoverTarget::Backend,
+                    model: model.to_string(),
+                })
+                .collect(),
+        };
+        let proxy = TestProxy::start(
+            upstream,
+            AppSettings {
+                usage_budgets: vec![UsageBudget {
+                    id: "capped".to_string(),
+                    scope: BudgetScope::Model,
+                    target: "capped-fallback".to_string(),
+                    period: BudgetPeriod::Daily,
+                    metric: BudgetMetric::Requests,
+                    soft_limit: None,
+                    hard_limit: Some(1),
+                }],
+                proxy_retries: no_retries(),
+                failover_chains: vec![
+                    chain("primary-a", &["capped-fallback", "open-fallback"]),
+                    chain("primary-b", &["capped-fallback"]),
+                ],
+                ..AppSettings::default()
+            },
+        )
+        .await;
+
+        // Use up the fallback's budget.
+        let response = proxy
+            .post(
+                "/v1/messages",
+                serde_json::json!({"model": "capped-fallback"}),
+            )
+            .await;
+        assert_eq!(response.status(), 200);
+        proxy.events(1).await;
+
+        let response = proxy
+            .post("/v1/messages", serde_json::json!({"model": "primary-a"}))
+            .await;
+        assert_eq!(response.status(), 200);
+        assert_eq!(
+            *seen.lock().unwrap(),
+            ["capped-fallback", "primary-a", "open-fallback"]
+        );
+
+        // When the capped fallback is the last route, the proxy answers it with 429.
+        let response = proxy
+            .post("/v1/messages", serde_json::json!({"model": "primary-b"}))
+            .await;
+        assert_eq!(response.status(), 429);
+        assert!(response.headers().contains_key("retry-after"));
+        assert_eq!(seen.lock().unwrap().len(), 4);
+        // Usage writes run concurrently, so look the rejection up rather than relying on order.
+        let events = proxy.events(5).await;
+        let rejected = events.iter().find(|e| e.status_code == 429).unwrap();
+        assert_eq!(rejected.model, "capped-fallback");
+        assert_eq!(rejected.error_category.as_deref(), Some("quota_exceeded"));
+
+        proxy.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_usage_recorder_dropped_mid_stream_records_cancelled() {
+        let (usage_tracker, dir) = temp_usage_tracker();
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(
+            reqwest::header::CONTENT_TYPE,
+            reqwest::header::HeaderValue::from_static("text/event-stream"),
+        );
+
+        let mut recorder = UsageRecorder::new(usage_tracker.clone(), test_seed(), 200, &headers);
+        recorder.observe(MESSAGE_START);
+        drop(recorder);
+        let events = wait_for_events(&usage_tracker, 1).await;
+        assert_eq!(events[0].status_code, 499);
+        assert_eq!(events[0].error_category.as_deref(), Some("cancelled"));
+        assert_eq!(events[0].input_tokens, Some(42));
+
+        // A body that ran to the end keeps the upstream status.
+        let mut recorder = UsageRecorder::new(usage_tracker.clone(), test_seed(), 200, &headers);
+        recorder.observe(MESSAGE_START);
+        recorder.complete = true;
+        drop(recorder);
+        let events = wait_for_events(&usage_tracker, 2).await;
+        assert_eq!(events[1].status_code, 200);
+        assert_eq!(events[1].error_category, None);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_in_flight_seed_records_cancelled_unless_taken() {
+        let (usage_tracker, dir) = temp_usage_tracker();
+
+        let mut answered = InFlightSeed::new(&usage_tracker, test_seed());
+        assert!(answered.take().is_some());
+        assert!(answered.take().is_none());
+        drop(answered);
+
+        let abandoned = test_seed();
+        let abandoned_id = abandoned.request_id.clone();
+        drop(InFlightSeed::new(&usage_tracker, abandoned));
+
+        wait_for_events(&usage_tracker, 1).await;
+        // Leave time for a second, unwanted write to land.
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let events = usage_tracker.recorded_events();
+        assert_eq!(events.len(), 1, "only the abandoned request is recorded");
+        assert_eq!(events[0].request_id, abandoned_id);
+        assert_eq!(events[0].status_code, 499);
+        assert_eq!(events[0].error_category.as_deref(), Some("cancelled"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_streamed_response_records_latency() {
+        let upstream = spawn_upstream(|_req| async {
+            let delayed = |delay_ms, chunk: &'static [u8]| async move {
+                tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+                Ok::<_, Box<dyn std::error::Error + Send + Sync>>(Frame::data(Bytes::from_static(
+                    chunk,
+                )))
+            };
+            let chunks = futures_util::stream::once(delayed(0, MESSAGE_START))
+                .chain(futures_util::stream::once(delayed(
+                    100,
+                    b"data: {\"type\":\"content_block_delta\",\"delta\":{\"text\":\"Hi\"}}\n\n",
+                )))
+                .chain(futures_util::stream::once(delayed(
+                    100,
+                    b"data: {\"type\":\"message_delta\",\"usage\":{\"output_tokens\":20}}\n\n",
+                )));
+            Response::builder()
+                .header("Content-Type", "text/event-stream")
+                .body(BodyExt::boxed_unsync(StreamBody::new(chunks)))
+                .unwrap()
+        })
+        .await;
+        let proxy = TestProxy::start(upstream, AppSettings::default()).await;
+
+        let response = proxy
+            .post(
+                "/v1/messages",
+                serde_json::json!({"model": "claude-sonnet-4-5", "stream": true}),
+            )
+            .await;
+        assert_eq!(response.status(), 200);
+        response.bytes().await.unwrap();
+
+        let events = proxy.events(1).await;
+        let event = &events[0];
+        assert_eq!(event.status_code, 200);
+        let ttfb_ms = event.ttfb_ms.unwrap();
+        let ttft_ms = event.ttft_ms.unwrap();
+        // Headers arrive with the first event; text only follows 100ms later.
+        assert!(ttft_ms >= ttfb_ms + 90, "ttfb {} ttft {}", ttfb_ms, ttft_ms);
+        let tokens_per_sec = event.output_tokens_per_sec.unwrap();
+        assert!(tokens_per_sec > 0.0 && tokens_per_sec <= 20.0 * 1000.0 / 90.0);
+
+        proxy.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_client_disconnect_mid_stream_aborts_upstream() {
+        struct DropSignal(Option<tokio::sync::oneshot::Sender<()>>);
+        impl Drop for DropSignal {
+            fn drop(&mut self) {
+                if let Some(tx) = self.0.take() {
+                    let _ = tx.send(());
+                }
+            }
+        }
+
+        let (closed_tx, closed_rx) = tokio::sync::oneshot::channel();
+        let closed_tx = Arc::new(std::sync::Mutex::new(Some(closed_tx)));
+        let upstream = spawn_upstream(move |_req| {
+            let signal = DropSignal(closed_tx.lock().unwrap().take());
+            async move {
+                let chunks = futures_util::stream::once(async {
+                    Ok::<_, Box<dyn std::error::Error + Send + Sync>>(Frame::data(
+                        Bytes::from_static(MESSAGE_START),
+                    ))
+                })
+                .chain(futures_util::stream::pending())
+                .map(move |frame| {
+                    let _ = &signal;
+                    frame
+                });
+                Response::builder()
+                    .header("Content-Type", "text/event-stream")
+                    .body(BodyExt::boxed_unsync(StreamBody::new(chunks)))
+                    .unwrap()
+            }
+        })
+        .await;
+        let proxy = TestProxy::start(upstream, AppSettings::default()).await;
+
+        let mut response = proxy
+            .post(
+                "/v1/messages",
+                serde_json::json!({"model": "claude-sonnet-4-5", "stream": true}),
+            )
+            .await;
+        assert_eq!(response.status(), 200);
+        assert!(response.chunk().await.unwrap().is_some());
+        drop(response);
+
+        tokio::time::timeout(Duration::from_secs(5), closed_rx)
+            .await
+            .expect("upstream stream should be dropped")
+            .unwrap();
+        let events = proxy.events(1).await;
+        assert_eq!(events[0].status_code, 499);
+        assert_eq!(events[0].error_category.as_deref(), Some("cancelled"));
+        assert_eq!(events[0].input_tokens, Some(42));
+
+        proxy.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_sized_response_read_in_full_is_not_cancelled() {
+        let upstream =
+            spawn_upstream(|_req| async { json_response(200, serde_json::json!({"ok": true})) })
+                .await;
+        let proxy = TestProxy::start(upstream, AppSettings::default()).await;
+
+        let response = proxy
+            .post(
+                "/v1/messages",
+                serde_json::json!({"model": "claude-sonnet-4-5"}),
+            )
+            .await;
+        assert_eq!(response.status(), 200);
+        response.bytes().await.unwrap();
+
+        let events = proxy.events(1).await;
+        assert_eq!(events[0].status_code, 200);
+        assert_eq!(events[0].error_category, None);
+
+        proxy.stop().await;
+    }
 }
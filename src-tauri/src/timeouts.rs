@@ -0,0 +1,302 @@
+use crate::model_rules::glob_matcher;
+use crate::types::{ProxyTimeouts, TimeoutOverride, TimeoutScope};
+use regex::Regex;
+use std::sync::RwLock;
+use std::time::Duration;
+
+/// Upper bound for any configured timeout, one day.
+const MAX_TIMEOUT_SECS: u32 = 24 * 60 * 60;
+
+/// Returned when the proxy gives up waiting on upstream. The message contains "timed out" so
+/// `classify_transport_error` files it as a timeout.
+#[derive(Debug)]
+pub struct UpstreamTimeout {
+    pub waited: Duration,
+    pub stage: &'static str,
+}
+
+impl std::fmt::Display for UpstreamTimeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Upstream timed out after {}s {}",
+            self.waited.as_secs(),
+            self.stage
+        )
+    }
+}
+
+impl std::error::Error for UpstreamTimeout {}
+
+fn limit(secs: u32) -> Option<Duration> {
+    (secs > 0).then(|| Duration::from_secs(secs as u64))
+}
+
+/// Read and total timeouts resolved for one request, plus the client to send it with.
+#[derive(Clone)]
+pub struct RequestTimeouts {
+    pub client: reqwest::Client,
+    pub read: Option<Duration>,
+    pub total: Option<Duration>,
+}
+
+impl RequestTimeouts {
+    /// Sends the request, failing with `UpstreamTimeout` when headers take longer than the
+    /// read timeout. The total timeout keeps running while the body streams.
+    pub async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, Box<dyn std::error::Error + Send + Sync>> {
+        let request = match self.total {
+            Some(total) => request.timeout(total),
+            None => request,
+        };
+        let Some(read) = self.read else {
+            return Ok(request.send().await?);
+        };
+        match tokio::time::timeout(read, request.send()).await {
+            Ok(result) => Ok(result?),
+            Err(_) => Err(Box::new(UpstreamTimeout {
+                waited: read,
+                stage: "waiting for response headers",
+            })),
+        }
+    }
+}
+
+pub fn validate(timeouts: &ProxyTimeouts) -> Result<(), String> {
+    let secs = [
+        timeouts.connect_secs,
+        timeouts.read_secs,
+        timeouts.total_secs,
+    ]
+    .into_iter()
+    .chain(
+        timeouts
+            .overrides
+            .iter()
+            .flat_map(|o| [o.read_secs, o.total_secs])
+            .flatten(),
+    );
+    for value in secs {
+        if value > MAX_TIMEOUT_SECS {
+            return Err(format!(
+                "Timeouts must be at most {} seconds",
+                MAX_TIMEOUT_SECS
+            ));
+        }
+    }
+    if timeouts.connect_secs == 0 {
+        return Err("Connect timeout must be greater than zero".to_string());
+    }
+    for rule in &timeouts.overrides {
+        if rule.pattern.trim().is_empty() {
+            return Err("Timeout override pattern cannot be empty".to_string());
+        }
+        if rule.read_secs.is_none() && rule.total_secs.is_none() {
+            return Err(format!(
+                "Timeout override '{}' must set a read or total timeout",
+                rule.pattern.trim()
+            ));
+        }
+        glob_matcher(&rule.pattern)?;
+    }
+    Ok(())
+}
+
+fn build_client(connect_secs: u32) -> reqwest::Client {
+    reqwest::Client::builder()
+        .connect_timeout(Duration::from_secs(connect_secs.max(1) as u64))
+        .pool_idle_timeout(Duration::from_secs(60))
+        .pool_max_idle_per_host(16)
+        .tcp_nodelay(true)
+        .build()
+        .expect("Failed to build proxy HTTP client")
+}
+
+struct PolicyState {
+    settings: ProxyTimeouts,
+    overrides: Vec<(Regex, TimeoutOverride)>,
+    client: reqwest::Client,
+}
+
+/// Live timeout settings used by ThinkingProxy for inference requests.
+pub struct TimeoutPolicy {
+    state: RwLock<PolicyState>,
+}
+
+impl Default for TimeoutPolicy {
+    fn default() -> Self {
+        Self::new(&ProxyTimeouts::default())
+    }
+}
+
+impl TimeoutPolicy {
+    pub fn new(settings: &ProxyTimeouts) -> Self {
+        Self {
+            state: RwLock::new(PolicyState {
+                settings: settings.clone(),
+                overrides: compile_overrides(&settings.overrides),
+                client: build_client(settings.connect_secs),
+            }),
+        }
+    }
+
+    /// Replaces the settings. The client is rebuilt only when the connect timeout changes,
+    /// so pooled connections survive other edits.
+    pub fn set_settings(&self, settings: &ProxyTimeouts) {
+        let Ok(mut state) = self.state.write() else {
+            return;
+        };
+        if state.settings.connect_secs != settings.connect_secs {
+            state.client = build_client(settings.connect_secs);
+        }
+        state.overrides = compile_overrides(&settings.overrides);
+        state.settings = settings.clone();
+    }
+
+    /// Timeouts for a request to `path` for `model`, after applying the first matching override.
+    pub fn resolve(&self, path: &str, model: &str) -> RequestTimeouts {
+        let state = match self.state.read() {
+            Ok(state) => state,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let mut read_secs = state.settings.read_secs;
+        let mut total_secs = state.settings.total_secs;
+        let matched = state
+            .overrides
+            .iter()
+            .find(|(matcher, rule)| match rule.scope {
+                TimeoutScope::Route => matcher.is_match(path),
+                TimeoutScope::Model => !model.is_empty() && matcher.is_match(model),
+            });
+        if let Some((_, rule)) = matched {
+            read_secs = rule.read_secs.unwrap_or(read_secs);
+            total_secs = rule.total_secs.unwrap_or(total_secs);
+        }
+        RequestTimeouts {
+            client: state.client.clone(),
+            read: limit(read_secs),
+            total: limit(total_secs),
+        }
+    }
+}
+
+fn compile_overrides(overrides: &[TimeoutOverride]) -> Vec<(Regex, TimeoutOverride)> {
+    overrides
+        .iter()
+        .filter_map(|rule| match glob_matcher(&rule.pattern) {
+            Ok(matcher) => Some((matcher, rule.clone())),
+            Err(e) => {
+                log::warn!(
+                    "[ThinkingProxy] Skipping timeout override {}: {}",
+                    rule.id,
+                    e
+                );
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn override_for(scope: TimeoutScope, pattern: &str, read: Option<u32>) -> TimeoutOverride {
+        TimeoutOverride {
+            id: pattern.to_string(),
+            scope,
+            pattern: pattern.to_string(),
+            read_secs: read,
+            total_secs: None,
+        }
+    }
+
+    #[test]
+    fn first_matching_override_replaces_defaults() {
+        let policy = TimeoutPolicy::new(&ProxyTimeouts {
+            connect_secs: 5,
+            read_secs: 90,
+            total_secs: 0,
+            overrides: vec![
+                override_for(TimeoutScope::Model, "claude-opus-*", Some(1800)),
+                override_for(TimeoutScope::Route, "/v1/models", Some(10)),
+                override_for(TimeoutScope::Model, "*", Some(0)),
+            ],
+        });
+
+        let opus = policy.resolve("/v1/messages", "claude-opus-4-5-20251101");
+        assert_eq!(opus.read, Some(Duration::from_secs(1800)));
+        assert_eq!(opus.total, None);
+        let models = policy.resolve("/v1/models", "");
+        assert_eq!(models.read, Some(Duration::from_secs(10)));
+        let other = policy.resolve("/v1/chat/completions", "gpt-5");
+        assert_eq!(other.read, None);
+    }
+
+    #[test]
+    fn validate_requires_a_connect_timeout() {
+        assert!(validate(&ProxyTimeouts::default()).is_ok());
+        let timeouts = ProxyTimeouts {
+            connect_secs: 0,
+            ..ProxyTimeouts::default()
+        };
+        assert_eq!(
+            validate(&timeouts).unwrap_err(),
+            "Connect timeout must be greater than zero"
+        );
+    }
+
+    #[test]
+    fn validate_caps_defaults_and_overrides() {
+        let too_long = format!("Timeouts must be at most {} seconds", MAX_TIMEOUT_SECS);
+        let timeouts = ProxyTimeouts {
+            total_secs: MAX_TIMEOUT_SECS + 1,
+            ..ProxyTimeouts::default()
+        };
+        assert_eq!(validate(&timeouts).unwrap_err(), too_long);
+
+        let timeouts = ProxyTimeouts {
+            overrides: vec![override_for(
+                TimeoutScope::Model,
+                "claude-*",
+                Some(MAX_TIMEOUT_SECS + 1),
+            )],
+            ..ProxyTimeouts::default()
+        };
+        assert_eq!(validate(&timeouts).unwrap_err(), too_long);
+    }
+
+    #[test]
+    fn validate_requires_override_pattern_and_limit() {
+        let with_override = |rule: TimeoutOverride| ProxyTimeouts {
+            overrides: vec![rule],
+            ..ProxyTimeouts::default()
+        };
+        assert_eq!(
+            validate(&with_override(override_for(
+                TimeoutScope::Route,
+                " ",
+                Some(10)
+            )))
+            .unwrap_err(),
+            "Timeout override pattern cannot be empty"
+        );
+        assert_eq!(
+            validate(&with_override(override_for(
+                TimeoutScope::Route,
+                "/v1/*",
+                None
+            )))
+            .unwrap_err(),
+            "Timeout override '/v1/*' must set a read or total timeout"
+        );
+        // A total timeout on its own is enough.
+        let total_only = TimeoutOverride {
+            total_secs: Some(600),
+            ..override_for(TimeoutScope::Route, "/v1/*", None)
+        };
+        assert!(validate(&with_override(total_only)).is_ok());
+    }
+}
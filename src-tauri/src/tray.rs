@@ -272,7 +272,10 @@ pub fn update_main_window_icon(app: &AppHandle) {
     }
 }
 
-pub fn update_tray_state(app: &AppHandle, is_running: bool) {
+/// `running_port` is the port the proxy is listening on, or `None` when it is stopped.
+pub fn update_tray_state(app: &AppHandle, running_port: Option<u16>) {
+    let is_running = running_port.is_some();
+    let proxy_port = running_port.unwrap_or_default();
     if let Some(tray) = app.tray_by_id("main-tray") {
         // Update icon
         let icon = load_tray_icon(app, is_running);
@@ -285,21 +288,21 @@ pub fn update_tray_state(app: &AppHandle, is_running: bool) {
 
         // Update tooltip
         let tooltip = if is_running {
-            "CodeForwarder - Running (port 8317)"
+            format!("CodeForwarder - Running (port {})", proxy_port)
         } else {
-            "CodeForwarder - Stopped"
+            "CodeForwarder - Stopped".to_string()
         };
-        tray.set_tooltip(Some(tooltip)).ok();
+        tray.set_tooltip(Some(&tooltip)).ok();
     }
 
     // Update menu items via stored references
     if let Ok(items) = app.state::<Mutex<TrayMenuItems>>().lock() {
         let status_text = if is_running {
-            "Server: Running (port 8317)"
+            format!("Server: Running (port {})", proxy_port)
         } else {
-            "Server: Stopped"
+            "Server: Stopped".to_string()
         };
-        items.status.set_text(status_text).ok();
+        items.status.set_text(&status_text).ok();
 
         let action_text = if is_running {
             "Stop Server"
@@ -73,6 +73,10 @@ pub struct ServiceAccounts {
     pub expired_count: usize,
 }
 
+pub const DEFAULT_BIND_ADDRESS: &str = "127.0.0.1";
+pub const DEFAULT_PROXY_PORT: u16 = 8317;
+pub const DEFAULT_BACKEND_PORT: u16 = 8318;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerState {
     pub is_running: bool,
@@ -86,8 +90,8 @@ impl Default for ServerState {
     fn default() -> Self {
         Self {
             is_running: false,
-            proxy_port: 8317,
-            backend_port: 8318,
+            proxy_port: DEFAULT_PROXY_PORT,
+            backend_port: DEFAULT_BACKEND_PORT,
             binary_available: false,
             binary_downloading: false,
         }
@@ -95,11 +99,66 @@ impl Default for ServerState {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerLogLine {
+    /// RFC 3339, UTC.
+    pub timestamp: String,
+    pub level: String,
+    /// `stdout`, `stderr` or `manager` (lines emitted by CodeForwarder itself).
+    pub source: String,
+    pub message: String,
+}
+
+/// Payload of the `server_crashed` event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerCrashEvent {
+    /// Crashes within the rapid-crash window, including this one.
+    pub crash_count: u32,
+    /// `false` once the supervisor has given up.
+    pub restarting: bool,
+    pub retry_in_ms: Option<u64>,
+    pub last_logs: Vec<ServerLogLine>,
+}
+
+/// Payload of the `proxy_draining` event, sent while the proxy stops.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProxyDrainEvent {
+    /// Requests still running; `0` once draining is over.
+    pub in_flight: usize,
+    /// Seconds until the remaining requests are closed.
+    pub remaining_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AppSettings {
     pub enabled_providers: HashMap<String, bool>,
     pub vercel_gateway_enabled: bool,
     pub vercel_api_key: String,
     pub launch_at_login: bool,
+    /// Address the thinking proxy listens on. The backend always stays on loopback.
+    pub bind_address: String,
+    pub proxy_port: u16,
+    pub backend_port: u16,
+    /// Mirror backend output to rotating files under `<auth dir>/logs`.
+    pub persist_server_logs: bool,
+    /// Reject proxy requests that don't present an issued client key.
+    pub require_client_api_key: bool,
+    /// Per-model price overrides for cost estimates, keyed by model-name prefix.
+    pub pricing_overrides: HashMap<String, ModelPrice>,
+    /// Token/request budgets enforced by ThinkingProxy before forwarding.
+    pub usage_budgets: Vec<UsageBudget>,
+    pub usage_retention: UsageRetention,
+    pub request_capture: RequestCaptureSettings,
+    /// User-defined model aliases, applied in order before the thinking rewrite.
+    pub model_rules: Vec<ModelRewriteRule>,
+    /// Upstream timeouts for proxied requests.
+    pub proxy_timeouts: ProxyTimeouts,
+    /// Retries for rate-limited or overloaded POST requests.
+    pub proxy_retries: ProxyRetries,
+    /// Fallback routes tried when a request fails upstream, first matching chain wins.
+    pub failover_chains: Vec<FailoverChain>,
+    /// How long stopping the proxy waits for in-flight requests before closing them.
+    pub shutdown_drain_secs: u32,
 }
 
 impl Default for AppSettings {
@@ -109,10 +168,59 @@ impl Default for AppSettings {
             vercel_gateway_enabled: false,
             vercel_api_key: String::new(),
             launch_at_login: false,
+            bind_address: DEFAULT_BIND_ADDRESS.to_string(),
+            proxy_port: DEFAULT_PROXY_PORT,
+            backend_port: DEFAULT_BACKEND_PORT,
+            persist_server_logs: false,
+            require_client_api_key: false,
+            pricing_overrides: HashMap::new(),
+            usage_budgets: Vec::new(),
+            usage_retention: UsageRetention::default(),
+            request_capture: RequestCaptureSettings::default(),
+            model_rules: Vec::new(),
+            proxy_timeouts: ProxyTimeouts::default(),
+            proxy_retries: ProxyRetries::default(),
+            failover_chains: Vec::new(),
+            shutdown_drain_secs: 30,
         }
     }
 }
 
+impl AppSettings {
+    pub fn validate_network(&self) -> Result<(), String> {
+        let bind_ip = self
+            .bind_address
+            .trim()
+            .parse::<std::net::IpAddr>()
+            .map_err(|_| format!("Invalid bind address: {}", self.bind_address))?;
+        // Anything beyond loopback serves the OAuth-backed accounts to the network.
+        if !bind_ip.is_loopback() && !self.require_client_api_key {
+            return Err(format!(
+                "Client API keys must be required when binding to {}",
+                bind_ip
+            ));
+        }
+        if self.proxy_port == 0 || self.backend_port == 0 {
+            return Err("Ports must be between 1 and 65535".to_string());
+        }
+        if self.proxy_port == self.backend_port {
+            return Err("Proxy and backend ports must differ".to_string());
+        }
+        Ok(())
+    }
+
+    /// URL clients on this machine should use to reach the proxy.
+    pub fn proxy_url(&self) -> String {
+        let host = match self.bind_address.trim().parse::<std::net::IpAddr>() {
+            Ok(ip) if ip.is_unspecified() || ip.is_loopback() => "localhost".to_string(),
+            Ok(std::net::IpAddr::V6(ip)) => format!("[{}]", ip),
+            Ok(ip) => ip.to_string(),
+            Err(_) => "localhost".to_string(),
+        };
+        format!("http://{}:{}", host, self.proxy_port)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum AuthCommand {
@@ -130,6 +238,391 @@ pub enum AuthCommand {
     AntigravityLogin,
 }
 
+/// USD per million tokens. Cache rates fall back to `input` when unset.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    pub input: f64,
+    pub output: f64,
+    #[serde(default)]
+    pub cache_read: Option<f64>,
+    #[serde(default)]
+    pub cache_write: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelPriceEntry {
+    /// Model-name prefix the price applies to.
+    pub model: String,
+    pub price: ModelPrice,
+    pub is_override: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetScope {
+    Provider,
+    Model,
+    Account,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetPeriod {
+    Daily,
+    Monthly,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetMetric {
+    Tokens,
+    Requests,
+}
+
+/// Usage budget for one provider, model or account key. Periods follow UTC days,
+/// like `usage_rollups_daily`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageBudget {
+    #[serde(default)]
+    pub id: String,
+    pub scope: BudgetScope,
+    /// Provider key, model name or account key, matched case-insensitively.
+    pub target: String,
+    pub period: BudgetPeriod,
+    pub metric: BudgetMetric,
+    /// Crossing this shows a notification once per period.
+    #[serde(default)]
+    pub soft_limit: Option<i64>,
+    /// Requests are rejected with 429 once usage reaches this.
+    #[serde(default)]
+    pub hard_limit: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModelMatchKind {
+    Exact,
+    /// `*` matches any run of characters, `?` a single one.
+    Glob,
+    Regex,
+}
+
+/// Maps an incoming model name to another model and/or injects request body fields.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelRewriteRule {
+    #[serde(default)]
+    pub id: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    pub match_kind: ModelMatchKind,
+    /// Matched case-insensitively against the whole model name.
+    pub pattern: String,
+    /// Replacement model; empty keeps the incoming name. Regex rules may use `$1` captures.
+    #[serde(default)]
+    pub target_model: String,
+    /// Top-level JSON fields set on the request body, e.g. `thinking` or `temperature`.
+    #[serde(default)]
+    pub inject: serde_json::Map<String, serde_json::Value>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Timeouts for upstream calls made by ThinkingProxy, in seconds. `0` disables a limit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProxyTimeouts {
+    pub connect_secs: u32,
+    /// Longest wait for response headers, and between body chunks once streaming.
+    pub read_secs: u32,
+    /// Longest a whole request may take, including the response body.
+    pub total_secs: u32,
+    /// Checked in order; the first matching override wins.
+    pub overrides: Vec<TimeoutOverride>,
+}
+
+impl Default for ProxyTimeouts {
+    fn default() -> Self {
+        Self {
+            connect_secs: 5,
+            // Extended thinking can take several minutes before the first byte.
+            read_secs: 900,
+            total_secs: 0,
+            overrides: Vec::new(),
+        }
+    }
+}
+
+/// Retries for POST inference requests answered with `429` or `529`, made before any of the
+/// response reaches the client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProxyRetries {
+    /// Attempts per request, including the first; `1` turns retries off.
+    pub max_attempts: u32,
+    /// Backoff before the first retry, doubled for each further one.
+    pub base_delay_ms: u64,
+    /// Longest wait before a retry. Responses asking to wait longer are relayed as is.
+    pub max_delay_ms: u64,
+}
+
+impl Default for ProxyRetries {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay_ms: 500,
+            max_delay_ms: 20_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeoutScope {
+    /// Matched against the request path, e.g. `/v1/messages`.
+    Route,
+    Model,
+}
+
+/// Replaces the read and/or total timeout for matching requests.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeoutOverride {
+    #[serde(default)]
+    pub id: String,
+    pub scope: TimeoutScope,
+    /// Case-insensitive glob (`*`, `?`) over the whole path or model name.
+    pub pattern: String,
+    #[serde(default)]
+    pub read_secs: Option<u32>,
+    #[serde(default)]
+    pub total_secs: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FailoverTarget {
+    /// The local CLIProxyAPI backend, which routes by model (e.g. `gemini-claude-*` to
+    /// Antigravity).
+    Backend,
+    /// The Vercel AI Gateway; only used for Claude models while the gateway is configured.
+    Vercel,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FailoverStep {
+    pub target: FailoverTarget,
+    /// Model to request. Each `*` takes the text matched by the next `*` of the chain pattern.
+    pub model: String,
+}
+
+/// Fallback routes for models matching `pattern`, tried in order after the normal route
+/// fails with 429, 529, a 5xx or a connection error.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FailoverChain {
+    #[serde(default)]
+    pub id: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Case-insensitive glob over the whole model name.
+    pub pattern: String,
+    pub steps: Vec<FailoverStep>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelRewritePreview {
+    pub rule_id: String,
+    pub model: String,
+    pub inject: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageBudgetStatus {
+    pub budget: UsageBudget,
+    /// Usage in the current period, in the budget's metric.
+    pub used: i64,
+    pub resets_at: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetAlertLevel {
+    Soft,
+    Hard,
+}
+
+/// Payload of the `budget_alert` event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetAlert {
+    pub budget: UsageBudget,
+    pub level: BudgetAlertLevel,
+    pub used: i64,
+    pub limit: i64,
+}
+
+/// Usage database retention; `0` keeps data forever.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UsageRetention {
+    /// Raw `usage_events` older than this are pruned; their totals stay in the daily rollups.
+    pub raw_event_days: u32,
+    /// `usage_json` blobs older than this are dropped from otherwise retained events.
+    pub usage_json_days: u32,
+}
+
+/// Opt-in capture of proxied request/response pairs for debugging.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RequestCaptureSettings {
+    pub enabled: bool,
+    /// Request and response bodies are each truncated to this size.
+    pub max_body_kb: u32,
+    /// Oldest captures are evicted once their total size exceeds this.
+    pub max_storage_mb: u32,
+}
+
+impl Default for RequestCaptureSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_body_kb: 64,
+            max_storage_mb: 50,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapturedHeader {
+    pub name: String,
+    /// `[redacted]` for credentials.
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestCaptureSummary {
+    pub request_id: String,
+    /// RFC 3339, UTC.
+    pub captured_at: String,
+    pub method: String,
+    pub path: String,
+    pub provider: String,
+    pub model: String,
+    pub client_label: String,
+    pub status_code: i64,
+    pub duration_ms: i64,
+    pub size_bytes: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestCapture {
+    #[serde(flatten)]
+    pub summary: RequestCaptureSummary,
+    pub client_id: String,
+    pub request_headers: Vec<CapturedHeader>,
+    /// Body as forwarded upstream (after the thinking rewrite); `None` for streamed uploads.
+    pub request_body: Option<String>,
+    pub request_body_bytes: i64,
+    pub request_body_truncated: bool,
+    pub response_headers: Vec<CapturedHeader>,
+    pub response_body: Option<String>,
+    pub response_body_bytes: i64,
+    pub response_body_truncated: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestReplayResult {
+    pub status_code: u16,
+    pub duration_ms: i64,
+    pub response_headers: Vec<CapturedHeader>,
+    pub response_body: String,
+    pub response_body_truncated: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageMaintenanceReport {
+    pub pruned_events: i64,
+    pub cleared_usage_json: i64,
+    pub vacuumed: bool,
+    pub ran_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageStorageStats {
+    pub db_path: String,
+    pub db_size_bytes: u64,
+    pub wal_size_bytes: u64,
+    /// Space held by free pages, reclaimable by VACUUM.
+    pub free_bytes: u64,
+    pub event_count: i64,
+    pub events_with_usage_json: i64,
+    pub rollup_count: i64,
+    pub oldest_event_day: Option<String>,
+    pub oldest_rollup_day: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UsageExportFormat {
+    Csv,
+    Jsonl,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UsageExportKind {
+    /// One row per `usage_events` request.
+    Events,
+    /// One row per `usage_rollups_daily` day/provider/model/account.
+    Rollups,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageExportRequest {
+    /// Destination file; defaults to `<auth dir>/exports/`.
+    #[serde(default)]
+    pub path: Option<String>,
+    pub format: UsageExportFormat,
+    pub kind: UsageExportKind,
+    /// RFC 3339 timestamp or `YYYY-MM-DD` (UTC).
+    #[serde(default)]
+    pub from: Option<String>,
+    /// Exclusive RFC 3339 timestamp, or an inclusive `YYYY-MM-DD` (UTC).
+    #[serde(default)]
+    pub to: Option<String>,
+    #[serde(default)]
+    pub provider: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub account_key: Option<String>,
+    /// `success`, `error`, or an HTTP status code. Raw events only.
+    #[serde(default)]
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageExportResult {
+    pub path: String,
+    pub rows: u64,
+}
+
+/// Client key as listed in the UI; the key itself is only returned on creation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientKeyInfo {
+    pub id: String,
+    pub name: String,
+    pub key_preview: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatedClientKey {
+    pub id: String,
+    pub name: String,
+    pub key: String,
+    pub created_at: String,
+}
+
 #[derive(Debug, Clone)]
 pub struct VercelGatewayConfig {
     pub enabled: bool,
@@ -165,9 +658,14 @@ pub struct UsageSummary {
     pub input_tokens: i64,
     pub output_tokens: i64,
     pub cached_tokens: i64,
+    pub cache_write_tokens: i64,
     pub reasoning_tokens: i64,
     pub error_count: i64,
     pub error_rate: f64,
+    /// Estimated cost at API list prices, in USD.
+    pub estimated_cost_usd: f64,
+    /// Requests for models without a known price (excluded from the estimate).
+    pub unpriced_requests: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -178,8 +676,10 @@ pub struct UsageTimeseriesPoint {
     pub input_tokens: i64,
     pub output_tokens: i64,
     pub cached_tokens: i64,
+    pub cache_write_tokens: i64,
     pub reasoning_tokens: i64,
     pub error_count: i64,
+    pub estimated_cost_usd: f64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -193,17 +693,206 @@ pub struct UsageBreakdownRow {
     pub input_tokens: i64,
     pub output_tokens: i64,
     pub cached_tokens: i64,
+    pub cache_write_tokens: i64,
     pub reasoning_tokens: i64,
     pub error_count: i64,
     pub last_seen: Option<String>,
+    /// `None` when the model has no known price.
+    pub estimated_cost_usd: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageClientRow {
+    /// Empty for requests without a recognized client key.
+    pub client_id: String,
+    pub client_label: String,
+    pub requests: i64,
+    pub total_tokens: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub error_count: i64,
+    pub last_seen: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageProviderRow {
+    pub provider: String,
+    pub requests: i64,
+    pub total_tokens: i64,
+}
+
+/// Why a tracked request failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCategory {
+    RateLimited,
+    AuthExpired,
+    QuotaExceeded,
+    #[serde(rename = "upstream_5xx")]
+    Upstream5xx,
+    BackendUnavailable,
+    ClientError,
+    Timeout,
+    /// The client disconnected before the response was complete.
+    Cancelled,
+}
+
+impl ErrorCategory {
+    pub const ALL: [ErrorCategory; 8] = [
+        Self::RateLimited,
+        Self::AuthExpired,
+        Self::QuotaExceeded,
+        Self::Upstream5xx,
+        Self::BackendUnavailable,
+        Self::ClientError,
+        Self::Timeout,
+        Self::Cancelled,
+    ];
+
+    /// Stored form in `usage_events.error_category`, matching the serde name.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::RateLimited => "rate_limited",
+            Self::AuthExpired => "auth_expired",
+            Self::QuotaExceeded => "quota_exceeded",
+            Self::Upstream5xx => "upstream_5xx",
+            Self::BackendUnavailable => "backend_unavailable",
+            Self::ClientError => "client_error",
+            Self::Timeout => "timeout",
+            Self::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.as_str() == value)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageErrorRow {
+    /// `None` for failures that couldn't be classified.
+    pub category: Option<ErrorCategory>,
+    pub provider: String,
+    pub requests: i64,
+    pub last_seen: Option<String>,
+    pub last_message: Option<String>,
+}
+
+/// Nearest-rank percentiles; `None` when there are no samples.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct LatencyPercentiles {
+    pub p50: Option<f64>,
+    pub p90: Option<f64>,
+    pub p99: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageLatencyStats {
+    pub samples: i64,
+    pub duration_ms: LatencyPercentiles,
+    pub ttfb_ms: LatencyPercentiles,
+    /// Streamed responses only.
+    pub ttft_ms: LatencyPercentiles,
+    pub output_tokens_per_sec: LatencyPercentiles,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageLatencyRow {
+    pub provider: String,
+    pub model: String,
+    pub account_key: String,
+    pub account_label: String,
+    pub stats: UsageLatencyStats,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageLatencyPoint {
+    pub bucket: String,
+    pub provider: String,
+    pub stats: UsageLatencyStats,
+}
+
+/// Latency and throughput of successful requests in the dashboard range.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageLatency {
+    pub overall: UsageLatencyStats,
+    /// Per provider/model/account, busiest first.
+    pub by_model: Vec<UsageLatencyRow>,
+    /// Per bucket and provider.
+    pub timeseries: Vec<UsageLatencyPoint>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UsageDashboard {
+    /// Preset key (`24h`, `7d`, `30d`, `all`) or `custom` for explicit bounds.
     pub range: String,
+    pub bucket: UsageBucket,
     pub summary: UsageSummary,
     pub timeseries: Vec<UsageTimeseriesPoint>,
+    pub providers: Vec<UsageProviderRow>,
+    /// One page of the provider/model/account breakdown.
     pub breakdown: Vec<UsageBreakdownRow>,
+    pub breakdown_total: i64,
+    pub page: u32,
+    pub page_size: u32,
+    pub clients: Vec<UsageClientRow>,
+    pub latency: UsageLatency,
+    /// Failed requests per error category and provider, most frequent first.
+    pub errors: Vec<UsageErrorRow>,
+    /// First UTC day (`YYYY-MM-DD`) still held as raw events, set when the range reaches into
+    /// days pruned by retention that this query couldn't read from daily rollups (minute or
+    /// hour buckets, client or status filters). Clients, latency and errors always cover raw
+    /// events only.
+    pub truncated_before: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UsageBucket {
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UsageStatusClass {
+    #[serde(rename = "success")]
+    Success,
+    #[serde(rename = "error")]
+    Error,
+    #[serde(rename = "4xx")]
+    ClientError,
+    #[serde(rename = "5xx")]
+    ServerError,
+}
+
+/// Dashboard query. `from`/`to` (RFC 3339 or `YYYY-MM-DD`) replace the `range` preset,
+/// and filters apply to every section of the dashboard.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UsageDashboardQuery {
+    pub range: Option<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    /// Picked from the span of the range when unset.
+    pub bucket: Option<UsageBucket>,
+    /// Minutes east of UTC used for bucketing, e.g. `-300` for UTC-5.
+    pub utc_offset_minutes: i32,
+    /// IANA zone used for bucketing instead of `utc_offset_minutes`, e.g. `America/New_York`.
+    /// Its offset is looked up per event, so ranges across a DST change bucket correctly.
+    pub time_zone: Option<String>,
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    pub account_key: Option<String>,
+    /// An empty id selects requests without a client key.
+    pub client_id: Option<String>,
+    pub status: Option<UsageStatusClass>,
+    /// Zero-based breakdown page.
+    pub page: u32,
+    /// Breakdown rows per page; `0` uses the default.
+    pub page_size: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -310,3 +999,28 @@ pub struct AgentInstallResult {
     pub skipped_invalid: usize,
     pub factory_settings_path: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_network_requires_client_keys_off_loopback() {
+        let settings = |bind_address: &str, require_client_api_key| AppSettings {
+            bind_address: bind_address.to_string(),
+            require_client_api_key,
+            ..AppSettings::default()
+        };
+        assert!(settings("127.0.0.1", false).validate_network().is_ok());
+        assert!(settings("::1", false).validate_network().is_ok());
+        assert!(settings("0.0.0.0", true).validate_network().is_ok());
+        assert_eq!(
+            settings("0.0.0.0", false).validate_network(),
+            Err("Client API keys must be required when binding to 0.0.0.0".to_string())
+        );
+        assert_eq!(
+            settings("192.168.1.20", false).validate_network(),
+            Err("Client API keys must be required when binding to 192.168.1.20".to_string())
+        );
+    }
+}
@@ -0,0 +1,375 @@
+use crate::types::{UsageExportFormat, UsageExportKind, UsageExportRequest};
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use rusqlite::types::{Value as SqlValue, ValueRef};
+use rusqlite::{params_from_iter, Connection};
+use serde_json::Value;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+const EVENT_COLUMNS: &str = r#"
+  request_id,
+  strftime('%Y-%m-%dT%H:%M:%SZ', timestamp_utc, 'unixepoch') AS timestamp,
+  timestamp_utc,
+  method,
+  path,
+  provider,
+  model,
+  account_key,
+  account_label,
+  client_id,
+  client_label,
+  status_code,
+  is_success,
+  duration_ms,
+  ttfb_ms,
+  ttft_ms,
+  output_tokens_per_sec,
+  request_bytes,
+  response_bytes,
+  input_tokens,
+  output_tokens,
+  total_tokens,
+  cached_tokens,
+  cache_write_tokens,
+  reasoning_tokens,
+  usage_json,
+  error_category,
+  error_message,
+  parent_request_id"#;
+
+const ROLLUP_COLUMNS: &str = r#"
+  day_utc,
+  provider,
+  model,
+  account_key,
+  requests,
+  total_tokens,
+  input_tokens,
+  output_tokens,
+  cached_tokens,
+  cache_write_tokens,
+  reasoning_tokens,
+  error_count"#;
+
+/// Accepts RFC 3339 timestamps or plain `YYYY-MM-DD` dates (UTC midnight).
+/// A plain date used as the upper bound includes that whole day.
+pub(crate) fn parse_bound(value: &str, is_end: bool) -> Result<DateTime<Utc>, String> {
+    let value = value.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date or timestamp: {}", value))?;
+    let date = if is_end {
+        date.succ_opt().unwrap_or(date)
+    } else {
+        date
+    };
+    Ok(Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0).unwrap_or_default()))
+}
+
+pub(crate) fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+}
+
+/// Builds the export query for the requested kind and filters.
+fn build_query(request: &UsageExportRequest) -> Result<(String, Vec<SqlValue>), String> {
+    let from = non_empty(&request.from)
+        .map(|v| parse_bound(v, false))
+        .transpose()?;
+    let to = non_empty(&request.to)
+        .map(|v| parse_bound(v, true))
+        .transpose()?;
+    if let (Some(from), Some(to)) = (from, to) {
+        if from >= to {
+            return Err("Export range start must be before its end".to_string());
+        }
+    }
+
+    let mut conditions: Vec<String> = Vec::new();
+    let mut params: Vec<SqlValue> = Vec::new();
+
+    match request.kind {
+        UsageExportKind::Events => {
+            if let Some(from) = from {
+                conditions.push("timestamp_utc >= ?".to_string());
+                params.push(SqlValue::Integer(from.timestamp()));
+            }
+            if let Some(to) = to {
+                conditions.push("timestamp_utc < ?".to_string());
+                params.push(SqlValue::Integer(to.timestamp()));
+            }
+        }
+        UsageExportKind::Rollups => {
+            // Rollups are per UTC day; a partial day is included whole.
+            if let Some(from) = from {
+                conditions.push("day_utc >= ?".to_string());
+                params.push(SqlValue::Text(from.format("%Y-%m-%d").to_string()));
+            }
+            if let Some(to) = to {
+                conditions.push("day_utc <= ?".to_string());
+                let last = to - chrono::Duration::seconds(1);
+                params.push(SqlValue::Text(last.format("%Y-%m-%d").to_string()));
+            }
+        }
+    }
+
+    for (column, value) in [
+        ("provider", non_empty(&request.provider)),
+        ("model", non_empty(&request.model)),
+        ("account_key", non_empty(&request.account_key)),
+    ] {
+        if let Some(value) = value {
+            conditions.push(format!("{} = ? COLLATE NOCASE", column));
+            params.push(SqlValue::Text(value.to_string()));
+        }
+    }
+
+    if let Some(status) = non_empty(&request.status) {
+        if matches!(request.kind, UsageExportKind::Rollups) {
+            return Err("The status filter only applies to raw event exports".to_string());
+        }
+        match status.to_ascii_lowercase().as_str() {
+            "success" => conditions.push("is_success = 1".to_string()),
+            "error" => conditions.push("is_success = 0".to_string()),
+            code => {
+                let code: i64 = code
+                    .parse()
+                    .map_err(|_| format!("Invalid status filter: {}", status))?;
+                conditions.push("status_code = ?".to_string());
+                params.push(SqlValue::Integer(code));
+            }
+        }
+    }
+
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
+    let sql = match request.kind {
+        UsageExportKind::Events => format!(
+            "SELECT {} FROM usage_events {} ORDER BY timestamp_utc ASC, id ASC",
+            EVENT_COLUMNS, where_clause
+        ),
+        UsageExportKind::Rollups => format!(
+            "SELECT {} FROM usage_rollups_daily {} ORDER BY day_utc ASC, provider, model, account_key",
+            ROLLUP_COLUMNS, where_clause
+        ),
+    };
+    Ok((sql, params))
+}
+
+/// Default export location: `<auth dir>/exports/usage-<kind>-<timestamp>.<ext>`.
+pub fn default_export_path(auth_dir: &Path, request: &UsageExportRequest) -> PathBuf {
+    let kind = match request.kind {
+        UsageExportKind::Events => "events",
+        UsageExportKind::Rollups => "rollups",
+    };
+    let ext = match request.format {
+        UsageExportFormat::Csv => "csv",
+        UsageExportFormat::Jsonl => "jsonl",
+    };
+    auth_dir.join("exports").join(format!(
+        "usage-{}-{}.{}",
+        kind,
+        Utc::now().format("%Y%m%d-%H%M%S"),
+        ext
+    ))
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn value_to_json(value: ValueRef<'_>) -> Value {
+    match value {
+        ValueRef::Null => Value::Null,
+        ValueRef::Integer(n) => n.into(),
+        ValueRef::Real(f) => f.into(),
+        ValueRef::Text(t) => String::from_utf8_lossy(t).into_owned().into(),
+        ValueRef::Blob(_) => Value::Null,
+    }
+}
+
+fn value_to_csv(value: ValueRef<'_>) -> String {
+    match value {
+        ValueRef::Null | ValueRef::Blob(_) => String::new(),
+        ValueRef::Integer(n) => n.to_string(),
+        ValueRef::Real(f) => f.to_string(),
+        ValueRef::Text(t) => csv_field(&String::from_utf8_lossy(t)),
+    }
+}
+
+/// Runs `sql` and writes each row as it is read, so large histories never sit in memory.
+fn write_rows(
+    conn: &Connection,
+    sql: &str,
+    params: &[SqlValue],
+    format: UsageExportFormat,
+    out: &mut impl Write,
+) -> Result<u64, String> {
+    let mut stmt = conn
+        .prepare(sql)
+        .map_err(|e| format!("Failed to prepare usage export query: {}", e))?;
+    let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+    let mut rows = stmt
+        .query(params_from_iter(params.iter()))
+        .map_err(|e| format!("Failed to run usage export query: {}", e))?;
+
+    let write_err = |e: std::io::Error| format!("Failed to write usage export: {}", e);
+    if matches!(format, UsageExportFormat::Csv) {
+        writeln!(out, "{}", columns.join(",")).map_err(write_err)?;
+    }
+
+    let mut count = 0u64;
+    while let Some(row) = rows
+        .next()
+        .map_err(|e| format!("Failed to read usage export row: {}", e))?
+    {
+        match format {
+            UsageExportFormat::Csv => {
+                let fields: Vec<String> = (0..columns.len())
+                    .map(|i| row.get_ref(i).map(value_to_csv).unwrap_or_default())
+                    .collect();
+                writeln!(out, "{}", fields.join(",")).map_err(write_err)?;
+            }
+            UsageExportFormat::Jsonl => {
+                // Written field by field to keep the column order of the query.
+                let fields: Vec<String> = columns
+                    .iter()
+                    .enumerate()
+                    .map(|(i, column)| {
+                        let value = row.get_ref(i).map(value_to_json).unwrap_or(Value::Null);
+                        format!("{}:{}", Value::from(column.as_str()), value)
+                    })
+                    .collect();
+                writeln!(out, "{{{}}}", fields.join(",")).map_err(write_err)?;
+            }
+        }
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Writes the export to `path` via a temporary file, returning the number of rows.
+pub fn export(conn: &Connection, request: &UsageExportRequest, path: &Path) -> Result<u64, String> {
+    let (sql, params) = build_query(request)?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create export directory: {}", e))?;
+    }
+    let tmp_path = path.with_extension("partial");
+    let file =
+        File::create(&tmp_path).map_err(|e| format!("Failed to create export file: {}", e))?;
+    let mut out = BufWriter::new(file);
+
+    let result = write_rows(conn, &sql, &params, request.format, &mut out).and_then(|count| {
+        out.flush()
+            .map_err(|e| format!("Failed to write usage export: {}", e))?;
+        Ok(count)
+    });
+    drop(out);
+
+    match result {
+        Ok(count) => {
+            fs::rename(&tmp_path, path)
+                .map_err(|e| format!("Failed to finalize export file: {}", e))?;
+            Ok(count)
+        }
+        Err(e) => {
+            fs::remove_file(&tmp_path).ok();
+            Err(e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(kind: UsageExportKind) -> UsageExportRequest {
+        UsageExportRequest {
+            path: None,
+            format: UsageExportFormat::Csv,
+            kind,
+            from: Some("2025-01-01".to_string()),
+            to: Some("2025-01-31".to_string()),
+            provider: Some("claude".to_string()),
+            model: None,
+            account_key: None,
+            status: Some("429".to_string()),
+        }
+    }
+
+    #[test]
+    fn build_query_applies_range_and_filters() {
+        let (sql, params) = build_query(&request(UsageExportKind::Events)).unwrap();
+        assert!(sql.contains("FROM usage_events WHERE timestamp_utc >= ? AND timestamp_utc < ?"));
+        assert!(sql.contains("provider = ? COLLATE NOCASE AND status_code = ?"));
+        assert_eq!(
+            params,
+            vec![
+                SqlValue::Integer(1_735_689_600),
+                SqlValue::Integer(1_738_368_000),
+                SqlValue::Text("claude".to_string()),
+                SqlValue::Integer(429),
+            ]
+        );
+
+        // Status is per request, so it can't filter rollups.
+        assert!(build_query(&request(UsageExportKind::Rollups)).is_err());
+        let (sql, params) = build_query(&UsageExportRequest {
+            status: None,
+            ..request(UsageExportKind::Rollups)
+        })
+        .unwrap();
+        assert!(sql.contains("FROM usage_rollups_daily WHERE day_utc >= ? AND day_utc <= ?"));
+        assert_eq!(params[1], SqlValue::Text("2025-01-31".to_string()));
+    }
+
+    #[test]
+    fn write_rows_streams_csv_and_jsonl() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE t (name TEXT, tokens INTEGER, note TEXT);
+             INSERT INTO t VALUES ('a', 10, NULL), ('b, \"c\"', 20, 'x');",
+        )
+        .unwrap();
+
+        let mut csv = Vec::new();
+        let count = write_rows(
+            &conn,
+            "SELECT * FROM t",
+            &[],
+            UsageExportFormat::Csv,
+            &mut csv,
+        )
+        .unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "name,tokens,note\na,10,\n\"b, \"\"c\"\"\",20,x\n"
+        );
+
+        let mut jsonl = Vec::new();
+        write_rows(
+            &conn,
+            "SELECT * FROM t WHERE tokens > ?",
+            &[SqlValue::Integer(15)],
+            UsageExportFormat::Jsonl,
+            &mut jsonl,
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(jsonl).unwrap(),
+            "{\"name\":\"b, \\\"c\\\"\",\"tokens\":20,\"note\":\"x\"}\n"
+        );
+    }
+}
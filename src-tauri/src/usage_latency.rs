@@ -0,0 +1,199 @@
+use crate::types::{
+    LatencyPercentiles, UsageLatency, UsageLatencyPoint, UsageLatencyRow, UsageLatencyStats,
+};
+use std::collections::{BTreeMap, HashMap};
+
+/// Timing recorded for one successful request.
+#[derive(Debug, Clone, Copy)]
+pub struct LatencySample {
+    pub duration_ms: i64,
+    pub ttfb_ms: Option<i64>,
+    pub ttft_ms: Option<i64>,
+    pub output_tokens_per_sec: Option<f64>,
+}
+
+/// Nearest-rank percentile of sorted values.
+fn percentile(sorted: &[f64], p: f64) -> Option<f64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted.get(rank.clamp(1, sorted.len()) - 1).copied()
+}
+
+fn percentiles(mut values: Vec<f64>) -> LatencyPercentiles {
+    values.sort_by(|a, b| a.total_cmp(b));
+    LatencyPercentiles {
+        p50: percentile(&values, 50.0),
+        p90: percentile(&values, 90.0),
+        p99: percentile(&values, 99.0),
+    }
+}
+
+#[derive(Default)]
+struct Samples {
+    count: i64,
+    duration_ms: Vec<f64>,
+    ttfb_ms: Vec<f64>,
+    ttft_ms: Vec<f64>,
+    output_tokens_per_sec: Vec<f64>,
+}
+
+impl Samples {
+    fn push(&mut self, sample: LatencySample) {
+        self.count += 1;
+        self.duration_ms.push(sample.duration_ms as f64);
+        self.ttfb_ms.extend(sample.ttfb_ms.map(|v| v as f64));
+        self.ttft_ms.extend(sample.ttft_ms.map(|v| v as f64));
+        self.output_tokens_per_sec
+            .extend(sample.output_tokens_per_sec.filter(|v| v.is_finite()));
+    }
+
+    fn into_stats(self) -> UsageLatencyStats {
+        UsageLatencyStats {
+            samples: self.count,
+            duration_ms: percentiles(self.duration_ms),
+            ttfb_ms: percentiles(self.ttfb_ms),
+            ttft_ms: percentiles(self.ttft_ms),
+            output_tokens_per_sec: percentiles(self.output_tokens_per_sec),
+        }
+    }
+}
+
+/// Collects per-request timings and reduces them to percentiles overall, per
+/// provider/model/account, and per bucket and provider.
+#[derive(Default)]
+pub struct LatencyAggregator {
+    overall: Samples,
+    by_model: HashMap<(String, String, String), (String, Samples)>,
+    by_bucket: BTreeMap<(String, String), Samples>,
+}
+
+impl LatencyAggregator {
+    pub fn observe(
+        &mut self,
+        bucket: &str,
+        provider: &str,
+        model: &str,
+        account_key: &str,
+        account_label: &str,
+        sample: LatencySample,
+    ) {
+        self.overall.push(sample);
+        self.by_model
+            .entry((
+                provider.to_string(),
+                model.to_string(),
+                account_key.to_string(),
+            ))
+            .or_insert_with(|| (account_label.to_string(), Samples::default()))
+            .1
+            .push(sample);
+        self.by_bucket
+            .entry((bucket.to_string(), provider.to_string()))
+            .or_default()
+            .push(sample);
+    }
+
+    /// Keeps the `max_rows` busiest provider/model/account rows.
+    pub fn finish(self, max_rows: usize) -> UsageLatency {
+        let mut by_model: Vec<UsageLatencyRow> = self
+            .by_model
+            .into_iter()
+            .map(
+                |((provider, model, account_key), (account_label, samples))| UsageLatencyRow {
+                    provider,
+                    model,
+                    account_key,
+                    account_label,
+                    stats: samples.into_stats(),
+                },
+            )
+            .collect();
+        by_model.sort_by(|a, b| {
+            b.stats
+                .samples
+                .cmp(&a.stats.samples)
+                .then_with(|| a.provider.cmp(&b.provider))
+                .then_with(|| a.model.cmp(&b.model))
+        });
+        by_model.truncate(max_rows);
+
+        UsageLatency {
+            overall: self.overall.into_stats(),
+            by_model,
+            timeseries: self
+                .by_bucket
+                .into_iter()
+                .map(|((bucket, provider), samples)| UsageLatencyPoint {
+                    bucket,
+                    provider,
+                    stats: samples.into_stats(),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(duration_ms: i64, ttft_ms: Option<i64>) -> LatencySample {
+        LatencySample {
+            duration_ms,
+            ttfb_ms: Some(duration_ms / 10),
+            ttft_ms,
+            output_tokens_per_sec: Some(duration_ms as f64 / 100.0),
+        }
+    }
+
+    #[test]
+    fn percentiles_use_nearest_rank() {
+        let values: Vec<f64> = (1..=100).map(|v| v as f64).collect();
+        let p = percentiles(values);
+        assert_eq!((p.p50, p.p90, p.p99), (Some(50.0), Some(90.0), Some(99.0)));
+
+        let p = percentiles(vec![300.0, 100.0, 200.0]);
+        assert_eq!(
+            (p.p50, p.p90, p.p99),
+            (Some(200.0), Some(300.0), Some(300.0))
+        );
+        assert_eq!(percentiles(Vec::new()), LatencyPercentiles::default());
+    }
+
+    #[test]
+    fn aggregator_groups_by_model_and_bucket() {
+        let mut aggregator = LatencyAggregator::default();
+        for duration in [100, 200, 300] {
+            aggregator.observe(
+                "2025-06-14",
+                "claude",
+                "claude-opus-4-5",
+                "a",
+                "A",
+                sample(duration, Some(50)),
+            );
+        }
+        aggregator.observe(
+            "2025-06-15",
+            "codex",
+            "gpt-5",
+            "b",
+            "B",
+            sample(1_000, None),
+        );
+
+        let latency = aggregator.finish(10);
+        assert_eq!(latency.overall.samples, 4);
+        assert_eq!(latency.by_model[0].model, "claude-opus-4-5");
+        assert_eq!(latency.by_model[0].stats.duration_ms.p50, Some(200.0));
+        assert_eq!(
+            latency.by_model[1].stats.ttft_ms,
+            LatencyPercentiles::default()
+        );
+        assert_eq!(latency.timeseries.len(), 2);
+        assert_eq!(latency.timeseries[1].provider, "codex");
+        assert_eq!(latency.timeseries[1].stats.ttfb_ms.p99, Some(100.0));
+    }
+}
@@ -1,10 +1,24 @@
-use chrono::{TimeZone, Utc};
-use rusqlite::{params, Connection};
+use chrono::{DateTime, Offset, TimeZone, Utc};
+use chrono_tz::Tz;
+use rusqlite::functions::FunctionFlags;
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params, params_from_iter, Connection};
 use serde_json::Value;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
 
 use crate::auth_manager;
-use crate::types::{UsageBreakdownRow, UsageDashboard, UsageSummary, UsageTimeseriesPoint};
+use crate::budgets;
+use crate::pricing::{PricingTable, TokenCounts};
+use crate::types::{
+    BudgetMetric, BudgetScope, ErrorCategory, UsageBreakdownRow, UsageBucket, UsageBudget,
+    UsageClientRow, UsageDashboard, UsageDashboardQuery, UsageErrorRow, UsageExportRequest,
+    UsageExportResult, UsageMaintenanceReport, UsageProviderRow, UsageRetention, UsageStatusClass,
+    UsageStorageStats, UsageSummary, UsageTimeseriesPoint,
+};
+use crate::usage_export;
+use crate::usage_latency::{LatencyAggregator, LatencySample};
 
 #[derive(Debug, Clone, Copy)]
 pub enum UsageRangeQuery {
@@ -42,14 +56,282 @@ impl UsageRangeQuery {
             Self::AllTime => None,
         }
     }
+}
+
+const DEFAULT_PAGE_SIZE: u32 = 50;
+const MAX_PAGE_SIZE: u32 = 500;
+/// Upper bound on timeseries points, so a minute bucket can't be asked for over months.
+const MAX_BUCKETS: i64 = 2_000;
+const MAX_UTC_OFFSET_MINUTES: i32 = 14 * 60;
+const MAX_LATENCY_ROWS: usize = 100;
+/// Upper bound on requests loaded for latency percentiles; larger ranges are sampled evenly.
+const MAX_LATENCY_SAMPLES: i64 = 20_000;
+
+/// Every how many successful requests one is loaded, so at most `MAX_LATENCY_SAMPLES` are.
+fn latency_sample_stride(matching: i64) -> i64 {
+    (matching.max(1) - 1) / MAX_LATENCY_SAMPLES + 1
+}
+
+fn bucket_seconds(bucket: UsageBucket) -> i64 {
+    match bucket {
+        UsageBucket::Minute => 60,
+        UsageBucket::Hour => 60 * 60,
+        UsageBucket::Day => 24 * 60 * 60,
+        UsageBucket::Week => 7 * 24 * 60 * 60,
+        UsageBucket::Month => 30 * 24 * 60 * 60,
+    }
+}
 
-    fn bucket_sql(&self) -> &'static str {
+/// Hourly up to two days, daily up to 90 days, weekly up to two years, monthly beyond.
+fn default_bucket(span_seconds: Option<i64>) -> UsageBucket {
+    const DAY: i64 = 24 * 60 * 60;
+    match span_seconds {
+        Some(span) if span <= 2 * DAY => UsageBucket::Hour,
+        Some(span) if span <= 90 * DAY => UsageBucket::Day,
+        Some(span) if span <= 730 * DAY => UsageBucket::Week,
+        _ => UsageBucket::Month,
+    }
+}
+
+/// The caller's local time for bucketing. A named zone is resolved per event, so buckets on
+/// either side of a DST change both line up with local midnight.
+#[derive(Debug, Clone, Copy)]
+enum BucketZone {
+    /// Minutes east of UTC.
+    Fixed(i32),
+    Named(Tz),
+}
+
+impl BucketZone {
+    fn offset_seconds(self, timestamp_utc: i64) -> i64 {
         match self {
-            Self::Last24Hours => "strftime('%Y-%m-%d %H:00:00', timestamp_utc, 'unixepoch')",
-            Self::Last7Days | Self::Last30Days => {
-                "strftime('%Y-%m-%d', timestamp_utc, 'unixepoch')"
+            BucketZone::Fixed(minutes) => minutes as i64 * 60,
+            BucketZone::Named(tz) => Utc
+                .timestamp_opt(timestamp_utc, 0)
+                .single()
+                .map(|dt| dt.with_timezone(&tz).offset().fix().local_minus_utc() as i64)
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// A dashboard query resolved against the current time: UTC bounds, bucketing in the
+/// caller's time zone, and the event filter shared by every dashboard section.
+#[derive(Debug, Clone)]
+pub struct UsageQuery {
+    range_key: String,
+    bucket: UsageBucket,
+    zone: BucketZone,
+    conditions: Vec<String>,
+    params: Vec<SqlValue>,
+    /// The same range and filters over `usage_rollups_daily`, in whole UTC days.
+    rollup_conditions: Vec<String>,
+    rollup_params: Vec<SqlValue>,
+    /// Rollups keep no client or status, so those filters can't be answered from them.
+    rollups_match_filter: bool,
+    page: u32,
+    page_size: u32,
+}
+
+impl UsageQuery {
+    pub fn resolve(request: &UsageDashboardQuery, now: DateTime<Utc>) -> Result<Self, String> {
+        let from = usage_export::non_empty(&request.from)
+            .map(|v| usage_export::parse_bound(v, false))
+            .transpose()?
+            .map(|dt| dt.timestamp());
+        let to = usage_export::non_empty(&request.to)
+            .map(|v| usage_export::parse_bound(v, true))
+            .transpose()?
+            .map(|dt| dt.timestamp());
+
+        let (range_key, start_ts, end_ts) = if from.is_some() || to.is_some() {
+            ("custom".to_string(), from, to)
+        } else {
+            let preset = UsageRangeQuery::from_input(request.range.as_deref().unwrap_or("7d"));
+            (
+                preset.as_key().to_string(),
+                preset.start_timestamp(now.timestamp()),
+                None,
+            )
+        };
+        if let (Some(start), Some(end)) = (start_ts, end_ts) {
+            if start >= end {
+                return Err("Usage range start must be before its end".to_string());
+            }
+        }
+
+        if request.utc_offset_minutes.abs() > MAX_UTC_OFFSET_MINUTES {
+            return Err(format!(
+                "Invalid UTC offset: {} minutes",
+                request.utc_offset_minutes
+            ));
+        }
+        let zone = match usage_export::non_empty(&request.time_zone) {
+            Some(name) => BucketZone::Named(
+                name.parse::<Tz>()
+                    .map_err(|_| format!("Unknown time zone: {}", name))?,
+            ),
+            None => BucketZone::Fixed(request.utc_offset_minutes),
+        };
+
+        let span = start_ts.map(|start| end_ts.unwrap_or(now.timestamp()) - start);
+        let bucket = request.bucket.unwrap_or_else(|| default_bucket(span));
+        match span {
+            Some(span) if span / bucket_seconds(bucket) > MAX_BUCKETS => {
+                return Err(format!(
+                    "Too many buckets for this range (max {}); choose a coarser bucket",
+                    MAX_BUCKETS
+                ));
+            }
+            None if matches!(bucket, UsageBucket::Minute | UsageBucket::Hour) => {
+                return Err("Minute and hour buckets need a range with a start date".to_string());
+            }
+            _ => {}
+        }
+
+        let mut conditions: Vec<String> = Vec::new();
+        let mut params: Vec<SqlValue> = Vec::new();
+        let mut rollup_conditions: Vec<String> = Vec::new();
+        let mut rollup_params: Vec<SqlValue> = Vec::new();
+        let utc_day = |ts: i64| {
+            Utc.timestamp_opt(ts, 0)
+                .single()
+                .map(|dt| dt.format("%Y-%m-%d").to_string())
+                .unwrap_or_default()
+        };
+        if let Some(start) = start_ts {
+            conditions.push("timestamp_utc >= ?".to_string());
+            params.push(SqlValue::Integer(start));
+            rollup_conditions.push("day_utc >= ?".to_string());
+            rollup_params.push(SqlValue::Text(utc_day(start)));
+        }
+        if let Some(end) = end_ts {
+            conditions.push("timestamp_utc < ?".to_string());
+            params.push(SqlValue::Integer(end));
+            rollup_conditions.push("day_utc <= ?".to_string());
+            rollup_params.push(SqlValue::Text(utc_day(end - 1)));
+        }
+        for (column, value) in [
+            ("provider", usage_export::non_empty(&request.provider)),
+            ("model", usage_export::non_empty(&request.model)),
+            ("account_key", usage_export::non_empty(&request.account_key)),
+        ] {
+            if let Some(value) = value {
+                conditions.push(format!("{} = ? COLLATE NOCASE", column));
+                params.push(SqlValue::Text(value.to_string()));
+                rollup_conditions.push(format!("{} = ? COLLATE NOCASE", column));
+                rollup_params.push(SqlValue::Text(value.to_string()));
             }
-            Self::AllTime => "strftime('%Y-%m', timestamp_utc, 'unixepoch')",
+        }
+        if let Some(client_id) = &request.client_id {
+            conditions.push("client_id = ?".to_string());
+            params.push(SqlValue::Text(client_id.trim().to_string()));
+        }
+        if let Some(status) = request.status {
+            conditions.push(
+                match status {
+                    UsageStatusClass::Success => "is_success = 1",
+                    UsageStatusClass::Error => "is_success = 0",
+                    UsageStatusClass::ClientError => "status_code BETWEEN 400 AND 499",
+                    UsageStatusClass::ServerError => "status_code >= 500",
+                }
+                .to_string(),
+            );
+        }
+
+        let page_size = match request.page_size {
+            0 => DEFAULT_PAGE_SIZE,
+            size => size.min(MAX_PAGE_SIZE),
+        };
+
+        Ok(Self {
+            range_key,
+            bucket,
+            zone,
+            conditions,
+            params,
+            rollup_conditions,
+            rollup_params,
+            rollups_match_filter: request.client_id.is_none() && request.status.is_none(),
+            page: request.page,
+            page_size,
+        })
+    }
+
+    fn where_clause(&self) -> String {
+        self.where_clause_with(None)
+    }
+
+    /// The shared filter plus one extra condition that takes no parameters.
+    fn where_clause_with(&self, extra: Option<&str>) -> String {
+        let conditions: Vec<&str> = self
+            .conditions
+            .iter()
+            .map(String::as_str)
+            .chain(extra)
+            .collect();
+        if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        }
+    }
+
+    /// Makes `local_offset(timestamp_utc)`, the caller's UTC offset in seconds at that
+    /// instant, available to `bucket_sql` on `conn`.
+    fn register_local_offset(&self, conn: &Connection) -> Result<(), String> {
+        let zone = self.zone;
+        conn.create_scalar_function(
+            "local_offset",
+            1,
+            FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+            move |ctx| Ok(zone.offset_seconds(ctx.get::<i64>(0)?)),
+        )
+        .map_err(|e| format!("Failed to register local offset function: {}", e))
+    }
+
+    /// Bucket label in the caller's local time; weeks start on Monday.
+    fn bucket_sql(&self) -> String {
+        let local = "timestamp_utc + local_offset(timestamp_utc), 'unixepoch'";
+        match self.bucket {
+            UsageBucket::Minute => format!("strftime('%Y-%m-%d %H:%M:00', {local})"),
+            UsageBucket::Hour => format!("strftime('%Y-%m-%d %H:00:00', {local})"),
+            UsageBucket::Day => format!("strftime('%Y-%m-%d', {local})"),
+            UsageBucket::Week => format!("date({local}, '-6 days', 'weekday 1')"),
+            UsageBucket::Month => format!("strftime('%Y-%m', {local})"),
+        }
+    }
+
+    /// Range filter over the rollups of days retention pruned from `usage_events`, i.e. those
+    /// before `first_event_day` (all of them when no raw events are left).
+    fn compacted_where(&self, first_event_day: Option<&str>) -> (String, Vec<SqlValue>) {
+        let mut conditions: Vec<&str> = self.rollup_conditions.iter().map(String::as_str).collect();
+        let mut params = self.rollup_params.clone();
+        if let Some(day) = first_event_day {
+            conditions.push("day_utc < ?");
+            params.push(SqlValue::Text(day.to_string()));
+        }
+        let clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+        (clause, params)
+    }
+
+    /// Pruned days can stand in for raw events only in day-or-coarser buckets and when the
+    /// filter uses nothing rollups don't keep.
+    fn reads_rollups(&self) -> bool {
+        self.rollups_match_filter && !matches!(self.bucket, UsageBucket::Minute | UsageBucket::Hour)
+    }
+
+    /// Bucket label for a rollup row. Rollups are whole UTC days, so the caller's offset
+    /// isn't applied to them.
+    fn rollup_bucket_sql(&self) -> &'static str {
+        match self.bucket {
+            UsageBucket::Week => "date(day_utc, '-6 days', 'weekday 1')",
+            UsageBucket::Month => "strftime('%Y-%m', day_utc)",
+            _ => "day_utc",
         }
     }
 }
@@ -64,27 +346,140 @@ pub struct UsageEvent {
     pub model: String,
     pub account_key: String,
     pub account_label: String,
+    /// Issued client key id, empty when the request carried no recognized key.
+    pub client_id: String,
+    pub client_label: String,
     pub status_code: i64,
     pub duration_ms: i64,
+    /// Until upstream response headers arrived; `None` when no response was relayed.
+    pub ttfb_ms: Option<i64>,
+    /// Until the first generated token of a streamed response.
+    pub ttft_ms: Option<i64>,
+    /// Output tokens over the generation window (after the first token for streams).
+    pub output_tokens_per_sec: Option<f64>,
     pub request_bytes: i64,
     pub response_bytes: i64,
     pub input_tokens: Option<i64>,
     pub output_tokens: Option<i64>,
     pub total_tokens: Option<i64>,
     pub cached_tokens: Option<i64>,
+    /// Prompt-cache writes (Anthropic `cache_creation_input_tokens`), kept apart from reads.
+    pub cache_write_tokens: Option<i64>,
     pub reasoning_tokens: Option<i64>,
     pub usage_json: Option<String>,
+    /// Set for failed requests only.
+    pub error_category: Option<ErrorCategory>,
+    /// Upstream error message (or proxy-side reason), truncated.
+    pub error_message: Option<String>,
+    /// Shared by every attempt of a request that went through a failover chain.
+    pub parent_request_id: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+const MAINTENANCE_INITIAL_DELAY: Duration = Duration::from_secs(5 * 60);
+const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+/// Vacuum once at least this share of database pages is free.
+const VACUUM_FREE_PAGE_PERCENT: i64 = 20;
+
+/// Runs `run_maintenance` shortly after startup and then daily, reading the retention policy
+/// fresh each time so settings changes apply without a restart.
+pub async fn run_maintenance_loop<F>(usage_tracker: Arc<UsageTracker>, retention: F)
+where
+    F: Fn() -> UsageRetention + Send + 'static,
+{
+    tokio::time::sleep(MAINTENANCE_INITIAL_DELAY).await;
+    loop {
+        if let Err(e) = usage_tracker.run_maintenance(retention()).await {
+            log::warn!("[UsageTracker] Maintenance failed: {}", e);
+        }
+        tokio::time::sleep(MAINTENANCE_INTERVAL).await;
+    }
+}
+
+/// `(id, cached_tokens, reasoning_tokens, cache_write_tokens)` recovered from `usage_json`.
+type TokenBackfill = (i64, Option<i64>, Option<i64>, Option<i64>);
+
+/// Aggregate columns shared by the dashboard queries, read back with `TokenTotals::from_row`.
+const TOKEN_AGGREGATES: &str = r#"COUNT(*) AS requests,
+                  COALESCE(SUM(COALESCE(total_tokens, 0)), 0) AS total_tokens,
+                  COALESCE(SUM(COALESCE(input_tokens, 0)), 0) AS input_tokens,
+                  COALESCE(SUM(COALESCE(output_tokens, 0)), 0) AS output_tokens,
+                  COALESCE(SUM(COALESCE(cached_tokens, 0)), 0) AS cached_tokens,
+                  COALESCE(SUM(COALESCE(cache_write_tokens, 0)), 0) AS cache_write_tokens,
+                  COALESCE(SUM(COALESCE(reasoning_tokens, 0)), 0) AS reasoning_tokens,
+                  COALESCE(SUM(CASE WHEN is_success = 0 THEN 1 ELSE 0 END), 0) AS error_count"#;
+const TOKEN_AGGREGATE_COLUMNS: usize = 8;
+/// Re-aggregates rows that already carry the `TOKEN_AGGREGATES` columns, such as daily rollups.
+const SUMMED_AGGREGATES: &str = r#"SUM(requests) AS requests,
+                  SUM(total_tokens) AS total_tokens,
+                  SUM(input_tokens) AS input_tokens,
+                  SUM(output_tokens) AS output_tokens,
+                  SUM(cached_tokens) AS cached_tokens,
+                  SUM(cache_write_tokens) AS cache_write_tokens,
+                  SUM(reasoning_tokens) AS reasoning_tokens,
+                  SUM(error_count) AS error_count"#;
+
+struct TokenTotals {
+    requests: i64,
+    total_tokens: i64,
+    input_tokens: i64,
+    output_tokens: i64,
+    cached_tokens: i64,
+    cache_write_tokens: i64,
+    reasoning_tokens: i64,
+    error_count: i64,
+}
+
+impl TokenTotals {
+    fn from_row(row: &rusqlite::Row<'_>, offset: usize) -> Self {
+        let get = |i: usize| row.get::<_, i64>(offset + i).unwrap_or(0);
+        Self {
+            requests: get(0),
+            total_tokens: get(1),
+            input_tokens: get(2),
+            output_tokens: get(3),
+            cached_tokens: get(4),
+            cache_write_tokens: get(5),
+            reasoning_tokens: get(6),
+            error_count: get(7),
+        }
+    }
+
+    fn counts(&self) -> TokenCounts {
+        TokenCounts {
+            input: self.input_tokens,
+            output: self.output_tokens,
+            cached: self.cached_tokens,
+            cache_write: self.cache_write_tokens,
+        }
+    }
+}
+
+fn format_last_seen(ts: i64) -> Option<String> {
+    if ts <= 0 {
+        return None;
+    }
+    Utc.timestamp_opt(ts, 0).single().map(|dt| dt.to_rfc3339())
+}
+
+/// Called after each usage event is written, with `total_tokens` as counted in its rollup.
+pub type UsageEventListener = Arc<dyn Fn(&UsageEvent) + Send + Sync>;
+
+#[derive(Clone)]
 pub struct UsageTracker {
     db_path: PathBuf,
+    listener: Arc<RwLock<Option<UsageEventListener>>>,
 }
 
 impl UsageTracker {
     pub fn new() -> Result<Self, String> {
-        let db_path = auth_manager::get_auth_dir().join("codeforwarder-usage.db");
-        let tracker = Self { db_path };
+        Self::open(auth_manager::get_auth_dir().join("codeforwarder-usage.db"))
+    }
+
+    pub(crate) fn open(db_path: PathBuf) -> Result<Self, String> {
+        let tracker = Self {
+            db_path,
+            listener: Arc::new(RwLock::new(None)),
+        };
         tracker.init_schema()?;
         Ok(tracker)
     }
@@ -118,17 +513,26 @@ impl UsageTracker {
               model TEXT NOT NULL,
               account_key TEXT NOT NULL,
               account_label TEXT NOT NULL,
+              client_id TEXT NOT NULL DEFAULT '',
+              client_label TEXT NOT NULL DEFAULT '',
               status_code INTEGER NOT NULL,
               is_success INTEGER NOT NULL,
               duration_ms INTEGER NOT NULL,
+              ttfb_ms INTEGER,
+              ttft_ms INTEGER,
+              output_tokens_per_sec REAL,
               request_bytes INTEGER NOT NULL,
               response_bytes INTEGER NOT NULL,
               input_tokens INTEGER,
               output_tokens INTEGER,
               total_tokens INTEGER,
               cached_tokens INTEGER,
+              cache_write_tokens INTEGER,
               reasoning_tokens INTEGER,
-              usage_json TEXT
+              usage_json TEXT,
+              error_category TEXT,
+              error_message TEXT,
+              parent_request_id TEXT
             );
 
             CREATE INDEX IF NOT EXISTS idx_usage_events_timestamp
@@ -150,6 +554,7 @@ impl UsageTracker {
               input_tokens INTEGER NOT NULL,
               output_tokens INTEGER NOT NULL,
               cached_tokens INTEGER NOT NULL DEFAULT 0,
+              cache_write_tokens INTEGER NOT NULL DEFAULT 0,
               reasoning_tokens INTEGER NOT NULL DEFAULT 0,
               error_count INTEGER NOT NULL,
               PRIMARY KEY (day_utc, provider, model, account_key)
@@ -170,7 +575,65 @@ impl UsageTracker {
             "ALTER TABLE usage_rollups_daily ADD COLUMN reasoning_tokens INTEGER NOT NULL DEFAULT 0",
             [],
         );
+        let _ = conn.execute(
+            "ALTER TABLE usage_events ADD COLUMN client_id TEXT NOT NULL DEFAULT ''",
+            [],
+        );
+        let _ = conn.execute(
+            "ALTER TABLE usage_events ADD COLUMN client_label TEXT NOT NULL DEFAULT ''",
+            [],
+        );
+        let _ = conn.execute(
+            "ALTER TABLE usage_events ADD COLUMN cache_write_tokens INTEGER",
+            [],
+        );
+        let _ = conn.execute(
+            "ALTER TABLE usage_rollups_daily ADD COLUMN cache_write_tokens INTEGER NOT NULL DEFAULT 0",
+            [],
+        );
+        for column in [
+            "ttfb_ms INTEGER",
+            "ttft_ms INTEGER",
+            "output_tokens_per_sec REAL",
+            "error_category TEXT",
+            "error_message TEXT",
+            "parent_request_id TEXT",
+        ] {
+            let _ = conn.execute(
+                &format!("ALTER TABLE usage_events ADD COLUMN {}", column),
+                [],
+            );
+        }
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_usage_events_client ON usage_events(client_id)",
+            [],
+        )
+        .map_err(|e| format!("Failed to create usage client index: {}", e))?;
         self.backfill_usage_from_json(&conn)?;
+        Self::backfill_error_categories(&conn)?;
+        Ok(())
+    }
+
+    /// Best-effort categories for failures recorded before error bodies were captured.
+    /// Proxy-generated 502s and budget 429s carry no response body.
+    fn backfill_error_categories(conn: &Connection) -> Result<(), String> {
+        conn.execute(
+            r#"
+            UPDATE usage_events
+            SET error_category = CASE
+              WHEN status_code = 502 AND response_bytes = 0 THEN 'backend_unavailable'
+              WHEN status_code = 429 AND response_bytes = 0 THEN 'quota_exceeded'
+              WHEN status_code = 429 THEN 'rate_limited'
+              WHEN status_code = 401 THEN 'auth_expired'
+              WHEN status_code IN (408, 504) THEN 'timeout'
+              WHEN status_code >= 500 THEN 'upstream_5xx'
+              ELSE 'client_error'
+            END
+            WHERE is_success = 0 AND error_category IS NULL
+            "#,
+            [],
+        )
+        .map_err(|e| format!("Failed to backfill usage error categories: {}", e))?;
         Ok(())
     }
 
@@ -178,10 +641,17 @@ impl UsageTracker {
         let mut stmt = conn
             .prepare(
                 r#"
-                SELECT id, usage_json, cached_tokens, reasoning_tokens
+                SELECT id, usage_json, cached_tokens, reasoning_tokens, cache_write_tokens
                 FROM usage_events
                 WHERE usage_json IS NOT NULL
-                  AND (cached_tokens IS NULL OR reasoning_tokens IS NULL)
+                  AND (
+                    cached_tokens IS NULL
+                    OR reasoning_tokens IS NULL
+                    OR (
+                      cache_write_tokens IS NULL
+                      AND usage_json LIKE '%cache_creation_input_tokens%'
+                    )
+                  )
                 "#,
             )
             .map_err(|e| format!("Failed to prepare usage backfill query: {}", e))?;
@@ -193,13 +663,14 @@ impl UsageTracker {
                     row.get::<_, Option<String>>(1)?,
                     row.get::<_, Option<i64>>(2)?,
                     row.get::<_, Option<i64>>(3)?,
+                    row.get::<_, Option<i64>>(4)?,
                 ))
             })
             .map_err(|e| format!("Failed to execute usage backfill query: {}", e))?;
 
-        let mut updates: Vec<(i64, Option<i64>, Option<i64>)> = Vec::new();
+        let mut updates: Vec<TokenBackfill> = Vec::new();
         for row in rows {
-            let (id, usage_json, cached_tokens, reasoning_tokens) =
+            let (id, usage_json, cached_tokens, reasoning_tokens, cache_write_tokens) =
                 row.map_err(|e| format!("Failed to read usage backfill row: {}", e))?;
 
             let Some(raw) = usage_json else {
@@ -216,7 +687,7 @@ impl UsageTracker {
                         "cached_tokens",
                         "cached_input_tokens",
                         "cache_read_input_tokens",
-                        "cache_creation_input_tokens",
+                        "cachedContentTokenCount",
                     ],
                 )
             });
@@ -227,8 +698,20 @@ impl UsageTracker {
                 )
             });
 
-            if extracted_cached != cached_tokens || extracted_reasoning != reasoning_tokens {
-                updates.push((id, extracted_cached, extracted_reasoning));
+            let extracted_cache_write = cache_write_tokens.or_else(|| {
+                Self::find_number_in_json_deep(&json, &["cache_creation_input_tokens"])
+            });
+
+            if extracted_cached != cached_tokens
+                || extracted_reasoning != reasoning_tokens
+                || extracted_cache_write != cache_write_tokens
+            {
+                updates.push((
+                    id,
+                    extracted_cached,
+                    extracted_reasoning,
+                    extracted_cache_write,
+                ));
             }
         }
 
@@ -236,10 +719,10 @@ impl UsageTracker {
             let tx = conn
                 .unchecked_transaction()
                 .map_err(|e| format!("Failed to start usage backfill transaction: {}", e))?;
-            for (id, cached_tokens, reasoning_tokens) in updates {
+            for (id, cached_tokens, reasoning_tokens, cache_write_tokens) in updates {
                 tx.execute(
-                    "UPDATE usage_events SET cached_tokens = ?, reasoning_tokens = ? WHERE id = ?",
-                    params![cached_tokens, reasoning_tokens, id],
+                    "UPDATE usage_events SET cached_tokens = ?, reasoning_tokens = ?, cache_write_tokens = ? WHERE id = ?",
+                    params![cached_tokens, reasoning_tokens, cache_write_tokens, id],
                 )
                 .map_err(|e| format!("Failed to update usage backfill row {}: {}", id, e))?;
             }
@@ -251,13 +734,39 @@ impl UsageTracker {
     }
 
     fn rebuild_daily_rollups(&self, conn: &Connection) -> Result<(), String> {
-        conn.execute("DELETE FROM usage_rollups_daily", [])
-            .map_err(|e| format!("Failed to clear daily rollups during rebuild: {}", e))?;
+        match Self::oldest_event_day(conn)? {
+            Some(oldest) => Self::fold_daily_rollups(conn, &oldest, None),
+            None => Ok(()),
+        }
+    }
+
+    fn oldest_event_day(conn: &Connection) -> Result<Option<String>, String> {
+        conn.query_row("SELECT MIN(day_utc) FROM usage_events", [], |row| {
+            row.get(0)
+        })
+        .map_err(|e| format!("Failed to read oldest usage day: {}", e))
+    }
+
+    /// Recomputes rollups for days in `[from_day, until_day)` from raw events. Callers start at
+    /// the oldest retained event day, so rollups for days already pruned by retention are kept.
+    fn fold_daily_rollups(
+        conn: &Connection,
+        from_day: &str,
+        until_day: Option<&str>,
+    ) -> Result<(), String> {
+        const DAY_RANGE: &str = "day_utc >= ?1 AND (?2 IS NULL OR day_utc < ?2)";
         conn.execute(
-            r#"
+            &format!("DELETE FROM usage_rollups_daily WHERE {DAY_RANGE}"),
+            params![from_day, until_day],
+        )
+        .map_err(|e| format!("Failed to clear daily rollups during rebuild: {}", e))?;
+        conn.execute(
+            &format!(
+                r#"
             INSERT INTO usage_rollups_daily (
               day_utc, provider, model, account_key, requests,
-              total_tokens, input_tokens, output_tokens, cached_tokens, reasoning_tokens, error_count
+              total_tokens, input_tokens, output_tokens, cached_tokens, cache_write_tokens,
+              reasoning_tokens, error_count
             )
             SELECT
               day_utc,
@@ -269,17 +778,137 @@ impl UsageTracker {
               COALESCE(SUM(COALESCE(input_tokens, 0)), 0) AS input_tokens,
               COALESCE(SUM(COALESCE(output_tokens, 0)), 0) AS output_tokens,
               COALESCE(SUM(COALESCE(cached_tokens, 0)), 0) AS cached_tokens,
+              COALESCE(SUM(COALESCE(cache_write_tokens, 0)), 0) AS cache_write_tokens,
               COALESCE(SUM(COALESCE(reasoning_tokens, 0)), 0) AS reasoning_tokens,
               COALESCE(SUM(CASE WHEN is_success = 0 THEN 1 ELSE 0 END), 0) AS error_count
             FROM usage_events
+            WHERE {DAY_RANGE}
             GROUP BY day_utc, provider, model, account_key
-            "#,
-            [],
+            "#
+            ),
+            params![from_day, until_day],
         )
         .map_err(|e| format!("Failed to rebuild daily rollups: {}", e))?;
         Ok(())
     }
 
+    /// Applies the retention policy, then checkpoints the WAL and vacuums when enough pages
+    /// are free. Raw events are only deleted in whole UTC days, after those days are re-folded
+    /// into `usage_rollups_daily`.
+    pub async fn run_maintenance(
+        &self,
+        retention: UsageRetention,
+    ) -> Result<UsageMaintenanceReport, String> {
+        let db_path = self.db_path.clone();
+        tokio::task::spawn_blocking(move || {
+            let conn = Self::open_connection(&db_path)?;
+            let now = Utc::now();
+            let mut report = UsageMaintenanceReport {
+                pruned_events: 0,
+                cleared_usage_json: 0,
+                vacuumed: false,
+                ran_at: now.to_rfc3339(),
+            };
+
+            if retention.raw_event_days > 0 {
+                let cutoff_day = (now - chrono::Duration::days(retention.raw_event_days as i64))
+                    .format("%Y-%m-%d")
+                    .to_string();
+                if let Some(oldest) = Self::oldest_event_day(&conn)? {
+                    if oldest < cutoff_day {
+                        let tx = conn
+                            .unchecked_transaction()
+                            .map_err(|e| format!("Failed to start retention transaction: {}", e))?;
+                        Self::fold_daily_rollups(&tx, &oldest, Some(&cutoff_day))?;
+                        report.pruned_events = tx
+                            .execute(
+                                "DELETE FROM usage_events WHERE day_utc < ?",
+                                params![cutoff_day],
+                            )
+                            .map_err(|e| format!("Failed to prune usage events: {}", e))?
+                            as i64;
+                        tx.commit().map_err(|e| {
+                            format!("Failed to commit retention transaction: {}", e)
+                        })?;
+                    }
+                }
+            }
+
+            if retention.usage_json_days > 0 {
+                let cutoff_ts =
+                    (now - chrono::Duration::days(retention.usage_json_days as i64)).timestamp();
+                report.cleared_usage_json = conn
+                    .execute(
+                        "UPDATE usage_events SET usage_json = NULL \
+                         WHERE usage_json IS NOT NULL AND timestamp_utc < ?",
+                        params![cutoff_ts],
+                    )
+                    .map_err(|e| format!("Failed to clear usage_json: {}", e))?
+                    as i64;
+            }
+
+            conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))
+                .map_err(|e| format!("Failed to checkpoint usage database: {}", e))?;
+
+            let page_count: i64 = conn
+                .query_row("PRAGMA page_count", [], |row| row.get(0))
+                .map_err(|e| format!("Failed to read usage database page count: {}", e))?;
+            let free_pages: i64 = conn
+                .query_row("PRAGMA freelist_count", [], |row| row.get(0))
+                .map_err(|e| format!("Failed to read usage database freelist: {}", e))?;
+            if free_pages > 0 && free_pages * 100 >= page_count * VACUUM_FREE_PAGE_PERCENT {
+                conn.execute_batch("VACUUM")
+                    .map_err(|e| format!("Failed to vacuum usage database: {}", e))?;
+                report.vacuumed = true;
+            }
+
+            log::info!(
+                "[UsageTracker] Maintenance: pruned {} events, cleared {} usage_json, vacuumed: {}",
+                report.pruned_events,
+                report.cleared_usage_json,
+                report.vacuumed
+            );
+            Ok(report)
+        })
+        .await
+        .map_err(|e| format!("Failed to join usage maintenance task: {}", e))?
+    }
+
+    pub async fn get_storage_stats(&self) -> Result<UsageStorageStats, String> {
+        let db_path = self.db_path.clone();
+        tokio::task::spawn_blocking(move || {
+            let conn = Self::open_connection(&db_path)?;
+            let count = |sql: &str| -> Result<i64, String> {
+                conn.query_row(sql, [], |row| row.get(0))
+                    .map_err(|e| format!("Failed to read usage storage stats: {}", e))
+            };
+            let file_size = |path: &Path| std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+            let mut wal_path = db_path.clone().into_os_string();
+            wal_path.push("-wal");
+
+            Ok(UsageStorageStats {
+                db_path: db_path.to_string_lossy().to_string(),
+                db_size_bytes: file_size(&db_path),
+                wal_size_bytes: file_size(Path::new(&wal_path)),
+                free_bytes: (count("PRAGMA freelist_count")? * count("PRAGMA page_size")?).max(0)
+                    as u64,
+                event_count: count("SELECT COUNT(*) FROM usage_events")?,
+                events_with_usage_json: count(
+                    "SELECT COUNT(*) FROM usage_events WHERE usage_json IS NOT NULL",
+                )?,
+                rollup_count: count("SELECT COUNT(*) FROM usage_rollups_daily")?,
+                oldest_event_day: Self::oldest_event_day(&conn)?,
+                oldest_rollup_day: conn
+                    .query_row("SELECT MIN(day_utc) FROM usage_rollups_daily", [], |row| {
+                        row.get(0)
+                    })
+                    .map_err(|e| format!("Failed to read usage storage stats: {}", e))?,
+            })
+        })
+        .await
+        .map_err(|e| format!("Failed to join usage storage stats task: {}", e))?
+    }
+
     fn find_number_in_json_deep(value: &Value, keys: &[&str]) -> Option<i64> {
         match value {
             Value::Object(map) => {
@@ -318,9 +947,15 @@ impl UsageTracker {
         }
     }
 
+    pub fn set_event_listener(&self, listener: Option<UsageEventListener>) {
+        if let Ok(mut current) = self.listener.write() {
+            *current = listener;
+        }
+    }
+
     pub async fn record_event(&self, event: UsageEvent) -> Result<(), String> {
         let db_path = self.db_path.clone();
-        tokio::task::spawn_blocking(move || {
+        let event = tokio::task::spawn_blocking(move || -> Result<UsageEvent, String> {
             let conn = Self::open_connection(&db_path)?;
             let tx = conn
                 .unchecked_transaction()
@@ -348,11 +983,16 @@ impl UsageTracker {
                 r#"
                 INSERT INTO usage_events (
                   request_id, timestamp_utc, day_utc, method, path, provider, model,
-                  account_key, account_label, status_code, is_success, duration_ms,
-                  request_bytes, response_bytes, input_tokens, output_tokens,
-                  total_tokens, cached_tokens, reasoning_tokens, usage_json
+                  account_key, account_label, client_id, client_label, status_code,
+                  is_success, duration_ms, ttfb_ms, ttft_ms, output_tokens_per_sec,
+                  request_bytes, response_bytes, input_tokens, output_tokens, total_tokens,
+                  cached_tokens, cache_write_tokens, reasoning_tokens, usage_json,
+                  error_category, error_message, parent_request_id
+                )
+                VALUES (
+                  ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?,
+                  ?, ?, ?
                 )
-                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
                 "#,
                 params![
                     event.request_id,
@@ -364,17 +1004,26 @@ impl UsageTracker {
                     event.model,
                     event.account_key,
                     event.account_label,
+                    event.client_id,
+                    event.client_label,
                     event.status_code,
                     is_success,
                     event.duration_ms,
+                    event.ttfb_ms,
+                    event.ttft_ms,
+                    event.output_tokens_per_sec,
                     event.request_bytes,
                     event.response_bytes,
                     event.input_tokens,
                     event.output_tokens,
                     total_tokens,
                     event.cached_tokens,
+                    event.cache_write_tokens,
                     event.reasoning_tokens,
                     event.usage_json,
+                    event.error_category.map(|category| category.as_str()),
+                    event.error_message,
+                    event.parent_request_id,
                 ],
             )
             .map_err(|e| format!("Failed to insert usage event: {}", e))?;
@@ -384,8 +1033,9 @@ impl UsageTracker {
                 r#"
                 INSERT INTO usage_rollups_daily (
                   day_utc, provider, model, account_key, requests, total_tokens,
-                  input_tokens, output_tokens, cached_tokens, reasoning_tokens, error_count
-                ) VALUES (?, ?, ?, ?, 1, ?, ?, ?, ?, ?, ?)
+                  input_tokens, output_tokens, cached_tokens, cache_write_tokens,
+                  reasoning_tokens, error_count
+                ) VALUES (?, ?, ?, ?, 1, ?, ?, ?, ?, ?, ?, ?)
                 ON CONFLICT(day_utc, provider, model, account_key)
                 DO UPDATE SET
                   requests = usage_rollups_daily.requests + 1,
@@ -393,6 +1043,7 @@ impl UsageTracker {
                   input_tokens = usage_rollups_daily.input_tokens + excluded.input_tokens,
                   output_tokens = usage_rollups_daily.output_tokens + excluded.output_tokens,
                   cached_tokens = usage_rollups_daily.cached_tokens + excluded.cached_tokens,
+                  cache_write_tokens = usage_rollups_daily.cache_write_tokens + excluded.cache_write_tokens,
                   reasoning_tokens = usage_rollups_daily.reasoning_tokens + excluded.reasoning_tokens,
                   error_count = usage_rollups_daily.error_count + excluded.error_count
                 "#,
@@ -405,6 +1056,7 @@ impl UsageTracker {
                     event.input_tokens.unwrap_or(0),
                     event.output_tokens.unwrap_or(0),
                     event.cached_tokens.unwrap_or(0),
+                    event.cache_write_tokens.unwrap_or(0),
                     event.reasoning_tokens.unwrap_or(0),
                     error_count,
                 ],
@@ -413,254 +1065,1044 @@ impl UsageTracker {
 
             tx.commit()
                 .map_err(|e| format!("Failed to commit usage transaction: {}", e))?;
-            Ok(())
+            Ok(UsageEvent {
+                total_tokens,
+                ..event
+            })
         })
         .await
-        .map_err(|e| format!("Failed to join usage write task: {}", e))?
+        .map_err(|e| format!("Failed to join usage write task: {}", e))??;
+
+        let listener = self.listener.read().ok().and_then(|l| l.clone());
+        if let Some(listener) = listener {
+            listener(&event);
+        }
+        Ok(())
     }
 
-    pub async fn get_usage_dashboard(
+    /// Streams matching events or rollups to a CSV/JSONL file.
+    pub async fn export_usage(
         &self,
-        range: UsageRangeQuery,
-    ) -> Result<UsageDashboard, String> {
+        request: UsageExportRequest,
+    ) -> Result<UsageExportResult, String> {
         let db_path = self.db_path.clone();
         tokio::task::spawn_blocking(move || {
+            let path = match request.path.as_deref().map(str::trim) {
+                Some(path) if !path.is_empty() => PathBuf::from(path),
+                _ => usage_export::default_export_path(&auth_manager::get_auth_dir(), &request),
+            };
             let conn = Self::open_connection(&db_path)?;
-            let now_ts = Utc::now().timestamp();
-            let start_ts = range.start_timestamp(now_ts);
+            let rows = usage_export::export(&conn, &request, &path)?;
+            log::info!(
+                "[UsageTracker] Exported {} rows to {}",
+                rows,
+                path.display()
+            );
+            Ok(UsageExportResult {
+                path: path.to_string_lossy().to_string(),
+                rows,
+            })
+        })
+        .await
+        .map_err(|e| format!("Failed to join usage export task: {}", e))?
+    }
 
-            let summary = if let Some(start) = start_ts {
-                let mut stmt = conn
-                    .prepare(
-                        r#"
-                        SELECT
-                          COUNT(*),
-                          COALESCE(SUM(COALESCE(total_tokens, 0)), 0),
-                          COALESCE(SUM(COALESCE(input_tokens, 0)), 0),
-                          COALESCE(SUM(COALESCE(output_tokens, 0)), 0),
-                          COALESCE(SUM(COALESCE(cached_tokens, 0)), 0),
-                          COALESCE(SUM(COALESCE(reasoning_tokens, 0)), 0),
-                          COALESCE(SUM(CASE WHEN is_success = 0 THEN 1 ELSE 0 END), 0)
-                        FROM usage_events
-                        WHERE timestamp_utc >= ?
-                        "#,
+    /// Current-period usage for each budget, in the budget's metric, read from the daily rollups.
+    pub async fn budget_usage(
+        &self,
+        budgets: Vec<UsageBudget>,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<i64>, String> {
+        let db_path = self.db_path.clone();
+        tokio::task::spawn_blocking(move || {
+            let conn = Self::open_connection(&db_path)?;
+            budgets
+                .iter()
+                .map(|budget| {
+                    let column = match budget.scope {
+                        BudgetScope::Provider => "provider",
+                        BudgetScope::Model => "model",
+                        BudgetScope::Account => "account_key",
+                    };
+                    let metric = match budget.metric {
+                        BudgetMetric::Tokens => "total_tokens",
+                        BudgetMetric::Requests => "requests",
+                    };
+                    let since = budgets::period_start(budget.period, now)
+                        .format("%Y-%m-%d")
+                        .to_string();
+                    conn.query_row(
+                        &format!(
+                            "SELECT COALESCE(SUM({metric}), 0) FROM usage_rollups_daily \
+                             WHERE day_utc >= ? AND {column} = ? COLLATE NOCASE"
+                        ),
+                        params![since, budget.target.trim()],
+                        |row| row.get::<_, i64>(0),
                     )
-                    .map_err(|e| format!("Failed to prepare usage summary query: {}", e))?;
-                stmt.query_row(params![start], |row| {
-                    Ok(UsageSummary {
-                        total_requests: row.get::<_, i64>(0)?,
-                        total_tokens: row.get::<_, i64>(1)?,
-                        input_tokens: row.get::<_, i64>(2)?,
-                        output_tokens: row.get::<_, i64>(3)?,
-                        cached_tokens: row.get::<_, i64>(4)?,
-                        reasoning_tokens: row.get::<_, i64>(5)?,
-                        error_count: row.get::<_, i64>(6)?,
-                        error_rate: 0.0,
-                    })
+                    .map_err(|e| format!("Failed to query budget usage: {}", e))
                 })
-                .map_err(|e| format!("Failed to execute usage summary query: {}", e))?
-            } else {
-                let mut stmt = conn
-                    .prepare(
-                        r#"
-                        SELECT
-                          COUNT(*),
-                          COALESCE(SUM(COALESCE(total_tokens, 0)), 0),
-                          COALESCE(SUM(COALESCE(input_tokens, 0)), 0),
-                          COALESCE(SUM(COALESCE(output_tokens, 0)), 0),
-                          COALESCE(SUM(COALESCE(cached_tokens, 0)), 0),
-                          COALESCE(SUM(COALESCE(reasoning_tokens, 0)), 0),
-                          COALESCE(SUM(CASE WHEN is_success = 0 THEN 1 ELSE 0 END), 0)
-                        FROM usage_events
-                        "#,
-                    )
-                    .map_err(|e| format!("Failed to prepare usage summary query: {}", e))?;
-                stmt.query_row([], |row| {
-                    Ok(UsageSummary {
-                        total_requests: row.get::<_, i64>(0)?,
-                        total_tokens: row.get::<_, i64>(1)?,
-                        input_tokens: row.get::<_, i64>(2)?,
-                        output_tokens: row.get::<_, i64>(3)?,
-                        cached_tokens: row.get::<_, i64>(4)?,
-                        reasoning_tokens: row.get::<_, i64>(5)?,
-                        error_count: row.get::<_, i64>(6)?,
-                        error_rate: 0.0,
-                    })
-                })
-                .map_err(|e| format!("Failed to execute usage summary query: {}", e))?
-            };
+                .collect()
+        })
+        .await
+        .map_err(|e| format!("Failed to join budget usage query task: {}", e))?
+    }
 
-            let mut summary = summary;
-            if summary.total_requests > 0 {
-                summary.error_rate =
-                    (summary.error_count as f64 / summary.total_requests as f64) * 100.0;
-            }
+    pub async fn get_usage_dashboard(
+        &self,
+        query: UsageQuery,
+        pricing: PricingTable,
+    ) -> Result<UsageDashboard, String> {
+        let db_path = self.db_path.clone();
+        tokio::task::spawn_blocking(move || {
+            let conn = Self::open_connection(&db_path)?;
+            query.register_local_offset(&conn)?;
+            let where_clause = query.where_clause();
+            let query_params = &query.params;
 
-            let bucket = range.bucket_sql();
-            let timeseries_sql = if start_ts.is_some() {
-                format!(
-                    r#"
-                    SELECT
-                      {bucket} AS bucket,
-                      COUNT(*) AS requests,
-                      COALESCE(SUM(COALESCE(total_tokens, 0)), 0) AS total_tokens,
-                      COALESCE(SUM(COALESCE(input_tokens, 0)), 0) AS input_tokens,
-                      COALESCE(SUM(COALESCE(output_tokens, 0)), 0) AS output_tokens,
-                      COALESCE(SUM(COALESCE(cached_tokens, 0)), 0) AS cached_tokens,
-                      COALESCE(SUM(COALESCE(reasoning_tokens, 0)), 0) AS reasoning_tokens,
-                      COALESCE(SUM(CASE WHEN is_success = 0 THEN 1 ELSE 0 END), 0) AS error_count
-                    FROM usage_events
-                    WHERE timestamp_utc >= ?
-                    GROUP BY bucket
-                    ORDER BY bucket ASC
-                    "#
+            // Days pruned by retention only live on in daily rollups. Summary, timeseries,
+            // providers and breakdown read them back when the query allows it; otherwise the
+            // dashboard reports the day its history now starts.
+            let first_event_day = Self::oldest_event_day(&conn)?;
+            let (compacted_where, compacted_params) =
+                query.compacted_where(first_event_day.as_deref());
+            let compacted_until: Option<String> = conn
+                .query_row(
+                    &format!(
+                        "SELECT date(MAX(day_utc), '+1 day') FROM usage_rollups_daily \
+                         {compacted_where}"
+                    ),
+                    params_from_iter(compacted_params.iter()),
+                    |row| row.get(0),
                 )
+                .map_err(|e| format!("Failed to read compacted usage days: {}", e))?;
+            let read_rollups = compacted_until.is_some() && query.reads_rollups();
+            let truncated_before = if compacted_until.is_some() && !read_rollups {
+                first_event_day.or(compacted_until)
             } else {
-                format!(
-                    r#"
-                    SELECT
-                      {bucket} AS bucket,
-                      COUNT(*) AS requests,
-                      COALESCE(SUM(COALESCE(total_tokens, 0)), 0) AS total_tokens,
-                      COALESCE(SUM(COALESCE(input_tokens, 0)), 0) AS input_tokens,
-                      COALESCE(SUM(COALESCE(output_tokens, 0)), 0) AS output_tokens,
-                      COALESCE(SUM(COALESCE(cached_tokens, 0)), 0) AS cached_tokens,
-                      COALESCE(SUM(COALESCE(reasoning_tokens, 0)), 0) AS reasoning_tokens,
-                      COALESCE(SUM(CASE WHEN is_success = 0 THEN 1 ELSE 0 END), 0) AS error_count
-                    FROM usage_events
-                    GROUP BY bucket
-                    ORDER BY bucket ASC
-                    "#
-                )
+                None
+            };
+            let mut merged_params = query_params.clone();
+            if read_rollups {
+                merged_params.extend(compacted_params);
+            }
+            let merged = |events_sql: String, rollups_sql: String| {
+                if read_rollups {
+                    format!("{events_sql}\n                UNION ALL\n{rollups_sql}")
+                } else {
+                    events_sql
+                }
             };
 
+            // Summary and timeseries are aggregated per model so each slice can be priced.
+            let bucket = query.bucket_sql();
+            let rollup_bucket = query.rollup_bucket_sql();
+            let per_model_sql = format!(
+                "{}\n                ORDER BY bucket ASC",
+                merged(
+                    format!(
+                        r#"
+                SELECT
+                  {bucket} AS bucket,
+                  model,
+                  {TOKEN_AGGREGATES}
+                FROM usage_events
+                {where_clause}
+                GROUP BY bucket, model"#
+                    ),
+                    format!(
+                        r#"
+                SELECT
+                  {rollup_bucket} AS bucket,
+                  model,
+                  {SUMMED_AGGREGATES}
+                FROM usage_rollups_daily
+                {compacted_where}
+                GROUP BY bucket, model"#
+                    ),
+                )
+            );
             let mut stmt = conn
-                .prepare(&timeseries_sql)
+                .prepare(&per_model_sql)
                 .map_err(|e| format!("Failed to prepare timeseries query: {}", e))?;
-            let mut rows = if let Some(start) = start_ts {
-                stmt.query(params![start])
-                    .map_err(|e| format!("Failed to query usage timeseries: {}", e))?
-            } else {
-                stmt.query([])
-                    .map_err(|e| format!("Failed to query usage timeseries: {}", e))?
-            };
+            let mut rows = stmt
+                .query(params_from_iter(merged_params.iter()))
+                .map_err(|e| format!("Failed to query usage timeseries: {}", e))?;
 
+            let mut summary = UsageSummary {
+                total_requests: 0,
+                total_tokens: 0,
+                input_tokens: 0,
+                output_tokens: 0,
+                cached_tokens: 0,
+                cache_write_tokens: 0,
+                reasoning_tokens: 0,
+                error_count: 0,
+                error_rate: 0.0,
+                estimated_cost_usd: 0.0,
+                unpriced_requests: 0,
+            };
             let mut timeseries: Vec<UsageTimeseriesPoint> = Vec::new();
             while let Some(row) = rows
                 .next()
                 .map_err(|e| format!("Failed to iterate usage timeseries rows: {}", e))?
             {
-                timeseries.push(UsageTimeseriesPoint {
-                    bucket: row.get::<_, String>(0).unwrap_or_else(|_| "".to_string()),
-                    requests: row.get::<_, i64>(1).unwrap_or(0),
-                    total_tokens: row.get::<_, i64>(2).unwrap_or(0),
-                    input_tokens: row.get::<_, i64>(3).unwrap_or(0),
-                    output_tokens: row.get::<_, i64>(4).unwrap_or(0),
-                    cached_tokens: row.get::<_, i64>(5).unwrap_or(0),
-                    reasoning_tokens: row.get::<_, i64>(6).unwrap_or(0),
-                    error_count: row.get::<_, i64>(7).unwrap_or(0),
-                });
+                let bucket: String = row.get::<_, String>(0).unwrap_or_default();
+                let model: String = row.get::<_, String>(1).unwrap_or_default();
+                let totals = TokenTotals::from_row(row, 2);
+                let cost = pricing.estimate(&model, totals.counts());
+
+                summary.total_requests += totals.requests;
+                summary.total_tokens += totals.total_tokens;
+                summary.input_tokens += totals.input_tokens;
+                summary.output_tokens += totals.output_tokens;
+                summary.cached_tokens += totals.cached_tokens;
+                summary.cache_write_tokens += totals.cache_write_tokens;
+                summary.reasoning_tokens += totals.reasoning_tokens;
+                summary.error_count += totals.error_count;
+                match cost {
+                    Some(cost) => summary.estimated_cost_usd += cost,
+                    None => summary.unpriced_requests += totals.requests,
+                }
+
+                if timeseries
+                    .last()
+                    .map(|p| p.bucket != bucket)
+                    .unwrap_or(true)
+                {
+                    timeseries.push(UsageTimeseriesPoint {
+                        bucket,
+                        requests: 0,
+                        total_tokens: 0,
+                        input_tokens: 0,
+                        output_tokens: 0,
+                        cached_tokens: 0,
+                        cache_write_tokens: 0,
+                        reasoning_tokens: 0,
+                        error_count: 0,
+                        estimated_cost_usd: 0.0,
+                    });
+                }
+                if let Some(point) = timeseries.last_mut() {
+                    point.requests += totals.requests;
+                    point.total_tokens += totals.total_tokens;
+                    point.input_tokens += totals.input_tokens;
+                    point.output_tokens += totals.output_tokens;
+                    point.cached_tokens += totals.cached_tokens;
+                    point.cache_write_tokens += totals.cache_write_tokens;
+                    point.reasoning_tokens += totals.reasoning_tokens;
+                    point.error_count += totals.error_count;
+                    point.estimated_cost_usd += cost.unwrap_or(0.0);
+                }
             }
+            drop(rows);
+            drop(stmt);
 
-            let breakdown_sql = if start_ts.is_some() {
+            if summary.total_requests > 0 {
+                summary.error_rate =
+                    (summary.error_count as f64 / summary.total_requests as f64) * 100.0;
+            }
+
+            let providers_sql = format!(
                 r#"
+                SELECT
+                  provider,
+                  SUM(requests) AS requests,
+                  SUM(total_tokens) AS total_tokens
+                FROM ({})
+                GROUP BY provider
+                ORDER BY total_tokens DESC, requests DESC
+                "#,
+                merged(
+                    format!(
+                        r#"
+                SELECT
+                  provider,
+                  COUNT(*) AS requests,
+                  COALESCE(SUM(COALESCE(total_tokens, 0)), 0) AS total_tokens
+                FROM usage_events
+                {where_clause}
+                GROUP BY provider"#
+                    ),
+                    format!(
+                        r#"
+                SELECT provider, SUM(requests), SUM(total_tokens)
+                FROM usage_rollups_daily
+                {compacted_where}
+                GROUP BY provider"#
+                    ),
+                )
+            );
+            let mut stmt = conn
+                .prepare(&providers_sql)
+                .map_err(|e| format!("Failed to prepare provider query: {}", e))?;
+            let providers = stmt
+                .query_map(params_from_iter(merged_params.iter()), |row| {
+                    Ok(UsageProviderRow {
+                        provider: row
+                            .get::<_, String>(0)
+                            .unwrap_or_else(|_| "unknown".to_string()),
+                        requests: row.get::<_, i64>(1).unwrap_or(0),
+                        total_tokens: row.get::<_, i64>(2).unwrap_or(0),
+                    })
+                })
+                .map_err(|e| format!("Failed to query provider usage: {}", e))?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| format!("Failed to read provider usage rows: {}", e))?;
+            drop(stmt);
+
+            // Rollups carry no account label, so rows are keyed by account and take the
+            // label from raw events when any are left.
+            let breakdown_rows = merged(
+                format!(
+                    r#"
                 SELECT
                   provider,
                   model,
                   account_key,
                   account_label,
-                  COUNT(*) AS requests,
-                  COALESCE(SUM(COALESCE(total_tokens, 0)), 0) AS total_tokens,
-                  COALESCE(SUM(COALESCE(input_tokens, 0)), 0) AS input_tokens,
-                  COALESCE(SUM(COALESCE(output_tokens, 0)), 0) AS output_tokens,
-                  COALESCE(SUM(COALESCE(cached_tokens, 0)), 0) AS cached_tokens,
-                  COALESCE(SUM(COALESCE(reasoning_tokens, 0)), 0) AS reasoning_tokens,
-                  COALESCE(SUM(CASE WHEN is_success = 0 THEN 1 ELSE 0 END), 0) AS error_count,
+                  {TOKEN_AGGREGATES},
                   MAX(timestamp_utc) AS last_seen
                 FROM usage_events
-                WHERE timestamp_utc >= ?
-                GROUP BY provider, model, account_key, account_label
-                ORDER BY total_tokens DESC, requests DESC
-                LIMIT 200
-                "#
-            } else {
+                {where_clause}
+                GROUP BY provider, model, account_key, account_label"#
+                ),
+                format!(
+                    r#"
+                SELECT
+                  provider,
+                  model,
+                  account_key,
+                  NULL AS account_label,
+                  {SUMMED_AGGREGATES},
+                  CAST(strftime('%s', MAX(day_utc)) AS INTEGER) AS last_seen
+                FROM usage_rollups_daily
+                {compacted_where}
+                GROUP BY provider, model, account_key"#
+                ),
+            );
+            let breakdown_total: i64 = conn
+                .query_row(
+                    &format!(
+                        r#"
+                        SELECT COUNT(*) FROM (
+                          SELECT 1 FROM ({breakdown_rows})
+                          GROUP BY provider, model, account_key
+                        )
+                        "#
+                    ),
+                    params_from_iter(merged_params.iter()),
+                    |row| row.get(0),
+                )
+                .map_err(|e| format!("Failed to count usage breakdown rows: {}", e))?;
+
+            let breakdown_sql = format!(
                 r#"
                 SELECT
                   provider,
                   model,
                   account_key,
-                  account_label,
-                  COUNT(*) AS requests,
-                  COALESCE(SUM(COALESCE(total_tokens, 0)), 0) AS total_tokens,
-                  COALESCE(SUM(COALESCE(input_tokens, 0)), 0) AS input_tokens,
-                  COALESCE(SUM(COALESCE(output_tokens, 0)), 0) AS output_tokens,
-                  COALESCE(SUM(COALESCE(cached_tokens, 0)), 0) AS cached_tokens,
-                  COALESCE(SUM(COALESCE(reasoning_tokens, 0)), 0) AS reasoning_tokens,
-                  COALESCE(SUM(CASE WHEN is_success = 0 THEN 1 ELSE 0 END), 0) AS error_count,
-                  MAX(timestamp_utc) AS last_seen
-                FROM usage_events
-                GROUP BY provider, model, account_key, account_label
-                ORDER BY total_tokens DESC, requests DESC
-                LIMIT 200
+                  COALESCE(MAX(account_label), account_key) AS account_label,
+                  {SUMMED_AGGREGATES},
+                  MAX(last_seen) AS last_seen
+                FROM ({breakdown_rows})
+                GROUP BY provider, model, account_key
+                ORDER BY total_tokens DESC, requests DESC, provider, model, account_key
+                LIMIT ? OFFSET ?
                 "#
-            };
-
+            );
             let mut stmt = conn
-                .prepare(breakdown_sql)
+                .prepare(&breakdown_sql)
                 .map_err(|e| format!("Failed to prepare breakdown query: {}", e))?;
-            let mut rows = if let Some(start) = start_ts {
-                stmt.query(params![start])
-                    .map_err(|e| format!("Failed to query usage breakdown: {}", e))?
-            } else {
-                stmt.query([])
-                    .map_err(|e| format!("Failed to query usage breakdown: {}", e))?
-            };
+            let mut page_params = merged_params.clone();
+            page_params.push(SqlValue::Integer(query.page_size as i64));
+            page_params.push(SqlValue::Integer(
+                query.page as i64 * query.page_size as i64,
+            ));
+            let mut rows = stmt
+                .query(params_from_iter(page_params.iter()))
+                .map_err(|e| format!("Failed to query usage breakdown: {}", e))?;
 
             let mut breakdown = Vec::new();
             while let Some(row) = rows
                 .next()
                 .map_err(|e| format!("Failed to iterate usage breakdown rows: {}", e))?
             {
-                let last_seen_ts: i64 = row.get::<_, i64>(11).unwrap_or(0);
-                let last_seen = if last_seen_ts > 0 {
-                    Utc.timestamp_opt(last_seen_ts, 0)
-                        .single()
-                        .map(|dt| dt.to_rfc3339())
-                } else {
-                    None
-                };
+                let model = row
+                    .get::<_, String>(1)
+                    .unwrap_or_else(|_| "unknown".to_string());
+                let totals = TokenTotals::from_row(row, 4);
+                let last_seen_ts: i64 = row.get::<_, i64>(4 + TOKEN_AGGREGATE_COLUMNS).unwrap_or(0);
                 breakdown.push(UsageBreakdownRow {
                     provider: row
                         .get::<_, String>(0)
                         .unwrap_or_else(|_| "unknown".to_string()),
-                    model: row
-                        .get::<_, String>(1)
-                        .unwrap_or_else(|_| "unknown".to_string()),
+                    estimated_cost_usd: pricing.estimate(&model, totals.counts()),
+                    model,
                     account_key: row
                         .get::<_, String>(2)
                         .unwrap_or_else(|_| "unknown".to_string()),
                     account_label: row
                         .get::<_, String>(3)
                         .unwrap_or_else(|_| "unknown".to_string()),
-                    requests: row.get::<_, i64>(4).unwrap_or(0),
-                    total_tokens: row.get::<_, i64>(5).unwrap_or(0),
-                    input_tokens: row.get::<_, i64>(6).unwrap_or(0),
-                    output_tokens: row.get::<_, i64>(7).unwrap_or(0),
-                    cached_tokens: row.get::<_, i64>(8).unwrap_or(0),
-                    reasoning_tokens: row.get::<_, i64>(9).unwrap_or(0),
-                    error_count: row.get::<_, i64>(10).unwrap_or(0),
-                    last_seen,
+                    requests: totals.requests,
+                    total_tokens: totals.total_tokens,
+                    input_tokens: totals.input_tokens,
+                    output_tokens: totals.output_tokens,
+                    cached_tokens: totals.cached_tokens,
+                    cache_write_tokens: totals.cache_write_tokens,
+                    reasoning_tokens: totals.reasoning_tokens,
+                    error_count: totals.error_count,
+                    last_seen: format_last_seen(last_seen_ts),
                 });
             }
+            drop(rows);
+            drop(stmt);
+
+            let clients_sql = format!(
+                r#"
+                SELECT
+                  client_id,
+                  MAX(client_label) AS client_label,
+                  COUNT(*) AS requests,
+                  COALESCE(SUM(COALESCE(total_tokens, 0)), 0) AS total_tokens,
+                  COALESCE(SUM(COALESCE(input_tokens, 0)), 0) AS input_tokens,
+                  COALESCE(SUM(COALESCE(output_tokens, 0)), 0) AS output_tokens,
+                  COALESCE(SUM(CASE WHEN is_success = 0 THEN 1 ELSE 0 END), 0) AS error_count,
+                  MAX(timestamp_utc) AS last_seen
+                FROM usage_events
+                {where_clause}
+                GROUP BY client_id
+                ORDER BY total_tokens DESC, requests DESC
+                LIMIT 200
+                "#
+            );
+            let mut stmt = conn
+                .prepare(&clients_sql)
+                .map_err(|e| format!("Failed to prepare client breakdown query: {}", e))?;
+            let mut rows = stmt
+                .query(params_from_iter(query_params.iter()))
+                .map_err(|e| format!("Failed to query client breakdown: {}", e))?;
+
+            let mut clients = Vec::new();
+            while let Some(row) = rows
+                .next()
+                .map_err(|e| format!("Failed to iterate client breakdown rows: {}", e))?
+            {
+                clients.push(UsageClientRow {
+                    client_id: row.get::<_, String>(0).unwrap_or_default(),
+                    client_label: row.get::<_, String>(1).unwrap_or_default(),
+                    requests: row.get::<_, i64>(2).unwrap_or(0),
+                    total_tokens: row.get::<_, i64>(3).unwrap_or(0),
+                    input_tokens: row.get::<_, i64>(4).unwrap_or(0),
+                    output_tokens: row.get::<_, i64>(5).unwrap_or(0),
+                    error_count: row.get::<_, i64>(6).unwrap_or(0),
+                    last_seen: format_last_seen(row.get::<_, i64>(7).unwrap_or(0)),
+                });
+            }
+
+            drop(rows);
+            drop(stmt);
+
+            // Percentiles aren't available in SQLite, so timings are reduced in Rust. Ids grow
+            // with time, so taking every n-th one samples long ranges evenly across buckets.
+            let matching: i64 = conn
+                .query_row(
+                    &format!(
+                        "SELECT COUNT(*) FROM usage_events {}",
+                        query.where_clause_with(Some("is_success = 1"))
+                    ),
+                    params_from_iter(query_params.iter()),
+                    |row| row.get(0),
+                )
+                .map_err(|e| format!("Failed to count usage latency rows: {}", e))?;
+            let stride = latency_sample_stride(matching);
+            let sampled = format!("is_success = 1 AND id % {} = 0", stride);
+            let latency_sql = format!(
+                r#"
+                SELECT
+                  {bucket} AS bucket,
+                  provider,
+                  model,
+                  account_key,
+                  account_label,
+                  duration_ms,
+                  ttfb_ms,
+                  ttft_ms,
+                  output_tokens_per_sec
+                FROM usage_events
+                {}
+                "#,
+                query.where_clause_with(Some(&sampled))
+            );
+            let mut stmt = conn
+                .prepare(&latency_sql)
+                .map_err(|e| format!("Failed to prepare latency query: {}", e))?;
+            let mut rows = stmt
+                .query(params_from_iter(query_params.iter()))
+                .map_err(|e| format!("Failed to query usage latency: {}", e))?;
+            let mut latency = LatencyAggregator::default();
+            while let Some(row) = rows
+                .next()
+                .map_err(|e| format!("Failed to iterate usage latency rows: {}", e))?
+            {
+                let text = |i: usize| row.get::<_, String>(i).unwrap_or_default();
+                latency.observe(
+                    &text(0),
+                    &text(1),
+                    &text(2),
+                    &text(3),
+                    &text(4),
+                    LatencySample {
+                        duration_ms: row.get::<_, i64>(5).unwrap_or(0),
+                        ttfb_ms: row.get::<_, Option<i64>>(6).unwrap_or(None),
+                        ttft_ms: row.get::<_, Option<i64>>(7).unwrap_or(None),
+                        output_tokens_per_sec: row.get::<_, Option<f64>>(8).unwrap_or(None),
+                    },
+                );
+            }
+
+            drop(rows);
+            drop(stmt);
+
+            let errors_sql = format!(
+                r#"
+                SELECT
+                  error_category,
+                  provider,
+                  COUNT(*) AS requests,
+                  MAX(timestamp_utc) AS last_seen,
+                  error_message
+                FROM usage_events
+                {}
+                GROUP BY error_category, provider
+                ORDER BY requests DESC
+                LIMIT 100
+                "#,
+                query.where_clause_with(Some("is_success = 0"))
+            );
+            let mut stmt = conn
+                .prepare(&errors_sql)
+                .map_err(|e| format!("Failed to prepare error breakdown query: {}", e))?;
+            // SQLite takes bare columns from the row holding MAX(), so the message is the latest.
+            let errors = stmt
+                .query_map(params_from_iter(query_params.iter()), |row| {
+                    Ok(UsageErrorRow {
+                        category: row
+                            .get::<_, Option<String>>(0)?
+                            .as_deref()
+                            .and_then(ErrorCategory::parse),
+                        provider: row.get::<_, String>(1)?,
+                        requests: row.get::<_, i64>(2)?,
+                        last_seen: format_last_seen(row.get::<_, i64>(3)?),
+                        last_message: row.get::<_, Option<String>>(4)?,
+                    })
+                })
+                .map_err(|e| format!("Failed to query error breakdown: {}", e))?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| format!("Failed to read error breakdown rows: {}", e))?;
 
             Ok(UsageDashboard {
-                range: range.as_key().to_string(),
+                range: query.range_key,
+                bucket: query.bucket,
                 summary,
                 timeseries,
+                providers,
                 breakdown,
+                breakdown_total,
+                page: query.page,
+                page_size: query.page_size,
+                clients,
+                latency: latency.finish(MAX_LATENCY_ROWS),
+                errors,
+                truncated_before,
             })
         })
         .await
         .map_err(|e| format!("Failed to join usage dashboard query task: {}", e))?
     }
 }
+
+/// A successful Claude event for tests in any module.
+#[cfg(test)]
+pub(crate) fn test_event(request_id: &str, timestamp_utc: i64, total_tokens: i64) -> UsageEvent {
+    UsageEvent {
+        request_id: request_id.to_string(),
+        timestamp_utc,
+        method: "POST".to_string(),
+        path: "/v1/messages".to_string(),
+        provider: "claude".to_string(),
+        model: "claude-sonnet-4-5".to_string(),
+        account_key: "a@example.com".to_string(),
+        account_label: "a@example.com".to_string(),
+        client_id: String::new(),
+        client_label: String::new(),
+        status_code: 200,
+        duration_ms: 10,
+        ttfb_ms: Some(5),
+        ttft_ms: None,
+        output_tokens_per_sec: None,
+        request_bytes: 100,
+        response_bytes: 200,
+        input_tokens: None,
+        output_tokens: None,
+        total_tokens: Some(total_tokens),
+        cached_tokens: None,
+        cache_write_tokens: None,
+        reasoning_tokens: None,
+        usage_json: Some("{}".to_string()),
+        error_category: None,
+        error_message: None,
+        parent_request_id: None,
+    }
+}
+
+/// A stored usage event, read back by tests in other modules.
+#[cfg(test)]
+#[derive(Debug)]
+pub(crate) struct RecordedEvent {
+    pub request_id: String,
+    pub model: String,
+    pub status_code: i64,
+    pub input_tokens: Option<i64>,
+    pub error_category: Option<String>,
+    pub parent_request_id: Option<String>,
+    pub client_id: String,
+    pub client_label: String,
+    pub ttfb_ms: Option<i64>,
+    pub ttft_ms: Option<i64>,
+    pub output_tokens_per_sec: Option<f64>,
+}
+
+#[cfg(test)]
+impl UsageTracker {
+    /// All events in the order they were recorded.
+    pub(crate) fn recorded_events(&self) -> Vec<RecordedEvent> {
+        let conn = Self::open_connection(&self.db_path).unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT request_id, model, status_code, input_tokens, error_category, \
+                 parent_request_id, client_id, client_label, ttfb_ms, ttft_ms, \
+                 output_tokens_per_sec FROM usage_events ORDER BY id",
+            )
+            .unwrap();
+        stmt.query_map([], |row| {
+            Ok(RecordedEvent {
+                request_id: row.get(0)?,
+                model: row.get(1)?,
+                status_code: row.get(2)?,
+                input_tokens: row.get(3)?,
+                error_category: row.get(4)?,
+                parent_request_id: row.get(5)?,
+                client_id: row.get(6)?,
+                client_label: row.get(7)?,
+                ttfb_ms: row.get(8)?,
+                ttft_ms: row.get(9)?,
+                output_tokens_per_sec: row.get(10)?,
+            })
+        })
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_event as event;
+    use super::*;
+
+    fn temp_db_dir() -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("codeforwarder-usage-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).expect("Failed to create temp dir");
+        dir
+    }
+
+    #[test]
+    fn resolve_picks_buckets_and_builds_filters() {
+        let now = Utc.with_ymd_and_hms(2025, 6, 15, 12, 0, 0).unwrap();
+        let query = UsageQuery::resolve(
+            &UsageDashboardQuery {
+                range: Some("24h".to_string()),
+                ..Default::default()
+            },
+            now,
+        )
+        .unwrap();
+        assert_eq!(query.range_key, "24h");
+        assert_eq!(query.bucket, UsageBucket::Hour);
+        assert_eq!(query.page_size, DEFAULT_PAGE_SIZE);
+
+        let query = UsageQuery::resolve(
+            &UsageDashboardQuery {
+                from: Some("2025-01-01".to_string()),
+                to: Some("2025-03-31".to_string()),
+                utc_offset_minutes: -300,
+                provider: Some("claude".to_string()),
+                client_id: Some(String::new()),
+                status: Some(UsageStatusClass::ServerError),
+                ..Default::default()
+            },
+            now,
+        )
+        .unwrap();
+        assert_eq!(query.range_key, "custom");
+        assert_eq!(query.bucket, UsageBucket::Day);
+        assert_eq!(
+            query.where_clause(),
+            "WHERE timestamp_utc >= ? AND timestamp_utc < ? AND provider = ? COLLATE NOCASE \
+             AND client_id = ? AND status_code >= 500"
+        );
+        assert!(query.bucket_sql().contains("local_offset(timestamp_utc)"));
+        assert_eq!(query.zone.offset_seconds(now.timestamp()), -300 * 60);
+
+        // Minute buckets over a quarter, or without a start, are refused.
+        assert!(UsageQuery::resolve(
+            &UsageDashboardQuery {
+                from: Some("2025-01-01".to_string()),
+                to: Some("2025-03-31".to_string()),
+                bucket: Some(UsageBucket::Minute),
+                ..Default::default()
+            },
+            now,
+        )
+        .is_err());
+        assert!(UsageQuery::resolve(
+            &UsageDashboardQuery {
+                range: Some("all".to_string()),
+                bucket: Some(UsageBucket::Hour),
+                ..Default::default()
+            },
+            now,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn latency_sampling_caps_loaded_rows() {
+        assert_eq!(latency_sample_stride(0), 1);
+        assert_eq!(latency_sample_stride(MAX_LATENCY_SAMPLES), 1);
+        assert_eq!(latency_sample_stride(MAX_LATENCY_SAMPLES + 1), 2);
+        for matching in [MAX_LATENCY_SAMPLES * 3, MAX_LATENCY_SAMPLES * 90 + 7] {
+            let stride = latency_sample_stride(matching);
+            assert!(matching / stride <= MAX_LATENCY_SAMPLES);
+        }
+    }
+
+    #[tokio::test]
+    async fn dashboard_buckets_by_time_zone_across_dst() {
+        let dir = temp_db_dir();
+        let tracker = UsageTracker::open(dir.join("usage.db")).unwrap();
+        // New York leaves UTC-5 for UTC-4 on 2025-03-09. These are 23:30 on March 7 and
+        // 00:30 on March 10 local time; any single offset puts one of them on the wrong day.
+        for (id, (day, hour)) in [(8, 4), (10, 4)].into_iter().enumerate() {
+            let ts = Utc
+                .with_ymd_and_hms(2025, 3, day, hour, 30, 0)
+                .unwrap()
+                .timestamp();
+            tracker
+                .record_event(event(&format!("r{}", id), ts, 10))
+                .await
+                .unwrap();
+        }
+
+        let request = UsageDashboardQuery {
+            from: Some("2025-03-01".to_string()),
+            to: Some("2025-03-15".to_string()),
+            bucket: Some(UsageBucket::Day),
+            utc_offset_minutes: -300,
+            time_zone: Some("America/New_York".to_string()),
+            ..Default::default()
+        };
+        let dashboard = tracker
+            .get_usage_dashboard(
+                UsageQuery::resolve(&request, Utc::now()).unwrap(),
+                PricingTable::default(),
+            )
+            .await
+            .unwrap();
+        let buckets: Vec<&str> = dashboard
+            .timeseries
+            .iter()
+            .map(|point| point.bucket.as_str())
+            .collect();
+        assert_eq!(buckets, ["2025-03-07", "2025-03-10"]);
+
+        let unknown = UsageDashboardQuery {
+            time_zone: Some("Mars/Olympus_Mons".to_string()),
+            ..request
+        };
+        assert_eq!(
+            UsageQuery::resolve(&unknown, Utc::now()).err().as_deref(),
+            Some("Unknown time zone: Mars/Olympus_Mons")
+        );
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn dashboard_buckets_in_local_time_and_pages_breakdown() {
+        let dir = temp_db_dir();
+        let tracker = UsageTracker::open(dir.join("usage.db")).unwrap();
+        // 2025-06-15 02:30 UTC is still 2025-06-14 in UTC-5.
+        let ts = Utc
+            .with_ymd_and_hms(2025, 6, 15, 2, 30, 0)
+            .unwrap()
+            .timestamp();
+        for (i, model) in ["claude-sonnet-4-5", "claude-opus-4-5", "claude-haiku-4-5"]
+            .iter()
+            .enumerate()
+        {
+            tracker
+                .record_event(UsageEvent {
+                    model: model.to_string(),
+                    ..event(&format!("r{}", i), ts, 10 * (i as i64 + 1))
+                })
+                .await
+                .unwrap();
+        }
+        tracker
+            .record_event(UsageEvent {
+                status_code: 500,
+                error_category: Some(ErrorCategory::Upstream5xx),
+                error_message: Some("Internal server error".to_string()),
+                ..event("failed", ts, 0)
+            })
+            .await
+            .unwrap();
+
+        let request = UsageDashboardQuery {
+            from: Some("2025-06-14".to_string()),
+            to: Some("2025-06-15".to_string()),
+            bucket: Some(UsageBucket::Day),
+            utc_offset_minutes: -300,
+            page_size: 2,
+            ..Default::default()
+        };
+        let now = Utc::now();
+        let dashboard = tracker
+            .get_usage_dashboard(
+                UsageQuery::resolve(&request, now).unwrap(),
+                PricingTable::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(dashboard.timeseries.len(), 1);
+        assert_eq!(dashboard.timeseries[0].bucket, "2025-06-14");
+        assert_eq!(dashboard.summary.total_requests, 4);
+        assert_eq!(dashboard.breakdown_total, 3);
+        assert_eq!(dashboard.breakdown.len(), 2);
+        assert_eq!(dashboard.breakdown[0].model, "claude-haiku-4-5");
+        // Latency only counts successful requests.
+        assert_eq!(dashboard.latency.overall.samples, 3);
+        assert_eq!(dashboard.errors.len(), 1);
+        assert_eq!(
+            dashboard.errors[0].category,
+            Some(ErrorCategory::Upstream5xx)
+        );
+        assert_eq!(
+            dashboard.errors[0].last_message.as_deref(),
+            Some("Internal server error")
+        );
+        assert_eq!(dashboard.latency.overall.ttfb_ms.p50, Some(5.0));
+
+        let second_page = UsageDashboardQuery {
+            page: 1,
+            ..request.clone()
+        };
+        let dashboard = tracker
+            .get_usage_dashboard(
+                UsageQuery::resolve(&second_page, now).unwrap(),
+                PricingTable::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(dashboard.breakdown.len(), 1);
+
+        let errors_only = UsageDashboardQuery {
+            status: Some(UsageStatusClass::Error),
+            ..request
+        };
+        let dashboard = tracker
+            .get_usage_dashboard(
+                UsageQuery::resolve(&errors_only, now).unwrap(),
+                PricingTable::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(dashboard.summary.total_requests, 1);
+        assert_eq!(dashboard.providers.len(), 1);
+        assert_eq!(dashboard.breakdown_total, 1);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn retention_prunes_events_but_keeps_rollups() {
+        let dir = temp_db_dir();
+        let db_path = dir.join("usage.db");
+
+        let now = Utc::now().timestamp();
+        let tracker = UsageTracker::open(db_path.clone()).unwrap();
+        tracker
+            .record_event(event("old", now - 40 * 86_400, 100))
+            .await
+            .unwrap();
+        tracker
+            .record_event(event("stale-json", now - 10 * 86_400, 20))
+            .await
+            .unwrap();
+        tracker.record_event(event("new", now, 5)).await.unwrap();
+
+        let report = tracker
+            .run_maintenance(UsageRetention {
+                raw_event_days: 30,
+                usage_json_days: 7,
+            })
+            .await
+            .unwrap();
+        assert_eq!(report.pruned_events, 1);
+        assert_eq!(report.cleared_usage_json, 1);
+
+        let stats = tracker.get_storage_stats().await.unwrap();
+        assert_eq!(stats.event_count, 2);
+        assert_eq!(stats.events_with_usage_json, 1);
+        assert_eq!(stats.rollup_count, 3);
+
+        // Reopening rebuilds rollups from raw events without dropping pruned days.
+        let tracker = UsageTracker::open(db_path).unwrap();
+        let stats = tracker.get_storage_stats().await.unwrap();
+        assert_eq!(stats.rollup_count, 3);
+        let conn = UsageTracker::open_connection(&tracker.db_path).unwrap();
+        let total: i64 = conn
+            .query_row(
+                "SELECT SUM(total_tokens) FROM usage_rollups_daily",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(total, 125);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn dashboard_totals_survive_retention() {
+        let dir = temp_db_dir();
+        let tracker = UsageTracker::open(dir.join("usage.db")).unwrap();
+        let now = Utc::now();
+        let old = now.timestamp() - 40 * 86_400;
+        tracker.record_event(event("old", old, 100)).await.unwrap();
+        tracker
+            .record_event(UsageEvent {
+                status_code: 500,
+                account_key: "b@example.com".to_string(),
+                account_label: "Account B".to_string(),
+                ..event("old-failed", old, 7)
+            })
+            .await
+            .unwrap();
+        tracker
+            .record_event(event("new", now.timestamp(), 5))
+            .await
+            .unwrap();
+
+        let dashboard = |range: &str, bucket: Option<UsageBucket>| {
+            let request = UsageDashboardQuery {
+                range: Some(range.to_string()),
+                bucket,
+                ..Default::default()
+            };
+            let tracker = tracker.clone();
+            async move {
+                tracker
+                    .get_usage_dashboard(
+                        UsageQuery::resolve(&request, now).unwrap(),
+                        PricingTable::default(),
+                    )
+                    .await
+                    .unwrap()
+            }
+        };
+        let points = |d: &UsageDashboard| {
+            d.timeseries
+                .iter()
+                .map(|p| (p.bucket.clone(), p.requests, p.total_tokens, p.error_count))
+                .collect::<Vec<_>>()
+        };
+        let accounts = |d: &UsageDashboard| {
+            d.breakdown
+                .iter()
+                .map(|r| {
+                    (
+                        r.account_key.clone(),
+                        r.account_label.clone(),
+                        r.total_tokens,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let before = dashboard("all", Some(UsageBucket::Day)).await;
+        tracker
+            .run_maintenance(UsageRetention {
+                raw_event_days: 30,
+                usage_json_days: 0,
+            })
+            .await
+            .unwrap();
+        let after = dashboard("all", Some(UsageBucket::Day)).await;
+
+        assert_eq!(after.summary.total_requests, 3);
+        assert_eq!(after.summary.total_tokens, before.summary.total_tokens);
+        assert_eq!(after.summary.error_count, 1);
+        assert_eq!(points(&after), points(&before));
+        assert_eq!(after.providers[0].total_tokens, 112);
+        assert_eq!(after.breakdown_total, 2);
+        // Pruned-only accounts fall back to their key for a label.
+        assert_eq!(
+            accounts(&after),
+            vec![
+                (
+                    "a@example.com".to_string(),
+                    "a@example.com".to_string(),
+                    105
+                ),
+                ("b@example.com".to_string(), "b@example.com".to_string(), 7),
+            ]
+        );
+        assert_eq!(after.truncated_before, None);
+
+        let monthly = dashboard("all", None).await;
+        assert_eq!(monthly.summary.total_tokens, 112);
+        assert_eq!(
+            monthly
+                .timeseries
+                .iter()
+                .map(|p| p.total_tokens)
+                .sum::<i64>(),
+            112
+        );
+
+        // Hourly buckets can't be served from daily rollups, so the range is marked as cut.
+        let request = UsageDashboardQuery {
+            from: Some((now - chrono::Duration::days(45)).to_rfc3339()),
+            bucket: Some(UsageBucket::Hour),
+            ..Default::default()
+        };
+        let hourly = tracker
+            .get_usage_dashboard(
+                UsageQuery::resolve(&request, now).unwrap(),
+                PricingTable::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(hourly.summary.total_tokens, 5);
+        assert_eq!(
+            hourly.truncated_before,
+            Some(now.format("%Y-%m-%d").to_string())
+        );
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
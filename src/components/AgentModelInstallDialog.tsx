@@ -6,41 +6,40 @@ import {
   type FactoryCustomModelInput,
   type ProviderModelDefinitionsResponse,
   type ProviderModelInfo,
+  type ServerState,
 } from "../types";
 import { toErrorMessage } from "../utils/error";
 import { renderAgentModelInstallDialogView } from "./AgentModelInstallDialogView";
 
 type FactoryProvider = "anthropic" | "openai";
 
-function channelDefaults(channel: string): { provider: FactoryProvider; baseUrl: string } {
+const DEFAULT_PROXY_PORT = 8317;
+
+function channelDefaults(
+  channel: string,
+  proxyPort: number,
+): { provider: FactoryProvider; baseUrl: string } {
   if (channel === "claude") {
-    return { provider: "anthropic", baseUrl: "http://localhost:8317" };
+    return { provider: "anthropic", baseUrl: `http://localhost:${proxyPort}` };
   }
-  return { provider: "openai", baseUrl: "http://localhost:8317/v1" };
+  return { provider: "openai", baseUrl: `http://localhost:${proxyPort}/v1` };
 }
 
-function isProxyBaseUrl(raw: string): boolean {
+function isProxyBaseUrl(raw: string, proxyPort: number): boolean {
   const trimmed = raw.trim();
   if (trimmed === "") return false;
-  const lower = trimmed.toLowerCase();
-  if (
-    lower.startsWith("http://localhost:8317") ||
-    lower.startsWith("https://localhost:8317") ||
-    lower.startsWith("http://127.0.0.1:8317") ||
-    lower.startsWith("https://127.0.0.1:8317") ||
-    lower.startsWith("http://0.0.0.0:8317") ||
-    lower.startsWith("https://0.0.0.0:8317") ||
-    lower.startsWith("http://[::1]:8317") ||
-    lower.startsWith("https://[::1]:8317")
-  ) {
-    return true;
-  }
   try {
     const url = new URL(trimmed);
     const port = url.port ? Number(url.port) : url.protocol === "https:" ? 443 : 80;
-    if (port !== 8317) return false;
+    if (port !== proxyPort) return false;
     const host = url.hostname.toLowerCase();
-    return host === "localhost" || host === "127.0.0.1" || host === "0.0.0.0" || host === "::1";
+    return (
+      host === "localhost" ||
+      host === "127.0.0.1" ||
+      host === "0.0.0.0" ||
+      host === "::1" ||
+      host === "[::1]"
+    );
   } catch {
     return false;
   }
@@ -165,9 +164,10 @@ function createInitialState(
   initialChannel: string | undefined,
   defaultDisplayPrefix: string | undefined,
   agentLabel: string,
+  proxyPort: number,
 ): InstallDialogState {
   const channel = initialChannel ?? "claude";
-  const defaults = channelDefaults(channel);
+  const defaults = channelDefaults(channel, proxyPort);
   return {
     channel,
     search: "",
@@ -195,8 +195,9 @@ function useAgentModelInstallDialog({
   onClose,
   onInstalled,
 }: AgentModelInstallDialogProps) {
+  const [proxyPort, setProxyPort] = useState(DEFAULT_PROXY_PORT);
   const [dialogState, setDialogState] = useState<InstallDialogState>(() =>
-    createInitialState(initialChannel, defaultDisplayPrefix, agentLabel),
+    createInitialState(initialChannel, defaultDisplayPrefix, agentLabel, DEFAULT_PROXY_PORT),
   );
   const [modelsFetch, setModelsFetch] = useState<ModelsFetchState>({
     modelsResponse: null,
@@ -216,22 +217,29 @@ function useAgentModelInstallDialog({
     budgetCsv,
   } = dialogState;
 
+  useEffect(() => {
+    if (!isOpen) return;
+    invoke<ServerState>("get_server_state")
+      .then((state) => setProxyPort(state.proxy_port))
+      .catch((err) => console.error("Failed to load proxy port:", err));
+  }, [isOpen]);
+
   // eslint-disable-next-line react-doctor/no-cascading-set-state
   useEffect(() => {
     if (!isOpen) return;
-    setDialogState(createInitialState(initialChannel, defaultDisplayPrefix, agentLabel));
+    setDialogState(createInitialState(initialChannel, defaultDisplayPrefix, agentLabel, proxyPort));
     setModelsFetch((prev) => ({ ...prev, lastError: null }));
-  }, [agentLabel, defaultDisplayPrefix, initialChannel, isOpen]);
+  }, [agentLabel, defaultDisplayPrefix, initialChannel, isOpen, proxyPort]);
 
   useEffect(() => {
     if (!isOpen) return;
-    const next = channelDefaults(channel);
+    const next = channelDefaults(channel, proxyPort);
     setDialogState((prev) => ({
       ...prev,
       factoryProvider: next.provider,
       baseUrl: next.baseUrl,
     }));
-  }, [channel, isOpen]);
+  }, [channel, isOpen, proxyPort]);
 
   useEffect(() => {
     if (!isOpen) return;
@@ -427,7 +435,7 @@ function useAgentModelInstallDialog({
 
   const levelsDisabled = unionLevels.length === 0;
   const budgetsDisabled = selectedModels.every((m) => !canUseThinkingBudgets(m));
-  const canInstall = selectedModels.length > 0 && isProxyBaseUrl(baseUrl);
+  const canInstall = selectedModels.length > 0 && isProxyBaseUrl(baseUrl, proxyPort);
 
   const handleChannelChange = (nextChannel: string) => {
     setDialogState((prev) => ({ ...prev, channel: nextChannel }));
@@ -495,7 +503,8 @@ function useAgentModelInstallDialog({
     canInstall,
     isLoading: modelsFetch.isLoading,
     lastError: modelsFetch.lastError,
-    isProxyBaseUrl: isProxyBaseUrl(baseUrl),
+    isProxyBaseUrl: isProxyBaseUrl(baseUrl, proxyPort),
+    proxyPort,
     onClose,
     onInstall: handleInstall,
     onSetAllVisible: setAllVisible,
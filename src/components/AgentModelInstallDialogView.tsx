@@ -45,6 +45,7 @@ interface AgentModelInstallDialogViewProps {
   isLoading: boolean;
   lastError: string | null;
   isProxyBaseUrl: boolean;
+  proxyPort: number;
   onClose: () => void;
   onInstall: () => void;
   onSetAllVisible: (checked: boolean) => void;
@@ -95,6 +96,7 @@ export function renderAgentModelInstallDialogView({
   isLoading,
   lastError,
   isProxyBaseUrl,
+  proxyPort,
   onClose,
   onInstall,
   onSetAllVisible,
@@ -304,7 +306,7 @@ export function renderAgentModelInstallDialogView({
                     />
                     {!isProxyBaseUrl ? (
                       <span className="text-xs text-destructive">
-                        Only proxy URLs on localhost:8317 are supported.
+                        Only proxy URLs on localhost:{proxyPort} are supported.
                       </span>
                     ) : null}
                   </div>
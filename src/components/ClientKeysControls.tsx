@@ -0,0 +1,137 @@
+import { useCallback, useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { Copy, KeyRound, Trash2 } from "lucide-react";
+import type { ClientKeyInfo, CreatedClientKey } from "../types";
+import { toErrorMessage } from "../utils/error";
+import { Button } from "./ui/button";
+import { Input } from "./ui/input";
+import { Switch } from "./ui/switch";
+
+interface ClientKeysControlsProps {
+  requireClientApiKey: boolean;
+  onRequireChange: (enabled: boolean) => void;
+}
+
+export default function ClientKeysControls({
+  requireClientApiKey,
+  onRequireChange,
+}: ClientKeysControlsProps) {
+  const [keys, setKeys] = useState<ClientKeyInfo[]>([]);
+  const [newName, setNewName] = useState("");
+  const [created, setCreated] = useState<CreatedClientKey | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  const refresh = useCallback(async () => {
+    try {
+      setKeys(await invoke<ClientKeyInfo[]>("list_client_keys"));
+    } catch (err) {
+      setError(toErrorMessage(err, "Failed to load client keys"));
+    }
+  }, []);
+
+  useEffect(() => {
+    refresh();
+  }, [refresh]);
+
+  const createKey = async () => {
+    try {
+      const key = await invoke<CreatedClientKey>("create_client_key", { name: newName.trim() });
+      setCreated(key);
+      setNewName("");
+      setError(null);
+      await refresh();
+    } catch (err) {
+      setError(toErrorMessage(err, "Failed to create client key"));
+    }
+  };
+
+  const deleteKey = async (id: string) => {
+    try {
+      await invoke("delete_client_key", { id });
+      if (created?.id === id) setCreated(null);
+      setError(null);
+      await refresh();
+    } catch (err) {
+      setError(toErrorMessage(err, "Failed to delete client key"));
+    }
+  };
+
+  return (
+    <div className="flex flex-col gap-3 py-3.5 border-b border-border">
+      <div className="flex items-center justify-between gap-4">
+        <div className="flex min-w-0 flex-1 flex-col gap-0.5">
+          <span className="text-sm font-medium">Client API keys</span>
+          <small className="text-xs text-muted-foreground">
+            Issue a key per tool and send it as <code>Authorization: Bearer</code> or{" "}
+            <code>x-api-key</code>. Usage is attributed to the key's name.
+          </small>
+        </div>
+        <Switch
+          checked={requireClientApiKey}
+          onCheckedChange={onRequireChange}
+          disabled={!requireClientApiKey && keys.length === 0}
+          aria-label="Require client API key"
+        />
+      </div>
+      <small className="text-xs text-muted-foreground">
+        {requireClientApiKey
+          ? "Requests without a valid key are rejected."
+          : "Keys are optional; requests without one are still accepted."}
+      </small>
+
+      {keys.length > 0 && (
+        <div className="flex flex-col gap-1.5">
+          {keys.map((key) => (
+            <div key={key.id} className="flex items-center justify-between gap-3 text-sm">
+              <div className="flex min-w-0 items-center gap-2">
+                <KeyRound className="h-4 w-4 text-muted-foreground" />
+                <span className="font-medium truncate">{key.name}</span>
+                <code className="text-xs text-muted-foreground">{key.key_preview}</code>
+              </div>
+              <Button
+                size="sm"
+                variant="ghost"
+                onClick={() => deleteKey(key.id)}
+                aria-label={`Delete ${key.name}`}
+              >
+                <Trash2 className="h-4 w-4" />
+              </Button>
+            </div>
+          ))}
+        </div>
+      )}
+
+      {created && (
+        <div className="flex flex-col gap-1.5 rounded-md border border-border bg-muted/50 p-3">
+          <small className="text-xs text-muted-foreground">
+            Copy the key for "{created.name}" now. It won't be shown again.
+          </small>
+          <div className="flex items-center gap-2">
+            <code className="flex-1 truncate text-xs">{created.key}</code>
+            <Button
+              size="sm"
+              variant="outline"
+              onClick={() => navigator.clipboard.writeText(created.key)}
+            >
+              <Copy className="mr-2 h-4 w-4" />
+              Copy
+            </Button>
+          </div>
+        </div>
+      )}
+
+      <div className="flex items-center gap-2">
+        <Input
+          value={newName}
+          placeholder="Name, e.g. Factory"
+          onChange={(e) => setNewName(e.target.value)}
+        />
+        <Button size="sm" variant="outline" onClick={createKey} disabled={newName.trim() === ""}>
+          Create key
+        </Button>
+      </div>
+
+      {error && <small className="text-xs text-destructive">{error}</small>}
+    </div>
+  );
+}
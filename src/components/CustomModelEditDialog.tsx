@@ -93,7 +93,7 @@ export default function CustomModelEditDialog({
           <DialogTitle>Edit Custom Model</DialogTitle>
           <DialogDescription>
             {canEdit
-              ? "Edits apply to proxy models only (localhost:8317)."
+              ? "Edits apply to local proxy models only."
               : "This model is not using the local proxy, so it is view-only."}
           </DialogDescription>
         </DialogHeader>
@@ -0,0 +1,157 @@
+import { useState } from "react";
+import { Plus, Trash2, X } from "lucide-react";
+import type { FailoverChain, FailoverStep, FailoverTarget } from "../types";
+import { Button } from "./ui/button";
+import { Input } from "./ui/input";
+import { Switch } from "./ui/switch";
+
+const SELECT_CLASS =
+  "flex h-9 rounded-md border border-input bg-transparent px-2 py-1 text-sm shadow-sm transition-colors focus-visible:outline-none focus-visible:ring-1 focus-visible:ring-ring";
+
+const TARGET_LABELS: Record<FailoverTarget, string> = {
+  backend: "Backend",
+  vercel: "Vercel",
+};
+
+interface FailoverChainsControlsProps {
+  chains: FailoverChain[];
+  onSave: (chains: FailoverChain[]) => Promise<void>;
+}
+
+function describeStep(step: FailoverStep): string {
+  return `${TARGET_LABELS[step.target]}: ${step.model}`;
+}
+
+function describeChain(chain: FailoverChain): string {
+  return `"${chain.pattern}" → ${chain.steps.map(describeStep).join(" → ")}`;
+}
+
+export default function FailoverChainsControls({ chains, onSave }: FailoverChainsControlsProps) {
+  const [pattern, setPattern] = useState("");
+  const [steps, setSteps] = useState<FailoverStep[]>([]);
+  const [target, setTarget] = useState<FailoverTarget>("backend");
+  const [model, setModel] = useState("");
+
+  const addStep = () => {
+    if (model.trim() === "") return;
+    setSteps([...steps, { target, model: model.trim() }]);
+    setModel("");
+  };
+
+  const addChain = async () => {
+    if (pattern.trim() === "" || steps.length === 0) return;
+    await onSave([...chains, { id: "", enabled: true, pattern: pattern.trim(), steps }]);
+    setPattern("");
+    setSteps([]);
+  };
+
+  return (
+    <div className="flex flex-col gap-3 py-3.5 border-b border-border">
+      <div className="flex min-w-0 flex-col gap-0.5">
+        <span className="text-sm font-medium">Failover chains</span>
+        <small className="text-xs text-muted-foreground">
+          When a matching model fails with 429, 529 or a 5xx before anything is sent back, the
+          request moves on to each fallback in turn. A <code>*</code> in a fallback model takes
+          the text matched by the pattern; routes asking to retry later are skipped meanwhile.
+        </small>
+      </div>
+
+      {chains.length > 0 && (
+        <div className="flex flex-col gap-2">
+          {chains.map((chain) => (
+            <div key={chain.id} className="flex items-center justify-between gap-3 text-sm">
+              <span
+                className={`min-w-0 truncate font-mono text-xs ${chain.enabled ? "" : "text-muted-foreground line-through"}`}
+                title={describeChain(chain)}
+              >
+                {describeChain(chain)}
+              </span>
+              <div className="flex shrink-0 items-center gap-1">
+                <Switch
+                  checked={chain.enabled}
+                  onCheckedChange={(enabled) =>
+                    onSave(chains.map((c) => (c.id === chain.id ? { ...c, enabled } : c)))
+                  }
+                  aria-label={`Enable ${describeChain(chain)}`}
+                />
+                <Button
+                  size="sm"
+                  variant="ghost"
+                  onClick={() => onSave(chains.filter((c) => c.id !== chain.id))}
+                  aria-label={`Delete ${describeChain(chain)}`}
+                >
+                  <Trash2 className="h-4 w-4" />
+                </Button>
+              </div>
+            </div>
+          ))}
+        </div>
+      )}
+
+      <Input
+        value={pattern}
+        placeholder="claude-opus-*"
+        onChange={(e) => setPattern(e.target.value)}
+        aria-label="Model pattern"
+      />
+      {steps.length > 0 && (
+        <div className="flex flex-col gap-1">
+          {steps.map((step, index) => (
+            <div key={index} className="flex items-center justify-between gap-3 text-xs">
+              <span className="min-w-0 truncate font-mono">
+                {index + 1}. {describeStep(step)}
+              </span>
+              <Button
+                size="sm"
+                variant="ghost"
+                onClick={() => setSteps(steps.filter((_, i) => i !== index))}
+                aria-label={`Remove ${describeStep(step)}`}
+              >
+                <X className="h-4 w-4" />
+              </Button>
+            </div>
+          ))}
+        </div>
+      )}
+      <div className="flex items-center gap-2">
+        <select
+          className={SELECT_CLASS}
+          value={target}
+          onChange={(e) => setTarget(e.target.value as FailoverTarget)}
+          aria-label="Fallback target"
+        >
+          {(Object.keys(TARGET_LABELS) as FailoverTarget[]).map((value) => (
+            <option key={value} value={value} className="bg-background text-foreground">
+              {TARGET_LABELS[value]}
+            </option>
+          ))}
+        </select>
+        <Input
+          value={model}
+          placeholder={target === "vercel" ? "claude-opus-*" : "gemini-claude-opus-*"}
+          onChange={(e) => setModel(e.target.value)}
+          aria-label="Fallback model"
+        />
+        <Button
+          size="sm"
+          variant="ghost"
+          onClick={addStep}
+          disabled={model.trim() === ""}
+          aria-label="Add fallback"
+        >
+          <Plus className="h-4 w-4" />
+        </Button>
+      </div>
+      <div className="flex justify-end">
+        <Button
+          size="sm"
+          variant="outline"
+          onClick={addChain}
+          disabled={pattern.trim() === "" || steps.length === 0}
+        >
+          Add chain
+        </Button>
+      </div>
+    </div>
+  );
+}
@@ -0,0 +1,245 @@
+import { useCallback, useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { ArrowDown, ArrowUp, Trash2 } from "lucide-react";
+import type { ModelMatchKind, ModelRewritePreview, ModelRewriteRule } from "../types";
+import { toErrorMessage } from "../utils/error";
+import { Button } from "./ui/button";
+import { Input } from "./ui/input";
+import { Switch } from "./ui/switch";
+
+const SELECT_CLASS =
+  "flex h-9 rounded-md border border-input bg-transparent px-2 py-1 text-sm shadow-sm transition-colors focus-visible:outline-none focus-visible:ring-1 focus-visible:ring-ring";
+
+const MATCH_OPTIONS: Array<{ label: string; value: ModelMatchKind; placeholder: string }> = [
+  { label: "Exact", value: "exact", placeholder: "fast" },
+  { label: "Glob", value: "glob", placeholder: "gpt-5*-mini" },
+  { label: "Regex", value: "regex", placeholder: "sonnet-(\\d+)" },
+];
+
+interface RuleForm {
+  matchKind: ModelMatchKind;
+  pattern: string;
+  targetModel: string;
+  inject: string;
+}
+
+const EMPTY_FORM: RuleForm = { matchKind: "exact", pattern: "", targetModel: "", inject: "" };
+
+function parseInject(raw: string): Record<string, unknown> | null {
+  if (raw.trim() === "") return {};
+  try {
+    const value = JSON.parse(raw);
+    return value && typeof value === "object" && !Array.isArray(value) ? value : null;
+  } catch {
+    return null;
+  }
+}
+
+function describeRule(rule: ModelRewriteRule): string {
+  const target = rule.target_model || "(same model)";
+  const fields = Object.keys(rule.inject);
+  return `${rule.match_kind} "${rule.pattern}" → ${target}${fields.length > 0 ? ` + ${fields.join(", ")}` : ""}`;
+}
+
+export default function ModelRulesControls() {
+  const [rules, setRules] = useState<ModelRewriteRule[]>([]);
+  const [form, setForm] = useState<RuleForm>(EMPTY_FORM);
+  const [previewModel, setPreviewModel] = useState("");
+  const [preview, setPreview] = useState<ModelRewritePreview | null | undefined>(undefined);
+  const [error, setError] = useState<string | null>(null);
+
+  const refresh = useCallback(async () => {
+    try {
+      setRules(await invoke<ModelRewriteRule[]>("get_model_rules"));
+    } catch (err) {
+      setError(toErrorMessage(err, "Failed to load model rules"));
+    }
+  }, []);
+
+  useEffect(() => {
+    refresh();
+  }, [refresh]);
+
+  const saveRules = async (next: ModelRewriteRule[]) => {
+    try {
+      setRules(await invoke<ModelRewriteRule[]>("set_model_rules", { rules: next }));
+      setPreview(undefined);
+      setError(null);
+      return true;
+    } catch (err) {
+      setError(toErrorMessage(err, "Failed to save model rules"));
+      return false;
+    }
+  };
+
+  const addRule = async () => {
+    const inject = parseInject(form.inject);
+    if (inject === null) {
+      setError("Injected fields must be a JSON object");
+      return;
+    }
+    const rule: ModelRewriteRule = {
+      id: "",
+      enabled: true,
+      match_kind: form.matchKind,
+      pattern: form.pattern.trim(),
+      target_model: form.targetModel.trim(),
+      inject,
+    };
+    if (await saveRules([...rules, rule])) {
+      setForm(EMPTY_FORM);
+    }
+  };
+
+  const moveRule = (index: number, offset: number) => {
+    const next = [...rules];
+    const [rule] = next.splice(index, 1);
+    next.splice(index + offset, 0, rule);
+    saveRules(next);
+  };
+
+  const runPreview = async () => {
+    try {
+      setPreview(
+        await invoke<ModelRewritePreview | null>("preview_model_rewrite", { model: previewModel }),
+      );
+      setError(null);
+    } catch (err) {
+      setError(toErrorMessage(err, "Failed to preview model rules"));
+    }
+  };
+
+  const canAdd =
+    form.pattern.trim() !== "" && (form.targetModel.trim() !== "" || form.inject.trim() !== "");
+  const placeholder =
+    MATCH_OPTIONS.find((option) => option.value === form.matchKind)?.placeholder ?? "";
+
+  return (
+    <div className="flex flex-col gap-3 py-3.5 border-b border-border">
+      <div className="flex min-w-0 flex-col gap-0.5">
+        <span className="text-sm font-medium">Model rules</span>
+        <small className="text-xs text-muted-foreground">
+          Rewrite incoming model names and inject request fields before forwarding. The first
+          matching rule wins; targets may use <code>-thinking-N</code> suffixes.
+        </small>
+      </div>
+
+      {rules.length > 0 && (
+        <div className="flex flex-col gap-2">
+          {rules.map((rule, index) => (
+            <div key={rule.id} className="flex items-center justify-between gap-3 text-sm">
+              <span
+                className={`min-w-0 truncate font-mono text-xs ${rule.enabled ? "" : "text-muted-foreground line-through"}`}
+                title={JSON.stringify(rule.inject)}
+              >
+                {describeRule(rule)}
+              </span>
+              <div className="flex shrink-0 items-center gap-1">
+                <Switch
+                  checked={rule.enabled}
+                  onCheckedChange={(enabled) =>
+                    saveRules(rules.map((r) => (r.id === rule.id ? { ...r, enabled } : r)))
+                  }
+                  aria-label={`Enable ${describeRule(rule)}`}
+                />
+                <Button
+                  size="sm"
+                  variant="ghost"
+                  onClick={() => moveRule(index, -1)}
+                  disabled={index === 0}
+                  aria-label="Move rule up"
+                >
+                  <ArrowUp className="h-4 w-4" />
+                </Button>
+                <Button
+                  size="sm"
+                  variant="ghost"
+                  onClick={() => moveRule(index, 1)}
+                  disabled={index === rules.length - 1}
+                  aria-label="Move rule down"
+                >
+                  <ArrowDown className="h-4 w-4" />
+                </Button>
+                <Button
+                  size="sm"
+                  variant="ghost"
+                  onClick={() => saveRules(rules.filter((r) => r.id !== rule.id))}
+                  aria-label={`Delete ${describeRule(rule)}`}
+                >
+                  <Trash2 className="h-4 w-4" />
+                </Button>
+              </div>
+            </div>
+          ))}
+        </div>
+      )}
+
+      <div className="grid grid-cols-2 gap-2 md:grid-cols-3">
+        <select
+          className={SELECT_CLASS}
+          value={form.matchKind}
+          onChange={(e) => setForm({ ...form, matchKind: e.target.value as ModelMatchKind })}
+          aria-label="Match kind"
+        >
+          {MATCH_OPTIONS.map((option) => (
+            <option key={option.value} value={option.value} className="bg-background text-foreground">
+              {option.label}
+            </option>
+          ))}
+        </select>
+        <Input
+          value={form.pattern}
+          placeholder={placeholder}
+          onChange={(e) => setForm({ ...form, pattern: e.target.value })}
+        />
+        <Input
+          className="col-span-2 md:col-span-1"
+          value={form.targetModel}
+          placeholder="claude-haiku-4-5"
+          onChange={(e) => setForm({ ...form, targetModel: e.target.value })}
+        />
+        <Input
+          className="col-span-2 md:col-span-3 font-mono text-xs"
+          value={form.inject}
+          placeholder='{"reasoning_effort": "high"}'
+          onChange={(e) => setForm({ ...form, inject: e.target.value })}
+        />
+      </div>
+      <div className="flex justify-end">
+        <Button size="sm" variant="outline" onClick={addRule} disabled={!canAdd}>
+          Add rule
+        </Button>
+      </div>
+
+      <form
+        className="flex items-center gap-2"
+        onSubmit={(e) => {
+          e.preventDefault();
+          runPreview();
+        }}
+      >
+        <Input
+          className="h-8 text-xs"
+          value={previewModel}
+          placeholder="Try a model name"
+          onChange={(e) => {
+            setPreviewModel(e.target.value);
+            setPreview(undefined);
+          }}
+        />
+        <Button type="submit" size="sm" variant="ghost" disabled={previewModel.trim() === ""}>
+          Test
+        </Button>
+      </form>
+      {preview !== undefined && (
+        <small className="text-xs text-muted-foreground font-mono">
+          {preview === null
+            ? "No rule matches; the model is forwarded unchanged."
+            : `→ ${preview.model}${Object.keys(preview.inject).length > 0 ? ` + ${JSON.stringify(preview.inject)}` : ""}`}
+        </small>
+      )}
+
+      {error && <small className="text-xs text-destructive">{error}</small>}
+    </div>
+  );
+}
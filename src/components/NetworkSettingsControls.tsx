@@ -0,0 +1,114 @@
+import { useState } from "react";
+import { Save } from "lucide-react";
+import { Button } from "./ui/button";
+import { Input } from "./ui/input";
+
+interface NetworkSettingsControlsProps {
+  bindAddress: string;
+  proxyPort: number;
+  backendPort: number;
+  requireClientApiKey: boolean;
+  onSave: (bindAddress: string, proxyPort: number, backendPort: number) => void;
+}
+
+function parsePort(raw: string): number | null {
+  const n = Number(raw.trim());
+  return Number.isInteger(n) && n > 0 && n <= 65535 ? n : null;
+}
+
+function isLoopback(address: string): boolean {
+  const trimmed = address.trim();
+  return trimmed === "::1" || /^127\.\d{1,3}\.\d{1,3}\.\d{1,3}$/.test(trimmed);
+}
+
+export default function NetworkSettingsControls({
+  bindAddress,
+  proxyPort,
+  backendPort,
+  requireClientApiKey,
+  onSave,
+}: NetworkSettingsControlsProps) {
+  const [localBindAddress, setLocalBindAddress] = useState(bindAddress);
+  const [localProxyPort, setLocalProxyPort] = useState(String(proxyPort));
+  const [localBackendPort, setLocalBackendPort] = useState(String(backendPort));
+
+  const parsedProxyPort = parsePort(localProxyPort);
+  const parsedBackendPort = parsePort(localBackendPort);
+  const exposedWithoutKeys =
+    localBindAddress.trim() !== "" && !isLoopback(localBindAddress) && !requireClientApiKey;
+  const isValid =
+    localBindAddress.trim() !== "" &&
+    !exposedWithoutKeys &&
+    parsedProxyPort !== null &&
+    parsedBackendPort !== null &&
+    parsedProxyPort !== parsedBackendPort;
+  const hasChanges =
+    localBindAddress.trim() !== bindAddress ||
+    parsedProxyPort !== proxyPort ||
+    parsedBackendPort !== backendPort;
+
+  return (
+    <div className="flex flex-col gap-3 py-3.5 border-b border-border">
+      <div className="flex min-w-0 flex-1 flex-col gap-0.5">
+        <span className="text-sm font-medium">Network</span>
+        <small className="text-xs text-muted-foreground">
+          Listen address and ports. Saving restarts a running server.
+        </small>
+      </div>
+      <div className="grid grid-cols-3 gap-3">
+        <div className="flex flex-col gap-1.5">
+          <label className="text-xs font-medium text-muted-foreground" htmlFor="network-bind-address">
+            Bind address
+          </label>
+          <Input
+            id="network-bind-address"
+            value={localBindAddress}
+            placeholder="127.0.0.1"
+            onChange={(e) => setLocalBindAddress(e.target.value)}
+          />
+        </div>
+        <div className="flex flex-col gap-1.5">
+          <label className="text-xs font-medium text-muted-foreground" htmlFor="network-proxy-port">
+            Proxy port
+          </label>
+          <Input
+            id="network-proxy-port"
+            inputMode="numeric"
+            value={localProxyPort}
+            onChange={(e) => setLocalProxyPort(e.target.value)}
+          />
+        </div>
+        <div className="flex flex-col gap-1.5">
+          <label className="text-xs font-medium text-muted-foreground" htmlFor="network-backend-port">
+            Backend port
+          </label>
+          <Input
+            id="network-backend-port"
+            inputMode="numeric"
+            value={localBackendPort}
+            onChange={(e) => setLocalBackendPort(e.target.value)}
+          />
+        </div>
+      </div>
+      {exposedWithoutKeys && (
+        <small className="text-xs text-destructive">
+          Listening beyond this machine exposes your accounts to the network. Turn on "Require
+          client API key" below before using this address.
+        </small>
+      )}
+      <div className="flex justify-end">
+        <Button
+          size="sm"
+          variant="outline"
+          onClick={() =>
+            isValid && onSave(localBindAddress.trim(), parsedProxyPort!, parsedBackendPort!)
+          }
+          disabled={!hasChanges || !isValid}
+        >
+          <Save className="mr-2 h-4 w-4" />
+          Save
+        </Button>
+      </div>
+    </div>
+  );
+}
@@ -0,0 +1,190 @@
+import { useCallback, useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { RotateCcw } from "lucide-react";
+import type { ModelPrice, ModelPriceEntry } from "../types";
+import { toErrorMessage } from "../utils/error";
+import { Button } from "./ui/button";
+import { Input } from "./ui/input";
+
+interface PriceForm {
+  model: string;
+  input: string;
+  output: string;
+  cacheRead: string;
+  cacheWrite: string;
+}
+
+const EMPTY_FORM: PriceForm = { model: "", input: "", output: "", cacheRead: "", cacheWrite: "" };
+
+function formatRate(value: number | null): string {
+  return value === null ? "-" : `$${value}`;
+}
+
+function parseOptionalRate(value: string): number | null {
+  return value.trim() === "" ? null : Number(value);
+}
+
+export default function PricingOverridesControls() {
+  const [entries, setEntries] = useState<ModelPriceEntry[]>([]);
+  const [form, setForm] = useState<PriceForm>(EMPTY_FORM);
+  const [error, setError] = useState<string | null>(null);
+
+  const refresh = useCallback(async () => {
+    try {
+      setEntries(await invoke<ModelPriceEntry[]>("get_model_pricing"));
+    } catch (err) {
+      setError(toErrorMessage(err, "Failed to load model pricing"));
+    }
+  }, []);
+
+  useEffect(() => {
+    refresh();
+  }, [refresh]);
+
+  const editEntry = (entry: ModelPriceEntry) => {
+    setForm({
+      model: entry.model,
+      input: String(entry.price.input),
+      output: String(entry.price.output),
+      cacheRead: entry.price.cache_read === null ? "" : String(entry.price.cache_read),
+      cacheWrite: entry.price.cache_write === null ? "" : String(entry.price.cache_write),
+    });
+  };
+
+  const saveOverride = async () => {
+    const price: ModelPrice = {
+      input: Number(form.input),
+      output: Number(form.output),
+      cache_read: parseOptionalRate(form.cacheRead),
+      cache_write: parseOptionalRate(form.cacheWrite),
+    };
+    try {
+      await invoke("set_model_price_override", { model: form.model, price });
+      setForm(EMPTY_FORM);
+      setError(null);
+      await refresh();
+    } catch (err) {
+      setError(toErrorMessage(err, "Failed to save price override"));
+    }
+  };
+
+  const resetOverride = async (model: string) => {
+    try {
+      await invoke("set_model_price_override", { model, price: null });
+      setError(null);
+      await refresh();
+    } catch (err) {
+      setError(toErrorMessage(err, "Failed to reset price override"));
+    }
+  };
+
+  const canSave =
+    form.model.trim() !== "" && form.input.trim() !== "" && form.output.trim() !== "";
+
+  return (
+    <div className="flex flex-col gap-3 py-3.5 border-b border-border">
+      <div className="flex min-w-0 flex-col gap-0.5">
+        <span className="text-sm font-medium">Model pricing</span>
+        <small className="text-xs text-muted-foreground">
+          USD per million tokens, matched by model-name prefix. Used for the estimated API cost
+          on the Usage tab. Click a row to override it.
+        </small>
+      </div>
+
+      <div className="max-h-[220px] overflow-auto rounded-md border border-border">
+        <table className="w-full text-xs">
+          <thead className="bg-muted/50 text-muted-foreground">
+            <tr>
+              <th className="px-2 py-1.5 text-left font-medium">Model prefix</th>
+              <th className="px-2 py-1.5 text-right font-medium">Input</th>
+              <th className="px-2 py-1.5 text-right font-medium">Output</th>
+              <th className="px-2 py-1.5 text-right font-medium">Cache read</th>
+              <th className="px-2 py-1.5 text-right font-medium">Cache write</th>
+              <th className="w-8" />
+            </tr>
+          </thead>
+          <tbody>
+            {entries.map((entry) => (
+              <tr
+                key={entry.model}
+                className="cursor-pointer border-t border-border hover:bg-muted/50"
+                onClick={() => editEntry(entry)}
+              >
+                <td className={`px-2 py-1.5 ${entry.is_override ? "font-medium" : ""}`}>
+                  {entry.model}
+                </td>
+                <td className="px-2 py-1.5 text-right tabular-nums">{formatRate(entry.price.input)}</td>
+                <td className="px-2 py-1.5 text-right tabular-nums">{formatRate(entry.price.output)}</td>
+                <td className="px-2 py-1.5 text-right tabular-nums">{formatRate(entry.price.cache_read)}</td>
+                <td className="px-2 py-1.5 text-right tabular-nums">{formatRate(entry.price.cache_write)}</td>
+                <td className="px-1 py-0.5 text-right">
+                  {entry.is_override && (
+                    <Button
+                      size="sm"
+                      variant="ghost"
+                      onClick={(e) => {
+                        e.stopPropagation();
+                        resetOverride(entry.model);
+                      }}
+                      aria-label={`Reset ${entry.model} to default price`}
+                    >
+                      <RotateCcw className="h-3.5 w-3.5" />
+                    </Button>
+                  )}
+                </td>
+              </tr>
+            ))}
+          </tbody>
+        </table>
+      </div>
+
+      <div className="grid grid-cols-2 gap-2 md:grid-cols-5">
+        <Input
+          className="col-span-2 md:col-span-1"
+          value={form.model}
+          placeholder="Model prefix"
+          onChange={(e) => setForm({ ...form, model: e.target.value })}
+        />
+        <Input
+          type="number"
+          min={0}
+          step="any"
+          value={form.input}
+          placeholder="Input"
+          onChange={(e) => setForm({ ...form, input: e.target.value })}
+        />
+        <Input
+          type="number"
+          min={0}
+          step="any"
+          value={form.output}
+          placeholder="Output"
+          onChange={(e) => setForm({ ...form, output: e.target.value })}
+        />
+        <Input
+          type="number"
+          min={0}
+          step="any"
+          value={form.cacheRead}
+          placeholder="Cache read"
+          onChange={(e) => setForm({ ...form, cacheRead: e.target.value })}
+        />
+        <Input
+          type="number"
+          min={0}
+          step="any"
+          value={form.cacheWrite}
+          placeholder="Cache write"
+          onChange={(e) => setForm({ ...form, cacheWrite: e.target.value })}
+        />
+      </div>
+      <div className="flex justify-end">
+        <Button size="sm" variant="outline" onClick={saveOverride} disabled={!canSave}>
+          Save override
+        </Button>
+      </div>
+
+      {error && <small className="text-xs text-destructive">{error}</small>}
+    </div>
+  );
+}
@@ -0,0 +1,103 @@
+import { useState } from "react";
+import { Save } from "lucide-react";
+import type { ProxyRetries } from "../types";
+import { Button } from "./ui/button";
+import { Input } from "./ui/input";
+
+interface ProxyRetriesControlsProps {
+  retries: ProxyRetries;
+  onSave: (retries: ProxyRetries) => Promise<void>;
+}
+
+function parseWhole(raw: string): number | null {
+  const n = Number(raw.trim());
+  return raw.trim() !== "" && Number.isInteger(n) && n >= 0 ? n : null;
+}
+
+export default function ProxyRetriesControls({ retries, onSave }: ProxyRetriesControlsProps) {
+  const [maxAttempts, setMaxAttempts] = useState(String(retries.max_attempts));
+  const [baseDelay, setBaseDelay] = useState(String(retries.base_delay_ms / 1000));
+  const [maxDelay, setMaxDelay] = useState(String(retries.max_delay_ms / 1000));
+
+  const parsedAttempts = parseWhole(maxAttempts);
+  const parsedBase = Number(baseDelay.trim());
+  const parsedMax = Number(maxDelay.trim());
+  const isValid =
+    parsedAttempts !== null &&
+    parsedAttempts >= 1 &&
+    parsedAttempts <= 10 &&
+    baseDelay.trim() !== "" &&
+    maxDelay.trim() !== "" &&
+    parsedBase >= 0 &&
+    parsedMax >= parsedBase &&
+    parsedMax <= 300;
+  const next: ProxyRetries = {
+    max_attempts: parsedAttempts ?? retries.max_attempts,
+    base_delay_ms: Math.round(parsedBase * 1000),
+    max_delay_ms: Math.round(parsedMax * 1000),
+  };
+  const hasChanges =
+    next.max_attempts !== retries.max_attempts ||
+    next.base_delay_ms !== retries.base_delay_ms ||
+    next.max_delay_ms !== retries.max_delay_ms;
+
+  return (
+    <div className="flex flex-col gap-3 py-3.5 border-b border-border">
+      <div className="flex min-w-0 flex-1 flex-col gap-0.5">
+        <span className="text-sm font-medium">Rate-limit retries</span>
+        <small className="text-xs text-muted-foreground">
+          POST requests answered with 429 or 529 are sent again before anything reaches the
+          client, with exponential backoff. Retry-After and Anthropic rate-limit resets are
+          honored; waits longer than the maximum delay are passed back instead. 1 attempt turns
+          retries off.
+        </small>
+      </div>
+      <div className="grid grid-cols-3 gap-3">
+        <div className="flex flex-col gap-1.5">
+          <label className="text-xs font-medium text-muted-foreground" htmlFor="retries-attempts">
+            Attempts
+          </label>
+          <Input
+            id="retries-attempts"
+            inputMode="numeric"
+            value={maxAttempts}
+            onChange={(e) => setMaxAttempts(e.target.value)}
+          />
+        </div>
+        <div className="flex flex-col gap-1.5">
+          <label className="text-xs font-medium text-muted-foreground" htmlFor="retries-base">
+            First delay (s)
+          </label>
+          <Input
+            id="retries-base"
+            inputMode="decimal"
+            value={baseDelay}
+            onChange={(e) => setBaseDelay(e.target.value)}
+          />
+        </div>
+        <div className="flex flex-col gap-1.5">
+          <label className="text-xs font-medium text-muted-foreground" htmlFor="retries-max">
+            Max delay (s)
+          </label>
+          <Input
+            id="retries-max"
+            inputMode="decimal"
+            value={maxDelay}
+            onChange={(e) => setMaxDelay(e.target.value)}
+          />
+        </div>
+      </div>
+      <div className="flex justify-end">
+        <Button
+          size="sm"
+          variant="outline"
+          onClick={() => isValid && onSave(next)}
+          disabled={!hasChanges || !isValid}
+        >
+          <Save className="mr-2 h-4 w-4" />
+          Save
+        </Button>
+      </div>
+    </div>
+  );
+}
@@ -0,0 +1,206 @@
+import { useState } from "react";
+import { Save, Trash2 } from "lucide-react";
+import type { ProxyTimeouts, TimeoutOverride, TimeoutScope } from "../types";
+import { Button } from "./ui/button";
+import { Input } from "./ui/input";
+
+const SELECT_CLASS =
+  "flex h-9 rounded-md border border-input bg-transparent px-2 py-1 text-sm shadow-sm transition-colors focus-visible:outline-none focus-visible:ring-1 focus-visible:ring-ring";
+
+interface ProxyTimeoutsControlsProps {
+  timeouts: ProxyTimeouts;
+  onSave: (timeouts: ProxyTimeouts) => Promise<void>;
+}
+
+/** Whole seconds, 0 allowed (no limit). */
+function parseSeconds(raw: string): number | null {
+  const n = Number(raw.trim());
+  return raw.trim() !== "" && Number.isInteger(n) && n >= 0 ? n : null;
+}
+
+/** Empty keeps the default for that limit. */
+function parseOptionalSeconds(raw: string): number | null | undefined {
+  if (raw.trim() === "") return null;
+  return parseSeconds(raw) ?? undefined;
+}
+
+function formatLimit(secs: number | null): string {
+  if (secs === null) return "default";
+  return secs === 0 ? "no limit" : `${secs}s`;
+}
+
+function describeOverride(rule: TimeoutOverride): string {
+  return `${rule.scope} "${rule.pattern}" · read ${formatLimit(rule.read_secs)} · total ${formatLimit(rule.total_secs)}`;
+}
+
+export default function ProxyTimeoutsControls({ timeouts, onSave }: ProxyTimeoutsControlsProps) {
+  const [connectSecs, setConnectSecs] = useState(String(timeouts.connect_secs));
+  const [readSecs, setReadSecs] = useState(String(timeouts.read_secs));
+  const [totalSecs, setTotalSecs] = useState(String(timeouts.total_secs));
+  const [scope, setScope] = useState<TimeoutScope>("model");
+  const [pattern, setPattern] = useState("");
+  const [overrideRead, setOverrideRead] = useState("");
+  const [overrideTotal, setOverrideTotal] = useState("");
+
+  const parsedConnect = parseSeconds(connectSecs);
+  const parsedRead = parseSeconds(readSecs);
+  const parsedTotal = parseSeconds(totalSecs);
+  const isValid =
+    parsedConnect !== null && parsedConnect > 0 && parsedRead !== null && parsedTotal !== null;
+  const hasChanges =
+    parsedConnect !== timeouts.connect_secs ||
+    parsedRead !== timeouts.read_secs ||
+    parsedTotal !== timeouts.total_secs;
+
+  const parsedOverrideRead = parseOptionalSeconds(overrideRead);
+  const parsedOverrideTotal = parseOptionalSeconds(overrideTotal);
+  const canAddOverride =
+    pattern.trim() !== "" &&
+    parsedOverrideRead !== undefined &&
+    parsedOverrideTotal !== undefined &&
+    (parsedOverrideRead !== null || parsedOverrideTotal !== null);
+
+  const addOverride = () => {
+    if (!canAddOverride) return;
+    onSave({
+      ...timeouts,
+      overrides: [
+        ...timeouts.overrides,
+        {
+          id: "",
+          scope,
+          pattern: pattern.trim(),
+          read_secs: parsedOverrideRead ?? null,
+          total_secs: parsedOverrideTotal ?? null,
+        },
+      ],
+    });
+  };
+
+  return (
+    <div className="flex flex-col gap-3 py-3.5 border-b border-border">
+      <div className="flex min-w-0 flex-1 flex-col gap-0.5">
+        <span className="text-sm font-medium">Upstream timeouts</span>
+        <small className="text-xs text-muted-foreground">
+          Seconds before the proxy gives up on the backend or gateway with a 504. The read timeout
+          covers the wait for the first byte and gaps while streaming; 0 means no limit.
+        </small>
+      </div>
+      <div className="grid grid-cols-3 gap-3">
+        <div className="flex flex-col gap-1.5">
+          <label className="text-xs font-medium text-muted-foreground" htmlFor="timeouts-connect">
+            Connect
+          </label>
+          <Input
+            id="timeouts-connect"
+            inputMode="numeric"
+            value={connectSecs}
+            onChange={(e) => setConnectSecs(e.target.value)}
+          />
+        </div>
+        <div className="flex flex-col gap-1.5">
+          <label className="text-xs font-medium text-muted-foreground" htmlFor="timeouts-read">
+            Read
+          </label>
+          <Input
+            id="timeouts-read"
+            inputMode="numeric"
+            value={readSecs}
+            onChange={(e) => setReadSecs(e.target.value)}
+          />
+        </div>
+        <div className="flex flex-col gap-1.5">
+          <label className="text-xs font-medium text-muted-foreground" htmlFor="timeouts-total">
+            Total
+          </label>
+          <Input
+            id="timeouts-total"
+            inputMode="numeric"
+            value={totalSecs}
+            onChange={(e) => setTotalSecs(e.target.value)}
+          />
+        </div>
+      </div>
+      <div className="flex justify-end">
+        <Button
+          size="sm"
+          variant="outline"
+          onClick={() =>
+            isValid &&
+            onSave({
+              ...timeouts,
+              connect_secs: parsedConnect!,
+              read_secs: parsedRead!,
+              total_secs: parsedTotal!,
+            })
+          }
+          disabled={!hasChanges || !isValid}
+        >
+          <Save className="mr-2 h-4 w-4" />
+          Save
+        </Button>
+      </div>
+
+      {timeouts.overrides.length > 0 && (
+        <div className="flex flex-col gap-2">
+          {timeouts.overrides.map((rule) => (
+            <div key={rule.id} className="flex items-center justify-between gap-3 text-sm">
+              <span className="min-w-0 truncate font-mono text-xs">{describeOverride(rule)}</span>
+              <Button
+                size="sm"
+                variant="ghost"
+                onClick={() =>
+                  onSave({
+                    ...timeouts,
+                    overrides: timeouts.overrides.filter((r) => r.id !== rule.id),
+                  })
+                }
+                aria-label={`Delete ${describeOverride(rule)}`}
+              >
+                <Trash2 className="h-4 w-4" />
+              </Button>
+            </div>
+          ))}
+        </div>
+      )}
+
+      <div className="grid grid-cols-2 gap-2 md:grid-cols-4">
+        <select
+          className={SELECT_CLASS}
+          value={scope}
+          onChange={(e) => setScope(e.target.value as TimeoutScope)}
+          aria-label="Override scope"
+        >
+          <option value="model" className="bg-background text-foreground">
+            Model
+          </option>
+          <option value="route" className="bg-background text-foreground">
+            Route
+          </option>
+        </select>
+        <Input
+          value={pattern}
+          placeholder={scope === "model" ? "claude-opus-*" : "/v1/messages"}
+          onChange={(e) => setPattern(e.target.value)}
+        />
+        <Input
+          inputMode="numeric"
+          value={overrideRead}
+          placeholder="Read (s)"
+          onChange={(e) => setOverrideRead(e.target.value)}
+        />
+        <Input
+          inputMode="numeric"
+          value={overrideTotal}
+          placeholder="Total (s)"
+          onChange={(e) => setOverrideTotal(e.target.value)}
+        />
+      </div>
+      <div className="flex justify-end">
+        <Button size="sm" variant="outline" onClick={addOverride} disabled={!canAddOverride}>
+          Add override
+        </Button>
+      </div>
+    </div>
+  );
+}
@@ -0,0 +1,308 @@
+import { useCallback, useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { Play, RefreshCw, Save, Trash2 } from "lucide-react";
+import type {
+  CapturedHeader,
+  RequestCapture,
+  RequestCaptureSettings,
+  RequestCaptureSummary,
+  RequestReplayResult,
+} from "../types";
+import { toErrorMessage } from "../utils/error";
+import { Button } from "./ui/button";
+import { Input } from "./ui/input";
+import { Switch } from "./ui/switch";
+import {
+  Dialog,
+  DialogContent,
+  DialogDescription,
+  DialogHeader,
+  DialogTitle,
+} from "./ui/dialog";
+
+interface RequestCaptureControlsProps {
+  capture: RequestCaptureSettings;
+  onSave: (capture: RequestCaptureSettings) => Promise<void>;
+}
+
+function parseLimit(raw: string): number | null {
+  const n = Number(raw.trim());
+  return Number.isInteger(n) && n > 0 ? n : null;
+}
+
+function formatBytes(bytes: number): string {
+  if (bytes < 1024) return `${bytes} B`;
+  if (bytes < 1024 * 1024) return `${(bytes / 1024).toFixed(1)} KB`;
+  return `${(bytes / (1024 * 1024)).toFixed(1)} MB`;
+}
+
+/** Pretty-prints JSON bodies; SSE and other text is shown as is. */
+function formatBody(body: string | null): string {
+  if (body === null) return "(not captured)";
+  try {
+    return JSON.stringify(JSON.parse(body), null, 2);
+  } catch {
+    return body;
+  }
+}
+
+function HeaderList({ headers }: { headers: CapturedHeader[] }) {
+  if (headers.length === 0) {
+    return <p className="text-xs text-muted-foreground">No headers.</p>;
+  }
+  return (
+    <div className="flex flex-col gap-0.5 font-mono text-xs">
+      {headers.map((header, index) => (
+        <div key={`${header.name}-${index}`} className="break-all">
+          <span className="text-muted-foreground">{header.name}:</span> {header.value}
+        </div>
+      ))}
+    </div>
+  );
+}
+
+function BodyBlock({ body, bytes, truncated }: { body: string | null; bytes: number; truncated: boolean }) {
+  return (
+    <div className="flex flex-col gap-1">
+      <small className="text-xs text-muted-foreground">
+        {formatBytes(bytes)}
+        {truncated ? " (truncated)" : ""}
+      </small>
+      <pre className="max-h-[280px] overflow-auto rounded-md border border-border bg-muted/50 p-2 text-xs whitespace-pre-wrap break-all">
+        {formatBody(body)}
+      </pre>
+    </div>
+  );
+}
+
+export default function RequestCaptureControls({ capture, onSave }: RequestCaptureControlsProps) {
+  const [bodyKb, setBodyKb] = useState(String(capture.max_body_kb));
+  const [storageMb, setStorageMb] = useState(String(capture.max_storage_mb));
+  const [captures, setCaptures] = useState<RequestCaptureSummary[]>([]);
+  const [selected, setSelected] = useState<RequestCapture | null>(null);
+  const [replay, setReplay] = useState<RequestReplayResult | null>(null);
+  const [replaying, setReplaying] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  const refresh = useCallback(async () => {
+    try {
+      setCaptures(await invoke<RequestCaptureSummary[]>("list_request_captures", { limit: 100 }));
+    } catch (err) {
+      setError(toErrorMessage(err, "Failed to load captured requests"));
+    }
+  }, []);
+
+  useEffect(() => {
+    refresh();
+  }, [refresh]);
+
+  const parsedBodyKb = parseLimit(bodyKb);
+  const parsedStorageMb = parseLimit(storageMb);
+  const isValid = parsedBodyKb !== null && parsedStorageMb !== null;
+  const hasChanges =
+    parsedBodyKb !== capture.max_body_kb || parsedStorageMb !== capture.max_storage_mb;
+
+  const openCapture = async (requestId: string) => {
+    try {
+      setSelected(await invoke<RequestCapture>("get_request_capture", { requestId }));
+      setReplay(null);
+      setError(null);
+    } catch (err) {
+      setError(toErrorMessage(err, "Failed to load captured request"));
+    }
+  };
+
+  const replaySelected = async () => {
+    if (!selected) return;
+    setReplaying(true);
+    try {
+      setReplay(
+        await invoke<RequestReplayResult>("replay_request_capture", {
+          requestId: selected.request_id,
+        }),
+      );
+      setError(null);
+      await refresh();
+    } catch (err) {
+      setError(toErrorMessage(err, "Failed to replay request"));
+    } finally {
+      setReplaying(false);
+    }
+  };
+
+  const clearCaptures = async () => {
+    try {
+      await invoke("clear_request_captures");
+      setError(null);
+      await refresh();
+    } catch (err) {
+      setError(toErrorMessage(err, "Failed to clear captured requests"));
+    }
+  };
+
+  return (
+    <div className="flex flex-col gap-3 py-3.5 border-b border-border">
+      <div className="flex items-center justify-between gap-4">
+        <div className="flex min-w-0 flex-col gap-0.5">
+          <span className="text-sm font-medium">Capture requests</span>
+          <small className="text-xs text-muted-foreground">
+            Stores proxied requests as forwarded upstream, with their responses. Credentials in
+            headers are redacted and bodies are truncated. Captures include prompt contents.
+          </small>
+        </div>
+        <Switch
+          checked={capture.enabled}
+          onCheckedChange={(enabled) => onSave({ ...capture, enabled })}
+          aria-label="Capture requests"
+        />
+      </div>
+
+      <div className="grid grid-cols-2 gap-3">
+        <div className="flex flex-col gap-1.5">
+          <label className="text-xs font-medium text-muted-foreground" htmlFor="capture-body-kb">
+            Body limit (KB)
+          </label>
+          <Input
+            id="capture-body-kb"
+            inputMode="numeric"
+            value={bodyKb}
+            onChange={(e) => setBodyKb(e.target.value)}
+          />
+        </div>
+        <div className="flex flex-col gap-1.5">
+          <label className="text-xs font-medium text-muted-foreground" htmlFor="capture-storage-mb">
+            Storage cap (MB)
+          </label>
+          <Input
+            id="capture-storage-mb"
+            inputMode="numeric"
+            value={storageMb}
+            onChange={(e) => setStorageMb(e.target.value)}
+          />
+        </div>
+      </div>
+      <div className="flex items-center justify-end gap-2">
+        <Button size="sm" variant="ghost" onClick={refresh} aria-label="Refresh captured requests">
+          <RefreshCw className="h-4 w-4" />
+        </Button>
+        <Button size="sm" variant="outline" onClick={clearCaptures} disabled={captures.length === 0}>
+          <Trash2 className="mr-2 h-4 w-4" />
+          Clear
+        </Button>
+        <Button
+          size="sm"
+          variant="outline"
+          onClick={() =>
+            isValid &&
+            onSave({ ...capture, max_body_kb: parsedBodyKb!, max_storage_mb: parsedStorageMb! })
+          }
+          disabled={!hasChanges || !isValid}
+        >
+          <Save className="mr-2 h-4 w-4" />
+          Save
+        </Button>
+      </div>
+
+      {captures.length > 0 && (
+        <div className="flex max-h-[240px] flex-col overflow-auto rounded-md border border-border">
+          {captures.map((row) => (
+            <button
+              type="button"
+              key={row.request_id}
+              className="flex items-center gap-3 border-b border-border px-3 py-2 text-left text-xs last:border-b-0 hover:bg-muted/50"
+              onClick={() => openCapture(row.request_id)}
+            >
+              <span
+                className={`w-10 shrink-0 tabular-nums font-medium ${row.status_code >= 400 ? "text-destructive" : ""}`}
+              >
+                {row.status_code}
+              </span>
+              <span className="min-w-0 flex-1 truncate">
+                {row.method} {row.path}
+                <span className="text-muted-foreground"> · {row.model}</span>
+              </span>
+              <span className="shrink-0 text-muted-foreground">
+                {new Date(row.captured_at).toLocaleTimeString()}
+              </span>
+            </button>
+          ))}
+        </div>
+      )}
+
+      {error && <small className="text-xs text-destructive">{error}</small>}
+
+      <Dialog open={selected !== null} onOpenChange={(open) => !open && setSelected(null)}>
+        <DialogContent className="max-w-[960px] max-h-[90vh] overflow-y-auto overscroll-none">
+          {selected && (
+            <>
+              <DialogHeader>
+                <DialogTitle>
+                  {selected.method} {selected.path}
+                </DialogTitle>
+                <DialogDescription>
+                  {selected.status_code} in {selected.duration_ms}ms · {selected.provider} ·{" "}
+                  {selected.model}
+                  {selected.client_label ? ` · ${selected.client_label}` : ""} ·{" "}
+                  {new Date(selected.captured_at).toLocaleString()}
+                </DialogDescription>
+              </DialogHeader>
+
+              <div className="flex flex-col gap-4">
+                <div className="flex flex-col gap-2">
+                  <span className="text-sm font-medium">Request</span>
+                  <HeaderList headers={selected.request_headers} />
+                  <BodyBlock
+                    body={selected.request_body}
+                    bytes={selected.request_body_bytes}
+                    truncated={selected.request_body_truncated}
+                  />
+                </div>
+                <div className="flex flex-col gap-2">
+                  <span className="text-sm font-medium">Response</span>
+                  <HeaderList headers={selected.response_headers} />
+                  <BodyBlock
+                    body={selected.response_body}
+                    bytes={selected.response_body_bytes}
+                    truncated={selected.response_body_truncated}
+                  />
+                </div>
+
+                <div className="flex items-center justify-end">
+                  <Button
+                    size="sm"
+                    variant="outline"
+                    onClick={replaySelected}
+                    disabled={replaying || selected.request_body_truncated}
+                    title={
+                      selected.request_body_truncated
+                        ? "The captured body was truncated and can't be replayed"
+                        : undefined
+                    }
+                  >
+                    <Play className="mr-2 h-4 w-4" />
+                    {replaying ? "Replaying..." : "Replay"}
+                  </Button>
+                </div>
+
+                {replay && (
+                  <div className="flex flex-col gap-2">
+                    <span className="text-sm font-medium">
+                      Replay: {replay.status_code} in {replay.duration_ms}ms
+                    </span>
+                    <HeaderList headers={replay.response_headers} />
+                    <BodyBlock
+                      body={replay.response_body}
+                      bytes={replay.response_body.length}
+                      truncated={replay.response_body_truncated}
+                    />
+                  </div>
+                )}
+                {error && <small className="text-xs text-destructive">{error}</small>}
+              </div>
+            </>
+          )}
+        </DialogContent>
+      </Dialog>
+    </div>
+  );
+}
@@ -1,9 +1,27 @@
 import { relaunch } from "@tauri-apps/plugin-process";
 import { invoke } from "@tauri-apps/api/core";
 import { FolderOpen } from "lucide-react";
+import ClientKeysControls from "./ClientKeysControls";
+import FailoverChainsControls from "./FailoverChainsControls";
+import PricingOverridesControls from "./PricingOverridesControls";
+import ModelRulesControls from "./ModelRulesControls";
+import NetworkSettingsControls from "./NetworkSettingsControls";
+import ProxyRetriesControls from "./ProxyRetriesControls";
+import ProxyTimeoutsControls from "./ProxyTimeoutsControls";
+import ShutdownDrainControls from "./ShutdownDrainControls";
 import TabHeader from "./TabHeader";
+import UsageBudgetsControls from "./UsageBudgetsControls";
+import RequestCaptureControls from "./RequestCaptureControls";
+import UsageRetentionControls from "./UsageRetentionControls";
 import { Button } from "./ui/button";
 import { Switch } from "./ui/switch";
+import type {
+  FailoverChain,
+  ProxyRetries,
+  ProxyTimeouts,
+  RequestCaptureSettings,
+  UsageRetention,
+} from "../types";
 
 interface SettingsTabProps {
   updateStatusLabel: string;
@@ -14,6 +32,15 @@ interface SettingsTabProps {
   checkForUpdates: (opts: { manual: boolean }) => void;
   settings: any;
   setLaunchAtLogin: (launch: boolean) => void;
+  setNetworkConfig: (bindAddress: string, proxyPort: number, backendPort: number) => void;
+  setServerLogPersistence: (enabled: boolean) => void;
+  setRequireClientApiKey: (enabled: boolean) => void;
+  setUsageRetention: (retention: UsageRetention) => Promise<void>;
+  setRequestCapture: (capture: RequestCaptureSettings) => Promise<void>;
+  setProxyTimeouts: (timeouts: ProxyTimeouts) => Promise<void>;
+  setProxyRetries: (retries: ProxyRetries) => Promise<void>;
+  setFailoverChains: (chains: FailoverChain[]) => Promise<void>;
+  setShutdownDrain: (secs: number) => Promise<void>;
 }
 
 export default function SettingsTab({
@@ -25,6 +52,15 @@ export default function SettingsTab({
   checkForUpdates,
   settings,
   setLaunchAtLogin,
+  setNetworkConfig,
+  setServerLogPersistence,
+  setRequireClientApiKey,
+  setUsageRetention,
+  setRequestCapture,
+  setProxyTimeouts,
+  setProxyRetries,
+  setFailoverChains,
+  setShutdownDrain,
 }: SettingsTabProps) {
   return (
     <div className="tab-content animate-in flex flex-col gap-6 pb-6">
@@ -70,6 +106,58 @@ export default function SettingsTab({
             aria-label="Launch at login"
           />
         </div>
+        <NetworkSettingsControls
+          key={`${settings.bind_address}:${settings.proxy_port}:${settings.backend_port}`}
+          bindAddress={settings.bind_address}
+          proxyPort={settings.proxy_port}
+          backendPort={settings.backend_port}
+          requireClientApiKey={settings.require_client_api_key}
+          onSave={setNetworkConfig}
+        />
+        <ProxyTimeoutsControls
+          key={JSON.stringify(settings.proxy_timeouts)}
+          timeouts={settings.proxy_timeouts}
+          onSave={setProxyTimeouts}
+        />
+        <ProxyRetriesControls
+          key={JSON.stringify(settings.proxy_retries)}
+          retries={settings.proxy_retries}
+          onSave={setProxyRetries}
+        />
+        <FailoverChainsControls chains={settings.failover_chains} onSave={setFailoverChains} />
+        <ShutdownDrainControls
+          key={settings.shutdown_drain_secs}
+          secs={settings.shutdown_drain_secs}
+          onSave={setShutdownDrain}
+        />
+        <ClientKeysControls
+          requireClientApiKey={settings.require_client_api_key}
+          onRequireChange={setRequireClientApiKey}
+        />
+        <PricingOverridesControls />
+        <ModelRulesControls />
+        <UsageBudgetsControls />
+        <UsageRetentionControls
+          retention={settings.usage_retention}
+          onSave={setUsageRetention}
+        />
+        <RequestCaptureControls
+          capture={settings.request_capture}
+          onSave={setRequestCapture}
+        />
+        <div className="flex items-center justify-between gap-4 py-3.5 border-b border-border">
+          <div className="flex min-w-0 flex-1 flex-col gap-0.5">
+            <span className="text-sm font-medium">Save backend logs</span>
+            <small className="text-xs text-muted-foreground">
+              Write backend output to logs/backend.log in the auth folder (rotated at 5 MB).
+            </small>
+          </div>
+          <Switch
+            checked={settings.persist_server_logs}
+            onCheckedChange={setServerLogPersistence}
+            aria-label="Save backend logs"
+          />
+        </div>
         <div className="flex items-center justify-between gap-4 py-3.5">
           <div className="flex min-w-0 flex-1 flex-col gap-0.5">
             <span className="text-sm font-medium">Auth files</span>
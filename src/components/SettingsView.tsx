@@ -163,6 +163,7 @@ function useSettingsView() {
   const {
     serverState,
     downloadProgress,
+    draining,
     startServer,
     stopServer,
     downloadBinary,
@@ -184,6 +185,15 @@ function useSettingsView() {
     setProviderEnabled,
     setVercelConfig,
     setLaunchAtLogin,
+    setNetworkConfig,
+    setServerLogPersistence,
+    setRequireClientApiKey,
+    setUsageRetention,
+    setRequestCapture,
+    setProxyTimeouts,
+    setProxyRetries,
+    setFailoverChains,
+    setShutdownDrain,
     lastError: settingsError,
     clearLastError: clearSettingsError,
   } = useSettings();
@@ -202,8 +212,8 @@ function useSettingsView() {
   const [activeTab, setActiveTab] = useState<TabKey>("dashboard");
   const settingsScrollRef = useRef<HTMLElement | null>(null);
   const {
-    range: usageRange,
-    setRange: setUsageRange,
+    query: usageQuery,
+    updateQuery: updateUsageQuery,
     dashboard: usageDashboard,
     isLoading: usageLoading,
     lastError: usageError,
@@ -404,6 +414,12 @@ function useSettingsView() {
             <Power className="h-3 w-3 shrink-0" />
             {serverState.is_running ? "Online" : "Offline"}
           </Badge>
+          {draining && (
+            <p className="text-xs text-muted-foreground">
+              Finishing {draining.in_flight} request{draining.in_flight === 1 ? "" : "s"} ·{" "}
+              {draining.remaining_secs}s left
+            </p>
+          )}
           <p className="text-xs text-muted-foreground">
             {enabledServiceCount} services · {activeAccounts} accounts
           </p>
@@ -434,8 +450,8 @@ function useSettingsView() {
             {activeTab === "usage" && (
               <UsageDashboard
                 dashboard={usageDashboard}
-                range={usageRange}
-                onRangeChange={setUsageRange}
+                query={usageQuery}
+                onQueryChange={updateUsageQuery}
                 onRefresh={refreshUsage}
                 isLoading={usageLoading}
                 error={usageError}
@@ -472,6 +488,15 @@ function useSettingsView() {
                 checkForUpdates={checkForUpdates}
                 settings={settings}
                 setLaunchAtLogin={setLaunchAtLogin}
+                setNetworkConfig={setNetworkConfig}
+                setServerLogPersistence={setServerLogPersistence}
+                setRequireClientApiKey={setRequireClientApiKey}
+                setUsageRetention={setUsageRetention}
+                setRequestCapture={setRequestCapture}
+                setProxyTimeouts={setProxyTimeouts}
+                setProxyRetries={setProxyRetries}
+                setFailoverChains={setFailoverChains}
+                setShutdownDrain={setShutdownDrain}
               />
             )}
           </div>
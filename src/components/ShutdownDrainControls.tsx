@@ -0,0 +1,46 @@
+import { useState } from "react";
+import { Save } from "lucide-react";
+import { Button } from "./ui/button";
+import { Input } from "./ui/input";
+
+interface ShutdownDrainControlsProps {
+  secs: number;
+  onSave: (secs: number) => Promise<void>;
+}
+
+export default function ShutdownDrainControls({ secs, onSave }: ShutdownDrainControlsProps) {
+  const [value, setValue] = useState(String(secs));
+
+  const parsed = Number(value.trim());
+  const isValid = value.trim() !== "" && Number.isInteger(parsed) && parsed >= 0 && parsed <= 300;
+
+  return (
+    <div className="flex items-center justify-between gap-4 py-3.5 border-b border-border">
+      <div className="flex min-w-0 flex-1 flex-col gap-0.5">
+        <span className="text-sm font-medium">Shutdown drain timeout (s)</span>
+        <small className="text-xs text-muted-foreground">
+          When the server stops or restarts, new connections are refused and requests already
+          running get this long to finish before they are closed. 0 closes them right away.
+        </small>
+      </div>
+      <div className="flex shrink-0 items-center gap-2">
+        <Input
+          className="w-20"
+          inputMode="numeric"
+          value={value}
+          onChange={(e) => setValue(e.target.value)}
+          aria-label="Shutdown drain timeout in seconds"
+        />
+        <Button
+          size="sm"
+          variant="outline"
+          onClick={() => isValid && onSave(parsed)}
+          disabled={!isValid || parsed === secs}
+        >
+          <Save className="mr-2 h-4 w-4" />
+          Save
+        </Button>
+      </div>
+    </div>
+  );
+}
@@ -0,0 +1,220 @@
+import { useCallback, useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { Trash2 } from "lucide-react";
+import type {
+  BudgetMetric,
+  BudgetPeriod,
+  BudgetScope,
+  UsageBudget,
+  UsageBudgetStatus,
+} from "../types";
+import { toErrorMessage } from "../utils/error";
+import { Button } from "./ui/button";
+import { Input } from "./ui/input";
+import { Progress } from "./ui/progress";
+
+const SELECT_CLASS =
+  "flex h-9 rounded-md border border-input bg-transparent px-2 py-1 text-sm shadow-sm transition-colors focus-visible:outline-none focus-visible:ring-1 focus-visible:ring-ring";
+
+const SCOPE_OPTIONS: Array<{ label: string; value: BudgetScope; placeholder: string }> = [
+  { label: "Provider", value: "provider", placeholder: "claude" },
+  { label: "Model", value: "model", placeholder: "claude-opus-4-5" },
+  { label: "Account", value: "account", placeholder: "Account key" },
+];
+
+interface BudgetForm {
+  scope: BudgetScope;
+  target: string;
+  period: BudgetPeriod;
+  metric: BudgetMetric;
+  softLimit: string;
+  hardLimit: string;
+}
+
+const EMPTY_FORM: BudgetForm = {
+  scope: "provider",
+  target: "",
+  period: "daily",
+  metric: "tokens",
+  softLimit: "",
+  hardLimit: "",
+};
+
+function parseLimit(value: string): number | null {
+  return value.trim() === "" ? null : Math.round(Number(value));
+}
+
+function formatNumber(value: number): string {
+  return new Intl.NumberFormat("en-US").format(Math.max(0, Math.round(value)));
+}
+
+function describeBudget(budget: UsageBudget): string {
+  return `${budget.period === "daily" ? "Daily" : "Monthly"} ${budget.metric} · ${budget.scope} ${budget.target}`;
+}
+
+export default function UsageBudgetsControls() {
+  const [statuses, setStatuses] = useState<UsageBudgetStatus[]>([]);
+  const [form, setForm] = useState<BudgetForm>(EMPTY_FORM);
+  const [error, setError] = useState<string | null>(null);
+
+  const refresh = useCallback(async () => {
+    try {
+      setStatuses(await invoke<UsageBudgetStatus[]>("get_usage_budgets"));
+    } catch (err) {
+      setError(toErrorMessage(err, "Failed to load usage budgets"));
+    }
+  }, []);
+
+  useEffect(() => {
+    refresh();
+  }, [refresh]);
+
+  const saveBudget = async () => {
+    const budget: UsageBudget = {
+      id: "",
+      scope: form.scope,
+      target: form.target.trim(),
+      period: form.period,
+      metric: form.metric,
+      soft_limit: parseLimit(form.softLimit),
+      hard_limit: parseLimit(form.hardLimit),
+    };
+    try {
+      await invoke("save_usage_budget", { budget });
+      setForm(EMPTY_FORM);
+      setError(null);
+      await refresh();
+    } catch (err) {
+      setError(toErrorMessage(err, "Failed to save usage budget"));
+    }
+  };
+
+  const deleteBudget = async (id: string) => {
+    try {
+      await invoke("delete_usage_budget", { id });
+      setError(null);
+      await refresh();
+    } catch (err) {
+      setError(toErrorMessage(err, "Failed to delete usage budget"));
+    }
+  };
+
+  const canSave =
+    form.target.trim() !== "" && (form.softLimit.trim() !== "" || form.hardLimit.trim() !== "");
+  const placeholder =
+    SCOPE_OPTIONS.find((option) => option.value === form.scope)?.placeholder ?? "";
+
+  return (
+    <div className="flex flex-col gap-3 py-3.5 border-b border-border">
+      <div className="flex min-w-0 flex-col gap-0.5">
+        <span className="text-sm font-medium">Usage budgets</span>
+        <small className="text-xs text-muted-foreground">
+          Soft limits show a notification. Hard limits reject requests with a 429 until the
+          period resets (UTC).
+        </small>
+      </div>
+
+      {statuses.length > 0 && (
+        <div className="flex flex-col gap-3">
+          {statuses.map(({ budget, used, resets_at }) => {
+            const limit = budget.hard_limit ?? budget.soft_limit ?? 1;
+            const overHard = budget.hard_limit !== null && used >= budget.hard_limit;
+            const overSoft = budget.soft_limit !== null && used >= budget.soft_limit;
+            return (
+              <div key={budget.id} className="flex flex-col gap-1.5">
+                <div className="flex items-center justify-between gap-3 text-sm">
+                  <span className="font-medium truncate">{describeBudget(budget)}</span>
+                  <div className="flex items-center gap-2">
+                    <span
+                      className={`text-xs tabular-nums ${overHard ? "text-destructive" : overSoft ? "text-amber-500" : "text-muted-foreground"}`}
+                    >
+                      {formatNumber(used)} / {formatNumber(limit)}
+                    </span>
+                    <Button
+                      size="sm"
+                      variant="ghost"
+                      onClick={() => deleteBudget(budget.id)}
+                      aria-label={`Delete ${describeBudget(budget)}`}
+                    >
+                      <Trash2 className="h-4 w-4" />
+                    </Button>
+                  </div>
+                </div>
+                <Progress value={Math.min(100, (used / limit) * 100)} className="h-1.5" />
+                <small className="text-xs text-muted-foreground">
+                  {budget.soft_limit !== null ? `Soft ${formatNumber(budget.soft_limit)}` : "No soft limit"}
+                  {" · "}
+                  {budget.hard_limit !== null ? `Hard ${formatNumber(budget.hard_limit)}` : "No hard limit"}
+                  {" · "}
+                  Resets {new Date(resets_at).toLocaleString()}
+                </small>
+              </div>
+            );
+          })}
+        </div>
+      )}
+
+      <div className="grid grid-cols-2 gap-2 md:grid-cols-3">
+        <select
+          className={SELECT_CLASS}
+          value={form.scope}
+          onChange={(e) => setForm({ ...form, scope: e.target.value as BudgetScope })}
+          aria-label="Budget scope"
+        >
+          {SCOPE_OPTIONS.map((option) => (
+            <option key={option.value} value={option.value} className="bg-background text-foreground">
+              {option.label}
+            </option>
+          ))}
+        </select>
+        <Input
+          className="md:col-span-2"
+          value={form.target}
+          placeholder={placeholder}
+          onChange={(e) => setForm({ ...form, target: e.target.value })}
+        />
+        <select
+          className={SELECT_CLASS}
+          value={form.period}
+          onChange={(e) => setForm({ ...form, period: e.target.value as BudgetPeriod })}
+          aria-label="Budget period"
+        >
+          <option value="daily" className="bg-background text-foreground">Daily</option>
+          <option value="monthly" className="bg-background text-foreground">Monthly</option>
+        </select>
+        <select
+          className={SELECT_CLASS}
+          value={form.metric}
+          onChange={(e) => setForm({ ...form, metric: e.target.value as BudgetMetric })}
+          aria-label="Budget metric"
+        >
+          <option value="tokens" className="bg-background text-foreground">Tokens</option>
+          <option value="requests" className="bg-background text-foreground">Requests</option>
+        </select>
+        <div className="col-span-2 grid grid-cols-2 gap-2 md:col-span-1">
+          <Input
+            type="number"
+            min={1}
+            value={form.softLimit}
+            placeholder="Soft limit"
+            onChange={(e) => setForm({ ...form, softLimit: e.target.value })}
+          />
+          <Input
+            type="number"
+            min={1}
+            value={form.hardLimit}
+            placeholder="Hard limit"
+            onChange={(e) => setForm({ ...form, hardLimit: e.target.value })}
+          />
+        </div>
+      </div>
+      <div className="flex justify-end">
+        <Button size="sm" variant="outline" onClick={saveBudget} disabled={!canSave}>
+          Add budget
+        </Button>
+      </div>
+
+      {error && <small className="text-xs text-destructive">{error}</small>}
+    </div>
+  );
+}
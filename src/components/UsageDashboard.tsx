@@ -1,10 +1,10 @@
 import { RefreshCw, AlertCircle } from "lucide-react";
-import type {
-  UsageDashboardPayload,
-  UsageRange,
-  UsageBreakdownRow,
-} from "../types";
+import type { UsageDashboardPayload, UsageDashboardQuery } from "../types";
 import TabHeader from "./TabHeader";
+import UsageExportControls from "./UsageExportControls";
+import UsageErrorsPanel from "./UsageErrorsPanel";
+import UsageLatencyPanel from "./UsageLatencyPanel";
+import UsageQueryControls from "./UsageQueryControls";
 import { Button } from "./ui/button";
 import { Alert, AlertDescription, AlertTitle } from "./ui/alert";
 import { Card, CardContent, CardHeader, CardTitle, CardDescription } from "./ui/card";
@@ -20,53 +20,46 @@ import { Progress } from "./ui/progress";
 
 interface UsageDashboardProps {
   dashboard: UsageDashboardPayload;
-  range: UsageRange;
-  onRangeChange: (range: UsageRange) => void;
+  query: UsageDashboardQuery;
+  onQueryChange: (patch: Partial<UsageDashboardQuery>) => void;
   onRefresh: () => void;
   isLoading: boolean;
   error: string | null;
   onDismissError: () => void;
 }
 
-const RANGE_OPTIONS: Array<{ label: string; value: UsageRange }> = [
-  { label: "24h", value: "24h" },
-  { label: "7d", value: "7d" },
-  { label: "30d", value: "30d" },
-  { label: "All", value: "all" },
-];
-
 function formatNumber(value: number): string {
   return new Intl.NumberFormat("en-US").format(Math.max(0, Math.round(value)));
 }
 
-function formatPercent(value: number): string {
-  return `${value.toFixed(1)}%`;
+function formatCost(value: number): string {
+  return new Intl.NumberFormat("en-US", {
+    style: "currency",
+    currency: "USD",
+    minimumFractionDigits: 2,
+    maximumFractionDigits: value > 0 && value < 1 ? 4 : 2,
+  }).format(Math.max(0, value));
 }
 
-function getProviderBreakdown(rows: UsageBreakdownRow[]) {
-  const byProvider = new Map<string, { requests: number; tokens: number }>();
-  rows.forEach((row) => {
-    const current = byProvider.get(row.provider) ?? { requests: 0, tokens: 0 };
-    current.requests += row.requests;
-    current.tokens += row.total_tokens;
-    byProvider.set(row.provider, current);
-  });
-  return [...byProvider.entries()]
-    .map(([provider, value]) => ({ provider, ...value }))
-    .sort((a, b) => b.tokens - a.tokens);
+function formatPercent(value: number): string {
+  return `${value.toFixed(1)}%`;
 }
 
 export default function UsageDashboard({
   dashboard,
-  range,
-  onRangeChange,
+  query,
+  onQueryChange,
   onRefresh,
   isLoading,
   error,
   onDismissError,
 }: UsageDashboardProps) {
   const usage = dashboard.dashboard;
-  const providerBreakdown = getProviderBreakdown(usage.breakdown);
+  const providerBreakdown = usage.providers.map((row) => ({
+    provider: row.provider,
+    requests: row.requests,
+    tokens: row.total_tokens,
+  }));
   const totalProviderTokens = providerBreakdown.reduce(
     (sum, row) => sum + row.tokens,
     0,
@@ -79,12 +72,16 @@ export default function UsageDashboard({
     1,
     ...providerBreakdown.map((row) => row.tokens),
   );
+  const pageStart = usage.page * usage.page_size;
+  const pageCount = Math.max(1, Math.ceil(usage.breakdown_total / usage.page_size));
+  const clientLabel =
+    usage.clients.find((row) => row.client_id === query.client_id)?.client_label ?? null;
 
   return (
     <div className="flex flex-col gap-6 pb-6 animate-in">
       <TabHeader
         title="Usage"
-        subtitle="Track requests and token usage by provider, model, and account."
+        subtitle="Track requests, token usage and estimated API cost by provider, model, and account."
       />
 
       {error ? (
@@ -100,19 +97,12 @@ export default function UsageDashboard({
         </Alert>
       ) : null}
 
-      <div className="flex flex-wrap items-center justify-between gap-4">
-        <div className="inline-flex items-center gap-1 rounded-full border border-border bg-muted/50 p-1">
-          {RANGE_OPTIONS.map((option) => (
-            <button
-              type="button"
-              key={option.value}
-              className={`rounded-full px-3 py-1.5 text-xs font-medium transition-colors ${range === option.value ? "bg-background text-foreground shadow-sm" : "text-muted-foreground hover:text-foreground"}`}
-              onClick={() => onRangeChange(option.value)}
-            >
-              {option.label}
-            </button>
-          ))}
-        </div>
+      <div className="flex flex-wrap items-start justify-between gap-4">
+        <UsageQueryControls
+          query={query}
+          clientLabel={clientLabel}
+          onQueryChange={onQueryChange}
+        />
         <Button
           size="sm"
           variant="outline"
@@ -124,7 +114,28 @@ export default function UsageDashboard({
         </Button>
       </div>
 
-      <div className="grid grid-cols-2 gap-4 md:grid-cols-3 lg:grid-cols-6">
+      {usage.truncated_before ? (
+        <p className="text-xs text-muted-foreground">
+          Older history was compacted into daily totals; this view starts at{" "}
+          {usage.truncated_before}. Use a day or coarser bucket without client or status
+          filters to include it.
+        </p>
+      ) : null}
+
+      <div className="grid grid-cols-2 gap-4 md:grid-cols-4 lg:grid-cols-7">
+        <Card>
+          <CardHeader className="p-4 pb-2">
+            <CardDescription className="text-xs font-medium uppercase tracking-wider">Est. API Cost</CardDescription>
+            <CardTitle className="text-2xl font-bold tabular-nums">
+              {formatCost(usage.summary.estimated_cost_usd)}
+            </CardTitle>
+            {usage.summary.unpriced_requests > 0 ? (
+              <small className="text-xs text-muted-foreground">
+                {formatNumber(usage.summary.unpriced_requests)} requests unpriced
+              </small>
+            ) : null}
+          </CardHeader>
+        </Card>
         <Card>
           <CardHeader className="p-4 pb-2">
             <CardDescription className="text-xs font-medium uppercase tracking-wider">Total Tokens</CardDescription>
@@ -198,7 +209,7 @@ export default function UsageDashboard({
                           Math.round((point.total_tokens / maxPointTokens) * 100),
                         )}%`,
                       }}
-                      title={`${point.bucket}: ${formatNumber(point.total_tokens)} tokens`}
+                      title={`${point.bucket}: ${formatNumber(point.total_tokens)} tokens, ~${formatCost(point.estimated_cost_usd)}`}
                     />
                   </div>
                 ))}
@@ -240,6 +251,53 @@ export default function UsageDashboard({
         </Card>
       </div>
 
+      <Card>
+        <CardHeader>
+          <CardTitle className="text-sm font-semibold uppercase tracking-wider text-muted-foreground">By Client</CardTitle>
+          <CardDescription>Attributed by client API key. Requests without a key are grouped as unidentified. Click a client to filter by it.</CardDescription>
+        </CardHeader>
+        <CardContent className="p-0">
+          {usage.clients.length === 0 ? (
+            <div className="p-6 text-sm text-muted-foreground">No client usage yet.</div>
+          ) : (
+            <Table>
+              <TableHeader className="bg-muted/50">
+                <TableRow>
+                  <TableHead>Client</TableHead>
+                  <TableHead className="text-right">Requests</TableHead>
+                  <TableHead className="text-right">Input</TableHead>
+                  <TableHead className="text-right">Output</TableHead>
+                  <TableHead className="text-right">Tokens</TableHead>
+                  <TableHead className="text-right">Errors</TableHead>
+                  <TableHead>Last Seen</TableHead>
+                </TableRow>
+              </TableHeader>
+              <TableBody>
+                {usage.clients.map((row) => (
+                  <TableRow
+                    key={row.client_id || "unidentified"}
+                    className="cursor-pointer"
+                    onClick={() => onQueryChange({ client_id: row.client_id })}
+                  >
+                    <TableCell className={row.client_id ? "font-medium" : "text-muted-foreground"}>
+                      {row.client_id ? row.client_label : "Unidentified"}
+                    </TableCell>
+                    <TableCell className="text-right tabular-nums">{formatNumber(row.requests)}</TableCell>
+                    <TableCell className="text-right tabular-nums text-muted-foreground">{formatNumber(row.input_tokens)}</TableCell>
+                    <TableCell className="text-right tabular-nums text-muted-foreground">{formatNumber(row.output_tokens)}</TableCell>
+                    <TableCell className="text-right tabular-nums">{formatNumber(row.total_tokens)}</TableCell>
+                    <TableCell className="text-right tabular-nums text-muted-foreground">{formatNumber(row.error_count)}</TableCell>
+                    <TableCell className="text-muted-foreground">
+                      {row.last_seen ? new Date(row.last_seen).toLocaleString() : "-"}
+                    </TableCell>
+                  </TableRow>
+                ))}
+              </TableBody>
+            </Table>
+          )}
+        </CardContent>
+      </Card>
+
       <Card>
         <CardHeader>
           <CardTitle className="text-sm font-semibold uppercase tracking-wider text-muted-foreground">Detailed Breakdown</CardTitle>
@@ -259,6 +317,7 @@ export default function UsageDashboard({
                     <TableHead className="text-right">Tokens</TableHead>
                     <TableHead className="text-right">Cached</TableHead>
                     <TableHead className="text-right">Reasoning</TableHead>
+                    <TableHead className="text-right">Est. Cost</TableHead>
                     <TableHead>Last Seen</TableHead>
                   </TableRow>
                 </TableHeader>
@@ -274,6 +333,9 @@ export default function UsageDashboard({
                       <TableCell className="text-right tabular-nums">{formatNumber(row.total_tokens)}</TableCell>
                       <TableCell className="text-right tabular-nums text-muted-foreground">{formatNumber(row.cached_tokens)}</TableCell>
                       <TableCell className="text-right tabular-nums text-muted-foreground">{formatNumber(row.reasoning_tokens)}</TableCell>
+                      <TableCell className="text-right tabular-nums">
+                        {row.estimated_cost_usd === null ? "-" : formatCost(row.estimated_cost_usd)}
+                      </TableCell>
                       <TableCell className="text-muted-foreground">
                         {row.last_seen
                           ? new Date(row.last_seen).toLocaleString()
@@ -285,8 +347,40 @@ export default function UsageDashboard({
               </Table>
             </div>
           )}
+          {usage.breakdown_total > usage.page_size && (
+            <div className="flex items-center justify-between gap-3 border-t border-border px-4 py-2 text-xs text-muted-foreground">
+              <span className="tabular-nums">
+                {formatNumber(pageStart + 1)}-{formatNumber(pageStart + usage.breakdown.length)} of{" "}
+                {formatNumber(usage.breakdown_total)}
+              </span>
+              <div className="flex items-center gap-2">
+                <Button
+                  size="sm"
+                  variant="outline"
+                  onClick={() => onQueryChange({ page: usage.page - 1 })}
+                  disabled={usage.page === 0}
+                >
+                  Previous
+                </Button>
+                <Button
+                  size="sm"
+                  variant="outline"
+                  onClick={() => onQueryChange({ page: usage.page + 1 })}
+                  disabled={usage.page + 1 >= pageCount}
+                >
+                  Next
+                </Button>
+              </div>
+            </div>
+          )}
         </CardContent>
       </Card>
+
+      <UsageLatencyPanel latency={usage.latency} />
+
+      <UsageErrorsPanel errors={usage.errors} />
+
+      <UsageExportControls query={query} />
     </div>
   );
 }
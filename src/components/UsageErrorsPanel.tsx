@@ -0,0 +1,106 @@
+import type { ErrorCategory, UsageErrorRow } from "../types";
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "./ui/card";
+import {
+  Table,
+  TableBody,
+  TableCell,
+  TableHead,
+  TableHeader,
+  TableRow,
+} from "./ui/table";
+
+const CATEGORY_LABELS: Record<ErrorCategory, string> = {
+  rate_limited: "Rate limited",
+  auth_expired: "Auth expired",
+  quota_exceeded: "Quota exceeded",
+  upstream_5xx: "Upstream 5xx",
+  backend_unavailable: "Backend unavailable",
+  client_error: "Client error",
+  timeout: "Timeout",
+  cancelled: "Cancelled by client",
+};
+
+function formatNumber(value: number): string {
+  return new Intl.NumberFormat("en-US").format(value);
+}
+
+function categoryTotals(errors: UsageErrorRow[]) {
+  const totals = new Map<string, number>();
+  errors.forEach((row) => {
+    const label = row.category ? CATEGORY_LABELS[row.category] : "Unclassified";
+    totals.set(label, (totals.get(label) ?? 0) + row.requests);
+  });
+  return [...totals.entries()].sort((a, b) => b[1] - a[1]);
+}
+
+interface UsageErrorsPanelProps {
+  errors: UsageErrorRow[];
+}
+
+export default function UsageErrorsPanel({ errors }: UsageErrorsPanelProps) {
+  const totals = categoryTotals(errors);
+  const totalErrors = totals.reduce((sum, [, count]) => sum + count, 0);
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle className="text-sm font-semibold uppercase tracking-wider text-muted-foreground">Errors</CardTitle>
+        <CardDescription>
+          Failed requests by cause, classified from the upstream status and error body.
+        </CardDescription>
+      </CardHeader>
+      <CardContent className="flex flex-col gap-3">
+        {totals.length === 0 ? (
+          <p className="text-sm text-muted-foreground">No failed requests in this range.</p>
+        ) : (
+          totals.map(([label, count]) => (
+            <div key={label} className="flex items-center gap-3">
+              <span className="w-36 shrink-0 truncate text-xs font-medium">{label}</span>
+              <div className="h-2 flex-1 overflow-hidden rounded-full bg-muted">
+                <div
+                  className="h-full rounded-full bg-destructive opacity-70"
+                  style={{ width: `${Math.max(2, Math.round((count / totalErrors) * 100))}%` }}
+                />
+              </div>
+              <span className="w-16 text-right text-xs tabular-nums">{formatNumber(count)}</span>
+            </div>
+          ))
+        )}
+      </CardContent>
+      {errors.length > 0 && (
+        <CardContent className="p-0">
+          <div className="max-h-[320px] overflow-auto overscroll-none [&_div[data-slot=table-container]]:overflow-visible">
+            <Table>
+              <TableHeader className="bg-muted/50 sticky top-0 z-10 shadow-sm backdrop-blur">
+                <TableRow>
+                  <TableHead>Category</TableHead>
+                  <TableHead>Provider</TableHead>
+                  <TableHead className="text-right">Requests</TableHead>
+                  <TableHead>Latest message</TableHead>
+                  <TableHead>Last Seen</TableHead>
+                </TableRow>
+              </TableHeader>
+              <TableBody>
+                {errors.map((row) => (
+                  <TableRow key={`${row.category}-${row.provider}`}>
+                    <TableCell className="font-medium">
+                      {row.category ? CATEGORY_LABELS[row.category] : "Unclassified"}
+                    </TableCell>
+                    <TableCell>{row.provider}</TableCell>
+                    <TableCell className="text-right tabular-nums">{formatNumber(row.requests)}</TableCell>
+                    <TableCell className="max-w-[280px] truncate text-muted-foreground" title={row.last_message ?? undefined}>
+                      {row.last_message ?? "-"}
+                    </TableCell>
+                    <TableCell className="text-muted-foreground">
+                      {row.last_seen ? new Date(row.last_seen).toLocaleString() : "-"}
+                    </TableCell>
+                  </TableRow>
+                ))}
+              </TableBody>
+            </Table>
+          </div>
+        </CardContent>
+      )}
+    </Card>
+  );
+}
@@ -0,0 +1,131 @@
+import { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { Download, FolderOpen } from "lucide-react";
+import type {
+  UsageExportFormat,
+  UsageExportKind,
+  UsageExportRequest,
+  UsageDashboardQuery,
+  UsageExportResult,
+  UsageRange,
+} from "../types";
+import { toErrorMessage } from "../utils/error";
+import { Button } from "./ui/button";
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "./ui/card";
+import { Input } from "./ui/input";
+
+const SELECT_CLASS =
+  "flex h-9 rounded-md border border-input bg-transparent px-2 py-1 text-sm shadow-sm transition-colors focus-visible:outline-none focus-visible:ring-1 focus-visible:ring-ring";
+
+const RANGE_HOURS: Record<UsageRange, number | null> = {
+  "24h": 24,
+  "7d": 24 * 7,
+  "30d": 24 * 30,
+  all: null,
+};
+
+interface UsageExportControlsProps {
+  query: UsageDashboardQuery;
+}
+
+export default function UsageExportControls({ query }: UsageExportControlsProps) {
+  const [format, setFormat] = useState<UsageExportFormat>("csv");
+  const [kind, setKind] = useState<UsageExportKind>("events");
+  const [provider, setProvider] = useState("");
+  const [status, setStatus] = useState("");
+  const [isExporting, setIsExporting] = useState(false);
+  const [result, setResult] = useState<UsageExportResult | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  const runExport = async () => {
+    const hours = RANGE_HOURS[query.range];
+    const isCustom = query.from !== null || query.to !== null;
+    const presetFrom =
+      hours === null ? null : new Date(Date.now() - hours * 3_600_000).toISOString();
+    const request: UsageExportRequest = {
+      path: null,
+      format,
+      kind,
+      from: isCustom ? query.from : presetFrom,
+      to: isCustom ? query.to : null,
+      provider: provider.trim() || null,
+      model: null,
+      account_key: null,
+      status: kind === "events" && status ? status : null,
+    };
+    setIsExporting(true);
+    try {
+      setResult(await invoke<UsageExportResult>("export_usage", { request }));
+      setError(null);
+    } catch (err) {
+      setError(toErrorMessage(err, "Failed to export usage"));
+    } finally {
+      setIsExporting(false);
+    }
+  };
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle className="text-sm font-semibold uppercase tracking-wider text-muted-foreground">Export</CardTitle>
+        <CardDescription>
+          Write the selected range to a file, as raw request events or daily rollups.
+        </CardDescription>
+      </CardHeader>
+      <CardContent className="flex flex-col gap-3">
+        <div className="flex flex-wrap items-center gap-2">
+          <select
+            className={SELECT_CLASS}
+            value={format}
+            onChange={(e) => setFormat(e.target.value as UsageExportFormat)}
+            aria-label="Export format"
+          >
+            <option value="csv" className="bg-background text-foreground">CSV</option>
+            <option value="jsonl" className="bg-background text-foreground">JSON Lines</option>
+          </select>
+          <select
+            className={SELECT_CLASS}
+            value={kind}
+            onChange={(e) => setKind(e.target.value as UsageExportKind)}
+            aria-label="Export rows"
+          >
+            <option value="events" className="bg-background text-foreground">Raw events</option>
+            <option value="rollups" className="bg-background text-foreground">Daily rollups</option>
+          </select>
+          <select
+            className={SELECT_CLASS}
+            value={status}
+            onChange={(e) => setStatus(e.target.value)}
+            disabled={kind !== "events"}
+            aria-label="Status filter"
+          >
+            <option value="" className="bg-background text-foreground">All statuses</option>
+            <option value="success" className="bg-background text-foreground">Successful</option>
+            <option value="error" className="bg-background text-foreground">Errors</option>
+          </select>
+          <Input
+            className="w-40"
+            value={provider}
+            placeholder="Provider (optional)"
+            onChange={(e) => setProvider(e.target.value)}
+          />
+          <Button size="sm" variant="outline" onClick={runExport} disabled={isExporting}>
+            <Download className="mr-2 h-4 w-4" />
+            {isExporting ? "Exporting..." : "Export"}
+          </Button>
+          <Button size="sm" variant="ghost" onClick={() => invoke("open_usage_exports_folder")}>
+            <FolderOpen className="mr-2 h-4 w-4" />
+            Open Folder
+          </Button>
+        </div>
+        {result && (
+          <small className="text-xs text-muted-foreground">
+            Wrote {new Intl.NumberFormat("en-US").format(result.rows)} rows to{" "}
+            <code>{result.path}</code>
+          </small>
+        )}
+        {error && <small className="text-xs text-destructive">{error}</small>}
+      </CardContent>
+    </Card>
+  );
+}
@@ -0,0 +1,149 @@
+import type { LatencyPercentiles, UsageLatency, UsageLatencyPoint } from "../types";
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "./ui/card";
+import {
+  Table,
+  TableBody,
+  TableCell,
+  TableHead,
+  TableHeader,
+  TableRow,
+} from "./ui/table";
+
+function formatMs(value: number | null): string {
+  if (value === null) return "-";
+  return value >= 1000 ? `${(value / 1000).toFixed(1)}s` : `${Math.round(value)}ms`;
+}
+
+function formatRate(value: number | null): string {
+  return value === null ? "-" : `${value.toFixed(1)}/s`;
+}
+
+function formatTriple(p: LatencyPercentiles, format: (value: number | null) => string): string {
+  return `${format(p.p50)} / ${format(p.p90)} / ${format(p.p99)}`;
+}
+
+function groupByProvider(points: UsageLatencyPoint[]) {
+  const byProvider = new Map<string, UsageLatencyPoint[]>();
+  points.forEach((point) => {
+    const rows = byProvider.get(point.provider) ?? [];
+    rows.push(point);
+    byProvider.set(point.provider, rows);
+  });
+  return [...byProvider.entries()];
+}
+
+const STAT_ROWS: Array<{
+  label: string;
+  key: "duration_ms" | "ttfb_ms" | "ttft_ms" | "output_tokens_per_sec";
+  format: (value: number | null) => string;
+}> = [
+  { label: "Total", key: "duration_ms", format: formatMs },
+  { label: "First byte", key: "ttfb_ms", format: formatMs },
+  { label: "First token", key: "ttft_ms", format: formatMs },
+  { label: "Output tok/s", key: "output_tokens_per_sec", format: formatRate },
+];
+
+interface UsageLatencyPanelProps {
+  latency: UsageLatency;
+}
+
+export default function UsageLatencyPanel({ latency }: UsageLatencyPanelProps) {
+  const providers = groupByProvider(latency.timeseries);
+  const maxP90 = Math.max(1, ...latency.timeseries.map((point) => point.stats.duration_ms.p90 ?? 0));
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle className="text-sm font-semibold uppercase tracking-wider text-muted-foreground">Latency</CardTitle>
+        <CardDescription>
+          p50 / p90 / p99 over successful requests. First byte is when upstream headers arrive;
+          first token applies to streamed responses.
+        </CardDescription>
+      </CardHeader>
+      <CardContent className="flex flex-col gap-6">
+        {latency.overall.samples === 0 ? (
+          <p className="text-sm text-muted-foreground">No latency samples for this range.</p>
+        ) : (
+          <>
+            <div className="grid grid-cols-2 gap-4 md:grid-cols-4">
+              {STAT_ROWS.map((row) => (
+                <div key={row.key} className="flex flex-col gap-1">
+                  <span className="text-xs font-medium uppercase tracking-wider text-muted-foreground">
+                    {row.label}
+                  </span>
+                  <span className="text-sm font-semibold tabular-nums">
+                    {formatTriple(latency.overall[row.key], row.format)}
+                  </span>
+                </div>
+              ))}
+            </div>
+
+            <div className="flex flex-col gap-3">
+              {providers.map(([provider, points]) => (
+                <div key={provider} className="flex items-end gap-3">
+                  <span className="w-28 shrink-0 truncate text-xs font-medium">{provider}</span>
+                  <div className="flex h-[48px] flex-1 items-end gap-1">
+                    {points.map((point) => (
+                      <div
+                        key={point.bucket}
+                        className="min-w-2 flex-1 rounded-t-sm bg-primary opacity-70 transition-all hover:opacity-100"
+                        style={{
+                          height: `${Math.max(4, Math.round(((point.stats.duration_ms.p90 ?? 0) / maxP90) * 100))}%`,
+                        }}
+                        title={`${point.bucket}: total ${formatTriple(point.stats.duration_ms, formatMs)}, first byte p50 ${formatMs(point.stats.ttfb_ms.p50)}, ${formatRate(point.stats.output_tokens_per_sec.p50)} (${point.stats.samples} requests)`}
+                      />
+                    ))}
+                  </div>
+                </div>
+              ))}
+            </div>
+          </>
+        )}
+      </CardContent>
+      {latency.by_model.length > 0 && (
+        <CardContent className="p-0">
+          <div className="max-h-[320px] overflow-auto overscroll-none [&_div[data-slot=table-container]]:overflow-visible">
+            <Table>
+              <TableHeader className="bg-muted/50 sticky top-0 z-10 shadow-sm backdrop-blur">
+                <TableRow>
+                  <TableHead>Provider</TableHead>
+                  <TableHead>Model</TableHead>
+                  <TableHead>Account</TableHead>
+                  <TableHead className="text-right">Requests</TableHead>
+                  <TableHead className="text-right">Total p50 / p90 / p99</TableHead>
+                  <TableHead className="text-right">First byte p50</TableHead>
+                  <TableHead className="text-right">First token p50</TableHead>
+                  <TableHead className="text-right">Tok/s p50</TableHead>
+                </TableRow>
+              </TableHeader>
+              <TableBody>
+                {latency.by_model.map((row) => (
+                  <TableRow key={`${row.provider}-${row.model}-${row.account_key}`}>
+                    <TableCell className="font-medium">{row.provider}</TableCell>
+                    <TableCell>{row.model}</TableCell>
+                    <TableCell className="max-w-[150px] truncate" title={row.account_label || row.account_key}>
+                      {row.account_label || row.account_key}
+                    </TableCell>
+                    <TableCell className="text-right tabular-nums">{row.stats.samples}</TableCell>
+                    <TableCell className="text-right tabular-nums">
+                      {formatTriple(row.stats.duration_ms, formatMs)}
+                    </TableCell>
+                    <TableCell className="text-right tabular-nums text-muted-foreground">
+                      {formatMs(row.stats.ttfb_ms.p50)}
+                    </TableCell>
+                    <TableCell className="text-right tabular-nums text-muted-foreground">
+                      {formatMs(row.stats.ttft_ms.p50)}
+                    </TableCell>
+                    <TableCell className="text-right tabular-nums">
+                      {formatRate(row.stats.output_tokens_per_sec.p50)}
+                    </TableCell>
+                  </TableRow>
+                ))}
+              </TableBody>
+            </Table>
+          </div>
+        </CardContent>
+      )}
+    </Card>
+  );
+}
@@ -0,0 +1,234 @@
+import { useState } from "react";
+import { X } from "lucide-react";
+import type { UsageBucket, UsageDashboardQuery, UsageRange, UsageStatusClass } from "../types";
+import { Button } from "./ui/button";
+import { Input } from "./ui/input";
+
+const SELECT_CLASS =
+  "flex h-8 rounded-md border border-input bg-transparent px-2 py-1 text-xs shadow-sm transition-colors focus-visible:outline-none focus-visible:ring-1 focus-visible:ring-ring";
+
+const RANGE_OPTIONS: Array<{ label: string; value: UsageRange }> = [
+  { label: "24h", value: "24h" },
+  { label: "7d", value: "7d" },
+  { label: "30d", value: "30d" },
+  { label: "All", value: "all" },
+];
+
+const BUCKET_OPTIONS: Array<{ label: string; value: UsageBucket | "" }> = [
+  { label: "Auto", value: "" },
+  { label: "Minute", value: "minute" },
+  { label: "Hour", value: "hour" },
+  { label: "Day", value: "day" },
+  { label: "Week", value: "week" },
+  { label: "Month", value: "month" },
+];
+
+const STATUS_OPTIONS: Array<{ label: string; value: UsageStatusClass | "" }> = [
+  { label: "All statuses", value: "" },
+  { label: "Success", value: "success" },
+  { label: "Errors", value: "error" },
+  { label: "4xx", value: "4xx" },
+  { label: "5xx", value: "5xx" },
+];
+
+/** Local midnight of a `YYYY-MM-DD` date, shifted by `days`, as an RFC 3339 timestamp. */
+function localDayStart(date: string, days = 0): string {
+  const value = new Date(`${date}T00:00`);
+  value.setDate(value.getDate() + days);
+  return value.toISOString();
+}
+
+function toLocalDate(timestamp: string | null, days = 0): string {
+  if (!timestamp) return "";
+  const value = new Date(timestamp);
+  value.setDate(value.getDate() + days);
+  const pad = (n: number) => String(n).padStart(2, "0");
+  return `${value.getFullYear()}-${pad(value.getMonth() + 1)}-${pad(value.getDate())}`;
+}
+
+interface UsageQueryControlsProps {
+  query: UsageDashboardQuery;
+  clientLabel: string | null;
+  onQueryChange: (patch: Partial<UsageDashboardQuery>) => void;
+}
+
+export default function UsageQueryControls({
+  query,
+  clientLabel,
+  onQueryChange,
+}: UsageQueryControlsProps) {
+  const isCustom = query.from !== null || query.to !== null;
+  const [showCustom, setShowCustom] = useState(isCustom);
+  const [customFrom, setCustomFrom] = useState(toLocalDate(query.from));
+  const [customTo, setCustomTo] = useState(toLocalDate(query.to, -1));
+  const [provider, setProvider] = useState(query.provider ?? "");
+  const [model, setModel] = useState(query.model ?? "");
+  const [account, setAccount] = useState(query.account_key ?? "");
+
+  const applyCustomRange = () => {
+    onQueryChange({
+      from: customFrom ? localDayStart(customFrom) : null,
+      // The end date is inclusive, so stop at the following local midnight.
+      to: customTo ? localDayStart(customTo, 1) : null,
+    });
+  };
+
+  const applyTextFilters = () => {
+    onQueryChange({
+      provider: provider.trim() || null,
+      model: model.trim() || null,
+      account_key: account.trim() || null,
+    });
+  };
+
+  const hasFilters =
+    query.provider !== null ||
+    query.model !== null ||
+    query.account_key !== null ||
+    query.client_id !== null ||
+    query.status !== null;
+
+  const clearFilters = () => {
+    setProvider("");
+    setModel("");
+    setAccount("");
+    onQueryChange({
+      provider: null,
+      model: null,
+      account_key: null,
+      client_id: null,
+      status: null,
+    });
+  };
+
+  return (
+    <div className="flex flex-col gap-3">
+      <div className="flex flex-wrap items-center gap-2">
+        <div className="inline-flex items-center gap-1 rounded-full border border-border bg-muted/50 p-1">
+          {RANGE_OPTIONS.map((option) => (
+            <button
+              type="button"
+              key={option.value}
+              className={`rounded-full px-3 py-1.5 text-xs font-medium transition-colors ${!showCustom && query.range === option.value ? "bg-background text-foreground shadow-sm" : "text-muted-foreground hover:text-foreground"}`}
+              onClick={() => {
+                setShowCustom(false);
+                onQueryChange({ range: option.value, from: null, to: null });
+              }}
+            >
+              {option.label}
+            </button>
+          ))}
+          <button
+            type="button"
+            className={`rounded-full px-3 py-1.5 text-xs font-medium transition-colors ${showCustom ? "bg-background text-foreground shadow-sm" : "text-muted-foreground hover:text-foreground"}`}
+            onClick={() => setShowCustom(true)}
+          >
+            Custom
+          </button>
+        </div>
+        <select
+          className={SELECT_CLASS}
+          value={query.bucket ?? ""}
+          onChange={(e) => onQueryChange({ bucket: (e.target.value || null) as UsageBucket | null })}
+          aria-label="Bucket size"
+        >
+          {BUCKET_OPTIONS.map((option) => (
+            <option key={option.value} value={option.value} className="bg-background text-foreground">
+              {option.label}
+            </option>
+          ))}
+        </select>
+        <select
+          className={SELECT_CLASS}
+          value={query.status ?? ""}
+          onChange={(e) =>
+            onQueryChange({ status: (e.target.value || null) as UsageStatusClass | null })
+          }
+          aria-label="Status filter"
+        >
+          {STATUS_OPTIONS.map((option) => (
+            <option key={option.value} value={option.value} className="bg-background text-foreground">
+              {option.label}
+            </option>
+          ))}
+        </select>
+      </div>
+
+      {showCustom && (
+        <div className="flex flex-wrap items-center gap-2">
+          <Input
+            type="date"
+            className="h-8 w-auto text-xs"
+            value={customFrom}
+            onChange={(e) => setCustomFrom(e.target.value)}
+            aria-label="From date"
+          />
+          <span className="text-xs text-muted-foreground">to</span>
+          <Input
+            type="date"
+            className="h-8 w-auto text-xs"
+            value={customTo}
+            onChange={(e) => setCustomTo(e.target.value)}
+            aria-label="To date"
+          />
+          <Button
+            size="sm"
+            variant="outline"
+            onClick={applyCustomRange}
+            disabled={!customFrom && !customTo}
+          >
+            Apply
+          </Button>
+        </div>
+      )}
+
+      <form
+        className="flex flex-wrap items-center gap-2"
+        onSubmit={(e) => {
+          e.preventDefault();
+          applyTextFilters();
+        }}
+      >
+        <Input
+          className="h-8 w-36 text-xs"
+          value={provider}
+          placeholder="Provider"
+          onChange={(e) => setProvider(e.target.value)}
+          onBlur={applyTextFilters}
+        />
+        <Input
+          className="h-8 w-44 text-xs"
+          value={model}
+          placeholder="Model"
+          onChange={(e) => setModel(e.target.value)}
+          onBlur={applyTextFilters}
+        />
+        <Input
+          className="h-8 w-44 text-xs"
+          value={account}
+          placeholder="Account"
+          onChange={(e) => setAccount(e.target.value)}
+          onBlur={applyTextFilters}
+        />
+        {query.client_id !== null && (
+          <span className="inline-flex items-center gap-1 rounded-full border border-border px-2.5 py-1 text-xs">
+            Client: {clientLabel ?? (query.client_id || "Unidentified")}
+            <button
+              type="button"
+              className="text-muted-foreground hover:text-foreground"
+              onClick={() => onQueryChange({ client_id: null })}
+              aria-label="Clear client filter"
+            >
+              <X className="h-3 w-3" />
+            </button>
+          </span>
+        )}
+        {hasFilters && (
+          <Button type="button" size="sm" variant="ghost" onClick={clearFilters}>
+            Clear filters
+          </Button>
+        )}
+      </form>
+    </div>
+  );
+}
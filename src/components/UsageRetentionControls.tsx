@@ -0,0 +1,142 @@
+import { useCallback, useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { Save } from "lucide-react";
+import type { UsageMaintenanceReport, UsageRetention, UsageStorageStats } from "../types";
+import { toErrorMessage } from "../utils/error";
+import { Button } from "./ui/button";
+import { Input } from "./ui/input";
+
+interface UsageRetentionControlsProps {
+  retention: UsageRetention;
+  onSave: (retention: UsageRetention) => Promise<void>;
+}
+
+function parseDays(raw: string): number | null {
+  const n = Number(raw.trim() === "" ? "0" : raw.trim());
+  return Number.isInteger(n) && n >= 0 ? n : null;
+}
+
+function formatBytes(bytes: number): string {
+  if (bytes < 1024) return `${bytes} B`;
+  if (bytes < 1024 * 1024) return `${(bytes / 1024).toFixed(1)} KB`;
+  return `${(bytes / (1024 * 1024)).toFixed(1)} MB`;
+}
+
+function formatNumber(value: number): string {
+  return new Intl.NumberFormat("en-US").format(value);
+}
+
+export default function UsageRetentionControls({ retention, onSave }: UsageRetentionControlsProps) {
+  const [eventDays, setEventDays] = useState(String(retention.raw_event_days));
+  const [jsonDays, setJsonDays] = useState(String(retention.usage_json_days));
+  const [stats, setStats] = useState<UsageStorageStats | null>(null);
+  const [report, setReport] = useState<UsageMaintenanceReport | null>(null);
+  const [running, setRunning] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  const refresh = useCallback(async () => {
+    try {
+      setStats(await invoke<UsageStorageStats>("get_usage_storage_stats"));
+    } catch (err) {
+      setError(toErrorMessage(err, "Failed to load usage storage stats"));
+    }
+  }, []);
+
+  useEffect(() => {
+    refresh();
+  }, [refresh]);
+
+  const parsedEventDays = parseDays(eventDays);
+  const parsedJsonDays = parseDays(jsonDays);
+  const isValid = parsedEventDays !== null && parsedJsonDays !== null;
+  const hasChanges =
+    parsedEventDays !== retention.raw_event_days || parsedJsonDays !== retention.usage_json_days;
+
+  const runMaintenance = async () => {
+    setRunning(true);
+    try {
+      setReport(await invoke<UsageMaintenanceReport>("run_usage_maintenance"));
+      setError(null);
+      await refresh();
+    } catch (err) {
+      setError(toErrorMessage(err, "Failed to run usage maintenance"));
+    } finally {
+      setRunning(false);
+    }
+  };
+
+  return (
+    <div className="flex flex-col gap-3 py-3.5 border-b border-border">
+      <div className="flex min-w-0 flex-col gap-0.5">
+        <span className="text-sm font-medium">Usage data retention</span>
+        <small className="text-xs text-muted-foreground">
+          Older raw events are pruned after being folded into daily totals, so budgets and
+          rollup exports keep long-term history. 0 keeps data forever. Runs daily.
+        </small>
+      </div>
+
+      {stats && (
+        <small className="text-xs text-muted-foreground tabular-nums">
+          {formatBytes(stats.db_size_bytes + stats.wal_size_bytes)} on disk
+          {stats.free_bytes > 0 && ` (${formatBytes(stats.free_bytes)} reclaimable)`}
+          {" · "}
+          {formatNumber(stats.event_count)} events ({formatNumber(stats.events_with_usage_json)}{" "}
+          with raw usage) since {stats.oldest_event_day ?? "-"}
+          {" · "}
+          {formatNumber(stats.rollup_count)} daily rollups since {stats.oldest_rollup_day ?? "-"}
+        </small>
+      )}
+
+      <div className="grid grid-cols-2 gap-3">
+        <div className="flex flex-col gap-1.5">
+          <label className="text-xs font-medium text-muted-foreground" htmlFor="retention-events">
+            Keep raw events (days)
+          </label>
+          <Input
+            id="retention-events"
+            inputMode="numeric"
+            value={eventDays}
+            onChange={(e) => setEventDays(e.target.value)}
+          />
+        </div>
+        <div className="flex flex-col gap-1.5">
+          <label className="text-xs font-medium text-muted-foreground" htmlFor="retention-json">
+            Keep raw usage payloads (days)
+          </label>
+          <Input
+            id="retention-json"
+            inputMode="numeric"
+            value={jsonDays}
+            onChange={(e) => setJsonDays(e.target.value)}
+          />
+        </div>
+      </div>
+      <div className="flex items-center justify-end gap-2">
+        <Button size="sm" variant="outline" onClick={runMaintenance} disabled={running}>
+          {running ? "Running..." : "Run maintenance now"}
+        </Button>
+        <Button
+          size="sm"
+          variant="outline"
+          onClick={() =>
+            isValid &&
+            onSave({ raw_event_days: parsedEventDays!, usage_json_days: parsedJsonDays! })
+          }
+          disabled={!hasChanges || !isValid}
+        >
+          <Save className="mr-2 h-4 w-4" />
+          Save
+        </Button>
+      </div>
+
+      {report && (
+        <small className="text-xs text-muted-foreground">
+          Pruned {formatNumber(report.pruned_events)} events, cleared{" "}
+          {formatNumber(report.cleared_usage_json)} raw payloads
+          {report.vacuumed ? ", compacted the database" : ""}.
+        </small>
+      )}
+      {error && <small className="text-xs text-destructive">{error}</small>}
+    </div>
+  );
+}
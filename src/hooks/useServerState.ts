@@ -1,7 +1,12 @@
 import { useState, useEffect, useCallback } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
-import type { ServerState, BinaryDownloadProgress } from "../types";
+import type {
+  ServerState,
+  BinaryDownloadProgress,
+  ProxyDrainEvent,
+  ServerCrashEvent,
+} from "../types";
 import { toErrorMessage } from "../utils/error";
 
 const DEFAULT_SERVER_STATE: ServerState = {
@@ -17,6 +22,7 @@ export function useServerState() {
   const [downloadProgress, setDownloadProgress] =
     useState<BinaryDownloadProgress | null>(null);
   const [lastError, setLastError] = useState<string | null>(null);
+  const [draining, setDraining] = useState<ProxyDrainEvent | null>(null);
 
   useEffect(() => {
     let mounted = true;
@@ -59,6 +65,20 @@ export function useServerState() {
       },
     );
 
+    const unlistenCrash = listen<ServerCrashEvent>("server_crashed", (event) => {
+      const { crash_count, restarting, last_logs } = event.payload;
+      if (restarting) return;
+      const lastLine = last_logs[last_logs.length - 1]?.message;
+      setLastError(
+        `Backend crashed ${crash_count} times and was not restarted.` +
+          (lastLine ? ` Last log: ${lastLine}` : ""),
+      );
+    });
+
+    const unlistenDrain = listen<ProxyDrainEvent>("proxy_draining", (event) => {
+      setDraining(event.payload.in_flight > 0 ? event.payload : null);
+    });
+
     const unlistenDownload = listen<BinaryDownloadProgress>(
       "binary_download_progress",
       (event) => {
@@ -69,6 +89,8 @@ export function useServerState() {
     return () => {
       mounted = false;
       unlistenStatus.then((fn) => fn());
+      unlistenCrash.then((fn) => fn());
+      unlistenDrain.then((fn) => fn());
       unlistenDownload.then((fn) => fn());
     };
   }, []);
@@ -106,6 +128,7 @@ export function useServerState() {
   return {
     serverState,
     downloadProgress,
+    draining,
     startServer,
     stopServer,
     downloadBinary,
@@ -1,6 +1,13 @@
 import { useState, useEffect, useCallback } from "react";
 import { invoke } from "@tauri-apps/api/core";
-import type { AppSettings } from "../types";
+import type {
+  AppSettings,
+  FailoverChain,
+  ProxyRetries,
+  ProxyTimeouts,
+  RequestCaptureSettings,
+  UsageRetention,
+} from "../types";
 import { toErrorMessage } from "../utils/error";
 
 const DEFAULT_SETTINGS: AppSettings = {
@@ -8,6 +15,20 @@ const DEFAULT_SETTINGS: AppSettings = {
   vercel_gateway_enabled: false,
   vercel_api_key: "",
   launch_at_login: false,
+  bind_address: "127.0.0.1",
+  proxy_port: 8317,
+  backend_port: 8318,
+  persist_server_logs: false,
+  require_client_api_key: false,
+  pricing_overrides: {},
+  usage_budgets: [],
+  usage_retention: { raw_event_days: 0, usage_json_days: 0 },
+  request_capture: { enabled: false, max_body_kb: 64, max_storage_mb: 50 },
+  model_rules: [],
+  proxy_timeouts: { connect_secs: 5, read_secs: 900, total_secs: 0, overrides: [] },
+  proxy_retries: { max_attempts: 3, base_delay_ms: 500, max_delay_ms: 20000 },
+  failover_chains: [],
+  shutdown_drain_secs: 30,
 };
 
 export function useSettings() {
@@ -92,11 +113,157 @@ export function useSettings() {
     }
   }, []);
 
+  const setNetworkConfig = useCallback(
+    async (bindAddress: string, proxyPort: number, backendPort: number) => {
+      try {
+        await invoke("set_network_config", { bindAddress, proxyPort, backendPort });
+        setSettings((prev) => {
+          if (!prev) return prev;
+          return {
+            ...prev,
+            bind_address: bindAddress,
+            proxy_port: proxyPort,
+            backend_port: backendPort,
+          };
+        });
+        setLastError(null);
+      } catch (err) {
+        console.error("Failed to set network config:", err);
+        setLastError(toErrorMessage(err, "Failed to update network settings"));
+      }
+    },
+    [],
+  );
+
+  const setServerLogPersistence = useCallback(async (enabled: boolean) => {
+    setSettings((prev) => {
+      if (!prev) return prev;
+      return { ...prev, persist_server_logs: enabled };
+    });
+    try {
+      await invoke("set_server_log_persistence", { enabled });
+      setLastError(null);
+    } catch (err) {
+      console.error("Failed to set server log persistence:", err);
+      setLastError(toErrorMessage(err, "Failed to update backend log settings"));
+      invoke<AppSettings>("get_settings")
+        .then(setSettings)
+        .catch((e) => console.error("Failed to refetch settings:", e));
+    }
+  }, []);
+
+  const setRequireClientApiKey = useCallback(async (enabled: boolean) => {
+    try {
+      await invoke("set_require_client_api_key", { enabled });
+      setSettings((prev) => {
+        if (!prev) return prev;
+        return { ...prev, require_client_api_key: enabled };
+      });
+      setLastError(null);
+    } catch (err) {
+      console.error("Failed to set client key requirement:", err);
+      setLastError(toErrorMessage(err, "Failed to update client key requirement"));
+    }
+  }, []);
+
+  const setUsageRetention = useCallback(async (retention: UsageRetention) => {
+    try {
+      await invoke("set_usage_retention", { retention });
+      setSettings((prev) => {
+        if (!prev) return prev;
+        return { ...prev, usage_retention: retention };
+      });
+      setLastError(null);
+    } catch (err) {
+      console.error("Failed to set usage retention:", err);
+      setLastError(toErrorMessage(err, "Failed to update usage retention"));
+    }
+  }, []);
+
+  const setRequestCapture = useCallback(async (capture: RequestCaptureSettings) => {
+    try {
+      await invoke("set_request_capture_settings", { capture });
+      setSettings((prev) => {
+        if (!prev) return prev;
+        return { ...prev, request_capture: capture };
+      });
+      setLastError(null);
+    } catch (err) {
+      console.error("Failed to set request capture:", err);
+      setLastError(toErrorMessage(err, "Failed to update request capture"));
+    }
+  }, []);
+
+  const setProxyTimeouts = useCallback(async (timeouts: ProxyTimeouts) => {
+    try {
+      const saved = await invoke<ProxyTimeouts>("set_proxy_timeouts", { timeouts });
+      setSettings((prev) => {
+        if (!prev) return prev;
+        return { ...prev, proxy_timeouts: saved };
+      });
+      setLastError(null);
+    } catch (err) {
+      console.error("Failed to set proxy timeouts:", err);
+      setLastError(toErrorMessage(err, "Failed to update proxy timeouts"));
+    }
+  }, []);
+
+  const setProxyRetries = useCallback(async (retries: ProxyRetries) => {
+    try {
+      await invoke("set_proxy_retries", { retries });
+      setSettings((prev) => {
+        if (!prev) return prev;
+        return { ...prev, proxy_retries: retries };
+      });
+      setLastError(null);
+    } catch (err) {
+      console.error("Failed to set proxy retries:", err);
+      setLastError(toErrorMessage(err, "Failed to update proxy retries"));
+    }
+  }, []);
+
+  const setFailoverChains = useCallback(async (chains: FailoverChain[]) => {
+    try {
+      const saved = await invoke<FailoverChain[]>("set_failover_chains", { chains });
+      setSettings((prev) => {
+        if (!prev) return prev;
+        return { ...prev, failover_chains: saved };
+      });
+      setLastError(null);
+    } catch (err) {
+      console.error("Failed to set failover chains:", err);
+      setLastError(toErrorMessage(err, "Failed to update failover chains"));
+    }
+  }, []);
+
+  const setShutdownDrain = useCallback(async (secs: number) => {
+    try {
+      await invoke("set_shutdown_drain", { secs });
+      setSettings((prev) => {
+        if (!prev) return prev;
+        return { ...prev, shutdown_drain_secs: secs };
+      });
+      setLastError(null);
+    } catch (err) {
+      console.error("Failed to set shutdown drain timeout:", err);
+      setLastError(toErrorMessage(err, "Failed to update shutdown drain timeout"));
+    }
+  }, []);
+
   return {
     settings,
     setProviderEnabled,
     setVercelConfig,
     setLaunchAtLogin,
+    setNetworkConfig,
+    setServerLogPersistence,
+    setRequireClientApiKey,
+    setUsageRetention,
+    setRequestCapture,
+    setProxyTimeouts,
+    setProxyRetries,
+    setFailoverChains,
+    setShutdownDrain,
     lastError,
     clearLastError: () => setLastError(null),
   };
@@ -1,30 +1,71 @@
 import { useCallback, useEffect, useMemo, useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
-import type { UsageDashboardPayload, UsageRange } from "../types";
+import type { UsageDashboardPayload, UsageDashboardQuery, UsageRange } from "../types";
 import { toErrorMessage } from "../utils/error";
 
 const DEFAULT_RANGE: UsageRange = "7d";
+const DEFAULT_PAGE_SIZE = 50;
+
+const EMPTY_PERCENTILES = { p50: null, p90: null, p99: null };
+
+const DEFAULT_QUERY: UsageDashboardQuery = {
+  range: DEFAULT_RANGE,
+  from: null,
+  to: null,
+  bucket: null,
+  utc_offset_minutes: 0,
+  time_zone: null,
+  provider: null,
+  model: null,
+  account_key: null,
+  client_id: null,
+  status: null,
+  page: 0,
+  page_size: DEFAULT_PAGE_SIZE,
+};
 
 const EMPTY_DASHBOARD: UsageDashboardPayload = {
   dashboard: {
     range: DEFAULT_RANGE,
+    bucket: "day",
     summary: {
       total_requests: 0,
       total_tokens: 0,
       input_tokens: 0,
       output_tokens: 0,
       cached_tokens: 0,
+      cache_write_tokens: 0,
       reasoning_tokens: 0,
       error_count: 0,
       error_rate: 0,
+      estimated_cost_usd: 0,
+      unpriced_requests: 0,
     },
     timeseries: [],
+    providers: [],
     breakdown: [],
+    breakdown_total: 0,
+    page: 0,
+    page_size: DEFAULT_PAGE_SIZE,
+    clients: [],
+    latency: {
+      overall: {
+        samples: 0,
+        duration_ms: EMPTY_PERCENTILES,
+        ttfb_ms: EMPTY_PERCENTILES,
+        ttft_ms: EMPTY_PERCENTILES,
+        output_tokens_per_sec: EMPTY_PERCENTILES,
+      },
+      by_model: [],
+      timeseries: [],
+    },
+    errors: [],
+    truncated_before: null,
   },
 };
 
 export function useUsageDashboard(isActive: boolean) {
-  const [range, setRange] = useState<UsageRange>(DEFAULT_RANGE);
+  const [query, setQuery] = useState<UsageDashboardQuery>(DEFAULT_QUERY);
   const [dashboard, setDashboard] = useState<UsageDashboardPayload>(EMPTY_DASHBOARD);
   const [isLoading, setIsLoading] = useState(true);
   const [lastError, setLastError] = useState<string | null>(null);
@@ -32,7 +73,13 @@ export function useUsageDashboard(isActive: boolean) {
   const fetchDashboard = useCallback(async () => {
     try {
       const result = await invoke<UsageDashboardPayload>("get_usage_dashboard", {
-        range,
+        // Buckets follow the local time zone; its offset is resolved per event, so ranges
+        // across a DST change still split at local midnight.
+        query: {
+          ...query,
+          utc_offset_minutes: -new Date().getTimezoneOffset(),
+          time_zone: Intl.DateTimeFormat().resolvedOptions().timeZone,
+        },
       });
       setDashboard(result);
       setLastError(null);
@@ -42,7 +89,12 @@ export function useUsageDashboard(isActive: boolean) {
     } finally {
       setIsLoading(false);
     }
-  }, [range]);
+  }, [query]);
+
+  // Any change other than paging starts over at the first breakdown page.
+  const updateQuery = useCallback((patch: Partial<UsageDashboardQuery>) => {
+    setQuery((prev) => ({ ...prev, page: 0, ...patch }));
+  }, []);
 
   useEffect(() => {
     setIsLoading(true);
@@ -63,14 +115,14 @@ export function useUsageDashboard(isActive: boolean) {
 
   return useMemo(
     () => ({
-      range,
-      setRange,
+      query,
+      updateQuery,
       dashboard,
       isLoading,
       lastError,
       refresh: fetchDashboard,
       clearLastError: () => setLastError(null),
     }),
-    [dashboard, fetchDashboard, isLoading, lastError, range],
+    [dashboard, fetchDashboard, isLoading, lastError, query, updateQuery],
   );
 }
@@ -38,6 +38,225 @@ export interface AppSettings {
   vercel_gateway_enabled: boolean;
   vercel_api_key: string;
   launch_at_login: boolean;
+  bind_address: string;
+  proxy_port: number;
+  backend_port: number;
+  persist_server_logs: boolean;
+  require_client_api_key: boolean;
+  pricing_overrides: Record<string, ModelPrice>;
+  usage_budgets: UsageBudget[];
+  usage_retention: UsageRetention;
+  request_capture: RequestCaptureSettings;
+  model_rules: ModelRewriteRule[];
+  proxy_timeouts: ProxyTimeouts;
+  proxy_retries: ProxyRetries;
+  failover_chains: FailoverChain[];
+  shutdown_drain_secs: number;
+}
+
+export type FailoverTarget = "backend" | "vercel";
+
+/** A fallback route; each `*` in the model takes the text matched by the chain pattern. */
+export interface FailoverStep {
+  target: FailoverTarget;
+  model: string;
+}
+
+/** Fallbacks tried in order when a matching model fails with 429, 529 or a 5xx. */
+export interface FailoverChain {
+  id: string;
+  enabled: boolean;
+  pattern: string;
+  steps: FailoverStep[];
+}
+
+/** Upstream timeouts in seconds; 0 disables a limit. */
+export interface ProxyTimeouts {
+  connect_secs: number;
+  read_secs: number;
+  total_secs: number;
+  overrides: TimeoutOverride[];
+}
+
+/** Retries for POST requests answered with 429 or 529, before anything is relayed. */
+export interface ProxyRetries {
+  /** Including the first attempt; 1 turns retries off. */
+  max_attempts: number;
+  base_delay_ms: number;
+  max_delay_ms: number;
+}
+
+export type TimeoutScope = "route" | "model";
+
+export interface TimeoutOverride {
+  id: string;
+  scope: TimeoutScope;
+  pattern: string;
+  read_secs: number | null;
+  total_secs: number | null;
+}
+
+export type ModelMatchKind = "exact" | "glob" | "regex";
+
+/** Rewrites a matching model name and injects extra request fields before forwarding. */
+export interface ModelRewriteRule {
+  id: string;
+  enabled: boolean;
+  match_kind: ModelMatchKind;
+  pattern: string;
+  target_model: string;
+  inject: Record<string, unknown>;
+}
+
+export interface ModelRewritePreview {
+  rule_id: string;
+  model: string;
+  inject: Record<string, unknown>;
+}
+
+/** Opt-in capture of proxied request/response pairs. */
+export interface RequestCaptureSettings {
+  enabled: boolean;
+  max_body_kb: number;
+  max_storage_mb: number;
+}
+
+export interface CapturedHeader {
+  name: string;
+  value: string;
+}
+
+export interface RequestCaptureSummary {
+  request_id: string;
+  captured_at: string;
+  method: string;
+  path: string;
+  provider: string;
+  model: string;
+  client_label: string;
+  status_code: number;
+  duration_ms: number;
+  size_bytes: number;
+}
+
+export interface RequestCapture extends RequestCaptureSummary {
+  client_id: string;
+  request_headers: CapturedHeader[];
+  request_body: string | null;
+  request_body_bytes: number;
+  request_body_truncated: boolean;
+  response_headers: CapturedHeader[];
+  response_body: string | null;
+  response_body_bytes: number;
+  response_body_truncated: boolean;
+}
+
+export interface RequestReplayResult {
+  status_code: number;
+  duration_ms: number;
+  response_headers: CapturedHeader[];
+  response_body: string;
+  response_body_truncated: boolean;
+}
+
+/** Days to keep usage data; 0 keeps it forever. */
+export interface UsageRetention {
+  raw_event_days: number;
+  usage_json_days: number;
+}
+
+export interface UsageMaintenanceReport {
+  pruned_events: number;
+  cleared_usage_json: number;
+  vacuumed: boolean;
+  ran_at: string;
+}
+
+export interface UsageStorageStats {
+  db_path: string;
+  db_size_bytes: number;
+  wal_size_bytes: number;
+  free_bytes: number;
+  event_count: number;
+  events_with_usage_json: number;
+  rollup_count: number;
+  oldest_event_day: string | null;
+  oldest_rollup_day: string | null;
+}
+
+export type BudgetScope = "provider" | "model" | "account";
+export type BudgetPeriod = "daily" | "monthly";
+export type BudgetMetric = "tokens" | "requests";
+
+export interface UsageBudget {
+  id: string;
+  scope: BudgetScope;
+  target: string;
+  period: BudgetPeriod;
+  metric: BudgetMetric;
+  soft_limit: number | null;
+  hard_limit: number | null;
+}
+
+export interface UsageBudgetStatus {
+  budget: UsageBudget;
+  used: number;
+  resets_at: string;
+}
+
+export interface BudgetAlert {
+  budget: UsageBudget;
+  level: "soft" | "hard";
+  used: number;
+  limit: number;
+}
+
+/** USD per million tokens. Cache prices fall back to `input` when null. */
+export interface ModelPrice {
+  input: number;
+  output: number;
+  cache_read: number | null;
+  cache_write: number | null;
+}
+
+export interface ModelPriceEntry {
+  model: string;
+  price: ModelPrice;
+  is_override: boolean;
+}
+
+export interface ClientKeyInfo {
+  id: string;
+  name: string;
+  key_preview: string;
+  created_at: string;
+}
+
+export interface CreatedClientKey {
+  id: string;
+  name: string;
+  key: string;
+  created_at: string;
+}
+
+export interface ServerLogLine {
+  timestamp: string;
+  level: "debug" | "info" | "warn" | "error";
+  source: "stdout" | "stderr" | "manager";
+  message: string;
+}
+
+export interface ServerCrashEvent {
+  crash_count: number;
+  restarting: boolean;
+  retry_in_ms: number | null;
+  last_logs: ServerLogLine[];
+}
+
+/** Payload of `proxy_draining`; `in_flight` is 0 once the proxy has stopped. */
+export interface ProxyDrainEvent {
+  in_flight: number;
+  remaining_secs: number;
 }
 
 export interface BinaryDownloadProgress {
@@ -54,9 +273,12 @@ interface UsageSummary {
   input_tokens: number;
   output_tokens: number;
   cached_tokens: number;
+  cache_write_tokens: number;
   reasoning_tokens: number;
   error_count: number;
   error_rate: number;
+  estimated_cost_usd: number;
+  unpriced_requests: number;
 }
 
 interface UsageTimeseriesPoint {
@@ -66,8 +288,10 @@ interface UsageTimeseriesPoint {
   input_tokens: number;
   output_tokens: number;
   cached_tokens: number;
+  cache_write_tokens: number;
   reasoning_tokens: number;
   error_count: number;
+  estimated_cost_usd: number;
 }
 
 export interface UsageBreakdownRow {
@@ -80,16 +304,139 @@ export interface UsageBreakdownRow {
   input_tokens: number;
   output_tokens: number;
   cached_tokens: number;
+  cache_write_tokens: number;
   reasoning_tokens: number;
   error_count: number;
+  estimated_cost_usd: number | null;
+  last_seen: string | null;
+}
+
+export interface UsageClientRow {
+  client_id: string;
+  client_label: string;
+  requests: number;
+  total_tokens: number;
+  input_tokens: number;
+  output_tokens: number;
+  error_count: number;
+  last_seen: string | null;
+}
+
+export interface UsageProviderRow {
+  provider: string;
+  requests: number;
+  total_tokens: number;
+}
+
+/** Nearest-rank percentiles; null when there are no samples. */
+export interface LatencyPercentiles {
+  p50: number | null;
+  p90: number | null;
+  p99: number | null;
+}
+
+export interface UsageLatencyStats {
+  samples: number;
+  duration_ms: LatencyPercentiles;
+  ttfb_ms: LatencyPercentiles;
+  ttft_ms: LatencyPercentiles;
+  output_tokens_per_sec: LatencyPercentiles;
+}
+
+export interface UsageLatencyRow {
+  provider: string;
+  model: string;
+  account_key: string;
+  account_label: string;
+  stats: UsageLatencyStats;
+}
+
+export interface UsageLatencyPoint {
+  bucket: string;
+  provider: string;
+  stats: UsageLatencyStats;
+}
+
+export interface UsageLatency {
+  overall: UsageLatencyStats;
+  by_model: UsageLatencyRow[];
+  timeseries: UsageLatencyPoint[];
+}
+
+export type ErrorCategory =
+  | "rate_limited"
+  | "auth_expired"
+  | "quota_exceeded"
+  | "upstream_5xx"
+  | "backend_unavailable"
+  | "client_error"
+  | "timeout"
+  | "cancelled";
+
+export interface UsageErrorRow {
+  category: ErrorCategory | null;
+  provider: string;
+  requests: number;
   last_seen: string | null;
+  last_message: string | null;
 }
 
 export interface UsageDashboard {
-  range: UsageRange;
+  range: UsageRange | "custom";
+  bucket: UsageBucket;
   summary: UsageSummary;
   timeseries: UsageTimeseriesPoint[];
+  providers: UsageProviderRow[];
   breakdown: UsageBreakdownRow[];
+  breakdown_total: number;
+  page: number;
+  page_size: number;
+  clients: UsageClientRow[];
+  latency: UsageLatency;
+  errors: UsageErrorRow[];
+  /** First UTC day still held as raw events when pruned days couldn't be read from rollups. */
+  truncated_before: string | null;
+}
+
+export type UsageBucket = "minute" | "hour" | "day" | "week" | "month";
+export type UsageStatusClass = "success" | "error" | "4xx" | "5xx";
+
+/** `from`/`to` replace the `range` preset; filters apply to every dashboard section. */
+export interface UsageDashboardQuery {
+  range: UsageRange;
+  from: string | null;
+  to: string | null;
+  bucket: UsageBucket | null;
+  utc_offset_minutes: number;
+  time_zone: string | null;
+  provider: string | null;
+  model: string | null;
+  account_key: string | null;
+  /** Empty string selects requests without a client key. */
+  client_id: string | null;
+  status: UsageStatusClass | null;
+  page: number;
+  page_size: number;
+}
+
+export type UsageExportFormat = "csv" | "jsonl";
+export type UsageExportKind = "events" | "rollups";
+
+export interface UsageExportRequest {
+  path: string | null;
+  format: UsageExportFormat;
+  kind: UsageExportKind;
+  from: string | null;
+  to: string | null;
+  provider: string | null;
+  model: string | null;
+  account_key: string | null;
+  status: string | null;
+}
+
+export interface UsageExportResult {
+  path: string;
+  rows: number;
 }
 
 export interface UsageDashboardPayload {